@@ -37,6 +37,8 @@ echo "Executing $testName"
 case $testName in
 test-stable)
   _ "$cargo" stable test --jobs "$NPROC" --all --exclude solana-local-cluster ${V:+--verbose} -- --nocapture
+  _ "$cargo" stable test --jobs "$NPROC" --package solana-bpf-loader-program \
+    --features experimental-token-transfer ${V:+--verbose} -- --nocapture
   ;;
 test-stable-perf)
   # BPF solana-sdk legacy compile test
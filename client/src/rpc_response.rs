@@ -4,6 +4,7 @@ use solana_sdk::{
     clock::{Epoch, Slot},
     fee_calculator::{FeeCalculator, FeeRateGovernor},
     inflation::Inflation,
+    process_instruction::{ProgramLogKind, ProgramLogLevel, ProgramLogRecord},
     transaction::{Result, TransactionError},
 };
 use solana_transaction_status::ConfirmedTransactionStatusWithSignature;
@@ -114,6 +115,72 @@ pub struct RpcLogsResponse {
     pub signature: String, // Signature as base58 string
     pub err: Option<TransactionError>,
     pub logs: Vec<String>,
+    /// Structured form of the program messages among `logs`
+    #[serde(default)]
+    pub records: Vec<RpcLogRecord>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcLogRecord {
+    pub program_id: String, // Pubkey as base58 string
+    pub stack_height: usize,
+    #[serde(default)]
+    pub level: RpcLogLevel,
+    pub kind: RpcLogKind,
+}
+
+impl From<&ProgramLogRecord> for RpcLogRecord {
+    fn from(record: &ProgramLogRecord) -> Self {
+        Self {
+            program_id: record.program_id.to_string(),
+            stack_height: record.stack_height,
+            level: record.kind.level().into(),
+            kind: match &record.kind {
+                ProgramLogKind::Msg(message) => RpcLogKind::Msg(message.clone()),
+                ProgramLogKind::U64s(args) => RpcLogKind::U64s(*args),
+                ProgramLogKind::Pubkey(pubkey) => RpcLogKind::Pubkey(pubkey.to_string()),
+                ProgramLogKind::ComputeUnits(remaining) => RpcLogKind::ComputeUnits(*remaining),
+                ProgramLogKind::AbortCode(code) => RpcLogKind::AbortCode(*code),
+                ProgramLogKind::Data(data) => RpcLogKind::Data(base64::encode(data)),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcLogLevel {
+    Debug,
+    Info,
+    Error,
+}
+
+impl Default for RpcLogLevel {
+    fn default() -> Self {
+        Self::Info
+    }
+}
+
+impl From<ProgramLogLevel> for RpcLogLevel {
+    fn from(level: ProgramLogLevel) -> Self {
+        match level {
+            ProgramLogLevel::Debug => Self::Debug,
+            ProgramLogLevel::Info => Self::Info,
+            ProgramLogLevel::Error => Self::Error,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcLogKind {
+    Msg(String),
+    U64s([u64; 5]),
+    Pubkey(String), // Pubkey as base58 string
+    ComputeUnits(u64),
+    AbortCode(u64),
+    Data(String), // Bytes as base64 string
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -313,3 +380,89 @@ impl From<ConfirmedTransactionStatusWithSignature> for RpcConfirmedTransactionSt
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn test_rpc_log_record_from_program_log_record() {
+        let program_id = Pubkey::new_unique();
+        let pubkey = Pubkey::new_unique();
+        let cases = vec![
+            (
+                ProgramLogKind::Msg("hello".to_string()),
+                RpcLogLevel::Info,
+                RpcLogKind::Msg("hello".to_string()),
+                json!({"msg": "hello"}),
+            ),
+            (
+                ProgramLogKind::U64s([1, 2, 3, 4, 5]),
+                RpcLogLevel::Info,
+                RpcLogKind::U64s([1, 2, 3, 4, 5]),
+                json!({"u64s": [1, 2, 3, 4, 5]}),
+            ),
+            (
+                ProgramLogKind::Pubkey(pubkey),
+                RpcLogLevel::Info,
+                RpcLogKind::Pubkey(pubkey.to_string()),
+                json!({"pubkey": pubkey.to_string()}),
+            ),
+            (
+                ProgramLogKind::ComputeUnits(42),
+                RpcLogLevel::Debug,
+                RpcLogKind::ComputeUnits(42),
+                json!({"computeUnits": 42}),
+            ),
+            (
+                ProgramLogKind::AbortCode(7),
+                RpcLogLevel::Error,
+                RpcLogKind::AbortCode(7),
+                json!({"abortCode": 7}),
+            ),
+            (
+                ProgramLogKind::Data(vec![0, 1, 2, 0xfe, 0xff]),
+                RpcLogLevel::Info,
+                RpcLogKind::Data("AAEC/v8=".to_string()),
+                json!({"data": "AAEC/v8="}),
+            ),
+        ];
+
+        for (kind, level, rpc_kind, json_kind) in cases {
+            let rpc_record = RpcLogRecord::from(&ProgramLogRecord {
+                program_id,
+                stack_height: 2,
+                kind,
+            });
+            assert_eq!(
+                rpc_record,
+                RpcLogRecord {
+                    program_id: program_id.to_string(),
+                    stack_height: 2,
+                    level,
+                    kind: rpc_kind,
+                }
+            );
+
+            let value = serde_json::to_value(&rpc_record).unwrap();
+            assert_eq!(value["kind"], json_kind);
+            assert_eq!(
+                serde_json::from_value::<RpcLogRecord>(value).unwrap(),
+                rpc_record
+            );
+        }
+    }
+
+    #[test]
+    fn test_rpc_log_record_default_level() {
+        let record: RpcLogRecord = serde_json::from_value(json!({
+            "programId": Pubkey::default().to_string(),
+            "stackHeight": 1,
+            "kind": {"msg": "hello"},
+        }))
+        .unwrap();
+        assert_eq!(record.level, RpcLogLevel::Info);
+    }
+}
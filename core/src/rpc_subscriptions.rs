@@ -16,7 +16,7 @@ use solana_client::{
     rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSignatureSubscribeConfig},
     rpc_filter::RpcFilterType,
     rpc_response::{
-        ProcessedSignatureResult, ReceivedSignatureResult, Response, RpcKeyedAccount,
+        ProcessedSignatureResult, ReceivedSignatureResult, Response, RpcKeyedAccount, RpcLogRecord,
         RpcLogsResponse, RpcResponseContext, RpcSignatureResult, SlotInfo,
     },
 };
@@ -365,6 +365,7 @@ fn filter_logs_results(
                 signature: log.signature.to_string(),
                 err: log.result.err(),
                 logs: log.log_messages,
+                records: log.log_records.iter().map(RpcLogRecord::from).collect(),
             })),
             last_notified_slot,
         ),
@@ -1302,12 +1303,14 @@ pub(crate) mod tests {
     use jsonrpc_core::futures::{self, stream::Stream};
     use jsonrpc_pubsub::typed::Subscriber;
     use serial_test_derive::serial;
+    use solana_client::rpc_response::{RpcLogKind, RpcLogLevel};
     use solana_runtime::{
         commitment::BlockCommitment,
         genesis_utils::{create_genesis_config, GenesisConfigInfo},
     };
     use solana_sdk::{
         message::Message,
+        process_instruction::{ProgramLogKind, ProgramLogRecord},
         signature::{Keypair, Signer},
         system_instruction, system_program, system_transaction,
         transaction::Transaction,
@@ -2251,4 +2254,36 @@ pub(crate) mod tests {
         subscriptions.remove_root_subscription(&root_sub_id);
         assert_eq!(subscriptions.total(), 0);
     }
+
+    #[test]
+    fn test_filter_logs_results_records() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(100);
+        let bank = Arc::new(Bank::new(&genesis_config));
+        let program_id = solana_sdk::pubkey::new_rand();
+        let record = ProgramLogRecord {
+            program_id,
+            stack_height: 2,
+            kind: ProgramLogKind::Pubkey(program_id),
+        };
+        let logs = vec![TransactionLogInfo {
+            signature: Signature::default(),
+            result: Ok(()),
+            is_vote: false,
+            log_messages: vec![record.to_string()],
+            log_records: vec![record],
+        }];
+
+        let (responses, _) = filter_logs_results(Some(logs), &(None, false), 0, None, bank);
+        let responses = responses.collect::<Vec<_>>();
+        assert_eq!(responses.len(), 1);
+        assert_eq!(
+            responses[0].records,
+            vec![RpcLogRecord {
+                program_id: program_id.to_string(),
+                stack_height: 2,
+                level: RpcLogLevel::Info,
+                kind: RpcLogKind::Pubkey(program_id.to_string()),
+            }]
+        );
+    }
 }
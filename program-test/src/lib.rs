@@ -7,10 +7,13 @@ use {
     solana_banks_client::start_client,
     solana_banks_server::banks_server::start_local_server,
     solana_program::{
-        account_info::AccountInfo, entrypoint::ProgramResult, fee_calculator::FeeCalculator,
+        account_info::AccountInfo, entrypoint::ProgramResult,
+        entrypoint::MAX_PERMITTED_DATA_INCREASE, fee_calculator::FeeCalculator, hash::hashv,
         hash::Hash, instruction::Instruction, instruction::InstructionError, message::Message,
-        native_token::sol_to_lamports, program_error::ProgramError, program_stubs, pubkey::Pubkey,
-        rent::Rent,
+        native_token::sol_to_lamports, program_error::ProgramError, program_stubs,
+        pseudorandom::derive_pseudorandom, pubkey::Pubkey, rent::Rent,
+        runtime_limits::RuntimeLimits, runtime_version::RuntimeVersion,
+        transaction_info::SIGNATURE_BYTES,
     },
     solana_runtime::{
         bank::{Bank, Builtin},
@@ -19,9 +22,13 @@ use {
     },
     solana_sdk::{
         account::Account,
+        compute_profiler::ComputeProfiler,
         keyed_account::KeyedAccount,
-        process_instruction::BpfComputeBudget,
-        process_instruction::{InvokeContext, MockInvokeContext, ProcessInstructionWithContext},
+        process_instruction::{
+            syscall_address_cost_key, syscall_byte_cost_key, BpfComputeBudget, InvokeContext,
+            MockInvokeContext, ProcessInstructionWithContext, SyscallCostOverrides, SyscallFilter,
+            SyscallTraceCollector,
+        },
         signature::{Keypair, Signer},
     },
     std::{
@@ -37,6 +44,9 @@ use {
     },
 };
 
+#[cfg(feature = "syscall-fault-injection")]
+use solana_sdk::process_instruction::{SyscallFault, SyscallFaults};
+
 // Export types so test clients can limit their solana crate dependencies
 pub use solana_banks_client::BanksClient;
 pub mod programs;
@@ -77,6 +87,11 @@ pub fn builtin_process_instruction(
     let mut mock_invoke_context = MockInvokeContext::default();
     mock_invoke_context.programs = invoke_context.get_programs().to_vec();
     mock_invoke_context.key = *program_id;
+    mock_invoke_context.program_ids = invoke_context.get_program_ids().to_vec();
+    mock_invoke_context.signatures = invoke_context.get_transaction_signatures().to_vec();
+    mock_invoke_context.recent_blockhash = *invoke_context.get_recent_blockhash();
+    mock_invoke_context.fee_payer = *invoke_context.get_fee_payer();
+    mock_invoke_context.pseudorandom_counter = invoke_context.get_pseudorandom_counter();
     // TODO: Populate MockInvokeContext more, or rework to avoid MockInvokeContext entirely.
     //       The context being passed into the program is incomplete...
     let local_invoke_context = RefCell::new(Rc::new(mock_invoke_context));
@@ -194,6 +209,92 @@ impl program_stubs::SyscallStubs for SyscallStubs {
         });
     }
 
+    fn sol_log_data(&self, data: &[u8]) {
+        INVOKE_CONTEXT.with(|invoke_context| {
+            let invoke_context = invoke_context.borrow_mut();
+            let logger = invoke_context.get_logger();
+            let logger = logger.borrow_mut();
+
+            if logger.log_enabled() {
+                logger.log(&format!("Program data: {}", base64::encode(data)));
+            }
+        });
+    }
+
+    fn sol_get_max_invoke_depth(&self) -> u64 {
+        INVOKE_CONTEXT.with(|invoke_context| {
+            invoke_context
+                .borrow()
+                .get_bpf_compute_budget()
+                .max_invoke_depth as u64
+        })
+    }
+
+    fn sol_get_runtime_version(&self) -> u64 {
+        INVOKE_CONTEXT.with(|invoke_context| invoke_context.borrow().get_runtime_version().to_u64())
+    }
+
+    fn sol_get_pseudorandom(&self, bytes: &mut [u8]) {
+        INVOKE_CONTEXT.with(|invoke_context| {
+            let invoke_context = invoke_context.borrow();
+            let program_id = invoke_context
+                .get_program_ids()
+                .last()
+                .cloned()
+                .unwrap_or_default();
+            let counter = invoke_context.get_pseudorandom_counter();
+            let mut counter = counter.borrow_mut();
+            derive_pseudorandom(
+                invoke_context.get_recent_blockhash(),
+                &program_id,
+                *counter,
+                bytes,
+            );
+            *counter += 1;
+        })
+    }
+
+    fn sol_get_instruction_ancestry(&self) -> Vec<Pubkey> {
+        INVOKE_CONTEXT.with(|invoke_context| invoke_context.borrow().get_program_ids().to_vec())
+    }
+
+    fn sol_get_transaction_signature(&self, index: u64) -> Option<[u8; SIGNATURE_BYTES]> {
+        INVOKE_CONTEXT.with(|invoke_context| {
+            invoke_context
+                .borrow()
+                .get_transaction_signatures()
+                .get(index as usize)
+                .map(|signature| {
+                    let mut bytes = [0; SIGNATURE_BYTES];
+                    bytes.copy_from_slice(signature.as_ref());
+                    bytes
+                })
+        })
+    }
+
+    fn sol_get_recent_blockhash_of_tx(&self) -> Hash {
+        INVOKE_CONTEXT.with(|invoke_context| *invoke_context.borrow().get_recent_blockhash())
+    }
+
+    fn sol_get_fee_payer(&self) -> Pubkey {
+        INVOKE_CONTEXT.with(|invoke_context| *invoke_context.borrow().get_fee_payer())
+    }
+
+    fn sol_get_runtime_limits(&self) -> RuntimeLimits {
+        INVOKE_CONTEXT.with(|invoke_context| {
+            let invoke_context = invoke_context.borrow();
+            let bpf_compute_budget = invoke_context.get_bpf_compute_budget();
+            RuntimeLimits {
+                max_invoke_depth: bpf_compute_budget.max_invoke_depth as u64,
+                max_call_depth: bpf_compute_budget.max_call_depth as u64,
+                stack_frame_size: bpf_compute_budget.stack_frame_size as u64,
+                heap_size: solana_bpf_loader_program::DEFAULT_HEAP_SIZE as u64,
+                max_signers: solana_bpf_loader_program::syscalls::MAX_SIGNERS as u64,
+                max_permitted_data_increase: MAX_PERMITTED_DATA_INCREASE as u64,
+            }
+        })
+    }
+
     fn sol_invoke_signed(
         &self,
         instruction: &Instruction,
@@ -369,10 +470,59 @@ pub fn read_file<P: AsRef<Path>>(path: P) -> Vec<u8> {
     file_data
 }
 
+/// Deterministic addresses derived from human readable labels, such as "payer" or "mint-A", so
+/// test accounts are stable across runs and can be reported by label
+#[derive(Clone, Debug, Default)]
+pub struct AddressLabels {
+    labels: HashMap<Pubkey, String>,
+}
+
+impl AddressLabels {
+    /// Address derived from `label`, identical across runs
+    pub fn address(&mut self, label: &str) -> Pubkey {
+        let address = Pubkey::new(hashv(&[b"solana-program-test", label.as_bytes()]).as_ref());
+        self.set_label(address, label);
+        address
+    }
+
+    /// Report `address`, which was not derived from a label, as `label`
+    pub fn set_label(&mut self, address: Pubkey, label: &str) {
+        self.labels.insert(address, label.to_string());
+    }
+
+    /// `address` along with its label, if it has one
+    pub fn format(&self, address: &Pubkey) -> String {
+        match self.label(address) {
+            Some(label) => format!("\"{}\" ({})", label, address),
+            None => address.to_string(),
+        }
+    }
+
+    /// Label `address` was derived from, if any
+    pub fn label(&self, address: &Pubkey) -> Option<&str> {
+        self.labels.get(address).map(|label| label.as_str())
+    }
+
+    /// Every derived address and its label
+    pub fn labels(&self) -> &HashMap<Pubkey, String> {
+        &self.labels
+    }
+}
+
 pub struct ProgramTest {
     accounts: Vec<(Pubkey, Account)>,
     builtins: Vec<Builtin>,
     bpf_compute_max_units: Option<u64>,
+    max_instruction_trace_length: Option<usize>,
+    transaction_account_lock_limit: Option<usize>,
+    syscall_cost_overrides: SyscallCostOverrides,
+    syscall_filter: Option<SyscallFilter>,
+    #[cfg(feature = "syscall-fault-injection")]
+    syscall_faults: SyscallFaults,
+    syscall_trace_collector: Option<Arc<dyn SyscallTraceCollector>>,
+    compute_profiler: Option<Arc<ComputeProfiler>>,
+    runtime_version: Option<RuntimeVersion>,
+    address_labels: AddressLabels,
     prefer_bpf: bool,
 }
 
@@ -403,6 +553,16 @@ impl Default for ProgramTest {
             accounts: vec![],
             builtins: vec![],
             bpf_compute_max_units: None,
+            max_instruction_trace_length: None,
+            transaction_account_lock_limit: None,
+            syscall_cost_overrides: SyscallCostOverrides::default(),
+            syscall_filter: None,
+            #[cfg(feature = "syscall-fault-injection")]
+            syscall_faults: SyscallFaults::default(),
+            syscall_trace_collector: None,
+            compute_profiler: None,
+            runtime_version: None,
+            address_labels: AddressLabels::default(),
             prefer_bpf,
         }
     }
@@ -429,6 +589,80 @@ impl ProgramTest {
         self.bpf_compute_max_units = Some(bpf_compute_max_units);
     }
 
+    /// Limit the number of cross-program invoked instructions recorded per transaction
+    pub fn set_max_instruction_trace_length(&mut self, max_instruction_trace_length: usize) {
+        self.max_instruction_trace_length = Some(max_instruction_trace_length);
+    }
+
+    /// Limit the number of accounts a transaction may lock
+    pub fn set_transaction_account_lock_limit(&mut self, transaction_account_lock_limit: usize) {
+        self.transaction_account_lock_limit = Some(transaction_account_lock_limit);
+    }
+
+    /// Override the compute cost of the syscall named `syscall_name`
+    pub fn set_syscall_cost(&mut self, syscall_name: &str, cost: u64) {
+        self.syscall_cost_overrides
+            .insert(syscall_name.to_string(), cost);
+    }
+
+    /// Override the per-byte compute cost of the syscall named `syscall_name`
+    pub fn set_syscall_byte_cost(&mut self, syscall_name: &str, cost: u64) {
+        self.syscall_cost_overrides
+            .insert(syscall_byte_cost_key(syscall_name), cost);
+    }
+
+    /// Override the per-address compute cost of the syscall named `syscall_name`
+    pub fn set_syscall_address_cost(&mut self, syscall_name: &str, cost: u64) {
+        self.syscall_cost_overrides
+            .insert(syscall_address_cost_key(syscall_name), cost);
+    }
+
+    /// Restrict the syscalls BPF programs may invoke, calls to a disabled syscall fail with
+    /// `SyscallError::SyscallDisabled`
+    pub fn set_syscall_filter(&mut self, syscall_filter: SyscallFilter) {
+        self.syscall_filter = Some(syscall_filter);
+    }
+
+    /// Inject `fault` into the `call`th call, counting from 1, of the syscall named
+    /// `syscall_name` in every BPF program invocation
+    #[cfg(feature = "syscall-fault-injection")]
+    pub fn inject_syscall_fault(&mut self, syscall_name: &str, call: u64, fault: SyscallFault) {
+        self.syscall_faults
+            .entry(syscall_name.to_string())
+            .or_default()
+            .insert(call, fault);
+    }
+
+    /// Trace every syscall made by BPF programs to `syscall_trace_collector`
+    pub fn set_syscall_trace_collector(
+        &mut self,
+        syscall_trace_collector: Arc<dyn SyscallTraceCollector>,
+    ) {
+        self.syscall_trace_collector = Some(syscall_trace_collector);
+    }
+
+    /// Record the compute units consumed by every syscall and program invocation to
+    /// `compute_profiler`
+    pub fn set_compute_profiler(&mut self, compute_profiler: Arc<ComputeProfiler>) {
+        self.compute_profiler = Some(compute_profiler);
+    }
+
+    /// Present `runtime_version` to programs instead of the current runtime's version, to
+    /// test how they behave on other revisions of the runtime interface
+    pub fn set_runtime_version(&mut self, runtime_version: RuntimeVersion) {
+        self.runtime_version = Some(runtime_version);
+    }
+
+    /// Deterministic address for `label`, which the test environment reports by label
+    pub fn address(&mut self, label: &str) -> Pubkey {
+        self.address_labels.address(label)
+    }
+
+    /// Labels of the addresses created by `address` and of the programs added so far
+    pub fn address_labels(&self) -> &AddressLabels {
+        &self.address_labels
+    }
+
     /// Add an account to the test environment
     pub fn add_account(&mut self, address: Pubkey, account: Account) {
         self.accounts.push((address, account));
@@ -493,6 +727,7 @@ impl ProgramTest {
     ) {
         let loader = solana_program::bpf_loader::id();
         let program_file = find_file(&format!("{}.so", program_name));
+        self.address_labels.set_label(program_id, program_name);
 
         if process_instruction.is_none() && program_file.is_none() {
             panic!("Unable to add program {} ({})", program_name, program_id);
@@ -609,10 +844,18 @@ impl ProgramTest {
 
         for (address, account) in self.accounts {
             if bank.get_account(&address).is_some() {
-                info!("Overriding account at {}", address);
+                info!(
+                    "Overriding account at {}",
+                    self.address_labels.format(&address)
+                );
             }
             bank.store_account(&address, &account);
         }
+        let mut address_labels = self.address_labels.labels().iter().collect::<Vec<_>>();
+        address_labels.sort_by(|(_, a), (_, b)| a.cmp(b));
+        for (address, label) in address_labels {
+            info!("\"{}\" is {}", label, address);
+        }
         bank.set_capitalization();
         if let Some(max_units) = self.bpf_compute_max_units {
             bank.set_bpf_compute_budget(Some(BpfComputeBudget {
@@ -620,6 +863,19 @@ impl ProgramTest {
                 ..BpfComputeBudget::default()
             }));
         }
+        bank.set_max_instruction_trace_length(self.max_instruction_trace_length);
+        bank.set_transaction_account_lock_limit(self.transaction_account_lock_limit);
+        if !self.syscall_cost_overrides.is_empty() {
+            bank.set_syscall_cost_overrides(Some(self.syscall_cost_overrides));
+        }
+        bank.set_syscall_filter(self.syscall_filter);
+        #[cfg(feature = "syscall-fault-injection")]
+        if !self.syscall_faults.is_empty() {
+            bank.set_syscall_faults(Some(self.syscall_faults));
+        }
+        bank.set_syscall_trace_collector(self.syscall_trace_collector);
+        bank.set_compute_profiler(self.compute_profiler);
+        bank.set_runtime_version(self.runtime_version);
 
         // Realistic fee_calculator part 1: Fake a single signature by calling
         // `bank.commit_transactions()` so that the fee calculator in the child bank will be
@@ -708,3 +964,51 @@ impl ProgramTestBanksClientExt for BanksClient {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noop_process_instruction(
+        _program_id: &Pubkey,
+        _keyed_accounts: &[KeyedAccount],
+        _instruction_data: &[u8],
+        _invoke_context: &mut dyn InvokeContext,
+    ) -> Result<(), InstructionError> {
+        Ok(())
+    }
+
+    #[test]
+    fn test_address_labels() {
+        let mut address_labels = AddressLabels::default();
+        let payer = address_labels.address("payer");
+        assert_eq!(payer, AddressLabels::default().address("payer"));
+        assert_ne!(payer, address_labels.address("mint-A"));
+        assert_eq!(address_labels.label(&payer), Some("payer"));
+        assert_eq!(
+            address_labels.format(&payer),
+            format!("\"payer\" ({})", payer)
+        );
+
+        let address = Pubkey::new_unique();
+        assert_eq!(address_labels.label(&address), None);
+        assert_eq!(address_labels.format(&address), address.to_string());
+        address_labels.set_label(address, "vault");
+        assert_eq!(address_labels.label(&address), Some("vault"));
+        assert_eq!(address_labels.labels().len(), 3);
+    }
+
+    #[test]
+    fn test_program_test_address_labels() {
+        let program_id = Pubkey::new_unique();
+        let mut program_test = ProgramTest::default();
+        program_test.add_program("noop", program_id, Some(noop_process_instruction));
+        let mint = program_test.address("mint-A");
+        assert_eq!(mint, AddressLabels::default().address("mint-A"));
+        assert_eq!(
+            program_test.address_labels().label(&program_id),
+            Some("noop")
+        );
+        assert_eq!(program_test.address_labels().label(&mint), Some("mint-A"));
+    }
+}
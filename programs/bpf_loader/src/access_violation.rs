@@ -0,0 +1,145 @@
+use solana_rbpf::ebpf::{MM_HEAP_START, MM_INPUT_START, MM_PROGRAM_START, MM_STACK_START};
+use std::{fmt, ops::Range};
+
+/// Size of each region of the VM address space
+const VM_REGION_SIZE: u64 = MM_STACK_START - MM_PROGRAM_START;
+
+/// Region of the VM address space an access violation landed in
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VmRegion {
+    Program,
+    Stack,
+    Heap,
+    Input,
+    /// Data of the account at this index into the program's accounts
+    AccountData(usize),
+    Unknown,
+}
+
+impl fmt::Display for VmRegion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VmRegion::Program => write!(f, "program"),
+            VmRegion::Stack => write!(f, "stack"),
+            VmRegion::Heap => write!(f, "heap"),
+            VmRegion::Input => write!(f, "input"),
+            VmRegion::AccountData(index) => write!(f, "account {} data", index),
+            VmRegion::Unknown => write!(f, "unknown region"),
+        }
+    }
+}
+
+/// Location of a failed memory access relative to the region containing it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessViolationInfo {
+    pub region: VmRegion,
+    pub offset: u64,
+    pub len: u64,
+}
+
+impl AccessViolationInfo {
+    /// Locate `len` bytes at `vm_addr`, `account_data_ranges` are the offsets
+    /// of each account's data within the input region
+    pub fn new(vm_addr: u64, len: u64, account_data_ranges: &[Range<usize>]) -> Self {
+        let (region, offset) = if vm_addr < MM_PROGRAM_START {
+            (VmRegion::Unknown, vm_addr)
+        } else if vm_addr < MM_STACK_START {
+            (VmRegion::Program, vm_addr - MM_PROGRAM_START)
+        } else if vm_addr < MM_HEAP_START {
+            (VmRegion::Stack, vm_addr - MM_STACK_START)
+        } else if vm_addr < MM_INPUT_START {
+            (VmRegion::Heap, vm_addr - MM_HEAP_START)
+        } else if vm_addr < MM_INPUT_START + VM_REGION_SIZE {
+            let offset = vm_addr - MM_INPUT_START;
+            match account_data_ranges
+                .iter()
+                .enumerate()
+                .find(|(_, range)| range.contains(&(offset as usize)))
+            {
+                Some((index, range)) => (VmRegion::AccountData(index), offset - range.start as u64),
+                None => (VmRegion::Input, offset),
+            }
+        } else {
+            (VmRegion::Unknown, vm_addr)
+        };
+        Self {
+            region,
+            offset,
+            len,
+        }
+    }
+}
+
+impl fmt::Display for AccessViolationInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at offset {:#x} of size {}",
+            self.region, self.offset, self.len
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_access_violation_info() {
+        let account_data_ranges = vec![0x60..0x80, 0x100..0x100, 0x200..0x300];
+        let locate = |vm_addr, len| AccessViolationInfo::new(vm_addr, len, &account_data_ranges);
+
+        assert_eq!(
+            locate(0x10, 8),
+            AccessViolationInfo {
+                region: VmRegion::Unknown,
+                offset: 0x10,
+                len: 8,
+            }
+        );
+        assert_eq!(locate(MM_PROGRAM_START + 4, 1).region, VmRegion::Program);
+        assert_eq!(locate(MM_STACK_START + 0x1000, 8).offset, 0x1000);
+        assert_eq!(locate(MM_STACK_START + 0x1000, 8).region, VmRegion::Stack);
+        assert_eq!(locate(MM_HEAP_START - 1, 1).region, VmRegion::Stack);
+        assert_eq!(locate(MM_HEAP_START, 1).region, VmRegion::Heap);
+        assert_eq!(
+            locate(MM_INPUT_START + 0x10, 8),
+            AccessViolationInfo {
+                region: VmRegion::Input,
+                offset: 0x10,
+                len: 8,
+            }
+        );
+        assert_eq!(
+            locate(MM_INPUT_START + 0x68, 8),
+            AccessViolationInfo {
+                region: VmRegion::AccountData(0),
+                offset: 0x8,
+                len: 8,
+            }
+        );
+        assert_eq!(locate(MM_INPUT_START + 0x80, 1).region, VmRegion::Input);
+        assert_eq!(locate(MM_INPUT_START + 0x100, 1).region, VmRegion::Input);
+        assert_eq!(
+            locate(MM_INPUT_START + 0x2ff, 2),
+            AccessViolationInfo {
+                region: VmRegion::AccountData(2),
+                offset: 0xff,
+                len: 2,
+            }
+        );
+        assert_eq!(
+            locate(MM_INPUT_START + VM_REGION_SIZE, 1).region,
+            VmRegion::Unknown
+        );
+
+        assert_eq!(
+            locate(MM_INPUT_START + 0x2ff, 2).to_string(),
+            "account 2 data at offset 0xff of size 2"
+        );
+        assert_eq!(
+            locate(MM_HEAP_START + 0x20, 4).to_string(),
+            "heap at offset 0x20 of size 4"
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use crate::alloc;
+
+use alloc::{Alloc, AllocErr};
+use std::{
+    alloc::Layout,
+    collections::{BTreeMap, BTreeSet},
+};
+
+/// Bump allocator that keeps freed blocks on an address ordered free list for reuse
+///
+/// Allocation sizes are tracked by the allocator rather than taken from the caller since
+/// programs free without passing the size.  Blocks are handed out best-fit, lowest address
+/// first among blocks of equal size, and zeroed, so allocation is deterministic and behaves
+/// like a fresh heap.  Finding a block takes at most two lookups in a size ordered index
+/// however fragmented the heap is, so the uncharged cost of a call stays bounded.
+#[derive(Debug)]
+pub struct BPFFreeListAllocator {
+    heap: Vec<u8>,
+    start: u64,
+    len: u64,
+    pos: u64,
+    /// Size of each live allocation keyed by its heap offset
+    allocations: BTreeMap<u64, u64>,
+    /// Size of each free block below `pos` keyed by its heap offset, adjacent blocks are
+    /// always merged
+    free: BTreeMap<u64, u64>,
+    /// The blocks of `free` as `(size, offset)`, ordered for best-fit lookups
+    free_by_size: BTreeSet<(u64, u64)>,
+}
+
+impl BPFFreeListAllocator {
+    pub fn new(heap: Vec<u8>, virtual_address: u64) -> Self {
+        let len = heap.len() as u64;
+        Self {
+            heap,
+            start: virtual_address,
+            len,
+            pos: 0,
+            allocations: BTreeMap::new(),
+            free: BTreeMap::new(),
+            free_by_size: BTreeSet::new(),
+        }
+    }
+
+    fn align_up(offset: u64, align: u64) -> Option<u64> {
+        Some(offset.checked_add(align - 1)? & !(align - 1))
+    }
+
+    /// Offset at which the smallest free block of at least `min_size` bytes fits the request
+    fn find_free_block(&self, min_size: u64, size: u64, align: u64) -> Option<(u64, u64, u64)> {
+        let (block_size, block) = *self.free_by_size.range((min_size, 0)..).next()?;
+        let offset = Self::align_up(block, align)?;
+        if offset.checked_add(size)? <= block + block_size {
+            Some((block, block_size, offset))
+        } else {
+            None
+        }
+    }
+
+    fn take_free_block(&mut self, size: u64, align: u64) -> Option<u64> {
+        // A block that is large enough may still be too small once its start is aligned, any
+        // block of `size + align - 1` bytes fits
+        let (block, block_size, offset) = self
+            .find_free_block(size, size, align)
+            .or_else(|| self.find_free_block(size.checked_add(align - 1)?, size, align))?;
+        self.remove_free(block, block_size);
+        if offset > block {
+            self.insert_free(block, offset - block);
+        }
+        let end = offset + size;
+        if end < block + block_size {
+            self.insert_free(end, block + block_size - end);
+        }
+        Some(offset)
+    }
+
+    fn insert_free(&mut self, offset: u64, size: u64) {
+        self.free.insert(offset, size);
+        self.free_by_size.insert((size, offset));
+    }
+
+    fn remove_free(&mut self, offset: u64, size: u64) {
+        self.free.remove(&offset);
+        self.free_by_size.remove(&(size, offset));
+    }
+
+    fn take_bump(&mut self, size: u64, align: u64) -> Option<u64> {
+        let offset = Self::align_up(self.pos, align)?;
+        if offset.checked_add(size)? > self.len {
+            return None;
+        }
+        if offset > self.pos {
+            self.release(self.pos, offset - self.pos);
+        }
+        self.pos = offset + size;
+        Some(offset)
+    }
+
+    /// Return a block to the free list, merging it with its neighbors or shrinking the bump
+    /// region if it ends there
+    fn release(&mut self, mut offset: u64, mut size: u64) {
+        if let Some((&prev, &prev_size)) = self.free.range(..offset).next_back() {
+            if prev + prev_size == offset {
+                self.remove_free(prev, prev_size);
+                offset = prev;
+                size += prev_size;
+            }
+        }
+        if let Some(&next_size) = self.free.get(&(offset + size)) {
+            self.remove_free(offset + size, next_size);
+            size += next_size;
+        }
+        if offset + size == self.pos {
+            self.pos = offset;
+        } else {
+            self.insert_free(offset, size);
+        }
+    }
+}
+
+impl Alloc for BPFFreeListAllocator {
+    fn alloc(&mut self, layout: Layout) -> Result<u64, AllocErr> {
+        // Zero sized allocations still occupy a byte so every allocation has a unique address
+        let size = (layout.size() as u64).max(1);
+        let align = layout.align() as u64;
+        let offset = self
+            .take_free_block(size, align)
+            .or_else(|| self.take_bump(size, align))
+            .ok_or(AllocErr)?;
+        for byte in self.heap[offset as usize..(offset + size) as usize].iter_mut() {
+            *byte = 0;
+        }
+        self.allocations.insert(offset, size);
+        Ok(self.start + offset)
+    }
+
+    fn dealloc(&mut self, addr: u64, _layout: Layout) {
+        // Frees of addresses that are not live allocations are ignored, as they are by the
+        // bump allocator
+        let allocation = addr
+            .checked_sub(self.start)
+            .and_then(|offset| Some((offset, self.allocations.remove(&offset)?)));
+        if let Some((offset, size)) = allocation {
+            self.release(offset, size);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: u64 = 0x1000;
+
+    fn layout(size: usize, align: usize) -> Layout {
+        Layout::from_size_align(size, align).unwrap()
+    }
+
+    #[test]
+    fn test_alignment_in_reused_blocks() {
+        let mut allocator = BPFFreeListAllocator::new(vec![0; 128], START);
+        let a = allocator.alloc(layout(3, 1)).unwrap();
+        let b = allocator.alloc(layout(40, 1)).unwrap();
+        let c = allocator.alloc(layout(1, 1)).unwrap();
+        assert_eq!((a, b, c), (START, START + 3, START + 43));
+
+        // The freed block starts unaligned, the allocation is aligned inside it and the
+        // padding before it stays free
+        allocator.dealloc(b, layout(40, 1));
+        let d = allocator.alloc(layout(16, 16)).unwrap();
+        assert_eq!(d, START + 16);
+        assert_eq!(allocator.free.get(&3), Some(&13));
+        assert_eq!(allocator.free.get(&32), Some(&11));
+        assert_eq!(allocator.alloc(layout(13, 1)).unwrap(), START + 3);
+
+        // A block that is large enough but not once aligned is passed over
+        let e = allocator.alloc(layout(16, 16)).unwrap();
+        assert_eq!(e, START + 48);
+        assert_eq!(allocator.free.get(&32), Some(&11));
+        assert_eq!(allocator.free.get(&44), Some(&4));
+        for addr in &[d, e] {
+            assert_eq!(addr % 16, 0);
+        }
+    }
+
+    #[test]
+    fn test_best_fit_is_bounded() {
+        // Fragment the heap into many small free blocks followed by one large one
+        let mut allocator = BPFFreeListAllocator::new(vec![0; 4096], START);
+        let blocks = (0..128)
+            .map(|_| allocator.alloc(layout(16, 16)).unwrap())
+            .collect::<Vec<_>>();
+        for block in blocks.iter().step_by(2) {
+            allocator.dealloc(*block, layout(16, 16));
+        }
+        allocator.dealloc(blocks[125], layout(16, 16));
+        assert_eq!(allocator.free.len(), 63);
+
+        // The smallest block that fits is found without visiting the others
+        assert_eq!(allocator.alloc(layout(32, 16)).unwrap(), blocks[124]);
+        assert_eq!(allocator.alloc(layout(16, 16)).unwrap(), blocks[0]);
+        assert_eq!(allocator.free.len(), allocator.free_by_size.len());
+    }
+
+    #[test]
+    fn test_unknown_and_double_free() {
+        let mut allocator = BPFFreeListAllocator::new(vec![0; 64], START);
+        let a = allocator.alloc(layout(16, 16)).unwrap();
+        let b = allocator.alloc(layout(16, 16)).unwrap();
+
+        // Addresses that are not the start of a live allocation are ignored
+        for addr in &[0, START - 1, a + 1, b + 16, START + 64, u64::MAX] {
+            allocator.dealloc(*addr, layout(16, 16));
+        }
+        assert!(allocator.free.is_empty());
+        assert_eq!(allocator.pos, 32);
+
+        // Freeing twice frees once
+        allocator.dealloc(a, layout(16, 16));
+        allocator.dealloc(a, layout(16, 16));
+        assert_eq!(allocator.free.get(&0), Some(&16));
+        assert_eq!(allocator.alloc(layout(16, 16)).unwrap(), a);
+        assert_eq!(allocator.alloc(layout(16, 16)).unwrap(), START + 32);
+        assert!(allocator.free.is_empty());
+    }
+
+    #[test]
+    fn test_shrink_bump_region() {
+        let mut allocator = BPFFreeListAllocator::new(vec![0; 64], START);
+        let a = allocator.alloc(layout(1, 1)).unwrap();
+        let b = allocator.alloc(layout(16, 16)).unwrap();
+        let c = allocator.alloc(layout(16, 16)).unwrap();
+        assert_eq!((a, b, c), (START, START + 16, START + 32));
+        // The alignment padding after `a` is free
+        assert_eq!(allocator.free.get(&1), Some(&15));
+
+        // Freeing below the end of the bump region keeps the block on the free list
+        allocator.dealloc(b, layout(16, 16));
+        assert_eq!(allocator.free.get(&1), Some(&31));
+        assert_eq!(allocator.pos, 48);
+
+        // Freeing the last allocation shrinks the bump region past the merged free blocks
+        allocator.dealloc(c, layout(16, 16));
+        assert!(allocator.free.is_empty());
+        assert!(allocator.free_by_size.is_empty());
+        assert_eq!(allocator.pos, 1);
+        allocator.dealloc(a, layout(1, 1));
+        assert_eq!(allocator.pos, 0);
+
+        // The whole heap is available again
+        assert_eq!(allocator.alloc(layout(64, 16)).unwrap(), START);
+        assert!(allocator.alloc(layout(1, 1)).is_err());
+    }
+}
@@ -1,14 +1,20 @@
+pub mod access_violation;
 pub mod alloc;
 pub mod allocator_bump;
+pub mod allocator_free_list;
 pub mod bpf_verifier;
 pub mod deprecated;
+pub mod memory;
+#[cfg(test)]
+mod overlap_harness;
 pub mod serialization;
 pub mod syscalls;
 pub mod with_jit;
 
 use crate::{
+    access_violation::AccessViolationInfo,
     bpf_verifier::VerifierError,
-    serialization::{deserialize_parameters, serialize_parameters},
+    serialization::{deserialize_parameters, serialize_parameters, serialized_account_data_ranges},
     syscalls::SyscallError,
 };
 use num_derive::{FromPrimitive, ToPrimitive};
@@ -26,7 +32,10 @@ use solana_sdk::{
     instruction::InstructionError,
     keyed_account::{is_executable, next_keyed_account, KeyedAccount},
     loader_instruction::LoaderInstruction,
-    process_instruction::{stable_log, ComputeMeter, Executor, InvokeContext},
+    process_instruction::{
+        stable_log, BpfComputeBudget, ComputeMeter, Executor, InvokeContext, ProgramLogKind,
+        ProgramLogRecord,
+    },
     program_utils::limited_deserialize,
     pubkey::Pubkey,
 };
@@ -48,6 +57,8 @@ pub enum BPFLoaderError {
     VirtualMachineFailedToRunProgram = 0x0b9f_0002,
     #[error("failed to compile program")]
     JustInTimeCompilationFailed = 0x0b9f_0003,
+    #[error("program aborted")]
+    ProgramAborted = 0x0b9f_0004,
 }
 impl<E> DecodeError<E> for BPFLoaderError {
     fn type_of() -> &'static str {
@@ -92,31 +103,58 @@ fn map_ebpf_error(
     InstructionError::InvalidAccountData
 }
 
+/// How executables are verified and configured, besides the syscalls they may call
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExecutableSettings {
+    /// Whether the verifier caps the size of programs
+    pub is_program_size_cap: bool,
+    pub max_call_depth: usize,
+    pub stack_frame_size: usize,
+}
+
+impl ExecutableSettings {
+    pub fn new(
+        is_feature_active: &dyn Fn(&Pubkey) -> bool,
+        bpf_compute_budget: &BpfComputeBudget,
+    ) -> Self {
+        Self {
+            is_program_size_cap: !is_feature_active(&bpf_compute_budget_balancing::id()),
+            max_call_depth: bpf_compute_budget.max_call_depth,
+            stack_frame_size: bpf_compute_budget.stack_frame_size,
+        }
+    }
+
+    /// VM configuration executables are created with
+    pub fn config(&self) -> Config {
+        Config {
+            max_call_depth: self.max_call_depth,
+            stack_frame_size: self.stack_frame_size,
+            enable_instruction_meter: true,
+            enable_instruction_tracing: false,
+        }
+    }
+}
+
 pub fn create_and_cache_executor(
     program: &KeyedAccount,
     invoke_context: &mut dyn InvokeContext,
     use_jit: bool,
 ) -> Result<Arc<BPFExecutor>, InstructionError> {
-    let bpf_compute_budget = invoke_context.get_bpf_compute_budget();
+    let settings = ExecutableSettings::new(
+        &|feature_id| invoke_context.is_feature_active(feature_id),
+        invoke_context.get_bpf_compute_budget(),
+    );
     let mut executable = Executable::<BPFError, ThisInstructionMeter>::from_elf(
         &program.try_account_ref()?.data,
         None,
-        Config {
-            max_call_depth: bpf_compute_budget.max_call_depth,
-            stack_frame_size: bpf_compute_budget.stack_frame_size,
-            enable_instruction_meter: true,
-            enable_instruction_tracing: false,
-        },
+        settings.config(),
     )
     .map_err(|e| map_ebpf_error(invoke_context, e))?;
     let (_, elf_bytes) = executable
         .get_text_bytes()
         .map_err(|e| map_ebpf_error(invoke_context, e))?;
-    bpf_verifier::check(
-        elf_bytes,
-        !invoke_context.is_feature_active(&bpf_compute_budget_balancing::id()),
-    )
-    .map_err(|e| map_ebpf_error(invoke_context, EbpfError::UserError(e)))?;
+    bpf_verifier::check(elf_bytes, settings.is_program_size_cap)
+        .map_err(|e| map_ebpf_error(invoke_context, EbpfError::UserError(e)))?;
     let syscall_registry = syscalls::register_syscalls(invoke_context)
         .map_err(|e| map_ebpf_error(invoke_context, e))?;
     executable.set_syscall_registry(syscall_registry);
@@ -130,7 +168,7 @@ pub fn create_and_cache_executor(
 
 /// Default program heap size, allocators
 /// are expected to enforce this
-const DEFAULT_HEAP_SIZE: usize = 32 * 1024;
+pub const DEFAULT_HEAP_SIZE: usize = 32 * 1024;
 
 /// Create the BPF virtual machine
 pub fn create_vm<'a>(
@@ -346,16 +384,45 @@ impl Executor for BPFExecutor {
                     }
                 }
                 Err(error) => {
+                    // Release the VM's borrow of the invoke context
+                    drop(vm);
                     log!(
                         logger,
                         "Program {} BPF VM error: {}",
                         program.unsigned_key(),
                         error
                     );
+                    if let EbpfError::AccessViolation(_, _, vm_addr, len, _) = error {
+                        if let Ok(account_data_ranges) =
+                            serialized_account_data_ranges(program_id, parameter_accounts)
+                        {
+                            log!(
+                                logger,
+                                "Program {} access violation in {}",
+                                program.unsigned_key(),
+                                AccessViolationInfo::new(vm_addr, len, &account_data_ranges)
+                            );
+                        }
+                    }
                     let error = match error {
                         EbpfError::UserError(BPFError::SyscallError(
                             SyscallError::InstructionError(error),
                         )) => error,
+                        EbpfError::UserError(BPFError::SyscallError(
+                            SyscallError::AbortWithCode(code),
+                        )) => {
+                            invoke_context.record_abort_code(code);
+                            if let Ok(logger) = logger.try_borrow_mut() {
+                                if logger.log_enabled() {
+                                    logger.log_record(&ProgramLogRecord {
+                                        program_id: *program.unsigned_key(),
+                                        stack_height: invoke_depth,
+                                        kind: ProgramLogKind::AbortCode(code),
+                                    });
+                                }
+                            }
+                            BPFLoaderError::ProgramAborted.into()
+                        }
                         _ => BPFLoaderError::VirtualMachineFailedToRunProgram.into(),
                     };
 
@@ -374,12 +441,19 @@ impl Executor for BPFExecutor {
 mod tests {
     use super::*;
     use rand::Rng;
-    use solana_runtime::message_processor::{Executors, ThisInvokeContext};
+    use solana_rbpf::ebpf;
+    use solana_runtime::{
+        log_collector::LogCollector,
+        message_processor::{ExecutionHooks, Executors, ThisInvokeContext, TransactionInfo},
+        syscall_trace_recorder::SyscallTraceRecorder,
+    };
     use solana_sdk::{
         account::Account,
         feature_set::FeatureSet,
         instruction::InstructionError,
-        process_instruction::{BpfComputeBudget, MockInvokeContext},
+        process_instruction::{
+            syscall_byte_cost_key, BpfComputeBudget, MockInvokeContext, SyscallCostOverrides,
+        },
         pubkey::Pubkey,
         rent::Rent,
     };
@@ -619,6 +693,7 @@ mod tests {
                 log_units: 100,
                 log_64_units: 100,
                 create_program_address_units: 1500,
+                create_program_addresses_batch_base_units: 100,
                 invoke_units: 1000,
                 max_invoke_depth: 2,
                 sha256_base_cost: 85,
@@ -626,7 +701,28 @@ mod tests {
                 max_call_depth: 20,
                 stack_frame_size: 4096,
                 log_pubkey_units: 100,
+                system_transfer_units: 250,
+                secp256r1_verify_units: 25_000,
+                x25519_dh_units: 2_500,
+                address_encoding_base_units: 100,
+                address_encoding_byte_units: 1,
+                length_prefix_decode_units: 10,
+                instruction_ancestry_units: 100,
+                transaction_signature_units: 100,
+                recent_blockhash_units: 100,
+                fee_payer_units: 100,
+                runtime_limits_units: 100,
+                pseudorandom_base_units: 100,
+                pseudorandom_byte_units: 1,
+                chacha20_poly1305_base_units: 100,
+                chacha20_poly1305_byte_units: 1,
+                keccak256_base_units: 85,
+                keccak256_byte_units: 1,
+                hmac_sha256_base_units: 170,
+                hmac_sha256_byte_units: 1,
             },
+            ExecutionHooks::default(),
+            TransactionInfo::default(),
             Rc::new(RefCell::new(Executors::default())),
             None,
             Arc::new(FeatureSet::default()),
@@ -653,6 +749,160 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_bpf_loader_syscall_cost_overrides() {
+        let hash = ebpf::hash_symbol_name(b"sol_get_pseudorandom").to_le_bytes();
+        #[rustfmt::skip]
+        let program = [
+            0x18, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // r1 = MM_INPUT_START
+            0x00, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00,
+            0xb7, 0x02, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00, // r2 = 64
+            0x85, 0x00, 0x00, 0x00, hash[0], hash[1], hash[2], hash[3], // sol_get_pseudorandom
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // exit
+        ];
+
+        // Both the base and the per-byte cost are charged at the overridden price
+        let mut syscall_cost_overrides = SyscallCostOverrides::default();
+        syscall_cost_overrides.insert("sol_get_pseudorandom".to_string(), 10);
+        syscall_cost_overrides.insert(syscall_byte_cost_key("sol_get_pseudorandom"), 2);
+        let recorder = Arc::new(SyscallTraceRecorder::default());
+        let program_id = Pubkey::default();
+        let mut invoke_context = ThisInvokeContext::new(
+            &program_id,
+            Rent::default(),
+            vec![],
+            &[],
+            None,
+            BpfComputeBudget::default(),
+            ExecutionHooks {
+                syscall_cost_overrides: Some(Arc::new(syscall_cost_overrides)),
+                syscall_trace_collector: Some(recorder.clone()),
+                ..ExecutionHooks::default()
+            },
+            TransactionInfo::default(),
+            Rc::new(RefCell::new(Executors::default())),
+            None,
+            Arc::new(FeatureSet::all_enabled()),
+        );
+        let compute_meter = invoke_context.get_compute_meter();
+        let mut executable = Executable::<BPFError, ThisInstructionMeter>::from_text_bytes(
+            &program,
+            None,
+            Config::default(),
+        )
+        .unwrap();
+        executable.set_syscall_registry(syscalls::register_syscalls(&mut invoke_context).unwrap());
+        let mut input = [0_u8; 64];
+        let mut vm = create_vm(
+            &bpf_loader::id(),
+            executable.as_ref(),
+            &mut input,
+            &[],
+            &mut invoke_context,
+        )
+        .unwrap();
+        let mut instruction_meter = ThisInstructionMeter::new(compute_meter);
+        assert_eq!(
+            vm.execute_program_interpreted(&mut instruction_meter)
+                .unwrap(),
+            0
+        );
+
+        let events = recorder.take_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].name, "sol_get_pseudorandom");
+        assert_eq!(events[0].compute_units_consumed, 10 + 2 * 64);
+    }
+
+    #[test]
+    fn test_bpf_loader_abort_with_code() {
+        let hash = ebpf::hash_symbol_name(b"sol_abort_with_code").to_le_bytes();
+        #[rustfmt::skip]
+        let program = [
+            0xb7, 0x01, 0x00, 0x00, 0x2a, 0x00, 0x00, 0x00, // r1 = 42
+            0x85, 0x00, 0x00, 0x00, hash[0], hash[1], hash[2], hash[3], // sol_abort_with_code
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // exit
+        ];
+
+        let program_key = solana_sdk::pubkey::new_rand();
+        let log_collector = Rc::new(LogCollector::default());
+        let mut invoke_context = ThisInvokeContext::new(
+            &program_key,
+            Rent::default(),
+            vec![],
+            &[],
+            Some(log_collector.clone()),
+            BpfComputeBudget::default(),
+            ExecutionHooks::default(),
+            TransactionInfo::default(),
+            Rc::new(RefCell::new(Executors::default())),
+            None,
+            Arc::new(FeatureSet::all_enabled()),
+        );
+        let mut executable = Executable::<BPFError, ThisInstructionMeter>::from_text_bytes(
+            &program,
+            None,
+            Config::default(),
+        )
+        .unwrap();
+        executable.set_syscall_registry(syscalls::register_syscalls(&mut invoke_context).unwrap());
+        let executor = BPFExecutor { executable };
+        let program_account = Account::new_ref(1, 0, &bpf_loader::id());
+        let keyed_accounts = [KeyedAccount::new(&program_key, false, &program_account)];
+
+        // The abort is reported apart from other VM failures and its code is kept
+        assert_eq!(
+            Err(InstructionError::Custom(
+                BPFLoaderError::ProgramAborted as u32
+            )),
+            executor.execute(
+                &bpf_loader::id(),
+                &keyed_accounts,
+                &[],
+                &mut invoke_context,
+                false
+            )
+        );
+        assert_eq!(
+            log_collector.get_records(),
+            vec![ProgramLogRecord {
+                program_id: program_key,
+                stack_height: 1,
+                kind: ProgramLogKind::AbortCode(42),
+            }]
+        );
+        assert_eq!(invoke_context.get_abort_code(), Some(42));
+
+        // The code is recorded even when logging is disabled
+        let mut invoke_context = ThisInvokeContext::new(
+            &program_key,
+            Rent::default(),
+            vec![],
+            &[],
+            None,
+            BpfComputeBudget::default(),
+            ExecutionHooks::default(),
+            TransactionInfo::default(),
+            Rc::new(RefCell::new(Executors::default())),
+            None,
+            Arc::new(FeatureSet::all_enabled()),
+        );
+        assert_eq!(invoke_context.get_abort_code(), None);
+        assert_eq!(
+            Err(InstructionError::Custom(
+                BPFLoaderError::ProgramAborted as u32
+            )),
+            executor.execute(
+                &bpf_loader::id(),
+                &keyed_accounts,
+                &[],
+                &mut invoke_context,
+                false
+            )
+        );
+        assert_eq!(invoke_context.get_abort_code(), Some(42));
+    }
+
     #[test]
     fn test_bpf_loader_serialize_unaligned() {
         let program_id = solana_sdk::pubkey::new_rand();
@@ -0,0 +1,427 @@
+use crate::{syscalls::SyscallError, BPFError};
+use solana_rbpf::{
+    error::EbpfError,
+    memory_region::{AccessType, MemoryMapping},
+};
+use solana_sdk::{bpf_loader_deprecated, pubkey::Pubkey};
+use std::{
+    cmp::Ordering,
+    marker::PhantomData,
+    mem::{align_of, size_of},
+    ptr::NonNull,
+    slice::from_raw_parts_mut,
+    sync::atomic::{self, AtomicU64},
+};
+
+pub fn translate(
+    memory_mapping: &MemoryMapping,
+    access_type: AccessType,
+    vm_addr: u64,
+    len: u64,
+) -> Result<u64, EbpfError<BPFError>> {
+    memory_mapping.map::<BPFError>(access_type, vm_addr, len)
+}
+
+fn translate_type_inner<'a, T>(
+    memory_mapping: &MemoryMapping,
+    access_type: AccessType,
+    vm_addr: u64,
+    loader_id: &Pubkey,
+) -> Result<&'a mut T, EbpfError<BPFError>> {
+    if loader_id != &bpf_loader_deprecated::id()
+        && (vm_addr as u64 as *mut T).align_offset(align_of::<T>()) != 0
+    {
+        Err(SyscallError::UnalignedPointer.into())
+    } else {
+        unsafe {
+            match translate(memory_mapping, access_type, vm_addr, size_of::<T>() as u64) {
+                Ok(value) => Ok(&mut *(value as *mut T)),
+                Err(e) => Err(e),
+            }
+        }
+    }
+}
+pub fn translate_type_mut<'a, T>(
+    memory_mapping: &MemoryMapping,
+    vm_addr: u64,
+    loader_id: &Pubkey,
+) -> Result<&'a mut T, EbpfError<BPFError>> {
+    translate_type_inner::<T>(memory_mapping, AccessType::Store, vm_addr, loader_id)
+}
+pub fn translate_type<'a, T>(
+    memory_mapping: &MemoryMapping,
+    vm_addr: u64,
+    loader_id: &Pubkey,
+) -> Result<&'a T, EbpfError<BPFError>> {
+    match translate_type_inner::<T>(memory_mapping, AccessType::Load, vm_addr, loader_id) {
+        Ok(value) => Ok(&*value),
+        Err(e) => Err(e),
+    }
+}
+
+fn translate_slice_inner<'a, T>(
+    memory_mapping: &MemoryMapping,
+    access_type: AccessType,
+    vm_addr: u64,
+    len: u64,
+    loader_id: &Pubkey,
+) -> Result<&'a mut [T], EbpfError<BPFError>> {
+    if loader_id != &bpf_loader_deprecated::id()
+        && (vm_addr as u64 as *mut T).align_offset(align_of::<T>()) != 0
+    {
+        Err(SyscallError::UnalignedPointer.into())
+    } else if len == 0 {
+        Ok(unsafe { from_raw_parts_mut(NonNull::dangling().as_ptr(), len as usize) })
+    } else {
+        match translate(
+            memory_mapping,
+            access_type,
+            vm_addr,
+            len.saturating_mul(size_of::<T>() as u64),
+        ) {
+            Ok(value) => Ok(unsafe { from_raw_parts_mut(value as *mut T, len as usize) }),
+            Err(e) => Err(e),
+        }
+    }
+}
+pub fn translate_slice_mut<'a, T>(
+    memory_mapping: &MemoryMapping,
+    vm_addr: u64,
+    len: u64,
+    loader_id: &Pubkey,
+) -> Result<&'a mut [T], EbpfError<BPFError>> {
+    translate_slice_inner::<T>(memory_mapping, AccessType::Store, vm_addr, len, loader_id)
+}
+pub fn translate_slice<'a, T>(
+    memory_mapping: &MemoryMapping,
+    vm_addr: u64,
+    len: u64,
+    loader_id: &Pubkey,
+) -> Result<&'a [T], EbpfError<BPFError>> {
+    match translate_slice_inner::<T>(memory_mapping, AccessType::Load, vm_addr, len, loader_id) {
+        Ok(value) => Ok(&*value),
+        Err(e) => Err(e),
+    }
+}
+
+/// Source of the ids that tie each `MutTranslation` to the set it was requested from
+static NEXT_MUT_TRANSLATION_SET_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Mutable translation requested from a `MutTranslationSet`, redeemable once
+/// against that set after it has been checked for overlap
+pub struct MutTranslation<T: ?Sized> {
+    set_id: u64,
+    host_addr: u64,
+    len: u64,
+    _type: PhantomData<*mut T>,
+}
+
+/// Collects the translations a syscall needs and checks that none of its
+/// outputs overlap each other or any of its inputs before handing out any
+/// mutable borrows, so outputs can never alias
+pub struct MutTranslationSet<'a> {
+    id: u64,
+    memory_mapping: &'a MemoryMapping,
+    loader_id: &'a Pubkey,
+    reject_overlaps: bool,
+    outputs: Vec<(u64, u64)>,
+    inputs: Vec<(u64, u64)>,
+}
+impl<'a> MutTranslationSet<'a> {
+    pub fn new(memory_mapping: &'a MemoryMapping, loader_id: &'a Pubkey) -> Self {
+        Self {
+            id: NEXT_MUT_TRANSLATION_SET_ID.fetch_add(1, atomic::Ordering::Relaxed),
+            memory_mapping,
+            loader_id,
+            reject_overlaps: true,
+            outputs: vec![],
+            inputs: vec![],
+        }
+    }
+
+    /// Create a set whose `check` accepts overlapping translations
+    ///
+    /// # Safety
+    ///
+    /// Overlapping outputs alias each other and any inputs they overlap.  Only
+    /// for syscalls that accepted overlapping buffers before the
+    /// `reject_overlapping_syscall_buffers` feature is active.
+    pub unsafe fn new_unchecked(memory_mapping: &'a MemoryMapping, loader_id: &'a Pubkey) -> Self {
+        Self {
+            reject_overlaps: false,
+            ..Self::new(memory_mapping, loader_id)
+        }
+    }
+
+    /// Request a mutable `T` at `vm_addr`
+    pub fn add_type<T>(&mut self, vm_addr: u64) -> Result<MutTranslation<T>, EbpfError<BPFError>> {
+        let value = translate_type_mut::<T>(self.memory_mapping, vm_addr, self.loader_id)?;
+        Ok(self.push_output(value as *mut T as u64, size_of::<T>() as u64, 0))
+    }
+
+    /// Request a mutable slice of `len` `T`s at `vm_addr`
+    pub fn add_slice<T>(
+        &mut self,
+        vm_addr: u64,
+        len: u64,
+    ) -> Result<MutTranslation<[T]>, EbpfError<BPFError>> {
+        let slice = translate_slice_mut::<T>(self.memory_mapping, vm_addr, len, self.loader_id)?;
+        Ok(self.push_output(
+            slice.as_mut_ptr() as u64,
+            len.saturating_mul(size_of::<T>() as u64),
+            len,
+        ))
+    }
+
+    /// Translate a `T` at `vm_addr` that the outputs must not overlap
+    pub fn add_input_type<'b, T>(&mut self, vm_addr: u64) -> Result<&'b T, EbpfError<BPFError>> {
+        let value = translate_type::<T>(self.memory_mapping, vm_addr, self.loader_id)?;
+        push_range(
+            &mut self.inputs,
+            value as *const T as u64,
+            size_of::<T>() as u64,
+        );
+        Ok(value)
+    }
+
+    /// Translate a slice of `len` `T`s at `vm_addr` that the outputs must not overlap
+    pub fn add_input_slice<'b, T>(
+        &mut self,
+        vm_addr: u64,
+        len: u64,
+    ) -> Result<&'b [T], EbpfError<BPFError>> {
+        let slice = translate_slice::<T>(self.memory_mapping, vm_addr, len, self.loader_id)?;
+        push_range(
+            &mut self.inputs,
+            slice.as_ptr() as u64,
+            len.saturating_mul(size_of::<T>() as u64),
+        );
+        Ok(slice)
+    }
+
+    fn push_output<T: ?Sized>(&mut self, host_addr: u64, size: u64, len: u64) -> MutTranslation<T> {
+        push_range(&mut self.outputs, host_addr, size);
+        MutTranslation {
+            set_id: self.id,
+            host_addr,
+            len,
+            _type: PhantomData,
+        }
+    }
+
+    /// Check that no two requested outputs overlap and that no output
+    /// overlaps an input.  Like the translate functions, the borrows handed
+    /// out live as long as the VM memory rather than `memory_mapping`
+    pub fn check<'b>(mut self) -> Result<MutTranslations<'b>, EbpfError<BPFError>> {
+        if self.reject_overlaps {
+            self.outputs.sort_unstable();
+            let outputs = &self.outputs;
+            if outputs.windows(2).any(|ranges| ranges[0].1 > ranges[1].0)
+                || self
+                    .inputs
+                    .iter()
+                    .any(|input| overlaps_any(outputs, *input))
+            {
+                return Err(SyscallError::OverlappingMutTranslations.into());
+            }
+        }
+        Ok(MutTranslations {
+            set_id: self.id,
+            _lifetime: PhantomData,
+        })
+    }
+}
+
+fn push_range(ranges: &mut Vec<(u64, u64)>, host_addr: u64, size: u64) {
+    // Empty translations do not reference VM memory and cannot overlap
+    if size > 0 {
+        ranges.push((host_addr, host_addr.saturating_add(size)));
+    }
+}
+
+/// Whether `range` overlaps any of the sorted, disjoint `ranges`
+fn overlaps_any(ranges: &[(u64, u64)], range: (u64, u64)) -> bool {
+    // The first range ending after `range` starts is the only candidate
+    let index = ranges
+        .binary_search_by(|(_, end)| {
+            if *end <= range.0 {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            }
+        })
+        .unwrap_or_else(|index| index);
+    ranges
+        .get(index)
+        .map(|(start, _)| *start < range.1)
+        .unwrap_or(false)
+}
+
+/// Proof that the translations of a `MutTranslationSet` do not overlap,
+/// redeeming only the translations requested from that set
+pub struct MutTranslations<'a> {
+    set_id: u64,
+    _lifetime: PhantomData<&'a mut ()>,
+}
+impl<'a> MutTranslations<'a> {
+    pub fn get<T>(&self, translation: MutTranslation<T>) -> Result<&'a mut T, EbpfError<BPFError>> {
+        self.check_set_id(translation.set_id)?;
+        Ok(unsafe { &mut *(translation.host_addr as *mut T) })
+    }
+
+    pub fn get_slice<T>(
+        &self,
+        translation: MutTranslation<[T]>,
+    ) -> Result<&'a mut [T], EbpfError<BPFError>> {
+        self.check_set_id(translation.set_id)?;
+        Ok(
+            unsafe {
+                from_raw_parts_mut(translation.host_addr as *mut T, translation.len as usize)
+            },
+        )
+    }
+
+    fn check_set_id(&self, set_id: u64) -> Result<(), EbpfError<BPFError>> {
+        if set_id == self.set_id {
+            Ok(())
+        } else {
+            Err(SyscallError::ForeignMutTranslation.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_rbpf::{memory_region::MemoryRegion, vm::Config};
+    use solana_sdk::bpf_loader;
+
+    const DEFAULT_CONFIG: Config = Config {
+        max_call_depth: 20,
+        stack_frame_size: 4_096,
+        enable_instruction_meter: true,
+        enable_instruction_tracing: false,
+    };
+
+    fn memory_mapping(data: &[u64]) -> MemoryMapping {
+        MemoryMapping::new(
+            vec![MemoryRegion {
+                host_addr: data.as_ptr() as *const _ as u64,
+                vm_addr: 96,
+                len: (data.len() * size_of::<u64>()) as u64,
+                vm_gap_shift: 63,
+                is_writable: true,
+            }],
+            &DEFAULT_CONFIG,
+        )
+    }
+
+    #[test]
+    fn test_mut_translation_set() {
+        let data = [0u64; 4];
+        let memory_mapping = memory_mapping(&data);
+        let loader_id = bpf_loader::id();
+
+        // Disjoint and adjacent translations are handed out
+        let mut translations = MutTranslationSet::new(&memory_mapping, &loader_id);
+        let first = translations.add_type::<u64>(96).unwrap();
+        let rest = translations.add_slice::<u64>(104, 3).unwrap();
+        let empty = translations.add_slice::<u64>(104, 0).unwrap();
+        let translations = translations.check().unwrap();
+        *translations.get(first).unwrap() = 1;
+        translations
+            .get_slice(rest)
+            .unwrap()
+            .copy_from_slice(&[2, 3, 4]);
+        assert!(translations.get_slice(empty).unwrap().is_empty());
+        assert_eq!(data, [1, 2, 3, 4]);
+
+        // Overlapping translations are rejected, in either order
+        for (type_addr, slice_addr) in &[(104, 96), (96, 96), (120, 112)] {
+            let mut translations = MutTranslationSet::new(&memory_mapping, &loader_id);
+            translations.add_slice::<u64>(*slice_addr, 2).unwrap();
+            translations.add_type::<u64>(*type_addr).unwrap();
+            assert_eq!(
+                translations.check().err(),
+                Some(SyscallError::OverlappingMutTranslations.into())
+            );
+        }
+
+        // Translation failures are reported when requested
+        let mut translations = MutTranslationSet::new(&memory_mapping, &loader_id);
+        match translations.add_slice::<u64>(104, 4) {
+            Err(EbpfError::AccessViolation(_, _, 104, 32, _)) => (),
+            _ => panic!(),
+        }
+        assert_eq!(
+            translations.add_type::<u64>(97).err(),
+            Some(SyscallError::UnalignedPointer.into())
+        );
+    }
+
+    #[test]
+    fn test_mut_translation_set_inputs() {
+        let data = [1u64, 2, 3, 4];
+        let memory_mapping = memory_mapping(&data);
+        let loader_id = bpf_loader::id();
+
+        // Inputs may overlap each other but not an output
+        let mut translations = MutTranslationSet::new(&memory_mapping, &loader_id);
+        let output = translations.add_slice::<u64>(112, 2).unwrap();
+        assert_eq!(translations.add_input_slice::<u64>(96, 2).unwrap(), &[1, 2]);
+        assert_eq!(*translations.add_input_type::<u64>(104).unwrap(), 2);
+        assert!(translations
+            .add_input_slice::<u64>(112, 0)
+            .unwrap()
+            .is_empty());
+        let translations = translations.check().unwrap();
+        translations
+            .get_slice(output)
+            .unwrap()
+            .copy_from_slice(&[5, 6]);
+        assert_eq!(data, [1, 2, 5, 6]);
+
+        for (input_addr, input_len) in &[(96, 4), (104, 2), (112, 1), (120, 1)] {
+            let mut translations = MutTranslationSet::new(&memory_mapping, &loader_id);
+            translations.add_type::<u64>(96).unwrap();
+            translations.add_slice::<u64>(112, 2).unwrap();
+            translations
+                .add_input_slice::<u64>(*input_addr, *input_len)
+                .unwrap();
+            assert_eq!(
+                translations.check().err(),
+                Some(SyscallError::OverlappingMutTranslations.into())
+            );
+        }
+
+        // Unless the set was created unchecked
+        let mut translations =
+            unsafe { MutTranslationSet::new_unchecked(&memory_mapping, &loader_id) };
+        translations.add_slice::<u64>(96, 2).unwrap();
+        translations.add_type::<u64>(104).unwrap();
+        translations.add_input_type::<u64>(96).unwrap();
+        assert!(translations.check().is_ok());
+    }
+
+    #[test]
+    fn test_mut_translation_from_another_set() {
+        let data = [0u64; 2];
+        let memory_mapping = memory_mapping(&data);
+        let loader_id = bpf_loader::id();
+
+        let mut first_set = MutTranslationSet::new(&memory_mapping, &loader_id);
+        let first = first_set.add_type::<u64>(96).unwrap();
+        let mut second_set = MutTranslationSet::new(&memory_mapping, &loader_id);
+        let second = second_set.add_type::<u64>(96).unwrap();
+        let _first_set = first_set.check().unwrap();
+        let second_set = second_set.check().unwrap();
+
+        // Redeeming against the wrong set would alias the other set's borrow
+        assert_eq!(
+            second_set.get(first).err(),
+            Some(SyscallError::ForeignMutTranslation.into())
+        );
+        *second_set.get(second).unwrap() = 1;
+        assert_eq!(data, [1, 0]);
+    }
+}
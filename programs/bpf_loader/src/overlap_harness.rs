@@ -0,0 +1,163 @@
+//! Places syscall buffers in VM memory so that outputs overlap inputs, each other or account
+//! regions at every offset, for tests checking that overlapping translations are rejected
+
+use crate::BPFError;
+use solana_rbpf::{
+    ebpf::MM_INPUT_START,
+    error::EbpfError,
+    memory_region::{MemoryMapping, MemoryRegion},
+    vm::Config,
+};
+use std::{mem::size_of, slice::from_raw_parts_mut};
+
+const DEFAULT_CONFIG: Config = Config {
+    max_call_depth: 20,
+    stack_frame_size: 4_096,
+    enable_instruction_meter: true,
+    enable_instruction_tracing: false,
+};
+
+/// Bytes of `len` zeroes starting with `inputs`, allocated with the alignment of `u64` so that
+/// offsets that are multiples of an alignment are also aligned on the host
+fn aligned_region(inputs: &[&[u8]], len: usize) -> Vec<u64> {
+    let mut region = vec![0u64; (len + size_of::<u64>() - 1) / size_of::<u64>()];
+    let bytes = region_bytes(&mut region, len);
+    let mut offset = 0;
+    for input in inputs {
+        bytes[offset..offset + input.len()].copy_from_slice(input);
+        offset += input.len();
+    }
+    region
+}
+
+fn region_bytes(region: &mut [u64], len: usize) -> &mut [u8] {
+    unsafe { from_raw_parts_mut(region.as_mut_ptr() as *mut u8, len) }
+}
+
+/// Map `region` writable at `MM_INPUT_START`
+fn input_mapping(region: &mut [u8]) -> MemoryMapping {
+    MemoryMapping::new(
+        vec![MemoryRegion {
+            host_addr: region.as_mut_ptr() as *const _ as u64,
+            vm_addr: MM_INPUT_START,
+            len: region.len() as u64,
+            vm_gap_shift: 63,
+            is_writable: true,
+        }],
+        &DEFAULT_CONFIG,
+    )
+}
+
+/// Whether the buffers of `len` and `other_len` bytes at `offset` and `other_offset` overlap,
+/// empty buffers never do
+fn overlaps(offset: usize, len: usize, other_offset: usize, other_len: usize) -> bool {
+    len > 0 && other_len > 0 && offset < other_offset + other_len && other_offset < offset + len
+}
+
+/// Lay out `inputs` at the start of a single writable region followed by `output_len` spare
+/// bytes, then `call` the syscall with the output placed at every offset of the region, so
+/// the output overlaps each input, straddles input boundaries and finally stands alone.
+/// `check` receives the syscall's result, the bytes at the output location and whether the
+/// output overlapped the inputs.
+pub fn for_each_overlapping_output(
+    inputs: &[&[u8]],
+    output_len: usize,
+    call: impl FnMut(&MemoryMapping, u64) -> Result<u64, EbpfError<BPFError>>,
+    check: impl Fn(Result<u64, EbpfError<BPFError>>, &[u8], bool),
+) {
+    for_each_overlapping_aligned_output(inputs, output_len, 1, call, check)
+}
+
+/// Like `for_each_overlapping_output`, but only places the output at offsets that are
+/// multiples of `align`, for outputs of types that must be aligned
+pub fn for_each_overlapping_aligned_output(
+    inputs: &[&[u8]],
+    output_len: usize,
+    align: usize,
+    mut call: impl FnMut(&MemoryMapping, u64) -> Result<u64, EbpfError<BPFError>>,
+    check: impl Fn(Result<u64, EbpfError<BPFError>>, &[u8], bool),
+) {
+    let inputs_len = inputs.iter().map(|input| input.len()).sum::<usize>();
+    let region_len = inputs_len + output_len;
+    for output_offset in (0..=inputs_len).step_by(align) {
+        let mut region = aligned_region(inputs, region_len);
+        let region = region_bytes(&mut region, region_len);
+        let memory_mapping = input_mapping(region);
+        let result = call(&memory_mapping, MM_INPUT_START + output_offset as u64);
+        check(
+            result,
+            &region[output_offset..output_offset + output_len],
+            overlaps(0, inputs_len, output_offset, output_len),
+        );
+    }
+}
+
+/// Lay out `inputs` followed by room for two outputs of `output_lens` bytes, then `call` the
+/// syscall with the first output at a fixed address and the second placed at every offset from
+/// ending right before the first output to starting right after it.  `check` receives the
+/// syscall's result, the bytes at both output locations and whether the outputs overlapped
+/// each other.  The outputs never overlap the inputs.
+pub fn for_each_overlapping_output_pair(
+    inputs: &[&[u8]],
+    output_lens: (usize, usize),
+    mut call: impl FnMut(&MemoryMapping, u64, u64) -> Result<u64, EbpfError<BPFError>>,
+    check: impl Fn(Result<u64, EbpfError<BPFError>>, &[u8], &[u8], bool),
+) {
+    let (first_len, second_len) = output_lens;
+    let inputs_len = inputs.iter().map(|input| input.len()).sum::<usize>();
+    let first_offset = inputs_len + second_len;
+    let region_len = first_offset + first_len + second_len;
+    for second_offset in inputs_len..=first_offset + first_len {
+        let mut region = aligned_region(inputs, region_len);
+        let region = region_bytes(&mut region, region_len);
+        let memory_mapping = input_mapping(region);
+        let result = call(
+            &memory_mapping,
+            MM_INPUT_START + first_offset as u64,
+            MM_INPUT_START + second_offset as u64,
+        );
+        check(
+            result,
+            &region[first_offset..first_offset + first_len],
+            &region[second_offset..second_offset + second_len],
+            overlaps(first_offset, first_len, second_offset, second_len),
+        );
+    }
+}
+
+/// Every address, `step` bytes apart, of a `size` byte buffer from ending right before the
+/// `len` byte region at `addr` to starting right after it, with whether the buffer overlaps the
+/// region.  Used to place account fields around the data of other accounts.
+pub fn placements_around(addr: u64, len: u64, size: u64, step: u64) -> Vec<(u64, bool)> {
+    (addr.saturating_sub(size)..=addr + len)
+        .step_by(step as usize)
+        .map(|placement| {
+            (
+                placement,
+                overlaps(
+                    addr as usize,
+                    len as usize,
+                    placement as usize,
+                    size as usize,
+                ),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placements_around() {
+        assert_eq!(
+            placements_around(16, 8, 8, 4),
+            vec![(8, false), (12, true), (16, true), (20, true), (24, false)]
+        );
+        assert_eq!(
+            placements_around(16, 0, 8, 8),
+            vec![(8, false), (16, false)]
+        );
+    }
+}
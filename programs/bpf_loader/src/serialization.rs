@@ -6,6 +6,7 @@ use solana_sdk::{
 use std::{
     io::prelude::*,
     mem::{align_of, size_of},
+    ops::Range,
 };
 
 /// Look for a duplicate account and return its position if found
@@ -43,6 +44,55 @@ pub fn deserialize_parameters(
     }
 }
 
+/// Byte ranges of each account's data within the serialized parameters,
+/// duplicate accounts share the range of the account they duplicate
+pub fn serialized_account_data_ranges(
+    loader_id: &Pubkey,
+    keyed_accounts: &[KeyedAccount],
+) -> Result<Vec<Range<usize>>, InstructionError> {
+    let is_aligned = *loader_id != bpf_loader_deprecated::id();
+    let mut ranges: Vec<Range<usize>> = Vec::with_capacity(keyed_accounts.len());
+    let mut start = size_of::<u64>(); // number of accounts
+    for (i, keyed_account) in keyed_accounts.iter().enumerate() {
+        let (is_dup, position) = is_dup(&keyed_accounts[..i], keyed_account);
+        start += size_of::<u8>(); // position
+        if is_dup {
+            if is_aligned {
+                start += 7; // padding to 64-bit aligned
+            }
+            let range = ranges[position].clone();
+            ranges.push(range);
+        } else if is_aligned {
+            start += size_of::<u8>() // is_signer
+                + size_of::<u8>() // is_writable
+                + size_of::<u8>() // executable
+                + 4 // padding to 128-bit aligned
+                + size_of::<Pubkey>() // key
+                + size_of::<Pubkey>() // owner
+                + size_of::<u64>() // lamports
+                + size_of::<u64>(); // data length
+            let end = start + keyed_account.data_len()? + MAX_PERMITTED_DATA_INCREASE;
+            ranges.push(start..end);
+            start = end;
+            start += (start as *const u8).align_offset(align_of::<u128>());
+            start += size_of::<u64>(); // rent_epoch
+        } else {
+            start += size_of::<u8>() // is_signer
+                + size_of::<u8>() // is_writable
+                + size_of::<Pubkey>() // key
+                + size_of::<u64>() // lamports
+                + size_of::<u64>(); // data length
+            let end = start + keyed_account.data_len()?;
+            ranges.push(start..end);
+            start = end
+                + size_of::<Pubkey>() // owner
+                + size_of::<u8>() // executable
+                + size_of::<u64>(); // rent_epoch
+        }
+    }
+    Ok(ranges)
+}
+
 pub fn serialize_parameters_unaligned(
     program_id: &Pubkey,
     keyed_accounts: &[KeyedAccount],
@@ -375,6 +425,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_serialized_account_data_ranges() {
+        let program_id = solana_sdk::pubkey::new_rand();
+        let dup_key = solana_sdk::pubkey::new_rand();
+        let keys = vec![
+            dup_key,
+            solana_sdk::pubkey::new_rand(),
+            dup_key,
+            solana_sdk::pubkey::new_rand(),
+        ];
+        let accounts = [
+            RefCell::new(Account::new(1, 5, &bpf_loader::id())),
+            RefCell::new(Account::new(2, 9, &bpf_loader::id())),
+            RefCell::new(Account::new(1, 5, &bpf_loader::id())),
+            RefCell::new(Account::new(3, 0, &bpf_loader::id())),
+        ];
+        for (i, account) in accounts.iter().enumerate() {
+            for (j, byte) in account.borrow_mut().data.iter_mut().enumerate() {
+                *byte = (i * 16 + j) as u8 + 1;
+            }
+        }
+        let keyed_accounts: Vec<_> = keys
+            .iter()
+            .zip(&accounts)
+            .map(|(key, account)| KeyedAccount::new(&key, false, &account))
+            .collect();
+        let instruction_data = vec![1u8, 2, 3];
+
+        for loader_id in &[bpf_loader::id(), bpf_loader_deprecated::id()] {
+            let serialized =
+                serialize_parameters(loader_id, &program_id, &keyed_accounts, &instruction_data)
+                    .unwrap();
+            let ranges = serialized_account_data_ranges(loader_id, &keyed_accounts).unwrap();
+            assert_eq!(ranges.len(), keyed_accounts.len());
+            assert_eq!(ranges[0], ranges[2]);
+            for (i, range) in ranges.iter().enumerate() {
+                assert!(range.end <= serialized.len());
+                let account = accounts[if i == 2 { 0 } else { i }].borrow();
+                let data = &account.data;
+                assert_eq!(
+                    &serialized[range.start..range.start + data.len()],
+                    &data[..]
+                );
+                let expected_len = if *loader_id == bpf_loader::id() {
+                    data.len() + MAX_PERMITTED_DATA_INCREASE
+                } else {
+                    data.len()
+                };
+                assert_eq!(range.len(), expected_len);
+            }
+        }
+    }
+
     // the old bpf_loader in-program deserializer bpf_loader::id()
     #[allow(clippy::type_complexity)]
     pub unsafe fn deserialize_unaligned<'a>(
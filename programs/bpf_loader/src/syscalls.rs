@@ -1,39 +1,73 @@
-use crate::{alloc, BPFError};
+use crate::{
+    alloc,
+    memory::{
+        translate_slice, translate_slice_mut, translate_type, translate_type_mut, MutTranslation,
+        MutTranslationSet,
+    },
+    BPFError, ExecutableSettings,
+};
 use alloc::Alloc;
 use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use ring::signature::{UnparsedPublicKey, ECDSA_P256_SHA256_FIXED};
 use solana_rbpf::{
     ebpf::MM_HEAP_START,
     error::EbpfError,
-    memory_region::{AccessType, MemoryMapping},
+    memory_region::MemoryMapping,
     question_mark,
     vm::{EbpfVm, SyscallObject, SyscallRegistry},
 };
-use solana_runtime::message_processor::MessageProcessor;
+use solana_runtime::{message_processor::MessageProcessor, system_instruction_processor};
 use solana_sdk::{
     account::Account,
     account_info::AccountInfo,
-    bpf_loader_deprecated,
+    base58, bech32, bpf_loader_deprecated, chacha20_poly1305,
+    clock::Epoch,
+    compute_profiler::InvocationProfiler,
     entrypoint::{MAX_PERMITTED_DATA_INCREASE, SUCCESS},
     feature_set::{
-        pubkey_log_syscall_enabled, ristretto_mul_syscall_enabled, sha256_syscall_enabled,
-        sol_log_compute_units_syscall,
+        abort_with_code_syscall_enabled, address_encoding_syscalls_enabled,
+        chacha20_poly1305_syscall_enabled, create_program_addresses_batch_syscall_enabled,
+        fee_payer_syscall_enabled, get_max_invoke_depth_syscall_enabled, heap_free_enabled,
+        hmac_sha256_syscall_enabled, instruction_ancestry_syscall_enabled,
+        keccak256_context_syscalls_enabled, length_prefix_decode_syscall_enabled,
+        log_data_syscall_enabled, pseudorandom_syscall_enabled, pubkey_log_syscall_enabled,
+        recent_blockhash_syscall_enabled, reject_overlapping_syscall_buffers,
+        ristretto_mul_syscall_enabled, runtime_limits_syscall_enabled,
+        runtime_version_syscall_enabled, secp256r1_verify_syscall_enabled,
+        sha256_context_syscalls_enabled, sha256_syscall_enabled, sol_log_compute_units_syscall,
+        system_transfer_syscall_enabled, transaction_signature_syscall_enabled,
+        x25519_dh_syscall_enabled, FeatureSet,
     },
-    hash::{Hasher, HASH_BYTES},
+    hash::{hmacv, Hash, Hasher, HASH_BYTES},
     instruction::{AccountMeta, Instruction, InstructionError},
+    keccak::Keccak256Context,
     keyed_account::KeyedAccount,
+    length_prefix::{self, LengthPrefix},
     message::Message,
-    process_instruction::{stable_log, ComputeMeter, InvokeContext, Logger},
+    process_instruction::{
+        stable_log, syscall_address_cost_key, syscall_byte_cost_key, BpfComputeBudget,
+        ComputeMeter, InvokeContext, Logger, ProgramLogKind, ProgramLogRecord, SyscallFault,
+        SyscallFaults, SyscallFilter, SyscallTraceCollector, SyscallTraceEvent,
+    },
     program_error::ProgramError,
-    pubkey::{Pubkey, PubkeyError, MAX_SEEDS},
+    pseudorandom::derive_pseudorandom,
+    pubkey::{Pubkey, PubkeyError, MAX_PROGRAM_ADDRESSES, MAX_SEEDS},
+    runtime_limits::RuntimeLimits,
+    secp256r1,
+    signature::Signature,
+    system_instruction, system_program,
+    transaction_info::SIGNATURE_BYTES,
+    x25519,
 };
 use std::{
     alloc::Layout,
     cell::{RefCell, RefMut},
+    collections::HashMap,
     convert::TryFrom,
     mem::{align_of, size_of},
     rc::Rc,
-    slice::from_raw_parts_mut,
     str::{from_utf8, Utf8Error},
+    sync::Arc,
 };
 use thiserror::Error as ThisError;
 
@@ -49,6 +83,8 @@ pub enum SyscallError {
     Abort,
     #[error("BPF program Panicked in {0} at {1}:{2}")]
     Panic(String, u64, u64),
+    #[error("BPF program aborted with code {0}")]
+    AbortWithCode(u64),
     #[error("cannot borrow invoke context")]
     InvokeContextBorrowFailed,
     #[error("malformed signer seed: {0}: {1:?}")]
@@ -63,6 +99,20 @@ pub enum SyscallError {
     UnalignedPointer,
     #[error("Too many signers")]
     TooManySigners,
+    #[error("Invalid hash context handle")]
+    InvalidHashContext,
+    #[error("Too many hash contexts")]
+    TooManyHashContexts,
+    #[error("Invalid ChaCha20-Poly1305 {0} length")]
+    InvalidAeadLength(&'static str),
+    #[error("Overlapping mutable memory translations")]
+    OverlappingMutTranslations,
+    #[error("Mutable memory translation redeemed against another set")]
+    ForeignMutTranslation,
+    #[error("Syscall {0} is disabled")]
+    SyscallDisabled(&'static str),
+    #[error("Injected fault in syscall {0}")]
+    InjectedFault(&'static str),
 }
 impl From<SyscallError> for EbpfError<BPFError> {
     fn from(error: SyscallError) -> Self {
@@ -86,50 +136,335 @@ impl SyscallConsume for Rc<RefCell<dyn ComputeMeter>> {
 /// Program heap allocators are intended to allocate/free from a given
 /// chunk of memory.  The specific allocator implementation is
 /// selectable at build-time.
-/// Only one allocator is currently supported
+/// The free list allocator is used once the `heap_free_enabled` feature is active
 
 /// Simple bump allocator, never frees
 use crate::allocator_bump::BPFAllocator;
+/// Bump allocator that reuses freed memory
+use crate::allocator_free_list::BPFFreeListAllocator;
 
 pub fn register_syscalls(
     invoke_context: &mut dyn InvokeContext,
 ) -> Result<SyscallRegistry, EbpfError<BPFError>> {
     let mut syscall_registry = SyscallRegistry::default();
+    register_syscalls_inner(
+        &|feature_id| invoke_context.is_feature_active(feature_id),
+        &mut syscall_registry,
+        &mut vec![],
+    )?;
+    Ok(syscall_registry)
+}
 
-    syscall_registry.register_syscall_by_name(b"abort", SyscallAbort::call)?;
-    syscall_registry.register_syscall_by_name(b"sol_panic_", SyscallPanic::call)?;
-    syscall_registry.register_syscall_by_name(b"sol_log_", SyscallLog::call)?;
-    syscall_registry.register_syscall_by_name(b"sol_log_64_", SyscallLogU64::call)?;
+/// Register the syscalls enabled under `is_feature_active`, recording each one's name
+fn register_syscalls_inner(
+    is_feature_active: &dyn Fn(&Pubkey) -> bool,
+    syscall_registry: &mut SyscallRegistry,
+    names: &mut Vec<&'static str>,
+) -> Result<(), EbpfError<BPFError>> {
+    macro_rules! register {
+        ($name:literal, $function:expr) => {{
+            names.push($name);
+            syscall_registry.register_syscall_by_name($name.as_bytes(), $function)?;
+        }};
+    }
 
-    if invoke_context.is_feature_active(&sol_log_compute_units_syscall::id()) {
-        syscall_registry
-            .register_syscall_by_name(b"sol_log_compute_units_", SyscallLogBpfComputeUnits::call)?;
+    register!("abort", SyscallTracer::<SyscallAbort>::call);
+    register!("sol_panic_", SyscallTracer::<SyscallPanic>::call);
+    if is_feature_active(&abort_with_code_syscall_enabled::id()) {
+        register!(
+            "sol_abort_with_code",
+            SyscallTracer::<SyscallAbortWithCode>::call
+        );
     }
+    register!("sol_log_", SyscallTracer::<SyscallLog>::call);
+    register!("sol_log_64_", SyscallTracer::<SyscallLogU64>::call);
 
-    if invoke_context.is_feature_active(&pubkey_log_syscall_enabled::id()) {
-        syscall_registry.register_syscall_by_name(b"sol_log_pubkey", SyscallLogPubkey::call)?;
+    if is_feature_active(&sol_log_compute_units_syscall::id()) {
+        register!(
+            "sol_log_compute_units_",
+            SyscallTracer::<SyscallLogBpfComputeUnits>::call
+        );
     }
 
-    if invoke_context.is_feature_active(&sha256_syscall_enabled::id()) {
-        syscall_registry.register_syscall_by_name(b"sol_sha256", SyscallSha256::call)?;
+    if is_feature_active(&pubkey_log_syscall_enabled::id()) {
+        register!("sol_log_pubkey", SyscallTracer::<SyscallLogPubkey>::call);
     }
 
-    if invoke_context.is_feature_active(&ristretto_mul_syscall_enabled::id()) {
-        syscall_registry
-            .register_syscall_by_name(b"sol_ristretto_mul", SyscallRistrettoMul::call)?;
+    if is_feature_active(&log_data_syscall_enabled::id()) {
+        register!("sol_log_data", SyscallTracer::<SyscallLogData>::call);
     }
 
-    syscall_registry.register_syscall_by_name(
-        b"sol_create_program_address",
-        SyscallCreateProgramAddress::call,
-    )?;
-    syscall_registry
-        .register_syscall_by_name(b"sol_invoke_signed_c", SyscallInvokeSignedC::call)?;
-    syscall_registry
-        .register_syscall_by_name(b"sol_invoke_signed_rust", SyscallInvokeSignedRust::call)?;
-    syscall_registry.register_syscall_by_name(b"sol_alloc_free_", SyscallAllocFree::call)?;
+    if is_feature_active(&sha256_syscall_enabled::id()) {
+        register!("sol_sha256", SyscallTracer::<SyscallSha256>::call);
+    }
 
-    Ok(syscall_registry)
+    if is_feature_active(&hmac_sha256_syscall_enabled::id()) {
+        register!("sol_hmac_sha256", SyscallTracer::<SyscallHmacSha256>::call);
+    }
+
+    if is_feature_active(&sha256_context_syscalls_enabled::id()) {
+        register!("sol_sha256_init", SyscallTracer::<SyscallSha256Init>::call);
+        register!(
+            "sol_sha256_update",
+            SyscallTracer::<SyscallSha256Update>::call
+        );
+        register!(
+            "sol_sha256_final",
+            SyscallTracer::<SyscallSha256Final>::call
+        );
+    }
+
+    if is_feature_active(&keccak256_context_syscalls_enabled::id()) {
+        register!(
+            "sol_keccak256_init",
+            SyscallTracer::<SyscallKeccak256Init>::call
+        );
+        register!(
+            "sol_keccak256_update",
+            SyscallTracer::<SyscallKeccak256Update>::call
+        );
+        register!(
+            "sol_keccak256_final",
+            SyscallTracer::<SyscallKeccak256Final>::call
+        );
+    }
+
+    if is_feature_active(&chacha20_poly1305_syscall_enabled::id()) {
+        register!(
+            "sol_chacha20_poly1305_open",
+            SyscallTracer::<SyscallChaCha20Poly1305Open>::call
+        );
+    }
+
+    if is_feature_active(&secp256r1_verify_syscall_enabled::id()) {
+        register!(
+            "sol_secp256r1_verify",
+            SyscallTracer::<SyscallSecp256r1Verify>::call
+        );
+    }
+
+    if is_feature_active(&x25519_dh_syscall_enabled::id()) {
+        register!("sol_x25519_dh", SyscallTracer::<SyscallX25519Dh>::call);
+    }
+
+    if is_feature_active(&address_encoding_syscalls_enabled::id()) {
+        register!(
+            "sol_base58_encode",
+            SyscallTracer::<SyscallBase58Encode>::call
+        );
+        register!(
+            "sol_base58_decode",
+            SyscallTracer::<SyscallBase58Decode>::call
+        );
+        register!(
+            "sol_bech32_encode",
+            SyscallTracer::<SyscallBech32Encode>::call
+        );
+        register!(
+            "sol_bech32_decode",
+            SyscallTracer::<SyscallBech32Decode>::call
+        );
+    }
+
+    if is_feature_active(&length_prefix_decode_syscall_enabled::id()) {
+        register!(
+            "sol_decode_length_prefix",
+            SyscallTracer::<SyscallDecodeLengthPrefix>::call
+        );
+    }
+
+    if is_feature_active(&ristretto_mul_syscall_enabled::id()) {
+        register!(
+            "sol_ristretto_mul",
+            SyscallTracer::<SyscallRistrettoMul>::call
+        );
+    }
+
+    register!(
+        "sol_create_program_address",
+        SyscallTracer::<SyscallCreateProgramAddress>::call
+    );
+    if is_feature_active(&create_program_addresses_batch_syscall_enabled::id()) {
+        register!(
+            "sol_create_program_addresses_batch",
+            SyscallTracer::<SyscallCreateProgramAddressesBatch>::call
+        );
+    }
+
+    if is_feature_active(&get_max_invoke_depth_syscall_enabled::id()) {
+        register!(
+            "sol_get_max_invoke_depth",
+            SyscallTracer::<SyscallGetMaxInvokeDepth>::call
+        );
+    }
+
+    if is_feature_active(&runtime_version_syscall_enabled::id()) {
+        register!(
+            "sol_get_runtime_version",
+            SyscallTracer::<SyscallGetRuntimeVersion>::call
+        );
+    }
+
+    if is_feature_active(&instruction_ancestry_syscall_enabled::id()) {
+        register!(
+            "sol_get_instruction_ancestry",
+            SyscallTracer::<SyscallGetInstructionAncestry>::call
+        );
+    }
+
+    if is_feature_active(&transaction_signature_syscall_enabled::id()) {
+        register!(
+            "sol_get_transaction_signature",
+            SyscallTracer::<SyscallGetTransactionSignature>::call
+        );
+    }
+
+    if is_feature_active(&recent_blockhash_syscall_enabled::id()) {
+        register!(
+            "sol_get_recent_blockhash_of_tx",
+            SyscallTracer::<SyscallGetRecentBlockhash>::call
+        );
+    }
+
+    if is_feature_active(&fee_payer_syscall_enabled::id()) {
+        register!(
+            "sol_get_fee_payer",
+            SyscallTracer::<SyscallGetFeePayer>::call
+        );
+    }
+
+    if is_feature_active(&runtime_limits_syscall_enabled::id()) {
+        register!(
+            "sol_get_runtime_limits",
+            SyscallTracer::<SyscallGetRuntimeLimits>::call
+        );
+    }
+
+    if is_feature_active(&pseudorandom_syscall_enabled::id()) {
+        register!(
+            "sol_get_pseudorandom",
+            SyscallTracer::<SyscallGetPseudorandom>::call
+        );
+    }
+
+    register!(
+        "sol_invoke_signed_c",
+        SyscallTracer::<SyscallInvokeSignedC>::call
+    );
+    register!(
+        "sol_invoke_signed_rust",
+        SyscallTracer::<SyscallInvokeSignedRust>::call
+    );
+    register!("sol_alloc_free_", SyscallTracer::<SyscallAllocFree>::call);
+
+    if is_feature_active(&system_transfer_syscall_enabled::id()) {
+        register!(
+            "sol_system_transfer_c",
+            SyscallTracer::<SyscallSystemTransferC>::call
+        );
+        register!(
+            "sol_system_transfer_rust",
+            SyscallTracer::<SyscallSystemTransferRust>::call
+        );
+    }
+
+    #[cfg(feature = "experimental-token-transfer")]
+    register!(
+        "sol_token_transfer",
+        SyscallTracer::<SyscallTokenTransfer>::call
+    );
+
+    Ok(())
+}
+
+/// How the syscalls and VM configuration presented to programs differ between two feature sets
+#[derive(Debug, Default, PartialEq)]
+pub struct SyscallEnvironmentDiff {
+    /// Syscalls only registered under the first feature set
+    pub removed_syscalls: Vec<&'static str>,
+    /// Syscalls only registered under the second feature set
+    pub added_syscalls: Vec<&'static str>,
+    /// VM configuration fields that differ, with their values under each feature set
+    pub config: Vec<(&'static str, usize, usize)>,
+    /// Whether the verifier caps program size under each feature set, if that differs
+    pub program_size_cap: Option<(bool, bool)>,
+}
+
+impl SyscallEnvironmentDiff {
+    pub fn is_empty(&self) -> bool {
+        self.removed_syscalls.is_empty()
+            && self.added_syscalls.is_empty()
+            && self.config.is_empty()
+            && self.program_size_cap.is_none()
+    }
+}
+
+/// Compare the environments programs are verified and executed in under `from` and `to`
+///
+/// Executors created under `from` must be recreated under `to` unless the diff is empty.
+pub fn diff_syscall_environments(
+    from: &FeatureSet,
+    to: &FeatureSet,
+) -> Result<SyscallEnvironmentDiff, EbpfError<BPFError>> {
+    let syscall_names = |feature_set: &FeatureSet| {
+        let mut names = vec![];
+        register_syscalls_inner(
+            &|feature_id| feature_set.is_active(feature_id),
+            &mut SyscallRegistry::default(),
+            &mut names,
+        )
+        .map(|_| names)
+    };
+    let from_names = syscall_names(from)?;
+    let to_names = syscall_names(to)?;
+
+    // Built the same way `create_and_cache_executor` builds them
+    let settings = |feature_set: &FeatureSet| {
+        ExecutableSettings::new(
+            &|feature_id| feature_set.is_active(feature_id),
+            &BpfComputeBudget::new(feature_set),
+        )
+    };
+    let from_settings = settings(from);
+    let to_settings = settings(to);
+    let config = vec![
+        (
+            "max_call_depth",
+            from_settings.max_call_depth,
+            to_settings.max_call_depth,
+        ),
+        (
+            "stack_frame_size",
+            from_settings.stack_frame_size,
+            to_settings.stack_frame_size,
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, from, to)| from != to)
+    .collect();
+    let program_size_cap = if from_settings.is_program_size_cap != to_settings.is_program_size_cap {
+        Some((
+            from_settings.is_program_size_cap,
+            to_settings.is_program_size_cap,
+        ))
+    } else {
+        None
+    };
+
+    Ok(SyscallEnvironmentDiff {
+        removed_syscalls: from_names
+            .iter()
+            .filter(|name| !to_names.contains(name))
+            .cloned()
+            .collect(),
+        added_syscalls: to_names
+            .iter()
+            .filter(|name| !from_names.contains(name))
+            .cloned()
+            .collect(),
+        config,
+        program_size_cap,
+    })
 }
 
 pub fn bind_syscall_context_objects<'a>(
@@ -140,204 +475,889 @@ pub fn bind_syscall_context_objects<'a>(
     heap: Vec<u8>,
 ) -> Result<(), EbpfError<BPFError>> {
     let bpf_compute_budget = invoke_context.get_bpf_compute_budget();
+    let tracing = SyscallTracing {
+        compute_meter: invoke_context.get_compute_meter(),
+        trace_collector: invoke_context.get_syscall_trace_collector(),
+        compute_profiler: invoke_context.get_compute_profiler(),
+        syscall_filter: invoke_context.get_syscall_filter(),
+        syscall_faults: invoke_context.get_syscall_faults(),
+    };
+    // Program logs are attributed to the currently executing program
+    let program_ids = invoke_context.get_program_ids();
+    let program_id = program_ids.last().cloned().unwrap_or_default();
+    let stack_height = program_ids.len();
+    let reject_overlapping_buffers =
+        invoke_context.is_feature_active(&reject_overlapping_syscall_buffers::id());
 
     // Syscall functions common across languages
 
-    vm.bind_syscall_context_object(Box::new(SyscallAbort {}), None)?;
-    vm.bind_syscall_context_object(Box::new(SyscallPanic { loader_id }), None)?;
+    vm.bind_syscall_context_object(tracing.trace("abort", SyscallAbort {}), None)?;
     vm.bind_syscall_context_object(
-        Box::new(SyscallLog {
-            cost: bpf_compute_budget.log_units,
-            compute_meter: invoke_context.get_compute_meter(),
-            logger: invoke_context.get_logger(),
-            loader_id,
-        }),
+        tracing.trace("sol_panic_", SyscallPanic { loader_id }),
+        None,
+    )?;
+    if invoke_context.is_feature_active(&abort_with_code_syscall_enabled::id()) {
+        vm.bind_syscall_context_object(
+            tracing.trace("sol_abort_with_code", SyscallAbortWithCode {}),
+            None,
+        )?;
+    }
+    vm.bind_syscall_context_object(
+        tracing.trace(
+            "sol_log_",
+            SyscallLog {
+                cost: syscall_cost(invoke_context, "sol_log_", bpf_compute_budget.log_units),
+                compute_meter: invoke_context.get_compute_meter(),
+                logger: invoke_context.get_logger(),
+                program_id,
+                stack_height,
+                loader_id,
+            },
+        ),
         None,
     )?;
     vm.bind_syscall_context_object(
-        Box::new(SyscallLogU64 {
-            cost: bpf_compute_budget.log_64_units,
-            compute_meter: invoke_context.get_compute_meter(),
-            logger: invoke_context.get_logger(),
-        }),
+        tracing.trace(
+            "sol_log_64_",
+            SyscallLogU64 {
+                cost: syscall_cost(
+                    invoke_context,
+                    "sol_log_64_",
+                    bpf_compute_budget.log_64_units,
+                ),
+                compute_meter: invoke_context.get_compute_meter(),
+                logger: invoke_context.get_logger(),
+                program_id,
+                stack_height,
+            },
+        ),
         None,
     )?;
 
     if invoke_context.is_feature_active(&sol_log_compute_units_syscall::id()) {
         vm.bind_syscall_context_object(
-            Box::new(SyscallLogBpfComputeUnits {
-                cost: 0,
-                compute_meter: invoke_context.get_compute_meter(),
-                logger: invoke_context.get_logger(),
-            }),
+            tracing.trace(
+                "sol_log_compute_units_",
+                SyscallLogBpfComputeUnits {
+                    cost: syscall_cost(invoke_context, "sol_log_compute_units_", 0),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    logger: invoke_context.get_logger(),
+                    program_id,
+                    stack_height,
+                },
+            ),
             None,
         )?;
     }
     if invoke_context.is_feature_active(&pubkey_log_syscall_enabled::id()) {
         vm.bind_syscall_context_object(
-            Box::new(SyscallLogPubkey {
-                cost: bpf_compute_budget.log_pubkey_units,
-                compute_meter: invoke_context.get_compute_meter(),
-                logger: invoke_context.get_logger(),
-                loader_id,
-            }),
+            tracing.trace(
+                "sol_log_pubkey",
+                SyscallLogPubkey {
+                    cost: syscall_cost(
+                        invoke_context,
+                        "sol_log_pubkey",
+                        bpf_compute_budget.log_pubkey_units,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    logger: invoke_context.get_logger(),
+                    program_id,
+                    stack_height,
+                    loader_id,
+                },
+            ),
+            None,
+        )?;
+    }
+    if invoke_context.is_feature_active(&log_data_syscall_enabled::id()) {
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_log_data",
+                SyscallLogData {
+                    cost: syscall_cost(
+                        invoke_context,
+                        "sol_log_data",
+                        bpf_compute_budget.log_units,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    logger: invoke_context.get_logger(),
+                    program_id,
+                    stack_height,
+                    loader_id,
+                },
+            ),
             None,
         )?;
     }
 
     if invoke_context.is_feature_active(&sha256_syscall_enabled::id()) {
         vm.bind_syscall_context_object(
-            Box::new(SyscallSha256 {
-                sha256_base_cost: bpf_compute_budget.sha256_base_cost,
-                sha256_byte_cost: bpf_compute_budget.sha256_byte_cost,
-                compute_meter: invoke_context.get_compute_meter(),
-                loader_id,
-            }),
+            tracing.trace(
+                "sol_sha256",
+                SyscallSha256 {
+                    sha256_base_cost: syscall_cost(
+                        invoke_context,
+                        "sol_sha256",
+                        bpf_compute_budget.sha256_base_cost,
+                    ),
+                    sha256_byte_cost: syscall_byte_cost(
+                        invoke_context,
+                        "sol_sha256",
+                        bpf_compute_budget.sha256_byte_cost,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    loader_id,
+                    reject_overlapping_buffers,
+                },
+            ),
             None,
         )?;
     }
 
-    if invoke_context.is_feature_active(&ristretto_mul_syscall_enabled::id()) {
+    if invoke_context.is_feature_active(&hmac_sha256_syscall_enabled::id()) {
         vm.bind_syscall_context_object(
-            Box::new(SyscallRistrettoMul {
-                cost: 0,
-                compute_meter: invoke_context.get_compute_meter(),
-                loader_id,
-            }),
+            tracing.trace(
+                "sol_hmac_sha256",
+                SyscallHmacSha256 {
+                    base_cost: syscall_cost(
+                        invoke_context,
+                        "sol_hmac_sha256",
+                        bpf_compute_budget.hmac_sha256_base_units,
+                    ),
+                    byte_cost: syscall_byte_cost(
+                        invoke_context,
+                        "sol_hmac_sha256",
+                        bpf_compute_budget.hmac_sha256_byte_units,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    loader_id,
+                    reject_overlapping_buffers,
+                },
+            ),
             None,
         )?;
     }
 
-    vm.bind_syscall_context_object(
-        Box::new(SyscallCreateProgramAddress {
-            cost: bpf_compute_budget.create_program_address_units,
-            compute_meter: invoke_context.get_compute_meter(),
-            loader_id,
-        }),
-        None,
-    )?;
-
-    // Cross-program invocation syscalls
-
-    let invoke_context = Rc::new(RefCell::new(invoke_context));
-    vm.bind_syscall_context_object(
-        Box::new(SyscallInvokeSignedC {
-            callers_keyed_accounts,
-            invoke_context: invoke_context.clone(),
-            loader_id,
-        }),
-        None,
-    )?;
-    vm.bind_syscall_context_object(
-        Box::new(SyscallInvokeSignedRust {
-            callers_keyed_accounts,
-            invoke_context: invoke_context.clone(),
-            loader_id,
-        }),
-        None,
-    )?;
-
-    // Memory allocator
-
-    vm.bind_syscall_context_object(
-        Box::new(SyscallAllocFree {
-            aligned: *loader_id != bpf_loader_deprecated::id(),
-            allocator: BPFAllocator::new(heap, MM_HEAP_START),
-        }),
-        None,
-    )?;
-
-    Ok(())
-}
+    if invoke_context.is_feature_active(&sha256_context_syscalls_enabled::id()) {
+        let contexts = Rc::new(RefCell::new(Sha256Contexts::default()));
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_sha256_init",
+                SyscallSha256Init {
+                    cost: syscall_cost(
+                        invoke_context,
+                        "sol_sha256_init",
+                        bpf_compute_budget.sha256_base_cost,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    contexts: contexts.clone(),
+                },
+            ),
+            None,
+        )?;
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_sha256_update",
+                SyscallSha256Update {
+                    base_cost: syscall_cost(
+                        invoke_context,
+                        "sol_sha256_update",
+                        bpf_compute_budget.sha256_base_cost,
+                    ),
+                    byte_cost: syscall_byte_cost(
+                        invoke_context,
+                        "sol_sha256_update",
+                        bpf_compute_budget.sha256_byte_cost,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    contexts: contexts.clone(),
+                    loader_id,
+                },
+            ),
+            None,
+        )?;
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_sha256_final",
+                SyscallSha256Final {
+                    cost: syscall_cost(
+                        invoke_context,
+                        "sol_sha256_final",
+                        bpf_compute_budget.sha256_base_cost,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    contexts,
+                    loader_id,
+                },
+            ),
+            None,
+        )?;
+    }
 
-fn translate(
-    memory_mapping: &MemoryMapping,
-    access_type: AccessType,
-    vm_addr: u64,
-    len: u64,
-) -> Result<u64, EbpfError<BPFError>> {
-    memory_mapping.map::<BPFError>(access_type, vm_addr, len)
-}
+    if invoke_context.is_feature_active(&keccak256_context_syscalls_enabled::id()) {
+        let contexts = Rc::new(RefCell::new(Keccak256Contexts::default()));
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_keccak256_init",
+                SyscallKeccak256Init {
+                    cost: syscall_cost(
+                        invoke_context,
+                        "sol_keccak256_init",
+                        bpf_compute_budget.keccak256_base_units,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    contexts: contexts.clone(),
+                },
+            ),
+            None,
+        )?;
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_keccak256_update",
+                SyscallKeccak256Update {
+                    base_cost: syscall_cost(
+                        invoke_context,
+                        "sol_keccak256_update",
+                        bpf_compute_budget.keccak256_base_units,
+                    ),
+                    byte_cost: syscall_byte_cost(
+                        invoke_context,
+                        "sol_keccak256_update",
+                        bpf_compute_budget.keccak256_byte_units,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    contexts: contexts.clone(),
+                    loader_id,
+                },
+            ),
+            None,
+        )?;
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_keccak256_final",
+                SyscallKeccak256Final {
+                    cost: syscall_cost(
+                        invoke_context,
+                        "sol_keccak256_final",
+                        bpf_compute_budget.keccak256_base_units,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    contexts,
+                    loader_id,
+                },
+            ),
+            None,
+        )?;
+    }
 
-fn translate_type_inner<'a, T>(
-    memory_mapping: &MemoryMapping,
-    access_type: AccessType,
-    vm_addr: u64,
-    loader_id: &Pubkey,
-) -> Result<&'a mut T, EbpfError<BPFError>> {
-    if loader_id != &bpf_loader_deprecated::id()
-        && (vm_addr as u64 as *mut T).align_offset(align_of::<T>()) != 0
-    {
-        Err(SyscallError::UnalignedPointer.into())
-    } else {
-        unsafe {
-            match translate(memory_mapping, access_type, vm_addr, size_of::<T>() as u64) {
-                Ok(value) => Ok(&mut *(value as *mut T)),
-                Err(e) => Err(e),
-            }
-        }
+    if invoke_context.is_feature_active(&chacha20_poly1305_syscall_enabled::id()) {
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_chacha20_poly1305_open",
+                SyscallChaCha20Poly1305Open {
+                    base_cost: syscall_cost(
+                        invoke_context,
+                        "sol_chacha20_poly1305_open",
+                        bpf_compute_budget.chacha20_poly1305_base_units,
+                    ),
+                    byte_cost: syscall_byte_cost(
+                        invoke_context,
+                        "sol_chacha20_poly1305_open",
+                        bpf_compute_budget.chacha20_poly1305_byte_units,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    loader_id,
+                    reject_overlapping_buffers,
+                },
+            ),
+            None,
+        )?;
     }
-}
-fn translate_type_mut<'a, T>(
-    memory_mapping: &MemoryMapping,
-    vm_addr: u64,
-    loader_id: &Pubkey,
-) -> Result<&'a mut T, EbpfError<BPFError>> {
-    translate_type_inner::<T>(memory_mapping, AccessType::Store, vm_addr, loader_id)
-}
-fn translate_type<'a, T>(
-    memory_mapping: &MemoryMapping,
-    vm_addr: u64,
-    loader_id: &Pubkey,
-) -> Result<&'a T, EbpfError<BPFError>> {
-    match translate_type_inner::<T>(memory_mapping, AccessType::Load, vm_addr, loader_id) {
-        Ok(value) => Ok(&*value),
-        Err(e) => Err(e),
+
+    if invoke_context.is_feature_active(&secp256r1_verify_syscall_enabled::id()) {
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_secp256r1_verify",
+                SyscallSecp256r1Verify {
+                    cost: syscall_cost(
+                        invoke_context,
+                        "sol_secp256r1_verify",
+                        bpf_compute_budget.secp256r1_verify_units,
+                    ),
+                    sha256_byte_cost: syscall_byte_cost(
+                        invoke_context,
+                        "sol_secp256r1_verify",
+                        bpf_compute_budget.sha256_byte_cost,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    loader_id,
+                },
+            ),
+            None,
+        )?;
     }
-}
 
-fn translate_slice_inner<'a, T>(
-    memory_mapping: &MemoryMapping,
-    access_type: AccessType,
-    vm_addr: u64,
-    len: u64,
-    loader_id: &Pubkey,
-) -> Result<&'a mut [T], EbpfError<BPFError>> {
-    if loader_id != &bpf_loader_deprecated::id()
-        && (vm_addr as u64 as *mut T).align_offset(align_of::<T>()) != 0
-    {
-        Err(SyscallError::UnalignedPointer.into())
-    } else if len == 0 {
-        Ok(unsafe { from_raw_parts_mut(0x1 as *mut T, len as usize) })
-    } else {
-        match translate(
-            memory_mapping,
-            access_type,
-            vm_addr,
-            len.saturating_mul(size_of::<T>() as u64),
-        ) {
-            Ok(value) => Ok(unsafe { from_raw_parts_mut(value as *mut T, len as usize) }),
-            Err(e) => Err(e),
-        }
+    if invoke_context.is_feature_active(&x25519_dh_syscall_enabled::id()) {
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_x25519_dh",
+                SyscallX25519Dh {
+                    cost: syscall_cost(
+                        invoke_context,
+                        "sol_x25519_dh",
+                        bpf_compute_budget.x25519_dh_units,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    loader_id,
+                    reject_overlapping_buffers,
+                },
+            ),
+            None,
+        )?;
     }
+
+    if invoke_context.is_feature_active(&address_encoding_syscalls_enabled::id()) {
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_base58_encode",
+                SyscallBase58Encode {
+                    base_cost: syscall_cost(
+                        invoke_context,
+                        "sol_base58_encode",
+                        bpf_compute_budget.address_encoding_base_units,
+                    ),
+                    byte_cost: syscall_byte_cost(
+                        invoke_context,
+                        "sol_base58_encode",
+                        bpf_compute_budget.address_encoding_byte_units,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    loader_id,
+                },
+            ),
+            None,
+        )?;
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_base58_decode",
+                SyscallBase58Decode {
+                    base_cost: syscall_cost(
+                        invoke_context,
+                        "sol_base58_decode",
+                        bpf_compute_budget.address_encoding_base_units,
+                    ),
+                    byte_cost: syscall_byte_cost(
+                        invoke_context,
+                        "sol_base58_decode",
+                        bpf_compute_budget.address_encoding_byte_units,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    loader_id,
+                },
+            ),
+            None,
+        )?;
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_bech32_encode",
+                SyscallBech32Encode {
+                    base_cost: syscall_cost(
+                        invoke_context,
+                        "sol_bech32_encode",
+                        bpf_compute_budget.address_encoding_base_units,
+                    ),
+                    byte_cost: syscall_byte_cost(
+                        invoke_context,
+                        "sol_bech32_encode",
+                        bpf_compute_budget.address_encoding_byte_units,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    loader_id,
+                },
+            ),
+            None,
+        )?;
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_bech32_decode",
+                SyscallBech32Decode {
+                    base_cost: syscall_cost(
+                        invoke_context,
+                        "sol_bech32_decode",
+                        bpf_compute_budget.address_encoding_base_units,
+                    ),
+                    byte_cost: syscall_byte_cost(
+                        invoke_context,
+                        "sol_bech32_decode",
+                        bpf_compute_budget.address_encoding_byte_units,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    loader_id,
+                },
+            ),
+            None,
+        )?;
+    }
+
+    if invoke_context.is_feature_active(&length_prefix_decode_syscall_enabled::id()) {
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_decode_length_prefix",
+                SyscallDecodeLengthPrefix {
+                    cost: syscall_cost(
+                        invoke_context,
+                        "sol_decode_length_prefix",
+                        bpf_compute_budget.length_prefix_decode_units,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    loader_id,
+                },
+            ),
+            None,
+        )?;
+    }
+
+    if invoke_context.is_feature_active(&ristretto_mul_syscall_enabled::id()) {
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_ristretto_mul",
+                SyscallRistrettoMul {
+                    cost: syscall_cost(invoke_context, "sol_ristretto_mul", 0),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    loader_id,
+                    reject_overlapping_buffers,
+                },
+            ),
+            None,
+        )?;
+    }
+
+    vm.bind_syscall_context_object(
+        tracing.trace(
+            "sol_create_program_address",
+            SyscallCreateProgramAddress {
+                cost: syscall_cost(
+                    invoke_context,
+                    "sol_create_program_address",
+                    bpf_compute_budget.create_program_address_units,
+                ),
+                compute_meter: invoke_context.get_compute_meter(),
+                loader_id,
+                reject_overlapping_buffers,
+            },
+        ),
+        None,
+    )?;
+    if invoke_context.is_feature_active(&create_program_addresses_batch_syscall_enabled::id()) {
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_create_program_addresses_batch",
+                SyscallCreateProgramAddressesBatch {
+                    base_cost: syscall_cost(
+                        invoke_context,
+                        "sol_create_program_addresses_batch",
+                        bpf_compute_budget.create_program_addresses_batch_base_units,
+                    ),
+                    address_cost: syscall_address_cost(
+                        invoke_context,
+                        "sol_create_program_addresses_batch",
+                        bpf_compute_budget.create_program_address_units,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    loader_id,
+                    reject_overlapping_buffers,
+                },
+            ),
+            None,
+        )?;
+    }
+
+    if invoke_context.is_feature_active(&get_max_invoke_depth_syscall_enabled::id()) {
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_get_max_invoke_depth",
+                SyscallGetMaxInvokeDepth {
+                    cost: syscall_cost(invoke_context, "sol_get_max_invoke_depth", 0),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    max_invoke_depth: bpf_compute_budget.max_invoke_depth as u64,
+                },
+            ),
+            None,
+        )?;
+    }
+
+    if invoke_context.is_feature_active(&runtime_version_syscall_enabled::id()) {
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_get_runtime_version",
+                SyscallGetRuntimeVersion {
+                    cost: syscall_cost(invoke_context, "sol_get_runtime_version", 0),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    runtime_version: invoke_context.get_runtime_version().to_u64(),
+                },
+            ),
+            None,
+        )?;
+    }
+
+    if invoke_context.is_feature_active(&instruction_ancestry_syscall_enabled::id()) {
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_get_instruction_ancestry",
+                SyscallGetInstructionAncestry {
+                    cost: syscall_cost(
+                        invoke_context,
+                        "sol_get_instruction_ancestry",
+                        bpf_compute_budget.instruction_ancestry_units,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    // The invocation stack does not change while this program executes
+                    program_ids: invoke_context.get_program_ids().to_vec(),
+                    loader_id,
+                },
+            ),
+            None,
+        )?;
+    }
+
+    if invoke_context.is_feature_active(&transaction_signature_syscall_enabled::id()) {
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_get_transaction_signature",
+                SyscallGetTransactionSignature {
+                    cost: syscall_cost(
+                        invoke_context,
+                        "sol_get_transaction_signature",
+                        bpf_compute_budget.transaction_signature_units,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    signatures: invoke_context.get_transaction_signatures().to_vec(),
+                    loader_id,
+                },
+            ),
+            None,
+        )?;
+    }
+
+    if invoke_context.is_feature_active(&recent_blockhash_syscall_enabled::id()) {
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_get_recent_blockhash_of_tx",
+                SyscallGetRecentBlockhash {
+                    cost: syscall_cost(
+                        invoke_context,
+                        "sol_get_recent_blockhash_of_tx",
+                        bpf_compute_budget.recent_blockhash_units,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    recent_blockhash: *invoke_context.get_recent_blockhash(),
+                    loader_id,
+                },
+            ),
+            None,
+        )?;
+    }
+
+    if invoke_context.is_feature_active(&fee_payer_syscall_enabled::id()) {
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_get_fee_payer",
+                SyscallGetFeePayer {
+                    cost: syscall_cost(
+                        invoke_context,
+                        "sol_get_fee_payer",
+                        bpf_compute_budget.fee_payer_units,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    fee_payer: *invoke_context.get_fee_payer(),
+                    loader_id,
+                },
+            ),
+            None,
+        )?;
+    }
+
+    if invoke_context.is_feature_active(&runtime_limits_syscall_enabled::id()) {
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_get_runtime_limits",
+                SyscallGetRuntimeLimits {
+                    cost: syscall_cost(
+                        invoke_context,
+                        "sol_get_runtime_limits",
+                        bpf_compute_budget.runtime_limits_units,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    limits: RuntimeLimits {
+                        max_invoke_depth: bpf_compute_budget.max_invoke_depth as u64,
+                        max_call_depth: bpf_compute_budget.max_call_depth as u64,
+                        stack_frame_size: bpf_compute_budget.stack_frame_size as u64,
+                        heap_size: heap.len() as u64,
+                        max_signers: MAX_SIGNERS as u64,
+                        max_permitted_data_increase: MAX_PERMITTED_DATA_INCREASE as u64,
+                    },
+                    loader_id,
+                },
+            ),
+            None,
+        )?;
+    }
+
+    if invoke_context.is_feature_active(&pseudorandom_syscall_enabled::id()) {
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_get_pseudorandom",
+                SyscallGetPseudorandom {
+                    base_cost: syscall_cost(
+                        invoke_context,
+                        "sol_get_pseudorandom",
+                        bpf_compute_budget.pseudorandom_base_units,
+                    ),
+                    byte_cost: syscall_byte_cost(
+                        invoke_context,
+                        "sol_get_pseudorandom",
+                        bpf_compute_budget.pseudorandom_byte_units,
+                    ),
+                    compute_meter: invoke_context.get_compute_meter(),
+                    recent_blockhash: *invoke_context.get_recent_blockhash(),
+                    program_id,
+                    counter: invoke_context.get_pseudorandom_counter(),
+                    loader_id,
+                },
+            ),
+            None,
+        )?;
+    }
+
+    // Cross-program invocation syscalls
+
+    let is_system_transfer_enabled =
+        invoke_context.is_feature_active(&system_transfer_syscall_enabled::id());
+    let is_heap_free_enabled = invoke_context.is_feature_active(&heap_free_enabled::id());
+    let invoke_context = Rc::new(RefCell::new(invoke_context));
+    vm.bind_syscall_context_object(
+        tracing.trace(
+            "sol_invoke_signed_c",
+            SyscallInvokeSignedC {
+                callers_keyed_accounts,
+                invoke_context: invoke_context.clone(),
+                loader_id,
+            },
+        ),
+        None,
+    )?;
+    vm.bind_syscall_context_object(
+        tracing.trace(
+            "sol_invoke_signed_rust",
+            SyscallInvokeSignedRust {
+                callers_keyed_accounts,
+                invoke_context: invoke_context.clone(),
+                loader_id,
+            },
+        ),
+        None,
+    )?;
+    #[cfg(feature = "experimental-token-transfer")]
+    vm.bind_syscall_context_object(
+        tracing.trace(
+            "sol_token_transfer",
+            SyscallTokenTransfer {
+                syscall: SyscallInvokeSignedRust {
+                    callers_keyed_accounts,
+                    invoke_context: invoke_context.clone(),
+                    loader_id,
+                },
+            },
+        ),
+        None,
+    )?;
+    if is_system_transfer_enabled {
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_system_transfer_c",
+                SyscallSystemTransferC {
+                    syscall: SyscallInvokeSignedC {
+                        callers_keyed_accounts,
+                        invoke_context: invoke_context.clone(),
+                        loader_id,
+                    },
+                },
+            ),
+            None,
+        )?;
+        vm.bind_syscall_context_object(
+            tracing.trace(
+                "sol_system_transfer_rust",
+                SyscallSystemTransferRust {
+                    syscall: SyscallInvokeSignedRust {
+                        callers_keyed_accounts,
+                        invoke_context: invoke_context.clone(),
+                        loader_id,
+                    },
+                },
+            ),
+            None,
+        )?;
+    }
+
+    // Memory allocator
+
+    vm.bind_syscall_context_object(
+        tracing.trace(
+            "sol_alloc_free_",
+            SyscallAllocFree {
+                aligned: *loader_id != bpf_loader_deprecated::id(),
+                allocator: if is_heap_free_enabled {
+                    Box::new(BPFFreeListAllocator::new(heap, MM_HEAP_START))
+                } else {
+                    Box::new(BPFAllocator::new(heap, MM_HEAP_START))
+                },
+            },
+        ),
+        None,
+    )?;
+
+    Ok(())
 }
-fn translate_slice_mut<'a, T>(
-    memory_mapping: &MemoryMapping,
-    vm_addr: u64,
-    len: u64,
-    loader_id: &Pubkey,
-) -> Result<&'a mut [T], EbpfError<BPFError>> {
-    translate_slice_inner::<T>(memory_mapping, AccessType::Store, vm_addr, len, loader_id)
-}
-fn translate_slice<'a, T>(
-    memory_mapping: &MemoryMapping,
-    vm_addr: u64,
-    len: u64,
-    loader_id: &Pubkey,
-) -> Result<&'a [T], EbpfError<BPFError>> {
-    match translate_slice_inner::<T>(memory_mapping, AccessType::Load, vm_addr, len, loader_id) {
-        Ok(value) => Ok(&*value),
-        Err(e) => Err(e),
+
+/// Reports every call of the wrapped syscall to the invoke context's
+/// `SyscallTraceCollector` and `ComputeProfiler`, if it has them, fails
+/// calls to syscalls disabled by its `SyscallFilter` and injects its
+/// `SyscallFaults`
+pub struct SyscallTracer<T> {
+    name: &'static str,
+    syscall: T,
+    enabled: bool,
+    faults: Option<HashMap<u64, SyscallFault>>,
+    calls: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    trace_collector: Option<Arc<dyn SyscallTraceCollector>>,
+    compute_profiler: Option<Rc<RefCell<InvocationProfiler>>>,
+}
+impl<T: SyscallObject<BPFError>> SyscallObject<BPFError> for SyscallTracer<T> {
+    fn call(
+        &mut self,
+        arg1: u64,
+        arg2: u64,
+        arg3: u64,
+        arg4: u64,
+        arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        if self.enabled
+            && self.faults.is_none()
+            && self.trace_collector.is_none()
+            && self.compute_profiler.is_none()
+        {
+            self.syscall
+                .call(arg1, arg2, arg3, arg4, arg5, memory_mapping, result);
+            return;
+        }
+        let fault = match &self.faults {
+            Some(faults) => {
+                self.calls = self.calls.saturating_add(1);
+                faults.get(&self.calls).copied()
+            }
+            None => None,
+        };
+        let before = self.compute_meter.borrow().get_remaining();
+        let callees_before = self
+            .compute_profiler
+            .as_ref()
+            .map_or(0, |compute_profiler| {
+                compute_profiler.borrow().consumed_by_callees()
+            });
+        if !self.enabled {
+            *result = Err(SyscallError::SyscallDisabled(self.name).into());
+        } else {
+            match fault {
+                Some(SyscallFault::Return(value)) => *result = Ok(value),
+                Some(SyscallFault::Fail) => {
+                    *result = Err(SyscallError::InjectedFault(self.name).into())
+                }
+                Some(SyscallFault::ConsumeComputeUnits(units)) => {
+                    match self.compute_meter.consume(units) {
+                        Ok(()) => {
+                            self.syscall
+                                .call(arg1, arg2, arg3, arg4, arg5, memory_mapping, result)
+                        }
+                        Err(err) => *result = Err(err),
+                    }
+                }
+                None => self
+                    .syscall
+                    .call(arg1, arg2, arg3, arg4, arg5, memory_mapping, result),
+            }
+        }
+        let after = self.compute_meter.borrow().get_remaining();
+        let compute_units_consumed = before.saturating_sub(after);
+        if let Some(compute_profiler) = &self.compute_profiler {
+            // Programs invoked by the syscall are profiled as their own frames
+            let compute_profiler = compute_profiler.borrow();
+            let consumed_by_callees = compute_profiler
+                .consumed_by_callees()
+                .saturating_sub(callees_before);
+            compute_profiler.record_syscall(
+                self.name,
+                compute_units_consumed.saturating_sub(consumed_by_callees),
+            );
+        }
+        if let Some(trace_collector) = &self.trace_collector {
+            trace_collector.collect(SyscallTraceEvent {
+                name: self.name.to_string(),
+                args: [arg1, arg2, arg3, arg4, arg5],
+                compute_units_consumed,
+                result: match result {
+                    Ok(value) => Ok(*value),
+                    Err(err) => Err(err.to_string()),
+                },
+            });
+        }
+    }
+}
+
+/// Wraps syscall context objects in `SyscallTracer`s as they are bound
+struct SyscallTracing {
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    trace_collector: Option<Arc<dyn SyscallTraceCollector>>,
+    compute_profiler: Option<Rc<RefCell<InvocationProfiler>>>,
+    syscall_filter: Option<Arc<SyscallFilter>>,
+    syscall_faults: Option<Arc<SyscallFaults>>,
+}
+impl SyscallTracing {
+    fn trace<T>(&self, name: &'static str, syscall: T) -> Box<SyscallTracer<T>> {
+        Box::new(SyscallTracer {
+            name,
+            syscall,
+            enabled: self
+                .syscall_filter
+                .as_ref()
+                .map_or(true, |syscall_filter| syscall_filter.is_enabled(name)),
+            faults: self
+                .syscall_faults
+                .as_ref()
+                .and_then(|syscall_faults| syscall_faults.get(name))
+                .cloned(),
+            calls: 0,
+            compute_meter: self.compute_meter.clone(),
+            trace_collector: self.trace_collector.clone(),
+            compute_profiler: self.compute_profiler.clone(),
+        })
+    }
+}
+
+/// Translation set of a syscall that accepted overlapping buffers until the
+/// `reject_overlapping_syscall_buffers` feature
+fn translation_set<'a>(
+    memory_mapping: &'a MemoryMapping,
+    loader_id: &'a Pubkey,
+    reject_overlapping_buffers: bool,
+) -> MutTranslationSet<'a> {
+    if reject_overlapping_buffers {
+        MutTranslationSet::new(memory_mapping, loader_id)
+    } else {
+        unsafe { MutTranslationSet::new_unchecked(memory_mapping, loader_id) }
     }
 }
 
@@ -381,6 +1401,24 @@ impl SyscallObject<BPFError> for SyscallAbort {
     }
 }
 
+/// Abort with code syscall function, called when the BPF program calls `sol_abort_with_code()`
+/// Causes the BPF program to be halted immediately, reporting a machine readable code
+pub struct SyscallAbortWithCode {}
+impl SyscallObject<BPFError> for SyscallAbortWithCode {
+    fn call(
+        &mut self,
+        code: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        _memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        *result = Err(SyscallError::AbortWithCode(code).into());
+    }
+}
+
 /// Panic syscall function, called when the BPF program calls 'sol_panic_()`
 /// Causes the BPF program to be halted immediately
 /// Log a user's info message
@@ -413,6 +1451,8 @@ pub struct SyscallLog<'a> {
     cost: u64,
     compute_meter: Rc<RefCell<dyn ComputeMeter>>,
     logger: Rc<RefCell<dyn Logger>>,
+    program_id: Pubkey,
+    stack_height: usize,
     loader_id: &'a Pubkey,
 }
 impl<'a> SyscallObject<BPFError> for SyscallLog<'a> {
@@ -434,7 +1474,14 @@ impl<'a> SyscallObject<BPFError> for SyscallLog<'a> {
                 len,
                 &self.loader_id,
                 &mut |string: &str| {
-                    stable_log::program_log(&self.logger, string);
+                    stable_log::program_log_record(
+                        &self.logger,
+                        ProgramLogRecord {
+                            program_id: self.program_id,
+                            stack_height: self.stack_height,
+                            kind: ProgramLogKind::Msg(string.to_string()),
+                        },
+                    );
                     Ok(0)
                 },
             ),
@@ -449,6 +1496,8 @@ pub struct SyscallLogU64 {
     cost: u64,
     compute_meter: Rc<RefCell<dyn ComputeMeter>>,
     logger: Rc<RefCell<dyn Logger>>,
+    program_id: Pubkey,
+    stack_height: usize,
 }
 impl SyscallObject<BPFError> for SyscallLogU64 {
     fn call(
@@ -462,12 +1511,13 @@ impl SyscallObject<BPFError> for SyscallLogU64 {
         result: &mut Result<u64, EbpfError<BPFError>>,
     ) {
         question_mark!(self.compute_meter.consume(self.cost), result);
-        stable_log::program_log(
+        stable_log::program_log_record(
             &self.logger,
-            &format!(
-                "{:#x}, {:#x}, {:#x}, {:#x}, {:#x}",
-                arg1, arg2, arg3, arg4, arg5
-            ),
+            ProgramLogRecord {
+                program_id: self.program_id,
+                stack_height: self.stack_height,
+                kind: ProgramLogKind::U64s([arg1, arg2, arg3, arg4, arg5]),
+            },
         );
         *result = Ok(0);
     }
@@ -478,6 +1528,8 @@ pub struct SyscallLogBpfComputeUnits {
     cost: u64,
     compute_meter: Rc<RefCell<dyn ComputeMeter>>,
     logger: Rc<RefCell<dyn Logger>>,
+    program_id: Pubkey,
+    stack_height: usize,
 }
 impl SyscallObject<BPFError> for SyscallLogBpfComputeUnits {
     fn call(
@@ -498,10 +1550,11 @@ impl SyscallObject<BPFError> for SyscallLogBpfComputeUnits {
             result
         );
         if logger.log_enabled() {
-            logger.log(&format!(
-                "Program consumption: {} units remaining",
-                self.compute_meter.borrow().get_remaining()
-            ));
+            logger.log_record(&ProgramLogRecord {
+                program_id: self.program_id,
+                stack_height: self.stack_height,
+                kind: ProgramLogKind::ComputeUnits(self.compute_meter.borrow().get_remaining()),
+            });
         }
         *result = Ok(0);
     }
@@ -512,6 +1565,8 @@ pub struct SyscallLogPubkey<'a> {
     cost: u64,
     compute_meter: Rc<RefCell<dyn ComputeMeter>>,
     logger: Rc<RefCell<dyn Logger>>,
+    program_id: Pubkey,
+    stack_height: usize,
     loader_id: &'a Pubkey,
 }
 impl<'a> SyscallObject<BPFError> for SyscallLogPubkey<'a> {
@@ -530,7 +1585,51 @@ impl<'a> SyscallObject<BPFError> for SyscallLogPubkey<'a> {
             translate_type::<Pubkey>(memory_mapping, pubkey_addr, self.loader_id),
             result
         );
-        stable_log::program_log(&self.logger, &pubkey.to_string());
+        stable_log::program_log_record(
+            &self.logger,
+            ProgramLogRecord {
+                program_id: self.program_id,
+                stack_height: self.stack_height,
+                kind: ProgramLogKind::Pubkey(*pubkey),
+            },
+        );
+        *result = Ok(0);
+    }
+}
+
+/// Log raw bytes
+pub struct SyscallLogData<'a> {
+    cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    logger: Rc<RefCell<dyn Logger>>,
+    program_id: Pubkey,
+    stack_height: usize,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BPFError> for SyscallLogData<'a> {
+    fn call(
+        &mut self,
+        addr: u64,
+        len: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.cost), result);
+        let data = question_mark!(
+            translate_slice::<u8>(memory_mapping, addr, len, self.loader_id),
+            result
+        );
+        stable_log::program_log_record(
+            &self.logger,
+            ProgramLogRecord {
+                program_id: self.program_id,
+                stack_height: self.stack_height,
+                kind: ProgramLogKind::Data(data.to_vec()),
+            },
+        );
         *result = Ok(0);
     }
 }
@@ -543,7 +1642,7 @@ impl<'a> SyscallObject<BPFError> for SyscallLogPubkey<'a> {
 /// to the VM to use for enforcement.
 pub struct SyscallAllocFree {
     aligned: bool,
-    allocator: BPFAllocator,
+    allocator: Box<dyn Alloc>,
 }
 impl SyscallObject<BPFError> for SyscallAllocFree {
     fn call(
@@ -585,6 +1684,7 @@ struct SyscallCreateProgramAddress<'a> {
     cost: u64,
     compute_meter: Rc<RefCell<dyn ComputeMeter>>,
     loader_id: &'a Pubkey,
+    reject_overlapping_buffers: bool,
 }
 impl<'a> SyscallObject<BPFError> for SyscallCreateProgramAddress<'a> {
     fn call(
@@ -598,9 +1698,14 @@ impl<'a> SyscallObject<BPFError> for SyscallCreateProgramAddress<'a> {
         result: &mut Result<u64, EbpfError<BPFError>>,
     ) {
         question_mark!(self.compute_meter.consume(self.cost), result);
+        let mut translations = translation_set(
+            memory_mapping,
+            self.loader_id,
+            self.reject_overlapping_buffers,
+        );
         // TODO need ref?
         let untranslated_seeds = question_mark!(
-            translate_slice::<&[&u8]>(memory_mapping, seeds_addr, seeds_len, self.loader_id),
+            translations.add_input_slice::<&[&u8]>(seeds_addr, seeds_len),
             result
         );
         if untranslated_seeds.len() > MAX_SEEDS {
@@ -611,18 +1716,16 @@ impl<'a> SyscallObject<BPFError> for SyscallCreateProgramAddress<'a> {
             untranslated_seeds
                 .iter()
                 .map(|untranslated_seed| {
-                    translate_slice::<u8>(
-                        memory_mapping,
+                    translations.add_input_slice::<u8>(
                         untranslated_seed.as_ptr() as *const _ as u64,
                         untranslated_seed.len() as u64,
-                        self.loader_id,
                     )
                 })
                 .collect::<Result<Vec<_>, EbpfError<BPFError>>>(),
             result
         );
         let program_id = question_mark!(
-            translate_type::<Pubkey>(memory_mapping, program_id_addr, self.loader_id),
+            translations.add_input_type::<Pubkey>(program_id_addr),
             result
         );
 
@@ -633,21 +1736,359 @@ impl<'a> SyscallObject<BPFError> for SyscallCreateProgramAddress<'a> {
                 return;
             }
         };
-        let address = question_mark!(
-            translate_slice_mut::<u8>(memory_mapping, address_addr, 32, self.loader_id),
-            result
-        );
-        address.copy_from_slice(new_address.as_ref());
+        let address = question_mark!(translations.add_slice::<u8>(address_addr, 32), result);
+        let translations = question_mark!(translations.check(), result);
+        question_mark!(translations.get_slice(address), result)
+            .copy_from_slice(new_address.as_ref());
         *result = Ok(0);
     }
 }
 
-/// SHA256
-pub struct SyscallSha256<'a> {
-    sha256_base_cost: u64,
-    sha256_byte_cost: u64,
+/// Create a program address for each of up to `MAX_PROGRAM_ADDRESSES` sets of seeds, charging
+/// `base_cost` per call and `address_cost` per address
+struct SyscallCreateProgramAddressesBatch<'a> {
+    base_cost: u64,
+    address_cost: u64,
     compute_meter: Rc<RefCell<dyn ComputeMeter>>,
     loader_id: &'a Pubkey,
+    reject_overlapping_buffers: bool,
+}
+impl<'a> SyscallObject<BPFError> for SyscallCreateProgramAddressesBatch<'a> {
+    fn call(
+        &mut self,
+        seed_sets_addr: u64,
+        seed_sets_len: u64,
+        program_id_addr: u64,
+        addresses_addr: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.base_cost), result);
+        if seed_sets_len > MAX_PROGRAM_ADDRESSES as u64 {
+            // `PubkeyError::TooManyProgramAddresses`
+            *result = Ok(2);
+            return;
+        }
+        question_mark!(
+            self.compute_meter
+                .consume(self.address_cost.saturating_mul(seed_sets_len)),
+            result
+        );
+        let mut translations = translation_set(
+            memory_mapping,
+            self.loader_id,
+            self.reject_overlapping_buffers,
+        );
+        let untranslated_seed_sets = question_mark!(
+            translations.add_input_slice::<&[&[u8]]>(seed_sets_addr, seed_sets_len),
+            result
+        );
+        let program_id = question_mark!(
+            translations.add_input_type::<Pubkey>(program_id_addr),
+            result
+        );
+
+        // Derive every address before translating the output
+        let mut new_addresses = Vec::with_capacity(untranslated_seed_sets.len());
+        for untranslated_seeds in untranslated_seed_sets.iter() {
+            let untranslated_seeds = question_mark!(
+                translations.add_input_slice::<&[u8]>(
+                    untranslated_seeds.as_ptr() as *const _ as u64,
+                    untranslated_seeds.len() as u64,
+                ),
+                result
+            );
+            if untranslated_seeds.len() > MAX_SEEDS {
+                *result = Ok(1);
+                return;
+            }
+            let seeds = question_mark!(
+                untranslated_seeds
+                    .iter()
+                    .map(|untranslated_seed| {
+                        translations.add_input_slice::<u8>(
+                            untranslated_seed.as_ptr() as *const _ as u64,
+                            untranslated_seed.len() as u64,
+                        )
+                    })
+                    .collect::<Result<Vec<_>, EbpfError<BPFError>>>(),
+                result
+            );
+            match Pubkey::create_program_address(&seeds, program_id) {
+                Ok(address) => new_addresses.push(address),
+                Err(_) => {
+                    *result = Ok(1);
+                    return;
+                }
+            }
+        }
+
+        let addresses = question_mark!(
+            translations.add_slice::<Pubkey>(addresses_addr, seed_sets_len),
+            result
+        );
+        let translations = question_mark!(translations.check(), result);
+        question_mark!(translations.get_slice(addresses), result).copy_from_slice(&new_addresses);
+        *result = Ok(0);
+    }
+}
+
+/// Get the maximum depth of cross-program invocations
+pub struct SyscallGetMaxInvokeDepth {
+    cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    max_invoke_depth: u64,
+}
+impl SyscallObject<BPFError> for SyscallGetMaxInvokeDepth {
+    fn call(
+        &mut self,
+        _arg1: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        _memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.cost), result);
+        *result = Ok(self.max_invoke_depth);
+    }
+}
+
+/// Get the version of the runtime's program interface, packed by `RuntimeVersion::to_u64`
+pub struct SyscallGetRuntimeVersion {
+    cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    runtime_version: u64,
+}
+impl SyscallObject<BPFError> for SyscallGetRuntimeVersion {
+    fn call(
+        &mut self,
+        _arg1: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        _memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.cost), result);
+        *result = Ok(self.runtime_version);
+    }
+}
+
+/// Get the program IDs of the invocation stack, from the top-level instruction's program to
+/// the currently executing program.  A program ID's index plus one is its stack height.
+pub struct SyscallGetInstructionAncestry<'a> {
+    cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    program_ids: Vec<Pubkey>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BPFError> for SyscallGetInstructionAncestry<'a> {
+    fn call(
+        &mut self,
+        program_ids_addr: u64,
+        program_ids_len: u64,
+        stack_height_addr: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.cost), result);
+        // Only as many program IDs as fit are written, outermost first
+        let len = program_ids_len.min(self.program_ids.len() as u64);
+        let mut translations = MutTranslationSet::new(memory_mapping, self.loader_id);
+        let program_ids = question_mark!(
+            translations.add_slice::<Pubkey>(program_ids_addr, len),
+            result
+        );
+        let stack_height = question_mark!(translations.add_type::<u64>(stack_height_addr), result);
+        let translations = question_mark!(translations.check(), result);
+        question_mark!(translations.get_slice(program_ids), result)
+            .copy_from_slice(&self.program_ids[..len as usize]);
+        *question_mark!(translations.get(stack_height), result) = self.program_ids.len() as u64;
+        *result = Ok(0);
+    }
+}
+
+/// Get a signature of the transaction being processed, by index
+pub struct SyscallGetTransactionSignature<'a> {
+    cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    signatures: Vec<Signature>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BPFError> for SyscallGetTransactionSignature<'a> {
+    fn call(
+        &mut self,
+        index: u64,
+        signature_addr: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.cost), result);
+        let signature = match self.signatures.get(index as usize) {
+            Some(signature) => signature,
+            None => {
+                *result = Ok(1);
+                return;
+            }
+        };
+        let signature_result = question_mark!(
+            translate_slice_mut::<u8>(
+                memory_mapping,
+                signature_addr,
+                SIGNATURE_BYTES as u64,
+                self.loader_id
+            ),
+            result
+        );
+        signature_result.copy_from_slice(signature.as_ref());
+        *result = Ok(0);
+    }
+}
+
+/// Get the recent blockhash the transaction being processed was signed against
+pub struct SyscallGetRecentBlockhash<'a> {
+    cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    recent_blockhash: Hash,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BPFError> for SyscallGetRecentBlockhash<'a> {
+    fn call(
+        &mut self,
+        blockhash_addr: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.cost), result);
+        let blockhash = question_mark!(
+            translate_type_mut::<Hash>(memory_mapping, blockhash_addr, self.loader_id),
+            result
+        );
+        *blockhash = self.recent_blockhash;
+        *result = Ok(0);
+    }
+}
+
+/// Get the fee payer of the transaction being processed
+pub struct SyscallGetFeePayer<'a> {
+    cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    fee_payer: Pubkey,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BPFError> for SyscallGetFeePayer<'a> {
+    fn call(
+        &mut self,
+        fee_payer_addr: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.cost), result);
+        let fee_payer = question_mark!(
+            translate_type_mut::<Pubkey>(memory_mapping, fee_payer_addr, self.loader_id),
+            result
+        );
+        *fee_payer = self.fee_payer;
+        *result = Ok(0);
+    }
+}
+
+/// Get the limits the runtime enforces on the executing program
+pub struct SyscallGetRuntimeLimits<'a> {
+    cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    limits: RuntimeLimits,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BPFError> for SyscallGetRuntimeLimits<'a> {
+    fn call(
+        &mut self,
+        limits_addr: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.cost), result);
+        let limits = question_mark!(
+            translate_type_mut::<RuntimeLimits>(memory_mapping, limits_addr, self.loader_id),
+            result
+        );
+        *limits = self.limits;
+        *result = Ok(0);
+    }
+}
+
+/// Get deterministic pseudorandom bytes, see `solana_program::pseudorandom`
+pub struct SyscallGetPseudorandom<'a> {
+    base_cost: u64,
+    byte_cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    recent_blockhash: Hash,
+    program_id: Pubkey,
+    /// Number of calls made so far by any program in the transaction
+    counter: Rc<RefCell<u64>>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BPFError> for SyscallGetPseudorandom<'a> {
+    fn call(
+        &mut self,
+        bytes_addr: u64,
+        bytes_len: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.base_cost), result);
+        question_mark!(
+            self.compute_meter
+                .consume(self.byte_cost.saturating_mul(bytes_len)),
+            result
+        );
+        let bytes = question_mark!(
+            translate_slice_mut::<u8>(memory_mapping, bytes_addr, bytes_len, self.loader_id),
+            result
+        );
+        let mut counter = question_mark!(
+            self.counter
+                .try_borrow_mut()
+                .map_err(|_| SyscallError::InvokeContextBorrowFailed),
+            result
+        );
+        derive_pseudorandom(&self.recent_blockhash, &self.program_id, *counter, bytes);
+        *counter += 1;
+        *result = Ok(0);
+    }
+}
+
+/// SHA256
+pub struct SyscallSha256<'a> {
+    sha256_base_cost: u64,
+    sha256_byte_cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    loader_id: &'a Pubkey,
+    reject_overlapping_buffers: bool,
 }
 impl<'a> SyscallObject<BPFError> for SyscallSha256<'a> {
     fn call(
@@ -661,29 +2102,24 @@ impl<'a> SyscallObject<BPFError> for SyscallSha256<'a> {
         result: &mut Result<u64, EbpfError<BPFError>>,
     ) {
         question_mark!(self.compute_meter.consume(self.sha256_base_cost), result);
+        let mut translations = translation_set(
+            memory_mapping,
+            self.loader_id,
+            self.reject_overlapping_buffers,
+        );
         let hash_result = question_mark!(
-            translate_slice_mut::<u8>(
-                memory_mapping,
-                result_addr,
-                HASH_BYTES as u64,
-                self.loader_id
-            ),
+            translations.add_slice::<u8>(result_addr, HASH_BYTES as u64),
             result
         );
-        let mut hasher = Hasher::default();
+        let mut inputs = vec![];
         if vals_len > 0 {
             let vals = question_mark!(
-                translate_slice::<&[u8]>(memory_mapping, vals_addr, vals_len, self.loader_id),
+                translations.add_input_slice::<&[u8]>(vals_addr, vals_len),
                 result
             );
             for val in vals.iter() {
                 let bytes = question_mark!(
-                    translate_slice::<u8>(
-                        memory_mapping,
-                        val.as_ptr() as u64,
-                        val.len() as u64,
-                        self.loader_id
-                    ),
+                    translations.add_input_slice::<u8>(val.as_ptr() as u64, val.len() as u64),
                     result
                 );
                 question_mark!(
@@ -691,1023 +2127,4386 @@ impl<'a> SyscallObject<BPFError> for SyscallSha256<'a> {
                         .consume(self.sha256_byte_cost * (val.len() as u64 / 2)),
                     result
                 );
-                hasher.hash(bytes);
+                inputs.push(bytes);
             }
         }
-        hash_result.copy_from_slice(&hasher.result().to_bytes());
+        let translations = question_mark!(translations.check(), result);
+        let mut hasher = Hasher::default();
+        hasher.hashv(&inputs);
+        question_mark!(translations.get_slice(hash_result), result)
+            .copy_from_slice(&hasher.result().to_bytes());
         *result = Ok(0);
     }
 }
 
-/// Ristretto point multiply
-pub struct SyscallRistrettoMul<'a> {
-    cost: u64,
+/// HMAC-SHA256
+pub struct SyscallHmacSha256<'a> {
+    base_cost: u64,
+    byte_cost: u64,
     compute_meter: Rc<RefCell<dyn ComputeMeter>>,
     loader_id: &'a Pubkey,
+    reject_overlapping_buffers: bool,
 }
-impl<'a> SyscallObject<BPFError> for SyscallRistrettoMul<'a> {
+impl<'a> SyscallObject<BPFError> for SyscallHmacSha256<'a> {
     fn call(
         &mut self,
-        point_addr: u64,
-        scalar_addr: u64,
+        key_addr: u64,
+        key_len: u64,
+        vals_addr: u64,
+        vals_len: u64,
         result_addr: u64,
-        _arg4: u64,
-        _arg5: u64,
         memory_mapping: &MemoryMapping,
         result: &mut Result<u64, EbpfError<BPFError>>,
     ) {
-        question_mark!(self.compute_meter.consume(self.cost), result);
-
-        let point = question_mark!(
-            translate_type::<RistrettoPoint>(memory_mapping, point_addr, self.loader_id),
+        question_mark!(self.compute_meter.consume(self.base_cost), result);
+        question_mark!(
+            self.compute_meter
+                .consume(self.byte_cost.saturating_mul(key_len / 2)),
             result
         );
-        let scalar = question_mark!(
-            translate_type::<Scalar>(memory_mapping, scalar_addr, self.loader_id),
+        let mut translations = translation_set(
+            memory_mapping,
+            self.loader_id,
+            self.reject_overlapping_buffers,
+        );
+        let key = question_mark!(
+            translations.add_input_slice::<u8>(key_addr, key_len),
             result
         );
-        let output = question_mark!(
-            translate_type_mut::<RistrettoPoint>(memory_mapping, result_addr, self.loader_id),
+        let hash_result = question_mark!(
+            translations.add_slice::<u8>(result_addr, HASH_BYTES as u64),
             result
         );
-        *output = point * scalar;
-
-        *result = Ok(0);
-    }
-}
-
-// Cross-program invocation syscalls
-
-struct AccountReferences<'a> {
-    lamports: &'a mut u64,
-    owner: &'a mut Pubkey,
-    data: &'a mut [u8],
-    ref_to_len_in_vm: &'a mut u64,
-    serialized_len_ptr: &'a mut u64,
+        let mut vals = vec![];
+        if vals_len > 0 {
+            let vals_in = question_mark!(
+                translations.add_input_slice::<&[u8]>(vals_addr, vals_len),
+                result
+            );
+            for val in vals_in.iter() {
+                question_mark!(
+                    self.compute_meter
+                        .consume(self.byte_cost.saturating_mul(val.len() as u64 / 2)),
+                    result
+                );
+                vals.push(question_mark!(
+                    translations.add_input_slice::<u8>(val.as_ptr() as u64, val.len() as u64),
+                    result
+                ));
+            }
+        }
+        let translations = question_mark!(translations.check(), result);
+        question_mark!(translations.get_slice(hash_result), result)
+            .copy_from_slice(&hmacv(key, &vals).to_bytes());
+        *result = Ok(0);
+    }
 }
-type TranslatedAccounts<'a> = (Vec<Rc<RefCell<Account>>>, Vec<AccountReferences<'a>>);
 
-/// Implemented by language specific data structure translators
-trait SyscallInvokeSigned<'a> {
-    fn get_context_mut(&self) -> Result<RefMut<&'a mut dyn InvokeContext>, EbpfError<BPFError>>;
-    fn get_callers_keyed_accounts(&self) -> &'a [KeyedAccount<'a>];
-    fn translate_instruction(
-        &self,
-        addr: u64,
-        memory_mapping: &MemoryMapping,
-    ) -> Result<Instruction, EbpfError<BPFError>>;
-    fn translate_accounts(
-        &self,
-        message: &Message,
-        account_infos_addr: u64,
-        account_infos_len: u64,
-        memory_mapping: &MemoryMapping,
-    ) -> Result<TranslatedAccounts<'a>, EbpfError<BPFError>>;
-    fn translate_signers(
-        &self,
-        program_id: &Pubkey,
-        signers_seeds_addr: u64,
-        signers_seeds_len: u64,
-        memory_mapping: &MemoryMapping,
-    ) -> Result<Vec<Pubkey>, EbpfError<BPFError>>;
-}
+/// Maximum number of contexts of each hash function a program may hold open at once
+pub const MAX_HASH_CONTEXTS: usize = 8;
 
-/// Cross-program invocation called from Rust
-pub struct SyscallInvokeSignedRust<'a> {
-    callers_keyed_accounts: &'a [KeyedAccount<'a>],
-    invoke_context: Rc<RefCell<&'a mut dyn InvokeContext>>,
-    loader_id: &'a Pubkey,
+/// Hash function whose intermediate state the runtime holds between syscalls
+pub trait ContextHasher: Default {
+    fn update(&mut self, bytes: &[u8]);
+    fn finalize(self) -> [u8; HASH_BYTES];
 }
-impl<'a> SyscallInvokeSigned<'a> for SyscallInvokeSignedRust<'a> {
-    fn get_context_mut(&self) -> Result<RefMut<&'a mut dyn InvokeContext>, EbpfError<BPFError>> {
-        self.invoke_context
-            .try_borrow_mut()
-            .map_err(|_| SyscallError::InvokeContextBorrowFailed.into())
+impl ContextHasher for Hasher {
+    fn update(&mut self, bytes: &[u8]) {
+        self.hash(bytes);
     }
-    fn get_callers_keyed_accounts(&self) -> &'a [KeyedAccount<'a>] {
-        self.callers_keyed_accounts
+    fn finalize(self) -> [u8; HASH_BYTES] {
+        self.result().to_bytes()
     }
-    fn translate_instruction(
-        &self,
-        addr: u64,
-        memory_mapping: &MemoryMapping,
-    ) -> Result<Instruction, EbpfError<BPFError>> {
-        let ix = translate_type::<Instruction>(memory_mapping, addr, self.loader_id)?;
-        let accounts = translate_slice::<AccountMeta>(
-            memory_mapping,
-            ix.accounts.as_ptr() as u64,
-            ix.accounts.len() as u64,
-            self.loader_id,
-        )?
-        .to_vec();
-        let data = translate_slice::<u8>(
-            memory_mapping,
-            ix.data.as_ptr() as u64,
-            ix.data.len() as u64,
-            self.loader_id,
-        )?
-        .to_vec();
-        Ok(Instruction {
-            program_id: ix.program_id,
-            accounts,
-            data,
-        })
+}
+impl ContextHasher for Keccak256Context {
+    fn update(&mut self, bytes: &[u8]) {
+        Keccak256Context::update(self, &[bytes]);
     }
-
-    fn translate_accounts(
-        &self,
-        message: &Message,
-        account_infos_addr: u64,
-        account_infos_len: u64,
-        memory_mapping: &MemoryMapping,
-    ) -> Result<TranslatedAccounts<'a>, EbpfError<BPFError>> {
-        let account_infos = if account_infos_len > 0 {
-            translate_slice::<AccountInfo>(
-                memory_mapping,
-                account_infos_addr,
-                account_infos_len,
-                self.loader_id,
-            )?
-        } else {
-            &[]
-        };
-
-        let mut accounts = Vec::with_capacity(message.account_keys.len());
-        let mut refs = Vec::with_capacity(message.account_keys.len());
-        'root: for account_key in message.account_keys.iter() {
-            for account_info in account_infos.iter() {
-                let key = translate_type::<Pubkey>(
-                    memory_mapping,
-                    account_info.key as *const _ as u64,
-                    self.loader_id,
-                )?;
-                if account_key == key {
-                    let lamports = {
-                        // Double translate lamports out of RefCell
-                        let ptr = translate_type::<u64>(
-                            memory_mapping,
-                            account_info.lamports.as_ptr() as u64,
-                            self.loader_id,
-                        )?;
-                        translate_type_mut::<u64>(memory_mapping, *ptr, self.loader_id)?
-                    };
-                    let owner = translate_type_mut::<Pubkey>(
-                        memory_mapping,
-                        account_info.owner as *const _ as u64,
-                        self.loader_id,
-                    )?;
-                    let (data, ref_to_len_in_vm, serialized_len_ptr) = {
-                        // Double translate data out of RefCell
-                        let data = *translate_type::<&[u8]>(
-                            memory_mapping,
-                            account_info.data.as_ptr() as *const _ as u64,
-                            self.loader_id,
-                        )?;
-                        let translated = translate(
-                            memory_mapping,
-                            AccessType::Store,
-                            unsafe { (account_info.data.as_ptr() as *const u64).offset(1) as u64 },
-                            8,
-                        )? as *mut u64;
-                        let ref_to_len_in_vm = unsafe { &mut *translated };
-                        let ref_of_len_in_input_buffer = unsafe { data.as_ptr().offset(-8) };
-                        let serialized_len_ptr = translate_type_mut::<u64>(
-                            memory_mapping,
-                            ref_of_len_in_input_buffer as *const _ as u64,
-                            self.loader_id,
-                        )?;
-                        (
-                            translate_slice_mut::<u8>(
-                                memory_mapping,
-                                data.as_ptr() as u64,
-                                data.len() as u64,
-                                self.loader_id,
-                            )?,
-                            ref_to_len_in_vm,
-                            serialized_len_ptr,
-                        )
-                    };
-
-                    accounts.push(Rc::new(RefCell::new(Account {
-                        lamports: *lamports,
-                        data: data.to_vec(),
-                        executable: account_info.executable,
-                        owner: *owner,
-                        rent_epoch: account_info.rent_epoch,
-                    })));
-                    refs.push(AccountReferences {
-                        lamports,
-                        owner,
-                        data,
-                        ref_to_len_in_vm,
-                        serialized_len_ptr,
-                    });
-                    continue 'root;
-                }
-            }
-            return Err(SyscallError::InstructionError(InstructionError::MissingAccount).into());
-        }
-
-        Ok((accounts, refs))
+    fn finalize(self) -> [u8; HASH_BYTES] {
+        Keccak256Context::finalize(self)
     }
+}
 
-    fn translate_signers(
-        &self,
-        program_id: &Pubkey,
-        signers_seeds_addr: u64,
-        signers_seeds_len: u64,
-        memory_mapping: &MemoryMapping,
-    ) -> Result<Vec<Pubkey>, EbpfError<BPFError>> {
-        let mut signers = Vec::new();
-        if signers_seeds_len > 0 {
-            let signers_seeds = translate_slice::<&[&[u8]]>(
-                memory_mapping,
-                signers_seeds_addr,
-                signers_seeds_len,
-                self.loader_id,
-            )?;
-            if signers_seeds.len() > MAX_SIGNERS {
-                return Err(SyscallError::TooManySigners.into());
-            }
-            for signer_seeds in signers_seeds.iter() {
-                let untranslated_seeds = translate_slice::<&[u8]>(
-                    memory_mapping,
-                    signer_seeds.as_ptr() as *const _ as u64,
-                    signer_seeds.len() as u64,
-                    self.loader_id,
-                )?;
-                if untranslated_seeds.len() > MAX_SEEDS {
-                    return Err(SyscallError::InstructionError(
-                        InstructionError::MaxSeedLengthExceeded,
-                    )
-                    .into());
-                }
-                let seeds = untranslated_seeds
-                    .iter()
-                    .map(|untranslated_seed| {
-                        translate_slice::<u8>(
-                            memory_mapping,
-                            untranslated_seed.as_ptr() as *const _ as u64,
-                            untranslated_seed.len() as u64,
-                            self.loader_id,
-                        )
-                    })
-                    .collect::<Result<Vec<_>, EbpfError<BPFError>>>()?;
-                let signer = Pubkey::create_program_address(&seeds, program_id)
-                    .map_err(SyscallError::BadSeeds)?;
-                signers.push(signer);
+/// Hashers opened by an init syscall, indexed by handle, until the matching final syscall
+#[derive(Default)]
+pub struct HashContexts<H> {
+    hashers: Vec<Option<H>>,
+}
+impl<H: ContextHasher> HashContexts<H> {
+    fn open(&mut self) -> Result<u64, EbpfError<BPFError>> {
+        let handle = match self.hashers.iter().position(Option::is_none) {
+            Some(handle) => handle,
+            None if self.hashers.len() < MAX_HASH_CONTEXTS => {
+                self.hashers.push(None);
+                self.hashers.len() - 1
             }
-            Ok(signers)
-        } else {
-            Ok(vec![])
-        }
+            None => return Err(SyscallError::TooManyHashContexts.into()),
+        };
+        self.hashers[handle] = Some(H::default());
+        Ok(handle as u64)
+    }
+    fn get_mut(&mut self, handle: u64) -> Result<&mut H, EbpfError<BPFError>> {
+        self.hashers
+            .get_mut(handle as usize)
+            .and_then(Option::as_mut)
+            .ok_or_else(|| SyscallError::InvalidHashContext.into())
+    }
+    fn close(&mut self, handle: u64) -> Result<H, EbpfError<BPFError>> {
+        self.hashers
+            .get_mut(handle as usize)
+            .and_then(Option::take)
+            .ok_or_else(|| SyscallError::InvalidHashContext.into())
     }
 }
-impl<'a> SyscallObject<BPFError> for SyscallInvokeSignedRust<'a> {
+/// SHA256 hashers opened by `sol_sha256_init`
+pub type Sha256Contexts = HashContexts<Hasher>;
+/// Keccak-256 hashers opened by `sol_keccak256_init`
+pub type Keccak256Contexts = HashContexts<Keccak256Context>;
+
+/// Open a hash context, returns its handle
+pub struct SyscallHashInit<H> {
+    cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    contexts: Rc<RefCell<HashContexts<H>>>,
+}
+impl<H: ContextHasher> SyscallObject<BPFError> for SyscallHashInit<H> {
     fn call(
         &mut self,
-        instruction_addr: u64,
-        account_infos_addr: u64,
-        account_infos_len: u64,
-        signers_seeds_addr: u64,
-        signers_seeds_len: u64,
-        memory_mapping: &MemoryMapping,
+        _arg1: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        _memory_mapping: &MemoryMapping,
         result: &mut Result<u64, EbpfError<BPFError>>,
     ) {
-        *result = call(
-            self,
-            instruction_addr,
-            account_infos_addr,
-            account_infos_len,
-            signers_seeds_addr,
-            signers_seeds_len,
-            memory_mapping,
-        );
+        question_mark!(self.compute_meter.consume(self.cost), result);
+        *result = self.contexts.borrow_mut().open();
     }
 }
+pub type SyscallSha256Init = SyscallHashInit<Hasher>;
+pub type SyscallKeccak256Init = SyscallHashInit<Keccak256Context>;
 
-/// Rust representation of C's SolInstruction
-#[derive(Debug)]
-struct SolInstruction {
-    program_id_addr: u64,
-    accounts_addr: u64,
-    accounts_len: usize,
-    data_addr: u64,
-    data_len: usize,
-}
-
-/// Rust representation of C's SolAccountMeta
-#[derive(Debug)]
-struct SolAccountMeta {
-    pubkey_addr: u64,
-    is_writable: bool,
-    is_signer: bool,
+/// Hash more data into an open hash context, charging `base_cost` per call and `byte_cost`
+/// per two bytes
+pub struct SyscallHashUpdate<'a, H> {
+    base_cost: u64,
+    byte_cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    contexts: Rc<RefCell<HashContexts<H>>>,
+    loader_id: &'a Pubkey,
 }
-
-/// Rust representation of C's SolAccountInfo
-#[derive(Debug)]
-struct SolAccountInfo {
-    key_addr: u64,
-    lamports_addr: u64,
-    data_len: u64,
-    data_addr: u64,
-    owner_addr: u64,
-    rent_epoch: u64,
-    is_signer: bool,
-    is_writable: bool,
-    executable: bool,
+impl<'a, H: ContextHasher> SyscallObject<BPFError> for SyscallHashUpdate<'a, H> {
+    fn call(
+        &mut self,
+        handle: u64,
+        vals_addr: u64,
+        vals_len: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.base_cost), result);
+        let mut contexts = self.contexts.borrow_mut();
+        let hasher = question_mark!(contexts.get_mut(handle), result);
+        if vals_len > 0 {
+            let vals = question_mark!(
+                translate_slice::<&[u8]>(memory_mapping, vals_addr, vals_len, self.loader_id),
+                result
+            );
+            for val in vals.iter() {
+                question_mark!(
+                    self.compute_meter.consume(
+                        self.byte_cost
+                            .saturating_mul((val.len() as u64).saturating_add(1) / 2)
+                    ),
+                    result
+                );
+                let bytes = question_mark!(
+                    translate_slice::<u8>(
+                        memory_mapping,
+                        val.as_ptr() as u64,
+                        val.len() as u64,
+                        self.loader_id
+                    ),
+                    result
+                );
+                hasher.update(bytes);
+            }
+        }
+        *result = Ok(0);
+    }
 }
+pub type SyscallSha256Update<'a> = SyscallHashUpdate<'a, Hasher>;
+pub type SyscallKeccak256Update<'a> = SyscallHashUpdate<'a, Keccak256Context>;
 
-/// Rust representation of C's SolSignerSeed
-#[derive(Debug)]
-struct SolSignerSeedC {
-    addr: u64,
-    len: u64,
+/// Close a hash context and write out its hash
+pub struct SyscallHashFinal<'a, H> {
+    cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    contexts: Rc<RefCell<HashContexts<H>>>,
+    loader_id: &'a Pubkey,
 }
-
-/// Rust representation of C's SolSignerSeeds
-#[derive(Debug)]
-struct SolSignerSeedsC {
-    addr: u64,
-    len: u64,
+impl<'a, H: ContextHasher> SyscallObject<BPFError> for SyscallHashFinal<'a, H> {
+    fn call(
+        &mut self,
+        handle: u64,
+        result_addr: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.cost), result);
+        let hash_result = question_mark!(
+            translate_slice_mut::<u8>(
+                memory_mapping,
+                result_addr,
+                HASH_BYTES as u64,
+                self.loader_id
+            ),
+            result
+        );
+        let hasher = question_mark!(self.contexts.borrow_mut().close(handle), result);
+        hash_result.copy_from_slice(&hasher.finalize());
+        *result = Ok(0);
+    }
 }
+pub type SyscallSha256Final<'a> = SyscallHashFinal<'a, Hasher>;
+pub type SyscallKeccak256Final<'a> = SyscallHashFinal<'a, Keccak256Context>;
 
-/// Cross-program invocation called from C
-pub struct SyscallInvokeSignedC<'a> {
-    callers_keyed_accounts: &'a [KeyedAccount<'a>],
-    invoke_context: Rc<RefCell<&'a mut dyn InvokeContext>>,
+/// ChaCha20-Poly1305 authenticated decryption, returns 1 if the ciphertext fails
+/// authentication
+pub struct SyscallChaCha20Poly1305Open<'a> {
+    base_cost: u64,
+    byte_cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
     loader_id: &'a Pubkey,
+    reject_overlapping_buffers: bool,
 }
-impl<'a> SyscallInvokeSigned<'a> for SyscallInvokeSignedC<'a> {
-    fn get_context_mut(&self) -> Result<RefMut<&'a mut dyn InvokeContext>, EbpfError<BPFError>> {
-        self.invoke_context
-            .try_borrow_mut()
-            .map_err(|_| SyscallError::InvokeContextBorrowFailed.into())
-    }
-    fn get_callers_keyed_accounts(&self) -> &'a [KeyedAccount<'a>] {
-        self.callers_keyed_accounts
-    }
-    fn translate_instruction(
-        &self,
-        addr: u64,
+impl<'a> SyscallObject<BPFError> for SyscallChaCha20Poly1305Open<'a> {
+    fn call(
+        &mut self,
+        inputs_addr: u64,
+        inputs_len: u64,
+        plaintext_addr: u64,
+        plaintext_len: u64,
+        _arg5: u64,
         memory_mapping: &MemoryMapping,
-    ) -> Result<Instruction, EbpfError<BPFError>> {
-        let ix_c = translate_type::<SolInstruction>(memory_mapping, addr, self.loader_id)?;
-        let program_id =
-            translate_type::<Pubkey>(memory_mapping, ix_c.program_id_addr, self.loader_id)?;
-        let meta_cs = translate_slice::<SolAccountMeta>(
-            memory_mapping,
-            ix_c.accounts_addr,
-            ix_c.accounts_len as u64,
-            self.loader_id,
-        )?;
-        let data = translate_slice::<u8>(
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.base_cost), result);
+        // Inputs are the key, nonce, additional data, ciphertext and tag
+        if inputs_len != 5 {
+            *result = Err(SyscallError::InvalidAeadLength("inputs").into());
+            return;
+        }
+        let mut translations = translation_set(
             memory_mapping,
-            ix_c.data_addr,
-            ix_c.data_len as u64,
             self.loader_id,
-        )?
-        .to_vec();
-        let accounts = meta_cs
-            .iter()
-            .map(|meta_c| {
-                let pubkey =
-                    translate_type::<Pubkey>(memory_mapping, meta_c.pubkey_addr, self.loader_id)?;
-                Ok(AccountMeta {
-                    pubkey: *pubkey,
-                    is_signer: meta_c.is_signer,
-                    is_writable: meta_c.is_writable,
-                })
-            })
-            .collect::<Result<Vec<AccountMeta>, EbpfError<BPFError>>>()?;
+            self.reject_overlapping_buffers,
+        );
+        let inputs = question_mark!(
+            translations.add_input_slice::<&[u8]>(inputs_addr, inputs_len),
+            result
+        );
+        for (input, expected_len, name) in &[
+            (inputs[0], chacha20_poly1305::KEY_BYTES, "key"),
+            (inputs[1], chacha20_poly1305::NONCE_BYTES, "nonce"),
+            (inputs[4], chacha20_poly1305::TAG_BYTES, "tag"),
+            (inputs[3], plaintext_len as usize, "plaintext"),
+        ] {
+            if input.len() != *expected_len {
+                *result = Err(SyscallError::InvalidAeadLength(*name).into());
+                return;
+            }
+        }
+        question_mark!(
+            self.compute_meter.consume(
+                self.byte_cost.saturating_mul(
+                    (inputs[2].len() as u64).saturating_add(inputs[3].len() as u64)
+                )
+            ),
+            result
+        );
 
-        Ok(Instruction {
-            program_id: *program_id,
-            accounts,
-            data,
-        })
+        let mut translated = vec![];
+        for input in inputs.iter() {
+            translated.push(question_mark!(
+                translations.add_input_slice::<u8>(input.as_ptr() as u64, input.len() as u64),
+                result
+            ));
+        }
+        let plaintext = question_mark!(
+            translations.add_slice::<u8>(plaintext_addr, plaintext_len),
+            result
+        );
+        let translations = question_mark!(translations.check(), result);
+        let plaintext = question_mark!(translations.get_slice(plaintext), result);
+        let mut key = [0; chacha20_poly1305::KEY_BYTES];
+        key.copy_from_slice(translated[0]);
+        let mut nonce = [0; chacha20_poly1305::NONCE_BYTES];
+        nonce.copy_from_slice(translated[1]);
+        let mut tag = [0; chacha20_poly1305::TAG_BYTES];
+        tag.copy_from_slice(translated[4]);
+
+        *result = match chacha20_poly1305::open(
+            &key,
+            &nonce,
+            translated[2],
+            translated[3],
+            &tag,
+            plaintext,
+        ) {
+            Ok(()) => Ok(0),
+            Err(_) => Ok(1),
+        };
     }
+}
 
-    fn translate_accounts(
-        &self,
-        message: &Message,
-        account_infos_addr: u64,
-        account_infos_len: u64,
+/// Verify a secp256r1 (NIST P-256) ECDSA signature over the SHA256 of a message, returns 1 if
+/// the signature or public key is invalid
+pub struct SyscallSecp256r1Verify<'a> {
+    cost: u64,
+    sha256_byte_cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BPFError> for SyscallSecp256r1Verify<'a> {
+    fn call(
+        &mut self,
+        message_addr: u64,
+        message_len: u64,
+        signature_addr: u64,
+        pubkey_addr: u64,
+        _arg5: u64,
         memory_mapping: &MemoryMapping,
-    ) -> Result<TranslatedAccounts<'a>, EbpfError<BPFError>> {
-        let account_infos = translate_slice::<SolAccountInfo>(
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.cost), result);
+        question_mark!(
+            self.compute_meter
+                .consume(self.sha256_byte_cost.saturating_mul(message_len / 2)),
+            result
+        );
+        let message = question_mark!(
+            translate_slice::<u8>(memory_mapping, message_addr, message_len, self.loader_id),
+            result
+        );
+        let signature = question_mark!(
+            translate_slice::<u8>(
+                memory_mapping,
+                signature_addr,
+                secp256r1::SECP256R1_SIGNATURE_BYTES as u64,
+                self.loader_id
+            ),
+            result
+        );
+        let pubkey = question_mark!(
+            translate_slice::<u8>(
+                memory_mapping,
+                pubkey_addr,
+                secp256r1::SECP256R1_PUBKEY_BYTES as u64,
+                self.loader_id
+            ),
+            result
+        );
+
+        let pubkey = UnparsedPublicKey::new(&ECDSA_P256_SHA256_FIXED, pubkey);
+        *result = match pubkey.verify(message, signature) {
+            Ok(()) => Ok(0),
+            Err(_) => Ok(1),
+        };
+    }
+}
+
+/// X25519 Diffie-Hellman, returns 1 if the point is of small order
+pub struct SyscallX25519Dh<'a> {
+    cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    loader_id: &'a Pubkey,
+    reject_overlapping_buffers: bool,
+}
+impl<'a> SyscallObject<BPFError> for SyscallX25519Dh<'a> {
+    fn call(
+        &mut self,
+        scalar_addr: u64,
+        point_addr: u64,
+        result_addr: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.cost), result);
+
+        let mut translations = translation_set(
             memory_mapping,
-            account_infos_addr,
-            account_infos_len,
             self.loader_id,
-        )?;
-        let mut accounts = Vec::with_capacity(message.account_keys.len());
-        let mut refs = Vec::with_capacity(message.account_keys.len());
-        'root: for account_key in message.account_keys.iter() {
-            for account_info in account_infos.iter() {
-                let key = translate_type::<Pubkey>(
-                    memory_mapping,
-                    account_info.key_addr,
-                    self.loader_id,
-                )?;
-                if account_key == key {
-                    let lamports = translate_type_mut::<u64>(
-                        memory_mapping,
-                        account_info.lamports_addr,
-                        self.loader_id,
-                    )?;
-                    let owner = translate_type_mut::<Pubkey>(
-                        memory_mapping,
-                        account_info.owner_addr,
-                        self.loader_id,
-                    )?;
-                    let data = translate_slice_mut::<u8>(
-                        memory_mapping,
-                        account_info.data_addr,
-                        account_info.data_len,
-                        self.loader_id,
-                    )?;
+            self.reject_overlapping_buffers,
+        );
+        let scalar = question_mark!(
+            translations.add_input_type::<[u8; x25519::X25519_BYTES]>(scalar_addr),
+            result
+        );
+        let point = question_mark!(
+            translations.add_input_type::<[u8; x25519::X25519_BYTES]>(point_addr),
+            result
+        );
+        let output = question_mark!(
+            translations.add_type::<[u8; x25519::X25519_BYTES]>(result_addr),
+            result
+        );
+        let translations = question_mark!(translations.check(), result);
+        let output = question_mark!(translations.get(output), result);
+        *result = match x25519::diffie_hellman(scalar, point) {
+            Some(shared_secret) => {
+                *output = shared_secret;
+                Ok(0)
+            }
+            None => Ok(1),
+        };
+    }
+}
 
-                    let first_info_addr = &account_infos[0] as *const _ as u64;
-                    let addr = &account_info.data_len as *const u64 as u64;
-                    let vm_addr = account_infos_addr + (addr - first_info_addr);
-                    let _ = translate(
-                        memory_mapping,
-                        AccessType::Store,
-                        vm_addr,
-                        size_of::<u64>() as u64,
-                    )?;
-                    let ref_to_len_in_vm = unsafe { &mut *(addr as *mut u64) };
+/// Copy encoded or decoded `bytes` to the output buffer and their length to `written_addr`,
+/// returns 1 if the output buffer is too small
+fn write_address_encoding_output(
+    memory_mapping: &MemoryMapping,
+    output_addr: u64,
+    output_len: u64,
+    written_addr: u64,
+    bytes: &[u8],
+    loader_id: &Pubkey,
+) -> Result<u64, EbpfError<BPFError>> {
+    if bytes.len() as u64 > output_len {
+        return Ok(1);
+    }
+    let mut translations = MutTranslationSet::new(memory_mapping, loader_id);
+    let written = translations.add_type::<u64>(written_addr)?;
+    let output = translations.add_slice::<u8>(output_addr, bytes.len() as u64)?;
+    let translations = translations.check()?;
+    let (written, output) = (translations.get(written)?, translations.get_slice(output)?);
+    output.copy_from_slice(bytes);
+    *written = bytes.len() as u64;
+    Ok(0)
+}
 
-                    let ref_of_len_in_input_buffer =
-                        unsafe { (account_info.data_addr as *mut u8).offset(-8) };
-                    let serialized_len_ptr = translate_type_mut::<u64>(
-                        memory_mapping,
-                        ref_of_len_in_input_buffer as *const _ as u64,
-                        self.loader_id,
-                    )?;
+/// Base58 encode, returns 1 if the input is too long or the output buffer too small
+pub struct SyscallBase58Encode<'a> {
+    base_cost: u64,
+    byte_cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BPFError> for SyscallBase58Encode<'a> {
+    fn call(
+        &mut self,
+        input_addr: u64,
+        input_len: u64,
+        output_addr: u64,
+        output_len: u64,
+        written_addr: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.base_cost), result);
+        if input_len > base58::MAX_BASE58_DECODED_LEN as u64 {
+            *result = Ok(1);
+            return;
+        }
+        question_mark!(
+            self.compute_meter
+                .consume(self.byte_cost.saturating_mul(input_len)),
+            result
+        );
+        let input = question_mark!(
+            translate_slice::<u8>(memory_mapping, input_addr, input_len, self.loader_id),
+            result
+        );
+        *result = match base58::encode(input) {
+            Ok(encoded) => write_address_encoding_output(
+                memory_mapping,
+                output_addr,
+                output_len,
+                written_addr,
+                encoded.as_bytes(),
+                self.loader_id,
+            ),
+            Err(_) => Ok(1),
+        };
+    }
+}
 
-                    accounts.push(Rc::new(RefCell::new(Account {
-                        lamports: *lamports,
-                        data: data.to_vec(),
-                        executable: account_info.executable,
-                        owner: *owner,
-                        rent_epoch: account_info.rent_epoch,
-                    })));
-                    refs.push(AccountReferences {
-                        lamports,
-                        owner,
-                        data,
-                        ref_to_len_in_vm,
-                        serialized_len_ptr,
-                    });
-                    continue 'root;
-                }
-            }
-            return Err(SyscallError::InstructionError(InstructionError::MissingAccount).into());
+/// Base58 decode, returns 1 if the input is too long, is not valid base58 or the output buffer
+/// is too small
+pub struct SyscallBase58Decode<'a> {
+    base_cost: u64,
+    byte_cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BPFError> for SyscallBase58Decode<'a> {
+    fn call(
+        &mut self,
+        input_addr: u64,
+        input_len: u64,
+        output_addr: u64,
+        output_len: u64,
+        written_addr: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.base_cost), result);
+        if input_len > base58::MAX_BASE58_ENCODED_LEN as u64 {
+            *result = Ok(1);
+            return;
         }
+        question_mark!(
+            self.compute_meter
+                .consume(self.byte_cost.saturating_mul(input_len)),
+            result
+        );
+        let input = question_mark!(
+            translate_slice::<u8>(memory_mapping, input_addr, input_len, self.loader_id),
+            result
+        );
+        let decoded = from_utf8(input)
+            .ok()
+            .and_then(|input| base58::decode(input).ok());
+        *result = match decoded {
+            Some(decoded) => write_address_encoding_output(
+                memory_mapping,
+                output_addr,
+                output_len,
+                written_addr,
+                &decoded,
+                self.loader_id,
+            ),
+            None => Ok(1),
+        };
+    }
+}
 
-        Ok((accounts, refs))
+/// Bech32 encode, returns 1 if the human-readable part is invalid, the encoding would be too
+/// long or the output buffer is too small
+pub struct SyscallBech32Encode<'a> {
+    base_cost: u64,
+    byte_cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BPFError> for SyscallBech32Encode<'a> {
+    fn call(
+        &mut self,
+        inputs_addr: u64,
+        inputs_len: u64,
+        output_addr: u64,
+        output_len: u64,
+        written_addr: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.base_cost), result);
+        // Inputs are the human-readable part and the data
+        if inputs_len != 2 {
+            *result = Ok(1);
+            return;
+        }
+        let inputs = question_mark!(
+            translate_slice::<&[u8]>(memory_mapping, inputs_addr, inputs_len, self.loader_id),
+            result
+        );
+        let input_len = inputs[0].len().saturating_add(inputs[1].len());
+        if input_len > bech32::MAX_BECH32_ENCODED_LEN {
+            *result = Ok(1);
+            return;
+        }
+        question_mark!(
+            self.compute_meter
+                .consume(self.byte_cost.saturating_mul(input_len as u64)),
+            result
+        );
+        let hrp = question_mark!(
+            translate_slice::<u8>(
+                memory_mapping,
+                inputs[0].as_ptr() as u64,
+                inputs[0].len() as u64,
+                self.loader_id
+            ),
+            result
+        );
+        let data = question_mark!(
+            translate_slice::<u8>(
+                memory_mapping,
+                inputs[1].as_ptr() as u64,
+                inputs[1].len() as u64,
+                self.loader_id
+            ),
+            result
+        );
+        let encoded = from_utf8(hrp)
+            .ok()
+            .and_then(|hrp| bech32::encode(hrp, data).ok());
+        *result = match encoded {
+            Some(encoded) => write_address_encoding_output(
+                memory_mapping,
+                output_addr,
+                output_len,
+                written_addr,
+                encoded.as_bytes(),
+                self.loader_id,
+            ),
+            None => Ok(1),
+        };
     }
+}
 
-    fn translate_signers(
-        &self,
-        program_id: &Pubkey,
-        signers_seeds_addr: u64,
-        signers_seeds_len: u64,
+/// Bech32 decode, only the data is output, returns 1 if the input is too long, is not valid
+/// bech32 or the output buffer is too small
+pub struct SyscallBech32Decode<'a> {
+    base_cost: u64,
+    byte_cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BPFError> for SyscallBech32Decode<'a> {
+    fn call(
+        &mut self,
+        input_addr: u64,
+        input_len: u64,
+        output_addr: u64,
+        output_len: u64,
+        written_addr: u64,
         memory_mapping: &MemoryMapping,
-    ) -> Result<Vec<Pubkey>, EbpfError<BPFError>> {
-        if signers_seeds_len > 0 {
-            let signers_seeds = translate_slice::<SolSignerSeedC>(
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.base_cost), result);
+        if input_len > bech32::MAX_BECH32_ENCODED_LEN as u64 {
+            *result = Ok(1);
+            return;
+        }
+        question_mark!(
+            self.compute_meter
+                .consume(self.byte_cost.saturating_mul(input_len)),
+            result
+        );
+        let input = question_mark!(
+            translate_slice::<u8>(memory_mapping, input_addr, input_len, self.loader_id),
+            result
+        );
+        let decoded = from_utf8(input)
+            .ok()
+            .and_then(|input| bech32::decode(input).ok());
+        *result = match decoded {
+            Some((_hrp, data)) => write_address_encoding_output(
                 memory_mapping,
-                signers_seeds_addr,
-                signers_seeds_len,
+                output_addr,
+                output_len,
+                written_addr,
+                &data,
                 self.loader_id,
-            )?;
-            if signers_seeds.len() > MAX_SIGNERS {
-                return Err(SyscallError::TooManySigners.into());
+            ),
+            None => Ok(1),
+        };
+    }
+}
+
+/// Decode a length prefix, returns 1 if the encoding is unknown or the input does not start with
+/// a valid prefix
+pub struct SyscallDecodeLengthPrefix<'a> {
+    cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BPFError> for SyscallDecodeLengthPrefix<'a> {
+    fn call(
+        &mut self,
+        input_addr: u64,
+        input_len: u64,
+        encoding: u64,
+        value_addr: u64,
+        consumed_addr: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.cost), result);
+        let prefix = match LengthPrefix::try_from(encoding) {
+            Ok(prefix) => prefix,
+            Err(()) => {
+                *result = Ok(1);
+                return;
             }
-            Ok(signers_seeds
-                .iter()
-                .map(|signer_seeds| {
-                    let seeds = translate_slice::<SolSignerSeedC>(
-                        memory_mapping,
-                        signer_seeds.addr,
-                        signer_seeds.len,
-                        self.loader_id,
-                    )?;
-                    if seeds.len() > MAX_SEEDS {
-                        return Err(SyscallError::InstructionError(
-                            InstructionError::MaxSeedLengthExceeded,
-                        )
-                        .into());
-                    }
-                    let seeds_bytes = seeds
-                        .iter()
-                        .map(|seed| {
-                            translate_slice::<u8>(
-                                memory_mapping,
-                                seed.addr,
-                                seed.len,
-                                self.loader_id,
-                            )
-                        })
-                        .collect::<Result<Vec<_>, EbpfError<BPFError>>>()?;
-                    Pubkey::create_program_address(&seeds_bytes, program_id)
-                        .map_err(|err| SyscallError::BadSeeds(err).into())
-                })
-                .collect::<Result<Vec<_>, EbpfError<BPFError>>>()?)
-        } else {
-            Ok(vec![])
-        }
+        };
+        // Only the bytes the prefix can occupy are read
+        let input = question_mark!(
+            translate_slice::<u8>(
+                memory_mapping,
+                input_addr,
+                input_len.min(prefix.max_size() as u64),
+                self.loader_id
+            ),
+            result
+        );
+        let mut translations = MutTranslationSet::new(memory_mapping, self.loader_id);
+        let value = question_mark!(translations.add_type::<u64>(value_addr), result);
+        let consumed = question_mark!(translations.add_type::<u64>(consumed_addr), result);
+        let translations = question_mark!(translations.check(), result);
+        let (value, consumed) = (
+            question_mark!(translations.get(value), result),
+            question_mark!(translations.get(consumed), result),
+        );
+        *result = match length_prefix::decode(prefix, input) {
+            Some((decoded, size)) => {
+                *value = decoded as u64;
+                *consumed = size as u64;
+                Ok(0)
+            }
+            None => Ok(1),
+        };
     }
 }
-impl<'a> SyscallObject<BPFError> for SyscallInvokeSignedC<'a> {
+
+/// Ristretto point multiply
+pub struct SyscallRistrettoMul<'a> {
+    cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    loader_id: &'a Pubkey,
+    reject_overlapping_buffers: bool,
+}
+impl<'a> SyscallObject<BPFError> for SyscallRistrettoMul<'a> {
     fn call(
         &mut self,
-        instruction_addr: u64,
+        point_addr: u64,
+        scalar_addr: u64,
+        result_addr: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.cost), result);
+
+        let mut translations = translation_set(
+            memory_mapping,
+            self.loader_id,
+            self.reject_overlapping_buffers,
+        );
+        let point = question_mark!(
+            translations.add_input_type::<RistrettoPoint>(point_addr),
+            result
+        );
+        let scalar = question_mark!(translations.add_input_type::<Scalar>(scalar_addr), result);
+        let output = question_mark!(translations.add_type::<RistrettoPoint>(result_addr), result);
+        let translations = question_mark!(translations.check(), result);
+        let output = question_mark!(translations.get(output), result);
+        *output = point * scalar;
+
+        *result = Ok(0);
+    }
+}
+
+// Cross-program invocation syscalls
+
+struct AccountReferences<'a> {
+    lamports: &'a mut u64,
+    owner: &'a mut Pubkey,
+    data: &'a mut [u8],
+    ref_to_len_in_vm: &'a mut u64,
+    serialized_len_ptr: &'a mut u64,
+}
+type TranslatedAccounts<'a> = (Vec<Rc<RefCell<Account>>>, Vec<AccountReferences<'a>>);
+
+/// Account references requested from a `MutTranslationSet`, redeemed once
+/// every account has been translated
+struct AccountTranslations {
+    lamports: MutTranslation<u64>,
+    owner: MutTranslation<Pubkey>,
+    data: MutTranslation<[u8]>,
+    ref_to_len_in_vm: MutTranslation<u64>,
+    serialized_len_ptr: MutTranslation<u64>,
+    executable: bool,
+    rent_epoch: Epoch,
+}
+
+/// Check the translations of every account for overlap and hand out their references
+fn redeem_account_translations<'a>(
+    translations: MutTranslationSet,
+    account_translations: Vec<AccountTranslations>,
+) -> Result<TranslatedAccounts<'a>, EbpfError<BPFError>> {
+    let translations = translations.check()?;
+    let mut accounts = Vec::with_capacity(account_translations.len());
+    let mut refs = Vec::with_capacity(account_translations.len());
+    for account_translation in account_translations {
+        let lamports = translations.get(account_translation.lamports)?;
+        let owner = translations.get(account_translation.owner)?;
+        let data = translations.get_slice(account_translation.data)?;
+        accounts.push(Rc::new(RefCell::new(Account {
+            lamports: *lamports,
+            data: data.to_vec(),
+            executable: account_translation.executable,
+            owner: *owner,
+            rent_epoch: account_translation.rent_epoch,
+        })));
+        refs.push(AccountReferences {
+            lamports,
+            owner,
+            data,
+            ref_to_len_in_vm: translations.get(account_translation.ref_to_len_in_vm)?,
+            serialized_len_ptr: translations.get(account_translation.serialized_len_ptr)?,
+        });
+    }
+    Ok((accounts, refs))
+}
+
+/// Implemented by language specific data structure translators
+trait SyscallInvokeSigned<'a> {
+    fn get_context_mut(&self) -> Result<RefMut<&'a mut dyn InvokeContext>, EbpfError<BPFError>>;
+    fn get_callers_keyed_accounts(&self) -> &'a [KeyedAccount<'a>];
+    fn translate_instruction(
+        &self,
+        addr: u64,
+        memory_mapping: &MemoryMapping,
+    ) -> Result<Instruction, EbpfError<BPFError>>;
+    fn translate_accounts(
+        &self,
+        message: &Message,
         account_infos_addr: u64,
         account_infos_len: u64,
+        memory_mapping: &MemoryMapping,
+        reject_overlapping_buffers: bool,
+    ) -> Result<TranslatedAccounts<'a>, EbpfError<BPFError>>;
+    fn translate_signers(
+        &self,
+        program_id: &Pubkey,
         signers_seeds_addr: u64,
         signers_seeds_len: u64,
         memory_mapping: &MemoryMapping,
-        result: &mut Result<u64, EbpfError<BPFError>>,
-    ) {
-        *result = call(
-            self,
-            instruction_addr,
-            account_infos_addr,
-            account_infos_len,
-            signers_seeds_addr,
+    ) -> Result<Vec<Pubkey>, EbpfError<BPFError>>;
+}
+
+/// Cross-program invocation called from Rust
+pub struct SyscallInvokeSignedRust<'a> {
+    callers_keyed_accounts: &'a [KeyedAccount<'a>],
+    invoke_context: Rc<RefCell<&'a mut dyn InvokeContext>>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallInvokeSigned<'a> for SyscallInvokeSignedRust<'a> {
+    fn get_context_mut(&self) -> Result<RefMut<&'a mut dyn InvokeContext>, EbpfError<BPFError>> {
+        self.invoke_context
+            .try_borrow_mut()
+            .map_err(|_| SyscallError::InvokeContextBorrowFailed.into())
+    }
+    fn get_callers_keyed_accounts(&self) -> &'a [KeyedAccount<'a>] {
+        self.callers_keyed_accounts
+    }
+    fn translate_instruction(
+        &self,
+        addr: u64,
+        memory_mapping: &MemoryMapping,
+    ) -> Result<Instruction, EbpfError<BPFError>> {
+        let ix = translate_type::<Instruction>(memory_mapping, addr, self.loader_id)?;
+        let accounts = translate_slice::<AccountMeta>(
+            memory_mapping,
+            ix.accounts.as_ptr() as u64,
+            ix.accounts.len() as u64,
+            self.loader_id,
+        )?
+        .to_vec();
+        let data = translate_slice::<u8>(
+            memory_mapping,
+            ix.data.as_ptr() as u64,
+            ix.data.len() as u64,
+            self.loader_id,
+        )?
+        .to_vec();
+        Ok(Instruction {
+            program_id: ix.program_id,
+            accounts,
+            data,
+        })
+    }
+
+    fn translate_accounts(
+        &self,
+        message: &Message,
+        account_infos_addr: u64,
+        account_infos_len: u64,
+        memory_mapping: &MemoryMapping,
+        reject_overlapping_buffers: bool,
+    ) -> Result<TranslatedAccounts<'a>, EbpfError<BPFError>> {
+        let account_infos = if account_infos_len > 0 {
+            translate_slice::<AccountInfo>(
+                memory_mapping,
+                account_infos_addr,
+                account_infos_len,
+                self.loader_id,
+            )?
+        } else {
+            &[]
+        };
+
+        let mut translations =
+            translation_set(memory_mapping, self.loader_id, reject_overlapping_buffers);
+        let mut account_translations = Vec::with_capacity(message.account_keys.len());
+        'root: for account_key in message.account_keys.iter() {
+            for account_info in account_infos.iter() {
+                let key = translate_type::<Pubkey>(
+                    memory_mapping,
+                    account_info.key as *const _ as u64,
+                    self.loader_id,
+                )?;
+                if account_key == key {
+                    let lamports = {
+                        // Double translate lamports out of RefCell
+                        let ptr = translate_type::<u64>(
+                            memory_mapping,
+                            account_info.lamports.as_ptr() as u64,
+                            self.loader_id,
+                        )?;
+                        translations.add_type::<u64>(*ptr)?
+                    };
+                    let owner =
+                        translations.add_type::<Pubkey>(account_info.owner as *const _ as u64)?;
+                    let (data, ref_to_len_in_vm, serialized_len_ptr) = {
+                        // Double translate data out of RefCell
+                        let data = *translate_type::<&[u8]>(
+                            memory_mapping,
+                            account_info.data.as_ptr() as *const _ as u64,
+                            self.loader_id,
+                        )?;
+                        let ref_to_len_in_vm = translations.add_type::<u64>(unsafe {
+                            (account_info.data.as_ptr() as *const u64).offset(1) as u64
+                        })?;
+                        let ref_of_len_in_input_buffer = unsafe { data.as_ptr().offset(-8) };
+                        let serialized_len_ptr = translations
+                            .add_type::<u64>(ref_of_len_in_input_buffer as *const _ as u64)?;
+                        (
+                            translations
+                                .add_slice::<u8>(data.as_ptr() as u64, data.len() as u64)?,
+                            ref_to_len_in_vm,
+                            serialized_len_ptr,
+                        )
+                    };
+
+                    account_translations.push(AccountTranslations {
+                        lamports,
+                        owner,
+                        data,
+                        ref_to_len_in_vm,
+                        serialized_len_ptr,
+                        executable: account_info.executable,
+                        rent_epoch: account_info.rent_epoch,
+                    });
+                    continue 'root;
+                }
+            }
+            return Err(SyscallError::InstructionError(InstructionError::MissingAccount).into());
+        }
+
+        redeem_account_translations(translations, account_translations)
+    }
+
+    fn translate_signers(
+        &self,
+        program_id: &Pubkey,
+        signers_seeds_addr: u64,
+        signers_seeds_len: u64,
+        memory_mapping: &MemoryMapping,
+    ) -> Result<Vec<Pubkey>, EbpfError<BPFError>> {
+        let mut signers = Vec::new();
+        if signers_seeds_len > 0 {
+            let signers_seeds = translate_slice::<&[&[u8]]>(
+                memory_mapping,
+                signers_seeds_addr,
+                signers_seeds_len,
+                self.loader_id,
+            )?;
+            if signers_seeds.len() > MAX_SIGNERS {
+                return Err(SyscallError::TooManySigners.into());
+            }
+            for signer_seeds in signers_seeds.iter() {
+                let untranslated_seeds = translate_slice::<&[u8]>(
+                    memory_mapping,
+                    signer_seeds.as_ptr() as *const _ as u64,
+                    signer_seeds.len() as u64,
+                    self.loader_id,
+                )?;
+                if untranslated_seeds.len() > MAX_SEEDS {
+                    return Err(SyscallError::InstructionError(
+                        InstructionError::MaxSeedLengthExceeded,
+                    )
+                    .into());
+                }
+                let seeds = untranslated_seeds
+                    .iter()
+                    .map(|untranslated_seed| {
+                        translate_slice::<u8>(
+                            memory_mapping,
+                            untranslated_seed.as_ptr() as *const _ as u64,
+                            untranslated_seed.len() as u64,
+                            self.loader_id,
+                        )
+                    })
+                    .collect::<Result<Vec<_>, EbpfError<BPFError>>>()?;
+                let signer = Pubkey::create_program_address(&seeds, program_id)
+                    .map_err(SyscallError::BadSeeds)?;
+                signers.push(signer);
+            }
+            Ok(signers)
+        } else {
+            Ok(vec![])
+        }
+    }
+}
+impl<'a> SyscallObject<BPFError> for SyscallInvokeSignedRust<'a> {
+    fn call(
+        &mut self,
+        instruction_addr: u64,
+        account_infos_addr: u64,
+        account_infos_len: u64,
+        signers_seeds_addr: u64,
+        signers_seeds_len: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        *result = call(
+            self,
+            "sol_invoke_signed_rust",
+            instruction_addr,
+            account_infos_addr,
+            account_infos_len,
+            signers_seeds_addr,
             signers_seeds_len,
             memory_mapping,
         );
     }
-}
-
-/// Call process instruction, common to both Rust and C
-fn call<'a>(
-    syscall: &mut dyn SyscallInvokeSigned<'a>,
-    instruction_addr: u64,
-    account_infos_addr: u64,
-    account_infos_len: u64,
-    signers_seeds_addr: u64,
-    signers_seeds_len: u64,
-    memory_mapping: &MemoryMapping,
-) -> Result<u64, EbpfError<BPFError>> {
-    let mut invoke_context = syscall.get_context_mut()?;
-    invoke_context
-        .get_compute_meter()
-        .consume(invoke_context.get_bpf_compute_budget().invoke_units)?;
-
-    // Translate and verify caller's data
+}
+
+/// Rust representation of C's SolInstruction
+#[derive(Debug)]
+struct SolInstruction {
+    program_id_addr: u64,
+    accounts_addr: u64,
+    accounts_len: usize,
+    data_addr: u64,
+    data_len: usize,
+}
+
+/// Rust representation of C's SolAccountMeta
+#[derive(Debug)]
+struct SolAccountMeta {
+    pubkey_addr: u64,
+    is_writable: bool,
+    is_signer: bool,
+}
+
+/// Rust representation of C's SolAccountInfo
+#[derive(Debug)]
+struct SolAccountInfo {
+    key_addr: u64,
+    lamports_addr: u64,
+    data_len: u64,
+    data_addr: u64,
+    owner_addr: u64,
+    rent_epoch: u64,
+    is_signer: bool,
+    is_writable: bool,
+    executable: bool,
+}
+
+/// Rust representation of C's SolSignerSeed
+#[derive(Debug)]
+struct SolSignerSeedC {
+    addr: u64,
+    len: u64,
+}
+
+/// Rust representation of C's SolSignerSeeds
+#[derive(Debug)]
+struct SolSignerSeedsC {
+    addr: u64,
+    len: u64,
+}
+
+/// Cross-program invocation called from C
+pub struct SyscallInvokeSignedC<'a> {
+    callers_keyed_accounts: &'a [KeyedAccount<'a>],
+    invoke_context: Rc<RefCell<&'a mut dyn InvokeContext>>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallInvokeSigned<'a> for SyscallInvokeSignedC<'a> {
+    fn get_context_mut(&self) -> Result<RefMut<&'a mut dyn InvokeContext>, EbpfError<BPFError>> {
+        self.invoke_context
+            .try_borrow_mut()
+            .map_err(|_| SyscallError::InvokeContextBorrowFailed.into())
+    }
+    fn get_callers_keyed_accounts(&self) -> &'a [KeyedAccount<'a>] {
+        self.callers_keyed_accounts
+    }
+    fn translate_instruction(
+        &self,
+        addr: u64,
+        memory_mapping: &MemoryMapping,
+    ) -> Result<Instruction, EbpfError<BPFError>> {
+        let ix_c = translate_type::<SolInstruction>(memory_mapping, addr, self.loader_id)?;
+        let program_id =
+            translate_type::<Pubkey>(memory_mapping, ix_c.program_id_addr, self.loader_id)?;
+        let meta_cs = translate_slice::<SolAccountMeta>(
+            memory_mapping,
+            ix_c.accounts_addr,
+            ix_c.accounts_len as u64,
+            self.loader_id,
+        )?;
+        let data = translate_slice::<u8>(
+            memory_mapping,
+            ix_c.data_addr,
+            ix_c.data_len as u64,
+            self.loader_id,
+        )?
+        .to_vec();
+        let accounts = meta_cs
+            .iter()
+            .map(|meta_c| {
+                let pubkey =
+                    translate_type::<Pubkey>(memory_mapping, meta_c.pubkey_addr, self.loader_id)?;
+                Ok(AccountMeta {
+                    pubkey: *pubkey,
+                    is_signer: meta_c.is_signer,
+                    is_writable: meta_c.is_writable,
+                })
+            })
+            .collect::<Result<Vec<AccountMeta>, EbpfError<BPFError>>>()?;
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts,
+            data,
+        })
+    }
+
+    fn translate_accounts(
+        &self,
+        message: &Message,
+        account_infos_addr: u64,
+        account_infos_len: u64,
+        memory_mapping: &MemoryMapping,
+        reject_overlapping_buffers: bool,
+    ) -> Result<TranslatedAccounts<'a>, EbpfError<BPFError>> {
+        let account_infos = translate_slice::<SolAccountInfo>(
+            memory_mapping,
+            account_infos_addr,
+            account_infos_len,
+            self.loader_id,
+        )?;
+        let mut translations =
+            translation_set(memory_mapping, self.loader_id, reject_overlapping_buffers);
+        let mut account_translations = Vec::with_capacity(message.account_keys.len());
+        'root: for account_key in message.account_keys.iter() {
+            for account_info in account_infos.iter() {
+                let key = translate_type::<Pubkey>(
+                    memory_mapping,
+                    account_info.key_addr,
+                    self.loader_id,
+                )?;
+                if account_key == key {
+                    let lamports = translations.add_type::<u64>(account_info.lamports_addr)?;
+                    let owner = translations.add_type::<Pubkey>(account_info.owner_addr)?;
+                    let data = translations
+                        .add_slice::<u8>(account_info.data_addr, account_info.data_len)?;
+
+                    let first_info_addr = &account_infos[0] as *const _ as u64;
+                    let addr = &account_info.data_len as *const u64 as u64;
+                    let vm_addr = account_infos_addr + (addr - first_info_addr);
+                    let ref_to_len_in_vm = translations.add_type::<u64>(vm_addr)?;
+
+                    let ref_of_len_in_input_buffer =
+                        unsafe { (account_info.data_addr as *mut u8).offset(-8) };
+                    let serialized_len_ptr = translations
+                        .add_type::<u64>(ref_of_len_in_input_buffer as *const _ as u64)?;
+
+                    account_translations.push(AccountTranslations {
+                        lamports,
+                        owner,
+                        data,
+                        ref_to_len_in_vm,
+                        serialized_len_ptr,
+                        executable: account_info.executable,
+                        rent_epoch: account_info.rent_epoch,
+                    });
+                    continue 'root;
+                }
+            }
+            return Err(SyscallError::InstructionError(InstructionError::MissingAccount).into());
+        }
+
+        redeem_account_translations(translations, account_translations)
+    }
+
+    fn translate_signers(
+        &self,
+        program_id: &Pubkey,
+        signers_seeds_addr: u64,
+        signers_seeds_len: u64,
+        memory_mapping: &MemoryMapping,
+    ) -> Result<Vec<Pubkey>, EbpfError<BPFError>> {
+        if signers_seeds_len > 0 {
+            let signers_seeds = translate_slice::<SolSignerSeedC>(
+                memory_mapping,
+                signers_seeds_addr,
+                signers_seeds_len,
+                self.loader_id,
+            )?;
+            if signers_seeds.len() > MAX_SIGNERS {
+                return Err(SyscallError::TooManySigners.into());
+            }
+            Ok(signers_seeds
+                .iter()
+                .map(|signer_seeds| {
+                    let seeds = translate_slice::<SolSignerSeedC>(
+                        memory_mapping,
+                        signer_seeds.addr,
+                        signer_seeds.len,
+                        self.loader_id,
+                    )?;
+                    if seeds.len() > MAX_SEEDS {
+                        return Err(SyscallError::InstructionError(
+                            InstructionError::MaxSeedLengthExceeded,
+                        )
+                        .into());
+                    }
+                    let seeds_bytes = seeds
+                        .iter()
+                        .map(|seed| {
+                            translate_slice::<u8>(
+                                memory_mapping,
+                                seed.addr,
+                                seed.len,
+                                self.loader_id,
+                            )
+                        })
+                        .collect::<Result<Vec<_>, EbpfError<BPFError>>>()?;
+                    Pubkey::create_program_address(&seeds_bytes, program_id)
+                        .map_err(|err| SyscallError::BadSeeds(err).into())
+                })
+                .collect::<Result<Vec<_>, EbpfError<BPFError>>>()?)
+        } else {
+            Ok(vec![])
+        }
+    }
+}
+impl<'a> SyscallObject<BPFError> for SyscallInvokeSignedC<'a> {
+    fn call(
+        &mut self,
+        instruction_addr: u64,
+        account_infos_addr: u64,
+        account_infos_len: u64,
+        signers_seeds_addr: u64,
+        signers_seeds_len: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        *result = call(
+            self,
+            "sol_invoke_signed_c",
+            instruction_addr,
+            account_infos_addr,
+            account_infos_len,
+            signers_seeds_addr,
+            signers_seeds_len,
+            memory_mapping,
+        );
+    }
+}
+
+/// Cost of the named syscall, unless overridden by the invoke context
+fn syscall_cost(invoke_context: &dyn InvokeContext, syscall_name: &str, cost: u64) -> u64 {
+    invoke_context
+        .get_syscall_cost_override(syscall_name)
+        .unwrap_or(cost)
+}
+
+/// Per-byte cost of the named syscall, unless overridden by the invoke context
+fn syscall_byte_cost(invoke_context: &dyn InvokeContext, syscall_name: &str, cost: u64) -> u64 {
+    invoke_context
+        .get_syscall_cost_override(&syscall_byte_cost_key(syscall_name))
+        .unwrap_or(cost)
+}
+
+/// Per-address cost of the named syscall, unless overridden by the invoke context
+fn syscall_address_cost(invoke_context: &dyn InvokeContext, syscall_name: &str, cost: u64) -> u64 {
+    invoke_context
+        .get_syscall_cost_override(&syscall_address_cost_key(syscall_name))
+        .unwrap_or(cost)
+}
+
+/// Call process instruction, common to both Rust and C
+#[allow(clippy::too_many_arguments)]
+fn call<'a>(
+    syscall: &mut dyn SyscallInvokeSigned<'a>,
+    syscall_name: &str,
+    instruction_addr: u64,
+    account_infos_addr: u64,
+    account_infos_len: u64,
+    signers_seeds_addr: u64,
+    signers_seeds_len: u64,
+    memory_mapping: &MemoryMapping,
+) -> Result<u64, EbpfError<BPFError>> {
+    let mut invoke_context = syscall.get_context_mut()?;
+    let invoke_units = syscall_cost(
+        &**invoke_context,
+        syscall_name,
+        invoke_context.get_bpf_compute_budget().invoke_units,
+    );
+    invoke_context.get_compute_meter().consume(invoke_units)?;
+
+    // Translate and verify caller's data
+
+    let instruction = syscall.translate_instruction(instruction_addr, &memory_mapping)?;
+    let caller_program_id = invoke_context
+        .get_caller()
+        .map_err(SyscallError::InstructionError)?;
+    let signers = syscall.translate_signers(
+        caller_program_id,
+        signers_seeds_addr,
+        signers_seeds_len,
+        memory_mapping,
+    )?;
+    let keyed_account_refs = syscall
+        .get_callers_keyed_accounts()
+        .iter()
+        .collect::<Vec<&KeyedAccount>>();
+    let (message, callee_program_id, callee_program_id_index) =
+        MessageProcessor::create_message(&instruction, &keyed_account_refs, &signers)
+            .map_err(SyscallError::InstructionError)?;
+    let (accounts, account_refs) = syscall.translate_accounts(
+        &message,
+        account_infos_addr,
+        account_infos_len,
+        memory_mapping,
+        invoke_context.is_feature_active(&reject_overlapping_syscall_buffers::id()),
+    )?;
+
+    // Process instruction
+
+    invoke_context.record_instruction(&instruction);
+    let program_account =
+        (**accounts
+            .get(callee_program_id_index)
+            .ok_or(SyscallError::InstructionError(
+                InstructionError::MissingAccount,
+            ))?)
+        .clone();
+    if !program_account.borrow().executable {
+        return Err(SyscallError::InstructionError(InstructionError::AccountNotExecutable).into());
+    }
+    let executable_accounts = vec![(callee_program_id, program_account)];
+
+    #[allow(clippy::deref_addrof)]
+    match MessageProcessor::process_cross_program_instruction(
+        &message,
+        &executable_accounts,
+        &accounts,
+        *(&mut *invoke_context),
+    ) {
+        Ok(()) => (),
+        Err(err) => match ProgramError::try_from(err) {
+            Ok(err) => return Ok(err.into()),
+            Err(err) => return Err(SyscallError::InstructionError(err).into()),
+        },
+    }
+
+    // Copy results back to caller
+
+    for (i, (account, account_ref)) in accounts.iter().zip(account_refs).enumerate() {
+        let account = account.borrow();
+        if message.is_writable(i) && !account.executable {
+            *account_ref.lamports = account.lamports;
+            *account_ref.owner = account.owner;
+            if account_ref.data.len() != account.data.len() {
+                *account_ref.ref_to_len_in_vm = account.data.len() as u64;
+                *account_ref.serialized_len_ptr = account.data.len() as u64;
+                if !account_ref.data.is_empty() {
+                    // Only support for `CreateAccount` at this time.
+                    // Need a way to limit total realloc size across multiple CPI calls
+                    return Err(
+                        SyscallError::InstructionError(InstructionError::InvalidRealloc).into(),
+                    );
+                }
+                if account.data.len() > account_ref.data.len() + MAX_PERMITTED_DATA_INCREASE {
+                    return Err(
+                        SyscallError::InstructionError(InstructionError::InvalidRealloc).into(),
+                    );
+                }
+            }
+            account_ref
+                .data
+                .clone_from_slice(&account.data[0..account_ref.data.len()]);
+        }
+    }
+
+    Ok(SUCCESS)
+}
+
+/// Rust representation of the transfer passed to `sol_system_transfer_*`, laid out the same way
+/// by Rust and C programs
+#[derive(Debug)]
+#[repr(C)]
+struct SystemTransfer {
+    from_addr: u64,
+    to_addr: u64,
+    lamports: u64,
+}
+
+/// System transfer called from Rust
+pub struct SyscallSystemTransferRust<'a> {
+    syscall: SyscallInvokeSignedRust<'a>,
+}
+impl<'a> SyscallObject<BPFError> for SyscallSystemTransferRust<'a> {
+    fn call(
+        &mut self,
+        transfer_addr: u64,
+        account_infos_addr: u64,
+        account_infos_len: u64,
+        signers_seeds_addr: u64,
+        signers_seeds_len: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        let loader_id = self.syscall.loader_id;
+        *result = system_transfer(
+            &mut self.syscall,
+            "sol_system_transfer_rust",
+            loader_id,
+            transfer_addr,
+            account_infos_addr,
+            account_infos_len,
+            signers_seeds_addr,
+            signers_seeds_len,
+            memory_mapping,
+        );
+    }
+}
+
+/// System transfer called from C
+pub struct SyscallSystemTransferC<'a> {
+    syscall: SyscallInvokeSignedC<'a>,
+}
+impl<'a> SyscallObject<BPFError> for SyscallSystemTransferC<'a> {
+    fn call(
+        &mut self,
+        transfer_addr: u64,
+        account_infos_addr: u64,
+        account_infos_len: u64,
+        signers_seeds_addr: u64,
+        signers_seeds_len: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        let loader_id = self.syscall.loader_id;
+        *result = system_transfer(
+            &mut self.syscall,
+            "sol_system_transfer_c",
+            loader_id,
+            transfer_addr,
+            account_infos_addr,
+            account_infos_len,
+            signers_seeds_addr,
+            signers_seeds_len,
+            memory_mapping,
+        );
+    }
+}
+
+/// Transfer lamports out of a system account on behalf of the system program, common to both
+/// Rust and C.  Performs the same checks as a cross-program invocation of
+/// `SystemInstruction::Transfer` but skips dispatching to the system program.  Like
+/// `sol_invoke_signed_*`, the caller may sign for program derived addresses with `signers_seeds`.
+#[allow(clippy::too_many_arguments)]
+fn system_transfer<'a>(
+    syscall: &mut dyn SyscallInvokeSigned<'a>,
+    syscall_name: &str,
+    loader_id: &Pubkey,
+    transfer_addr: u64,
+    account_infos_addr: u64,
+    account_infos_len: u64,
+    signers_seeds_addr: u64,
+    signers_seeds_len: u64,
+    memory_mapping: &MemoryMapping,
+) -> Result<u64, EbpfError<BPFError>> {
+    let mut invoke_context = syscall.get_context_mut()?;
+    let system_transfer_units = syscall_cost(
+        &**invoke_context,
+        syscall_name,
+        invoke_context
+            .get_bpf_compute_budget()
+            .system_transfer_units,
+    );
+    invoke_context
+        .get_compute_meter()
+        .consume(system_transfer_units)?;
+
+    // Translate and verify caller's data
+
+    let transfer = translate_type::<SystemTransfer>(memory_mapping, transfer_addr, loader_id)?;
+    let from = translate_type::<Pubkey>(memory_mapping, transfer.from_addr, loader_id)?;
+    let to = translate_type::<Pubkey>(memory_mapping, transfer.to_addr, loader_id)?;
+    let lamports = transfer.lamports;
+    let instruction = system_instruction::transfer(from, to, lamports);
+    let caller_program_id = invoke_context
+        .get_caller()
+        .map_err(SyscallError::InstructionError)?;
+    let signers = syscall.translate_signers(
+        caller_program_id,
+        signers_seeds_addr,
+        signers_seeds_len,
+        memory_mapping,
+    )?;
+    let keyed_account_refs = syscall
+        .get_callers_keyed_accounts()
+        .iter()
+        .collect::<Vec<&KeyedAccount>>();
+    let (message, _, _) =
+        MessageProcessor::create_message(&instruction, &keyed_account_refs, &signers)
+            .map_err(SyscallError::InstructionError)?;
+    let (accounts, account_refs) = syscall.translate_accounts(
+        &message,
+        account_infos_addr,
+        account_infos_len,
+        memory_mapping,
+        invoke_context.is_feature_active(&reject_overlapping_syscall_buffers::id()),
+    )?;
+
+    // Process transfer
+
+    invoke_context.record_instruction(&instruction);
+    let compiled_instruction = &message.instructions[0];
+    invoke_context
+        .verify_and_update(&message, compiled_instruction, &accounts)
+        .map_err(SyscallError::InstructionError)?;
+    invoke_context
+        .push(&system_program::id())
+        .map_err(SyscallError::InstructionError)?;
+    let result = {
+        let keyed_accounts = compiled_instruction
+            .accounts
+            .iter()
+            .map(|index| {
+                let index = *index as usize;
+                KeyedAccount::new(
+                    &message.account_keys[index],
+                    message.is_signer(index),
+                    &accounts[index],
+                )
+            })
+            .collect::<Vec<_>>();
+        system_instruction_processor::transfer(&keyed_accounts[0], &keyed_accounts[1], lamports)
+    }
+    .and_then(|_| invoke_context.verify_and_update(&message, compiled_instruction, &accounts));
+    invoke_context.pop();
+    if let Err(err) = result {
+        return match ProgramError::try_from(err) {
+            Ok(err) => Ok(err.into()),
+            Err(err) => Err(SyscallError::InstructionError(err).into()),
+        };
+    }
+
+    // Copy results back to caller
+
+    for (i, (account, account_ref)) in accounts.iter().zip(account_refs).enumerate() {
+        let account = account.borrow();
+        if message.is_writable(i) && !account.executable {
+            *account_ref.lamports = account.lamports;
+        }
+    }
+
+    Ok(SUCCESS)
+}
+
+#[cfg(feature = "experimental-token-transfer")]
+mod spl_token {
+    solana_sdk::declare_id!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+}
+
+/// Experimental native SPL Token transfer, only available when built with the
+/// `experimental-token-transfer` feature and never registered by a validator.
+///
+/// Takes the same arguments as `sol_invoke_signed_rust` minus the signer seeds and executes
+/// `Transfer` and `TransferChecked` instructions of the SPL Token program natively, so that the
+/// savings of a native transfer path can be measured against the BPF program.
+#[cfg(feature = "experimental-token-transfer")]
+pub struct SyscallTokenTransfer<'a> {
+    syscall: SyscallInvokeSignedRust<'a>,
+}
+#[cfg(feature = "experimental-token-transfer")]
+impl<'a> SyscallObject<BPFError> for SyscallTokenTransfer<'a> {
+    fn call(
+        &mut self,
+        instruction_addr: u64,
+        account_infos_addr: u64,
+        account_infos_len: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        *result = token_transfer(
+            &mut self.syscall,
+            "sol_token_transfer",
+            instruction_addr,
+            account_infos_addr,
+            account_infos_len,
+            memory_mapping,
+        );
+    }
+}
+
+#[cfg(feature = "experimental-token-transfer")]
+fn token_transfer<'a>(
+    syscall: &mut dyn SyscallInvokeSigned<'a>,
+    syscall_name: &str,
+    instruction_addr: u64,
+    account_infos_addr: u64,
+    account_infos_len: u64,
+    memory_mapping: &MemoryMapping,
+) -> Result<u64, EbpfError<BPFError>> {
+    let mut invoke_context = syscall.get_context_mut()?;
+    let invoke_units = syscall_cost(
+        &**invoke_context,
+        syscall_name,
+        invoke_context.get_bpf_compute_budget().invoke_units,
+    );
+    invoke_context.get_compute_meter().consume(invoke_units)?;
+
+    // Translate and verify caller's data
+
+    let instruction = syscall.translate_instruction(instruction_addr, memory_mapping)?;
+    if !spl_token::check_id(&instruction.program_id) {
+        return Err(SyscallError::ProgramNotSupported.into());
+    }
+    let keyed_account_refs = syscall
+        .get_callers_keyed_accounts()
+        .iter()
+        .collect::<Vec<&KeyedAccount>>();
+    let (message, _, _) = MessageProcessor::create_message(&instruction, &keyed_account_refs, &[])
+        .map_err(SyscallError::InstructionError)?;
+    let (accounts, account_refs) = syscall.translate_accounts(
+        &message,
+        account_infos_addr,
+        account_infos_len,
+        memory_mapping,
+        invoke_context.is_feature_active(&reject_overlapping_syscall_buffers::id()),
+    )?;
+
+    // Process transfer
+
+    invoke_context.record_instruction(&instruction);
+    let compiled_instruction = &message.instructions[0];
+    invoke_context
+        .verify_and_update(&message, compiled_instruction, &accounts)
+        .map_err(SyscallError::InstructionError)?;
+    invoke_context
+        .push(&instruction.program_id)
+        .map_err(SyscallError::InstructionError)?;
+    let result = {
+        let keyed_accounts = compiled_instruction
+            .accounts
+            .iter()
+            .map(|index| {
+                let index = *index as usize;
+                KeyedAccount::new(
+                    &message.account_keys[index],
+                    message.is_signer(index),
+                    &accounts[index],
+                )
+            })
+            .collect::<Vec<_>>();
+        process_token_transfer(&keyed_accounts, &compiled_instruction.data)
+    }
+    .and_then(|_| invoke_context.verify_and_update(&message, compiled_instruction, &accounts));
+    invoke_context.pop();
+    if let Err(err) = result {
+        return match ProgramError::try_from(err) {
+            Ok(err) => Ok(err.into()),
+            Err(err) => Err(SyscallError::InstructionError(err).into()),
+        };
+    }
+
+    // Copy results back to caller
+
+    for (i, (account, account_ref)) in accounts.iter().zip(account_refs).enumerate() {
+        let account = account.borrow();
+        if message.is_writable(i) && !account.executable {
+            account_ref.data.clone_from_slice(&account.data);
+        }
+    }
+
+    Ok(SUCCESS)
+}
+
+/// Native equivalent of the SPL Token v2 `Transfer` and `TransferChecked` instructions.
+/// Multisig authorities and native (wrapped SOL) accounts are not supported.
+#[cfg(feature = "experimental-token-transfer")]
+fn process_token_transfer(
+    keyed_accounts: &[KeyedAccount],
+    instruction_data: &[u8],
+) -> Result<(), InstructionError> {
+    use solana_sdk::keyed_account::next_keyed_account;
+
+    // Token account layout
+    const ACCOUNT_LEN: usize = 165;
+    const MINT: std::ops::Range<usize> = 0..32;
+    const OWNER: std::ops::Range<usize> = 32..64;
+    const AMOUNT: std::ops::Range<usize> = 64..72;
+    const DELEGATE_TAG: std::ops::Range<usize> = 72..76;
+    const DELEGATE: std::ops::Range<usize> = 76..108;
+    const STATE: usize = 108;
+    const IS_NATIVE_TAG: std::ops::Range<usize> = 109..113;
+    const DELEGATED_AMOUNT: std::ops::Range<usize> = 121..129;
+    // Mint layout
+    const MINT_LEN: usize = 82;
+    const DECIMALS: usize = 44;
+    const IS_INITIALIZED: usize = 45;
+    // Account states
+    const UNINITIALIZED: u8 = 0;
+    const FROZEN: u8 = 2;
+    // Token errors
+    const INSUFFICIENT_FUNDS: u32 = 1;
+    const INVALID_MINT: u32 = 2;
+    const MINT_MISMATCH: u32 = 3;
+    const OWNER_MISMATCH: u32 = 4;
+    const UNINITIALIZED_STATE: u32 = 9;
+    const NATIVE_NOT_SUPPORTED: u32 = 10;
+    const INVALID_INSTRUCTION: u32 = 12;
+    const OVERFLOW: u32 = 14;
+    const ACCOUNT_FROZEN: u32 = 17;
+    const MINT_DECIMALS_MISMATCH: u32 = 18;
+
+    fn read_u64(data: &[u8]) -> u64 {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&data[..8]);
+        u64::from_le_bytes(bytes)
+    }
+
+    let (amount, expected_decimals) = match instruction_data.split_first() {
+        Some((3, data)) if data.len() >= 8 => (read_u64(data), None),
+        Some((12, data)) if data.len() >= 9 => (read_u64(data), Some(data[8])),
+        _ => return Err(InstructionError::Custom(INVALID_INSTRUCTION)),
+    };
+    let keyed_accounts_iter = &mut keyed_accounts.iter();
+    let source = next_keyed_account(keyed_accounts_iter)?;
+    let mint = match expected_decimals {
+        Some(_) => Some(next_keyed_account(keyed_accounts_iter)?),
+        None => None,
+    };
+    let destination = next_keyed_account(keyed_accounts_iter)?;
+    let authority = next_keyed_account(keyed_accounts_iter)?;
+
+    let mut source_data = source.try_account_ref()?.data.clone();
+    let mut destination_data = destination.try_account_ref()?.data.clone();
+    if source_data.len() != ACCOUNT_LEN || destination_data.len() != ACCOUNT_LEN {
+        return Err(InstructionError::InvalidAccountData);
+    }
+    if source_data[STATE] == UNINITIALIZED || destination_data[STATE] == UNINITIALIZED {
+        return Err(InstructionError::Custom(UNINITIALIZED_STATE));
+    }
+    if source_data[STATE] == FROZEN || destination_data[STATE] == FROZEN {
+        return Err(InstructionError::Custom(ACCOUNT_FROZEN));
+    }
+    if source_data[IS_NATIVE_TAG] != [0; 4] {
+        return Err(InstructionError::Custom(NATIVE_NOT_SUPPORTED));
+    }
+    let source_amount = read_u64(&source_data[AMOUNT]);
+    if source_amount < amount {
+        return Err(InstructionError::Custom(INSUFFICIENT_FUNDS));
+    }
+    if source_data[MINT] != destination_data[MINT] {
+        return Err(InstructionError::Custom(MINT_MISMATCH));
+    }
+    if let (Some(mint), Some(expected_decimals)) = (mint, expected_decimals) {
+        if mint.unsigned_key().as_ref() != &source_data[MINT] {
+            return Err(InstructionError::Custom(MINT_MISMATCH));
+        }
+        let mint = mint.try_account_ref()?;
+        if mint.data.len() != MINT_LEN || mint.data[IS_INITIALIZED] == 0 {
+            return Err(InstructionError::Custom(INVALID_MINT));
+        }
+        if mint.data[DECIMALS] != expected_decimals {
+            return Err(InstructionError::Custom(MINT_DECIMALS_MISMATCH));
+        }
+    }
+
+    let is_delegate = source_data[DELEGATE_TAG] == [1, 0, 0, 0]
+        && authority.unsigned_key().as_ref() == &source_data[DELEGATE];
+    if is_delegate {
+        if authority.signer_key().is_none() {
+            return Err(InstructionError::MissingRequiredSignature);
+        }
+        let delegated_amount = read_u64(&source_data[DELEGATED_AMOUNT]);
+        if delegated_amount < amount {
+            return Err(InstructionError::Custom(INSUFFICIENT_FUNDS));
+        }
+        let delegated_amount = delegated_amount - amount;
+        source_data[DELEGATED_AMOUNT].copy_from_slice(&delegated_amount.to_le_bytes());
+        if delegated_amount == 0 {
+            for byte in source_data[DELEGATE_TAG.start..DELEGATE.end].iter_mut() {
+                *byte = 0;
+            }
+        }
+    } else if authority.unsigned_key().as_ref() == &source_data[OWNER] {
+        if authority.signer_key().is_none() {
+            return Err(InstructionError::MissingRequiredSignature);
+        }
+    } else {
+        return Err(InstructionError::Custom(OWNER_MISMATCH));
+    }
+
+    if source.unsigned_key() == destination.unsigned_key() {
+        return Ok(());
+    }
+
+    let destination_amount = read_u64(&destination_data[AMOUNT])
+        .checked_add(amount)
+        .ok_or(InstructionError::Custom(OVERFLOW))?;
+    source_data[AMOUNT].copy_from_slice(&(source_amount - amount).to_le_bytes());
+    destination_data[AMOUNT].copy_from_slice(&destination_amount.to_le_bytes());
+    source.try_account_ref_mut()?.data = source_data;
+    destination.try_account_ref_mut()?.data = destination_data;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        memory::translate,
+        overlap_harness::{
+            for_each_overlapping_aligned_output, for_each_overlapping_output,
+            for_each_overlapping_output_pair, placements_around,
+        },
+    };
+    use rand::Rng;
+    use solana_rbpf::{
+        ebpf::MM_INPUT_START,
+        memory_region::{AccessType, MemoryRegion},
+        vm::Config,
+    };
+    use solana_runtime::{
+        message_processor::{
+            ExecutionHooks, Executors, PreAccount, ThisInvokeContext, TransactionInfo,
+        },
+        syscall_trace_recorder::SyscallTraceRecorder,
+    };
+    use solana_sdk::{
+        bpf_loader,
+        compute_profiler::{ComputeProfiler, SyscallProfile},
+        feature_set::{bpf_compute_budget_balancing, max_program_call_depth_64},
+        hash::{hashv, hmac},
+        keccak, native_loader,
+        process_instruction::{MockComputeMeter, MockLogger, RUNTIME_VERSION},
+        rent::Rent,
+        runtime_version::RuntimeVersion,
+        system_instruction::SystemError,
+    };
+    use std::{collections::HashSet, str::FromStr};
+
+    const DEFAULT_CONFIG: Config = Config {
+        max_call_depth: 20,
+        stack_frame_size: 4_096,
+        enable_instruction_meter: true,
+        enable_instruction_tracing: false,
+    };
+
+    macro_rules! assert_access_violation {
+        ($result:expr, $va:expr, $len:expr) => {
+            match $result {
+                Err(EbpfError::AccessViolation(_, _, va, len, _)) if $va == va && len == len => (),
+                _ => panic!(),
+            }
+        };
+    }
+
+    #[test]
+    fn test_translate() {
+        const START: u64 = 100;
+        const LENGTH: u64 = 1000;
+        let data = vec![0u8; LENGTH as usize];
+        let addr = data.as_ptr() as u64;
+        let memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion::new_from_slice(&data, START, 0, false)],
+            &DEFAULT_CONFIG,
+        );
+
+        let cases = vec![
+            (true, START, 0, addr),
+            (true, START, 1, addr),
+            (true, START, LENGTH, addr),
+            (true, START + 1, LENGTH - 1, addr + 1),
+            (false, START + 1, LENGTH, 0),
+            (true, START + LENGTH - 1, 1, addr + LENGTH - 1),
+            (true, START + LENGTH, 0, addr + LENGTH),
+            (false, START + LENGTH, 1, 0),
+            (false, START, LENGTH + 1, 0),
+            (false, 0, 0, 0),
+            (false, 0, 1, 0),
+            (false, START - 1, 0, 0),
+            (false, START - 1, 1, 0),
+            (true, START + LENGTH / 2, LENGTH / 2, addr + LENGTH / 2),
+        ];
+        for (ok, start, length, value) in cases {
+            if ok {
+                assert_eq!(
+                    translate(&memory_mapping, AccessType::Load, start, length,).unwrap(),
+                    value
+                )
+            } else {
+                assert!(translate(&memory_mapping, AccessType::Load, start, length,).is_err())
+            }
+        }
+    }
+
+    #[test]
+    fn test_translate_type() {
+        // Pubkey
+        let pubkey = solana_sdk::pubkey::new_rand();
+        let addr = &pubkey as *const _ as u64;
+        let memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion {
+                host_addr: addr,
+                vm_addr: 100,
+                len: std::mem::size_of::<Pubkey>() as u64,
+                vm_gap_shift: 63,
+                is_writable: false,
+            }],
+            &DEFAULT_CONFIG,
+        );
+        let translated_pubkey =
+            translate_type::<Pubkey>(&memory_mapping, 100, &bpf_loader::id()).unwrap();
+        assert_eq!(pubkey, *translated_pubkey);
+
+        // Instruction
+        let instruction = Instruction::new(
+            solana_sdk::pubkey::new_rand(),
+            &"foobar",
+            vec![AccountMeta::new(solana_sdk::pubkey::new_rand(), false)],
+        );
+        let addr = &instruction as *const _ as u64;
+        let mut memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion {
+                host_addr: addr,
+                vm_addr: 96,
+                len: std::mem::size_of::<Instruction>() as u64,
+                vm_gap_shift: 63,
+                is_writable: false,
+            }],
+            &DEFAULT_CONFIG,
+        );
+        let translated_instruction =
+            translate_type::<Instruction>(&memory_mapping, 96, &bpf_loader::id()).unwrap();
+        assert_eq!(instruction, *translated_instruction);
+        memory_mapping.resize_region::<BPFError>(0, 1).unwrap();
+        assert!(translate_type::<Instruction>(&memory_mapping, 100, &bpf_loader::id()).is_err());
+    }
+
+    #[test]
+    fn test_translate_alignment_matrix() {
+        const START: u64 = 0x1000;
+        let data = vec![0u64; 8];
+        let memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion {
+                host_addr: data.as_ptr() as *const _ as u64,
+                vm_addr: START,
+                len: (data.len() * size_of::<u64>()) as u64,
+                vm_gap_shift: 63,
+                is_writable: true,
+            }],
+            &DEFAULT_CONFIG,
+        );
+
+        // Every syscall translates its pointers through these helpers, so this pins alignment
+        // enforcement for all of them: only the deprecated loader accepts unaligned pointers
+        macro_rules! assert_alignment {
+            ($t:ty) => {
+                for loader_id in &[bpf_loader::id(), bpf_loader_deprecated::id()] {
+                    for offset in 0..size_of::<u64>() as u64 {
+                        let vm_addr = START + offset;
+                        let aligned = *loader_id == bpf_loader_deprecated::id()
+                            || offset % align_of::<$t>() as u64 == 0;
+                        let check = |result: Result<(), EbpfError<BPFError>>| {
+                            if aligned {
+                                result.unwrap();
+                            } else {
+                                assert_eq!(
+                                    result,
+                                    Err(SyscallError::UnalignedPointer.into()),
+                                    "{} at offset {}",
+                                    stringify!($t),
+                                    offset
+                                );
+                            }
+                        };
+                        check(
+                            translate_type::<$t>(&memory_mapping, vm_addr, loader_id).map(|_| ()),
+                        );
+                        check(
+                            translate_type_mut::<$t>(&memory_mapping, vm_addr, loader_id)
+                                .map(|_| ()),
+                        );
+                        check(
+                            translate_slice::<$t>(&memory_mapping, vm_addr, 2, loader_id)
+                                .map(|_| ()),
+                        );
+                        check(
+                            translate_slice_mut::<$t>(&memory_mapping, vm_addr, 2, loader_id)
+                                .map(|_| ()),
+                        );
+                        // Alignment is enforced even when there is nothing to translate
+                        check(
+                            translate_slice::<$t>(&memory_mapping, vm_addr, 0, loader_id)
+                                .map(|_| ()),
+                        );
+                    }
+                }
+            };
+        }
+        assert_alignment!(u8);
+        assert_alignment!(u16);
+        assert_alignment!(u32);
+        assert_alignment!(u64);
+        assert_alignment!(Pubkey);
+        assert_alignment!(&[u8]);
+    }
+
+    #[test]
+    fn test_translate_fuzz() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            // Disjoint, aligned regions at random addresses separated by random gaps
+            let mut buffers = vec![];
+            let mut regions = vec![];
+            let mut vm_addr = rng.gen_range(0, 8) * 8;
+            for _ in 0..rng.gen_range(1, 6) {
+                let buffer = vec![0u64; rng.gen_range(1, 32)];
+                let len = (buffer.len() * size_of::<u64>()) as u64;
+                regions.push((vm_addr, len, buffer.as_ptr() as u64, rng.gen::<bool>()));
+                vm_addr += len + rng.gen_range(0, 8) * 8;
+                buffers.push(buffer);
+            }
+            let memory_mapping = MemoryMapping::new(
+                regions
+                    .iter()
+                    .map(|(vm_addr, len, host_addr, is_writable)| MemoryRegion {
+                        host_addr: *host_addr,
+                        vm_addr: *vm_addr,
+                        len: *len,
+                        vm_gap_shift: 63,
+                        is_writable: *is_writable,
+                    })
+                    .collect(),
+                &DEFAULT_CONFIG,
+            );
+
+            for _ in 0..100 {
+                // Bias the arguments towards region boundaries
+                let (region_vm_addr, region_len, _, _) = regions[rng.gen_range(0, regions.len())];
+                let vm_addr = match rng.gen_range(0, 4) {
+                    0 => region_vm_addr,
+                    1 => region_vm_addr + region_len,
+                    2 => region_vm_addr.wrapping_sub(rng.gen_range(0, 16)),
+                    _ => region_vm_addr + rng.gen_range(0, region_len),
+                };
+                let len = match rng.gen_range(0, 4) {
+                    0 => region_len,
+                    1 => rng.gen_range(0, 2 * region_len),
+                    2 => u64::MAX - rng.gen_range(0, 16),
+                    _ => rng.gen_range(1, 16),
+                };
+                let is_store = rng.gen::<bool>();
+
+                let expected = regions
+                    .iter()
+                    .find(|(region_vm_addr, region_len, _, _)| {
+                        vm_addr >= *region_vm_addr
+                            && vm_addr
+                                .checked_add(len)
+                                .map_or(false, |end| end <= region_vm_addr + region_len)
+                    })
+                    .filter(|(_, _, _, is_writable)| !is_store || *is_writable)
+                    .map(|(region_vm_addr, _, host_addr, _)| {
+                        host_addr + (vm_addr - region_vm_addr)
+                    });
+                let translated = if is_store {
+                    translate_slice_mut::<u8>(&memory_mapping, vm_addr, len, &bpf_loader::id())
+                        .map(|slice| slice.as_ptr() as u64)
+                } else {
+                    translate_slice::<u8>(&memory_mapping, vm_addr, len, &bpf_loader::id())
+                        .map(|slice| slice.as_ptr() as u64)
+                };
+                if len == 0 {
+                    // Empty slices do not reference VM memory
+                    assert!(translated.is_ok());
+                    continue;
+                }
+                assert_eq!(
+                    translated.ok(),
+                    expected,
+                    "vm_addr: {:#x}, len: {:#x}, store: {}, regions: {:?}",
+                    vm_addr,
+                    len,
+                    is_store,
+                    regions
+                );
+
+                // Slices of u64 are only accepted when aligned and entirely within one region
+                let translated = translate_slice::<u64>(
+                    &memory_mapping,
+                    vm_addr,
+                    len / size_of::<u64>() as u64,
+                    &bpf_loader::id(),
+                );
+                if let Ok(slice) = translated {
+                    if !slice.is_empty() {
+                        let host_addr = slice.as_ptr() as u64;
+                        let host_end = host_addr + (slice.len() * size_of::<u64>()) as u64;
+                        assert_eq!(host_addr % align_of::<u64>() as u64, 0);
+                        assert!(buffers.iter().any(|buffer| {
+                            let start = buffer.as_ptr() as u64;
+                            let end = start + (buffer.len() * size_of::<u64>()) as u64;
+                            start <= host_addr && host_end <= end
+                        }));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_translate_slice() {
+        // zero len
+        let good_data = vec![1u8, 2, 3, 4, 5];
+        let data: Vec<u8> = vec![];
+        assert_eq!(0x1 as *const u8, data.as_ptr());
+        let addr = good_data.as_ptr() as *const _ as u64;
+        let memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion {
+                host_addr: addr,
+                vm_addr: 100,
+                len: good_data.len() as u64,
+                vm_gap_shift: 63,
+                is_writable: false,
+            }],
+            &DEFAULT_CONFIG,
+        );
+        let translated_data =
+            translate_slice::<u8>(&memory_mapping, data.as_ptr() as u64, 0, &bpf_loader::id())
+                .unwrap();
+        assert_eq!(data, translated_data);
+        assert_eq!(0, translated_data.len());
+
+        // u8
+        let mut data = vec![1u8, 2, 3, 4, 5];
+        let addr = data.as_ptr() as *const _ as u64;
+        let memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion {
+                host_addr: addr,
+                vm_addr: 100,
+                len: data.len() as u64,
+                vm_gap_shift: 63,
+                is_writable: false,
+            }],
+            &DEFAULT_CONFIG,
+        );
+        let translated_data =
+            translate_slice::<u8>(&memory_mapping, 100, data.len() as u64, &bpf_loader::id())
+                .unwrap();
+        assert_eq!(data, translated_data);
+        data[0] = 10;
+        assert_eq!(data, translated_data);
+        assert!(translate_slice::<u8>(
+            &memory_mapping,
+            data.as_ptr() as u64,
+            u64::MAX,
+            &bpf_loader::id()
+        )
+        .is_err());
+
+        assert!(translate_slice::<u8>(
+            &memory_mapping,
+            100 - 1,
+            data.len() as u64,
+            &bpf_loader::id()
+        )
+        .is_err());
+
+        // u64
+        let mut data = vec![1u64, 2, 3, 4, 5];
+        let addr = data.as_ptr() as *const _ as u64;
+        let memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion {
+                host_addr: addr,
+                vm_addr: 96,
+                len: (data.len() * size_of::<u64>()) as u64,
+                vm_gap_shift: 63,
+                is_writable: false,
+            }],
+            &DEFAULT_CONFIG,
+        );
+        let translated_data =
+            translate_slice::<u64>(&memory_mapping, 96, data.len() as u64, &bpf_loader::id())
+                .unwrap();
+        assert_eq!(data, translated_data);
+        data[0] = 10;
+        assert_eq!(data, translated_data);
+        assert!(translate_slice::<u64>(&memory_mapping, 96, u64::MAX, &bpf_loader::id(),).is_err());
+
+        // Pubkeys
+        let mut data = vec![solana_sdk::pubkey::new_rand(); 5];
+        let addr = data.as_ptr() as *const _ as u64;
+        let memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion {
+                host_addr: addr,
+                vm_addr: 100,
+                len: (data.len() * std::mem::size_of::<Pubkey>()) as u64,
+                vm_gap_shift: 63,
+                is_writable: false,
+            }],
+            &DEFAULT_CONFIG,
+        );
+        let translated_data =
+            translate_slice::<Pubkey>(&memory_mapping, 100, data.len() as u64, &bpf_loader::id())
+                .unwrap();
+        assert_eq!(data, translated_data);
+        data[0] = solana_sdk::pubkey::new_rand(); // Both should point to same place
+        assert_eq!(data, translated_data);
+    }
+
+    #[test]
+    fn test_translate_string_and_do() {
+        let string = "Gaggablaghblagh!";
+        let addr = string.as_ptr() as *const _ as u64;
+        let memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion {
+                host_addr: addr,
+                vm_addr: 100,
+                len: string.len() as u64,
+                vm_gap_shift: 63,
+                is_writable: false,
+            }],
+            &DEFAULT_CONFIG,
+        );
+        assert_eq!(
+            42,
+            translate_string_and_do(
+                &memory_mapping,
+                100,
+                string.len() as u64,
+                &bpf_loader::id(),
+                &mut |string: &str| {
+                    assert_eq!(string, "Gaggablaghblagh!");
+                    Ok(42)
+                }
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "UserError(SyscallError(Abort))")]
+    fn test_syscall_abort() {
+        let memory_mapping = MemoryMapping::new(vec![MemoryRegion::default()], &DEFAULT_CONFIG);
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        SyscallAbort::call(
+            &mut SyscallAbort {},
+            0,
+            0,
+            0,
+            0,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        result.unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "UserError(SyscallError(AbortWithCode(42)))")]
+    fn test_syscall_abort_with_code() {
+        let memory_mapping = MemoryMapping::new(vec![MemoryRegion::default()], &DEFAULT_CONFIG);
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        SyscallAbortWithCode::call(
+            &mut SyscallAbortWithCode {},
+            42,
+            0,
+            0,
+            0,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        result.unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "UserError(SyscallError(Panic(\"Gaggablaghblagh!\", 42, 84)))")]
+    fn test_syscall_sol_panic() {
+        let string = "Gaggablaghblagh!";
+        let addr = string.as_ptr() as *const _ as u64;
+        let memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion {
+                host_addr: addr,
+                vm_addr: 100,
+                len: string.len() as u64,
+                vm_gap_shift: 63,
+                is_writable: false,
+            }],
+            &DEFAULT_CONFIG,
+        );
+        let mut syscall_panic = SyscallPanic {
+            loader_id: &bpf_loader::id(),
+        };
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall_panic.call(
+            100,
+            string.len() as u64,
+            42,
+            84,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        result.unwrap();
+    }
+
+    #[test]
+    fn test_syscall_sol_log() {
+        let string = "Gaggablaghblagh!";
+        let addr = string.as_ptr() as *const _ as u64;
+
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 3 }));
+        let log = Rc::new(RefCell::new(vec![]));
+        let logger: Rc<RefCell<dyn Logger>> =
+            Rc::new(RefCell::new(MockLogger { log: log.clone() }));
+        let mut syscall_sol_log = SyscallLog {
+            cost: 1,
+            compute_meter,
+            logger,
+            program_id: Pubkey::default(),
+            stack_height: 1,
+            loader_id: &bpf_loader::id(),
+        };
+        let memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion {
+                host_addr: addr,
+                vm_addr: 100,
+                len: string.len() as u64,
+                vm_gap_shift: 63,
+                is_writable: false,
+            }],
+            &DEFAULT_CONFIG,
+        );
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall_sol_log.call(
+            100,
+            string.len() as u64,
+            0,
+            0,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        result.unwrap();
+        assert_eq!(log.borrow().len(), 1);
+        assert_eq!(log.borrow()[0], "Program log: Gaggablaghblagh!");
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall_sol_log.call(
+            101, // AccessViolation
+            string.len() as u64,
+            0,
+            0,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_access_violation!(result, 101, string.len() as u64);
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall_sol_log.call(
+            100,
+            string.len() as u64 * 2, // AccessViolation
+            0,
+            0,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_access_violation!(result, 100, string.len() as u64 * 2);
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall_sol_log.call(
+            100,
+            string.len() as u64,
+            0,
+            0,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(
+            Err(EbpfError::UserError(BPFError::SyscallError(
+                SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
+            ))),
+            result
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_log_u64() {
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter {
+                remaining: std::u64::MAX,
+            }));
+        let log = Rc::new(RefCell::new(vec![]));
+        let logger: Rc<RefCell<dyn Logger>> =
+            Rc::new(RefCell::new(MockLogger { log: log.clone() }));
+        let mut syscall_sol_log_u64 = SyscallLogU64 {
+            cost: 0,
+            compute_meter,
+            logger,
+            program_id: Pubkey::default(),
+            stack_height: 1,
+        };
+        let memory_mapping = MemoryMapping::new(vec![], &DEFAULT_CONFIG);
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall_sol_log_u64.call(1, 2, 3, 4, 5, &memory_mapping, &mut result);
+        result.unwrap();
+
+        assert_eq!(log.borrow().len(), 1);
+        assert_eq!(log.borrow()[0], "Program log: 0x1, 0x2, 0x3, 0x4, 0x5");
+    }
+
+    #[test]
+    fn test_syscall_sol_log_record() {
+        struct RecordLogger {
+            records: Rc<RefCell<Vec<ProgramLogRecord>>>,
+        }
+        impl Logger for RecordLogger {
+            fn log_enabled(&self) -> bool {
+                true
+            }
+            fn log(&self, _message: &str) {
+                panic!("unexpected string log");
+            }
+            fn log_record(&self, record: &ProgramLogRecord) {
+                self.records.borrow_mut().push(record.clone());
+            }
+        }
+
+        let program_id = solana_sdk::pubkey::new_rand();
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 100 }));
+        let records = Rc::new(RefCell::new(vec![]));
+        let logger: Rc<RefCell<dyn Logger>> = Rc::new(RefCell::new(RecordLogger {
+            records: records.clone(),
+        }));
+        let memory_mapping = MemoryMapping::new(vec![], &DEFAULT_CONFIG);
+
+        let mut syscall_sol_log_u64 = SyscallLogU64 {
+            cost: 1,
+            compute_meter: compute_meter.clone(),
+            logger: logger.clone(),
+            program_id,
+            stack_height: 2,
+        };
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall_sol_log_u64.call(1, 2, 3, 4, 5, &memory_mapping, &mut result);
+        result.unwrap();
+
+        let mut syscall_sol_log_compute_units = SyscallLogBpfComputeUnits {
+            cost: 1,
+            compute_meter,
+            logger,
+            program_id,
+            stack_height: 2,
+        };
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall_sol_log_compute_units.call(0, 0, 0, 0, 0, &memory_mapping, &mut result);
+        result.unwrap();
+
+        assert_eq!(
+            *records.borrow(),
+            vec![
+                ProgramLogRecord {
+                    program_id,
+                    stack_height: 2,
+                    kind: ProgramLogKind::U64s([1, 2, 3, 4, 5]),
+                },
+                ProgramLogRecord {
+                    program_id,
+                    stack_height: 2,
+                    kind: ProgramLogKind::ComputeUnits(98),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_pubkey() {
+        let pubkey = Pubkey::from_str("MoqiU1vryuCGQSxFKA1SZ316JdLEFFhoAu6cKUNk7dN").unwrap();
+        let addr = &pubkey.as_ref()[0] as *const _ as u64;
+
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 2 }));
+        let log = Rc::new(RefCell::new(vec![]));
+        let logger: Rc<RefCell<dyn Logger>> =
+            Rc::new(RefCell::new(MockLogger { log: log.clone() }));
+        let mut syscall_sol_pubkey = SyscallLogPubkey {
+            cost: 1,
+            compute_meter,
+            logger,
+            program_id: Pubkey::default(),
+            stack_height: 1,
+            loader_id: &bpf_loader::id(),
+        };
+        let memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion {
+                host_addr: addr,
+                vm_addr: 100,
+                len: 32,
+                vm_gap_shift: 63,
+                is_writable: false,
+            }],
+            &DEFAULT_CONFIG,
+        );
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall_sol_pubkey.call(100, 0, 0, 0, 0, &memory_mapping, &mut result);
+        result.unwrap();
+        assert_eq!(log.borrow().len(), 1);
+        assert_eq!(
+            log.borrow()[0],
+            "Program log: MoqiU1vryuCGQSxFKA1SZ316JdLEFFhoAu6cKUNk7dN"
+        );
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall_sol_pubkey.call(
+            101, // AccessViolation
+            32,
+            0,
+            0,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_access_violation!(result, 101, 32);
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall_sol_pubkey.call(100, 32, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(
+            Err(EbpfError::UserError(BPFError::SyscallError(
+                SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
+            ))),
+            result
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_log_data() {
+        let data = [0u8, 1, 2, 0xfe, 0xff];
+        let addr = data.as_ptr() as *const _ as u64;
+
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 2 }));
+        let log = Rc::new(RefCell::new(vec![]));
+        let logger: Rc<RefCell<dyn Logger>> =
+            Rc::new(RefCell::new(MockLogger { log: log.clone() }));
+        let mut syscall_sol_log_data = SyscallLogData {
+            cost: 1,
+            compute_meter,
+            logger,
+            program_id: Pubkey::default(),
+            stack_height: 1,
+            loader_id: &bpf_loader::id(),
+        };
+        let memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion {
+                host_addr: addr,
+                vm_addr: 100,
+                len: data.len() as u64,
+                vm_gap_shift: 63,
+                is_writable: false,
+            }],
+            &DEFAULT_CONFIG,
+        );
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall_sol_log_data.call(
+            100,
+            data.len() as u64,
+            0,
+            0,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        result.unwrap();
+        assert_eq!(log.borrow().len(), 1);
+        assert_eq!(log.borrow()[0], "Program data: AAEC/v8=");
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall_sol_log_data.call(
+            100,
+            data.len() as u64 * 2, // AccessViolation
+            0,
+            0,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_access_violation!(result, 100, data.len() as u64 * 2);
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall_sol_log_data.call(
+            100,
+            data.len() as u64,
+            0,
+            0,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(
+            Err(EbpfError::UserError(BPFError::SyscallError(
+                SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
+            ))),
+            result
+        );
+        assert_eq!(log.borrow().len(), 1);
+    }
+
+    #[test]
+    fn test_syscall_sol_alloc_free() {
+        // large alloc
+        {
+            let heap = vec![0_u8; 100];
+            let memory_mapping = MemoryMapping::new(
+                vec![MemoryRegion::new_from_slice(&heap, MM_HEAP_START, 0, true)],
+                &DEFAULT_CONFIG,
+            );
+            let mut syscall = SyscallAllocFree {
+                aligned: true,
+                allocator: Box::new(BPFAllocator::new(heap, MM_HEAP_START)),
+            };
+            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+            syscall.call(100, 0, 0, 0, 0, &memory_mapping, &mut result);
+            assert_ne!(result.unwrap(), 0);
+            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+            syscall.call(100, 0, 0, 0, 0, &memory_mapping, &mut result);
+            assert_eq!(result.unwrap(), 0);
+            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+            syscall.call(u64::MAX, 0, 0, 0, 0, &memory_mapping, &mut result);
+            assert_eq!(result.unwrap(), 0);
+        }
+        // many small unaligned allocs
+        {
+            let heap = vec![0_u8; 100];
+            let memory_mapping = MemoryMapping::new(
+                vec![MemoryRegion::new_from_slice(&heap, MM_HEAP_START, 0, true)],
+                &DEFAULT_CONFIG,
+            );
+            let mut syscall = SyscallAllocFree {
+                aligned: false,
+                allocator: Box::new(BPFAllocator::new(heap, MM_HEAP_START)),
+            };
+            for _ in 0..100 {
+                let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+                syscall.call(1, 0, 0, 0, 0, &memory_mapping, &mut result);
+                assert_ne!(result.unwrap(), 0);
+            }
+            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+            syscall.call(100, 0, 0, 0, 0, &memory_mapping, &mut result);
+            assert_eq!(result.unwrap(), 0);
+        }
+        // many small aligned allocs
+        {
+            let heap = vec![0_u8; 100];
+            let memory_mapping = MemoryMapping::new(
+                vec![MemoryRegion::new_from_slice(&heap, MM_HEAP_START, 0, true)],
+                &DEFAULT_CONFIG,
+            );
+            let mut syscall = SyscallAllocFree {
+                aligned: true,
+                allocator: Box::new(BPFAllocator::new(heap, MM_HEAP_START)),
+            };
+            for _ in 0..12 {
+                let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+                syscall.call(1, 0, 0, 0, 0, &memory_mapping, &mut result);
+                assert_ne!(result.unwrap(), 0);
+            }
+            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+            syscall.call(100, 0, 0, 0, 0, &memory_mapping, &mut result);
+            assert_eq!(result.unwrap(), 0);
+        }
+        // aligned allocs
+
+        fn check_alignment<T>() {
+            let heap = vec![0_u8; 100];
+            let memory_mapping = MemoryMapping::new(
+                vec![MemoryRegion::new_from_slice(&heap, MM_HEAP_START, 0, true)],
+                &DEFAULT_CONFIG,
+            );
+            let mut syscall = SyscallAllocFree {
+                aligned: true,
+                allocator: Box::new(BPFAllocator::new(heap, MM_HEAP_START)),
+            };
+            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+            syscall.call(
+                size_of::<u8>() as u64,
+                0,
+                0,
+                0,
+                0,
+                &memory_mapping,
+                &mut result,
+            );
+            let address = result.unwrap();
+            assert_ne!(address, 0);
+            assert_eq!((address as *const u8).align_offset(align_of::<u8>()), 0);
+        }
+        check_alignment::<u8>();
+        check_alignment::<u16>();
+        check_alignment::<u32>();
+        check_alignment::<u64>();
+        check_alignment::<u128>();
+    }
+
+    #[test]
+    fn test_syscall_sol_alloc_free_list() {
+        fn alloc(syscall: &mut SyscallAllocFree, memory_mapping: &MemoryMapping, size: u64) -> u64 {
+            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+            syscall.call(size, 0, 0, 0, 0, memory_mapping, &mut result);
+            result.unwrap()
+        }
+        fn free(syscall: &mut SyscallAllocFree, memory_mapping: &MemoryMapping, addr: u64) {
+            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+            syscall.call(0, addr, 0, 0, 0, memory_mapping, &mut result);
+            assert_eq!(result.unwrap(), 0);
+        }
+
+        // Allocating and freeing in a loop exhausts the bump allocator but not the free list
+        let heap = vec![0_u8; 100];
+        let memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion::new_from_slice(&heap, MM_HEAP_START, 0, true)],
+            &DEFAULT_CONFIG,
+        );
+        let mut syscall = SyscallAllocFree {
+            aligned: true,
+            allocator: Box::new(BPFAllocator::new(heap, MM_HEAP_START)),
+        };
+        let addr = alloc(&mut syscall, &memory_mapping, 64);
+        assert_eq!(addr, MM_HEAP_START);
+        free(&mut syscall, &memory_mapping, addr);
+        assert_eq!(alloc(&mut syscall, &memory_mapping, 64), 0);
+
+        let heap = vec![0_u8; 100];
+        let memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion::new_from_slice(&heap, MM_HEAP_START, 0, true)],
+            &DEFAULT_CONFIG,
+        );
+        let mut syscall = SyscallAllocFree {
+            aligned: true,
+            allocator: Box::new(BPFFreeListAllocator::new(heap, MM_HEAP_START)),
+        };
+        for _ in 0..1000 {
+            let addr = alloc(&mut syscall, &memory_mapping, 64);
+            assert_eq!(addr, MM_HEAP_START);
+            free(&mut syscall, &memory_mapping, addr);
+        }
+
+        // Freed blocks are reused best-fit and zeroed
+        let a = alloc(&mut syscall, &memory_mapping, 16);
+        let b = alloc(&mut syscall, &memory_mapping, 16);
+        let c = alloc(&mut syscall, &memory_mapping, 16);
+        assert_eq!(
+            (a, b, c),
+            (MM_HEAP_START, MM_HEAP_START + 16, MM_HEAP_START + 32)
+        );
+        let host_addr = memory_mapping
+            .map::<BPFError>(AccessType::Store, a, 16)
+            .unwrap();
+        let a_bytes = unsafe { std::slice::from_raw_parts_mut(host_addr as *mut u8, 16) };
+        a_bytes.copy_from_slice(&[0xff; 16]);
+        free(&mut syscall, &memory_mapping, b);
+        assert_eq!(alloc(&mut syscall, &memory_mapping, 1), b);
+        free(&mut syscall, &memory_mapping, a);
+        assert_eq!(alloc(&mut syscall, &memory_mapping, 16), a);
+        assert_eq!(a_bytes, &[0; 16]);
+
+        // Adjacent free blocks are merged
+        free(&mut syscall, &memory_mapping, a);
+        free(&mut syscall, &memory_mapping, b);
+        assert_eq!(alloc(&mut syscall, &memory_mapping, 32), a);
+
+        // Freeing the last allocation returns its memory to the bump region
+        free(&mut syscall, &memory_mapping, c);
+        assert_eq!(alloc(&mut syscall, &memory_mapping, 68), c);
+        assert_eq!(alloc(&mut syscall, &memory_mapping, 1), 0);
+
+        // Unknown and repeated frees are ignored
+        free(&mut syscall, &memory_mapping, a + 1);
+        free(&mut syscall, &memory_mapping, c);
+        free(&mut syscall, &memory_mapping, c);
+        assert_eq!(alloc(&mut syscall, &memory_mapping, 68), c);
+    }
+
+    #[test]
+    fn test_syscall_sha256() {
+        let bytes1 = "Gaggablaghblagh!";
+        let bytes2 = "flurbos";
+
+        struct MockSlice {
+            pub addr: u64,
+            pub len: usize,
+        }
+        let mock_slice1 = MockSlice {
+            addr: 4096,
+            len: bytes1.len(),
+        };
+        let mock_slice2 = MockSlice {
+            addr: 8192,
+            len: bytes2.len(),
+        };
+        let bytes_to_hash = [mock_slice1, mock_slice2]; // TODO
+        let hash_result = [0; HASH_BYTES];
+        let ro_len = bytes_to_hash.len() as u64;
+        let ro_va = 96;
+        let rw_va = 192;
+        let memory_mapping = MemoryMapping::new(
+            vec![
+                MemoryRegion {
+                    host_addr: bytes1.as_ptr() as *const _ as u64,
+                    vm_addr: 4096,
+                    len: bytes1.len() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: bytes2.as_ptr() as *const _ as u64,
+                    vm_addr: 8192,
+                    len: bytes2.len() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: bytes_to_hash.as_ptr() as *const _ as u64,
+                    vm_addr: 96,
+                    len: 32,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: hash_result.as_ptr() as *const _ as u64,
+                    vm_addr: rw_va,
+                    len: HASH_BYTES as u64,
+                    vm_gap_shift: 63,
+                    is_writable: true,
+                },
+            ],
+            &DEFAULT_CONFIG,
+        );
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter {
+                remaining: (bytes1.len() + bytes2.len()) as u64,
+            }));
+        let mut syscall = SyscallSha256 {
+            sha256_base_cost: 0,
+            sha256_byte_cost: 2,
+            compute_meter,
+            loader_id: &bpf_loader_deprecated::id(),
+            reject_overlapping_buffers: true,
+        };
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(ro_va, ro_len, rw_va, 0, 0, &memory_mapping, &mut result);
+        result.unwrap();
+
+        let hash_local = hashv(&[bytes1.as_ref(), bytes2.as_ref()]).to_bytes();
+        assert_eq!(hash_result, hash_local);
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(
+            ro_va - 1, // AccessViolation
+            ro_len,
+            rw_va,
+            0,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_access_violation!(result, ro_va - 1, ro_len);
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(
+            ro_va,
+            ro_len + 1, // AccessViolation
+            rw_va,
+            0,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_access_violation!(result, ro_va, ro_len + 1);
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(
+            ro_va,
+            ro_len,
+            rw_va - 1, // AccessViolation
+            0,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_access_violation!(result, rw_va - 1, HASH_BYTES as u64);
+
+        syscall.call(ro_va, ro_len, rw_va, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(
+            Err(EbpfError::UserError(BPFError::SyscallError(
+                SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
+            ))),
+            result
+        );
+    }
+
+    #[test]
+    fn test_syscall_hmac_sha256() {
+        let key = "Jefe";
+        let bytes1 = "what do ya ";
+        let bytes2 = "want for nothing?";
+
+        struct MockSlice {
+            pub addr: u64,
+            pub len: usize,
+        }
+        let bytes_to_hash = [
+            MockSlice {
+                addr: 4096,
+                len: bytes1.len(),
+            },
+            MockSlice {
+                addr: 8192,
+                len: bytes2.len(),
+            },
+        ];
+        let hash_result = [0; HASH_BYTES];
+        let key_va = 16384;
+        let ro_va = 96;
+        let ro_len = bytes_to_hash.len() as u64;
+        let rw_va = 192;
+        let memory_mapping = MemoryMapping::new(
+            vec![
+                MemoryRegion {
+                    host_addr: bytes1.as_ptr() as *const _ as u64,
+                    vm_addr: 4096,
+                    len: bytes1.len() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: bytes2.as_ptr() as *const _ as u64,
+                    vm_addr: 8192,
+                    len: bytes2.len() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: key.as_ptr() as *const _ as u64,
+                    vm_addr: key_va,
+                    len: key.len() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: bytes_to_hash.as_ptr() as *const _ as u64,
+                    vm_addr: ro_va,
+                    len: 32,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: hash_result.as_ptr() as *const _ as u64,
+                    vm_addr: rw_va,
+                    len: HASH_BYTES as u64,
+                    vm_gap_shift: 63,
+                    is_writable: true,
+                },
+            ],
+            &DEFAULT_CONFIG,
+        );
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter {
+                remaining: 10
+                    + 2 * (key.len() / 2 + bytes1.len() / 2 + bytes2.len() / 2) as u64
+                    + 1,
+            }));
+        let mut syscall = SyscallHmacSha256 {
+            base_cost: 10,
+            byte_cost: 2,
+            compute_meter: compute_meter.clone(),
+            loader_id: &bpf_loader_deprecated::id(),
+            reject_overlapping_buffers: true,
+        };
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(
+            key_va,
+            key.len() as u64,
+            ro_va,
+            ro_len,
+            rw_va,
+            &memory_mapping,
+            &mut result,
+        );
+        result.unwrap();
+        assert_eq!(
+            hash_result,
+            hmac(key.as_ref(), b"what do ya want for nothing?").to_bytes()
+        );
+        assert_eq!(compute_meter.borrow().get_remaining(), 1);
+
+        compute_meter.borrow_mut().consume(1).unwrap();
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(
+            key_va,
+            key.len() as u64,
+            ro_va,
+            ro_len,
+            rw_va,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(
+            Err(EbpfError::UserError(BPFError::SyscallError(
+                SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
+            ))),
+            result
+        );
+
+        // Key lengths whose cost overflows exhaust the budget
+        let mut overflowing_syscall = SyscallHmacSha256 {
+            base_cost: 0,
+            byte_cost: 3,
+            compute_meter: Rc::new(RefCell::new(MockComputeMeter {
+                remaining: u64::MAX - 1,
+            })),
+            loader_id: &bpf_loader_deprecated::id(),
+            reject_overlapping_buffers: true,
+        };
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        overflowing_syscall.call(
+            key_va,
+            u64::MAX,
+            ro_va,
+            ro_len,
+            rw_va,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(
+            Err(EbpfError::UserError(BPFError::SyscallError(
+                SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
+            ))),
+            result
+        );
+
+        let mut syscall = SyscallHmacSha256 {
+            base_cost: 0,
+            byte_cost: 0,
+            compute_meter,
+            loader_id: &bpf_loader_deprecated::id(),
+            reject_overlapping_buffers: true,
+        };
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(
+            key_va,
+            key.len() as u64 + 1, // AccessViolation
+            ro_va,
+            ro_len,
+            rw_va,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_access_violation!(result, key_va, key.len() as u64 + 1);
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(
+            key_va,
+            key.len() as u64,
+            ro_va - 1, // AccessViolation
+            ro_len,
+            rw_va,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_access_violation!(result, ro_va - 1, 32);
+    }
+
+    #[test]
+    fn test_syscall_sha256_context() {
+        let bytes1 = "Gaggablaghblagh!";
+        let bytes2 = "flurbos";
+
+        struct MockSlice {
+            pub addr: u64,
+            pub len: usize,
+        }
+        let slices1 = [MockSlice {
+            addr: 4096,
+            len: bytes1.len(),
+        }];
+        let slices2 = [MockSlice {
+            addr: 8192,
+            len: bytes2.len(),
+        }];
+        let hash_result = [0; HASH_BYTES];
+        let rw_va = 192;
+        let memory_mapping = MemoryMapping::new(
+            vec![
+                MemoryRegion {
+                    host_addr: bytes1.as_ptr() as *const _ as u64,
+                    vm_addr: 4096,
+                    len: bytes1.len() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: bytes2.as_ptr() as *const _ as u64,
+                    vm_addr: 8192,
+                    len: bytes2.len() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: slices1.as_ptr() as *const _ as u64,
+                    vm_addr: 96,
+                    len: 16,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: slices2.as_ptr() as *const _ as u64,
+                    vm_addr: 128,
+                    len: 16,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: hash_result.as_ptr() as *const _ as u64,
+                    vm_addr: rw_va,
+                    len: HASH_BYTES as u64,
+                    vm_gap_shift: 63,
+                    is_writable: true,
+                },
+            ],
+            &DEFAULT_CONFIG,
+        );
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 1_000 }));
+        let contexts = Rc::new(RefCell::new(Sha256Contexts::default()));
+        let loader_id = bpf_loader::id();
+        let mut init = SyscallSha256Init {
+            cost: 1,
+            compute_meter: compute_meter.clone(),
+            contexts: contexts.clone(),
+        };
+        let mut update = SyscallSha256Update {
+            base_cost: 1,
+            byte_cost: 2,
+            compute_meter: compute_meter.clone(),
+            contexts: contexts.clone(),
+            loader_id: &loader_id,
+        };
+        let mut finalize = SyscallSha256Final {
+            cost: 1,
+            compute_meter: compute_meter.clone(),
+            contexts,
+            loader_id: &loader_id,
+        };
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        init.call(0, 0, 0, 0, 0, &memory_mapping, &mut result);
+        let handle = result.unwrap();
+        for (vals_addr, vals_len) in &[(96, 1), (0, 0), (128, 1)] {
+            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+            update.call(
+                handle,
+                *vals_addr,
+                *vals_len,
+                0,
+                0,
+                &memory_mapping,
+                &mut result,
+            );
+            result.unwrap();
+        }
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        finalize.call(handle, rw_va, 0, 0, 0, &memory_mapping, &mut result);
+        result.unwrap();
+        assert_eq!(
+            hash_result,
+            hashv(&[bytes1.as_ref(), bytes2.as_ref()]).to_bytes()
+        );
+        assert_eq!(
+            compute_meter.borrow().get_remaining(),
+            1_000
+                - 1
+                - 3
+                - 2 * ((bytes1.len() as u64 + 1) / 2)
+                - 2 * ((bytes2.len() as u64 + 1) / 2)
+                - 1
+        );
+
+        // Empty updates still consume compute
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        init.call(0, 0, 0, 0, 0, &memory_mapping, &mut result);
+        let empty_handle = result.unwrap();
+        let remaining = compute_meter.borrow().get_remaining();
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        update.call(empty_handle, 0, 0, 0, 0, &memory_mapping, &mut result);
+        result.unwrap();
+        assert_eq!(compute_meter.borrow().get_remaining(), remaining - 1);
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        finalize.call(empty_handle, rw_va, 0, 0, 0, &memory_mapping, &mut result);
+        result.unwrap();
+
+        // Finalized contexts are closed
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        update.call(handle, 96, 1, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(
+            Err(EbpfError::UserError(BPFError::SyscallError(
+                SyscallError::InvalidHashContext
+            ))),
+            result
+        );
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        finalize.call(handle, rw_va, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(
+            Err(EbpfError::UserError(BPFError::SyscallError(
+                SyscallError::InvalidHashContext
+            ))),
+            result
+        );
+
+        // Handles are reused, and only so many contexts may be open at once
+        let handles = (0..MAX_HASH_CONTEXTS)
+            .map(|_| {
+                let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+                init.call(0, 0, 0, 0, 0, &memory_mapping, &mut result);
+                result.unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(handles, (0..MAX_HASH_CONTEXTS as u64).collect::<Vec<_>>());
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        init.call(0, 0, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(
+            Err(EbpfError::UserError(BPFError::SyscallError(
+                SyscallError::TooManyHashContexts
+            ))),
+            result
+        );
+    }
+
+    #[test]
+    fn test_syscall_keccak256_context() {
+        let bytes: &[u8] = b"Gaggablaghblagh!";
+        let slices = [4096, bytes.len() as u64];
+        let hash_result = [0; keccak::HASH_BYTES];
+        let rw_va = 192;
+        let memory_mapping = MemoryMapping::new(
+            vec![
+                MemoryRegion {
+                    host_addr: bytes.as_ptr() as *const _ as u64,
+                    vm_addr: 4096,
+                    len: bytes.len() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: slices.as_ptr() as *const _ as u64,
+                    vm_addr: 96,
+                    len: 16,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: hash_result.as_ptr() as *const _ as u64,
+                    vm_addr: rw_va,
+                    len: keccak::HASH_BYTES as u64,
+                    vm_gap_shift: 63,
+                    is_writable: true,
+                },
+            ],
+            &DEFAULT_CONFIG,
+        );
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 1_000 }));
+        let contexts = Rc::new(RefCell::new(Keccak256Contexts::default()));
+        let loader_id = bpf_loader::id();
+        let mut init = SyscallKeccak256Init {
+            cost: 1,
+            compute_meter: compute_meter.clone(),
+            contexts: contexts.clone(),
+        };
+        let mut update = SyscallKeccak256Update {
+            base_cost: 1,
+            byte_cost: 2,
+            compute_meter: compute_meter.clone(),
+            contexts: contexts.clone(),
+            loader_id: &loader_id,
+        };
+        let mut finalize = SyscallKeccak256Final {
+            cost: 1,
+            compute_meter: compute_meter.clone(),
+            contexts,
+            loader_id: &loader_id,
+        };
+
+        // The same data hashed in two updates
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        init.call(0, 0, 0, 0, 0, &memory_mapping, &mut result);
+        let handle = result.unwrap();
+        for _ in 0..2 {
+            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+            update.call(handle, 96, 1, 0, 0, &memory_mapping, &mut result);
+            result.unwrap();
+        }
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        finalize.call(handle, rw_va, 0, 0, 0, &memory_mapping, &mut result);
+        result.unwrap();
+        assert_eq!(hash_result, keccak::hashv(&[bytes, bytes]));
+        assert_eq!(
+            compute_meter.borrow().get_remaining(),
+            1_000 - 1 - 2 * (1 + 2 * ((bytes.len() as u64 + 1) / 2)) - 1
+        );
+
+        // Finalized contexts are closed
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        finalize.call(handle, rw_va, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(
+            Err(EbpfError::UserError(BPFError::SyscallError(
+                SyscallError::InvalidHashContext
+            ))),
+            result
+        );
+    }
+
+    #[test]
+    fn test_syscall_chacha20_poly1305_open() {
+        let key = [0x42; chacha20_poly1305::KEY_BYTES];
+        let nonce = [0x24; chacha20_poly1305::NONCE_BYTES];
+        let aad = b"header";
+        let ciphertext = [
+            0x85, 0x73, 0xf1, 0x6f, 0x88, 0xb5, 0x78, 0xc9, 0xf1, 0x4b, 0xec, 0xea, 0x93, 0x79,
+        ];
+        let tag = [
+            0x9b, 0x13, 0xd7, 0x82, 0xe3, 0x48, 0xb7, 0x18, 0xdd, 0xe0, 0x5b, 0xd0, 0xd0, 0x6c,
+            0x33, 0x01,
+        ];
+
+        struct MockSlice {
+            pub addr: u64,
+            pub len: usize,
+        }
+        let inputs = |key_len, aad_len| {
+            [
+                MockSlice {
+                    addr: 4096,
+                    len: key_len,
+                },
+                MockSlice {
+                    addr: 8192,
+                    len: nonce.len(),
+                },
+                MockSlice {
+                    addr: 12288,
+                    len: aad_len,
+                },
+                MockSlice {
+                    addr: 16384,
+                    len: ciphertext.len(),
+                },
+                MockSlice {
+                    addr: 20480,
+                    len: tag.len(),
+                },
+            ]
+        };
+        let valid_inputs = inputs(key.len(), aad.len());
+        let short_aad_inputs = inputs(key.len(), aad.len() - 1);
+        let short_key_inputs = inputs(key.len() - 1, aad.len());
+        let plaintext = [0u8; 14];
+        let plaintext_va = 24576;
+        let mut regions = vec![MemoryRegion {
+            host_addr: plaintext.as_ptr() as *const _ as u64,
+            vm_addr: plaintext_va,
+            len: plaintext.len() as u64,
+            vm_gap_shift: 63,
+            is_writable: true,
+        }];
+        for (bytes, vm_addr) in &[
+            (&key[..], 4096),
+            (&nonce[..], 8192),
+            (&aad[..], 12288),
+            (&ciphertext[..], 16384),
+            (&tag[..], 20480),
+        ] {
+            regions.push(MemoryRegion {
+                host_addr: bytes.as_ptr() as *const _ as u64,
+                vm_addr: *vm_addr,
+                len: bytes.len() as u64,
+                vm_gap_shift: 63,
+                is_writable: false,
+            });
+        }
+        for (inputs, vm_addr) in &[
+            (&valid_inputs, 96),
+            (&short_aad_inputs, 192),
+            (&short_key_inputs, 288),
+        ] {
+            regions.push(MemoryRegion {
+                host_addr: inputs.as_ptr() as *const _ as u64,
+                vm_addr: *vm_addr,
+                len: (inputs.len() * size_of::<MockSlice>()) as u64,
+                vm_gap_shift: 63,
+                is_writable: false,
+            });
+        }
+        let memory_mapping = MemoryMapping::new(regions, &DEFAULT_CONFIG);
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 1_000 }));
+        let mut syscall = SyscallChaCha20Poly1305Open {
+            base_cost: 10,
+            byte_cost: 2,
+            compute_meter: compute_meter.clone(),
+            loader_id: &bpf_loader::id(),
+            reject_overlapping_buffers: true,
+        };
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(96, 5, plaintext_va, 14, 0, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(&plaintext, b"attack at dawn");
+        assert_eq!(
+            compute_meter.borrow().get_remaining(),
+            1_000 - 10 - 2 * (aad.len() + ciphertext.len()) as u64
+        );
+
+        // Authentication failures are reported to the caller
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(192, 5, plaintext_va, 14, 0, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 1);
+
+        for (inputs_addr, inputs_len, plaintext_len, name) in &[
+            (96, 4, 14, "inputs"),
+            (288, 5, 14, "key"),
+            (96, 5, 13, "plaintext"),
+        ] {
+            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+            syscall.call(
+                *inputs_addr,
+                *inputs_len,
+                plaintext_va,
+                *plaintext_len,
+                0,
+                &memory_mapping,
+                &mut result,
+            );
+            assert_eq!(
+                Err(EbpfError::UserError(BPFError::SyscallError(
+                    SyscallError::InvalidAeadLength(*name)
+                ))),
+                result
+            );
+        }
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(
+            96,
+            5,
+            plaintext_va + 1, // AccessViolation
+            14,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_access_violation!(result, plaintext_va + 1, 14);
+    }
+
+    #[test]
+    fn test_syscall_secp256r1_verify() {
+        let message = b"hello passkey";
+        let tampered_message = b"hello passkez";
+        let pubkey: [u8; secp256r1::SECP256R1_PUBKEY_BYTES] = [
+            0x04, 0x9f, 0xad, 0x84, 0xae, 0xae, 0x08, 0xbb, 0xef, 0x7f, 0x01, 0x00, 0x14, 0xd8,
+            0x2c, 0xef, 0x6a, 0x09, 0xde, 0x2b, 0x0c, 0xf8, 0x71, 0xb5, 0xce, 0x0c, 0x4f, 0x1d,
+            0x13, 0xa5, 0x9a, 0x59, 0x34, 0x07, 0xcb, 0x45, 0x76, 0x9f, 0x10, 0x70, 0xe2, 0xc2,
+            0x47, 0x0f, 0xe5, 0xb1, 0xbf, 0xe6, 0x31, 0x33, 0xc0, 0xb0, 0xcd, 0xc6, 0x4e, 0xa4,
+            0xbf, 0x37, 0x91, 0xa8, 0xec, 0x2a, 0x07, 0xfd, 0x4f,
+        ];
+        let signature: [u8; secp256r1::SECP256R1_SIGNATURE_BYTES] = [
+            0x17, 0x3d, 0x5e, 0x75, 0x8f, 0xc5, 0x20, 0xe6, 0xfa, 0x97, 0x59, 0x55, 0xc5, 0xef,
+            0x60, 0xdb, 0xc7, 0x11, 0x31, 0x1f, 0x25, 0x37, 0xd2, 0xb1, 0x01, 0x91, 0x81, 0x73,
+            0x3e, 0x2f, 0xd8, 0x81, 0xeb, 0x46, 0x46, 0xad, 0xb8, 0xb1, 0x60, 0x83, 0x32, 0x38,
+            0x75, 0xe4, 0x62, 0x22, 0xe8, 0x53, 0x7d, 0xe0, 0x2b, 0xf5, 0x0e, 0xed, 0xd8, 0x1f,
+            0xc0, 0x41, 0x44, 0x3e, 0xa5, 0x6c, 0x61, 0x09,
+        ];
+        let mut malformed_pubkey = pubkey;
+        malformed_pubkey[0] = 0x02;
+        let memory_mapping = MemoryMapping::new(
+            vec![
+                MemoryRegion {
+                    host_addr: message.as_ptr() as *const _ as u64,
+                    vm_addr: 4096,
+                    len: message.len() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: tampered_message.as_ptr() as *const _ as u64,
+                    vm_addr: 8192,
+                    len: tampered_message.len() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: signature.as_ptr() as *const _ as u64,
+                    vm_addr: 12288,
+                    len: signature.len() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: pubkey.as_ptr() as *const _ as u64,
+                    vm_addr: 16384,
+                    len: pubkey.len() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: malformed_pubkey.as_ptr() as *const _ as u64,
+                    vm_addr: 20480,
+                    len: malformed_pubkey.len() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+            ],
+            &DEFAULT_CONFIG,
+        );
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 1_000 }));
+        let mut syscall = SyscallSecp256r1Verify {
+            cost: 100,
+            sha256_byte_cost: 2,
+            compute_meter: compute_meter.clone(),
+            loader_id: &bpf_loader::id(),
+        };
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(4096, 13, 12288, 16384, 0, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(
+            compute_meter.borrow().get_remaining(),
+            1_000 - 100 - 2 * (message.len() as u64 / 2)
+        );
+
+        // Invalid signatures and public keys are reported to the caller
+        for (message_addr, pubkey_addr) in &[(8192, 16384), (4096, 20480)] {
+            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+            syscall.call(
+                *message_addr,
+                13,
+                12288,
+                *pubkey_addr,
+                0,
+                &memory_mapping,
+                &mut result,
+            );
+            assert_eq!(result.unwrap(), 1);
+        }
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(
+            4096,
+            13,
+            12288 + 1, // AccessViolation
+            16384,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_access_violation!(result, 12288 + 1, 64);
+    }
 
-    let instruction = syscall.translate_instruction(instruction_addr, &memory_mapping)?;
-    let caller_program_id = invoke_context
-        .get_caller()
-        .map_err(SyscallError::InstructionError)?;
-    let signers = syscall.translate_signers(
-        caller_program_id,
-        signers_seeds_addr,
-        signers_seeds_len,
-        memory_mapping,
-    )?;
-    let keyed_account_refs = syscall
-        .get_callers_keyed_accounts()
-        .iter()
-        .collect::<Vec<&KeyedAccount>>();
-    let (message, callee_program_id, callee_program_id_index) =
-        MessageProcessor::create_message(&instruction, &keyed_account_refs, &signers)
-            .map_err(SyscallError::InstructionError)?;
-    let (accounts, account_refs) = syscall.translate_accounts(
-        &message,
-        account_infos_addr,
-        account_infos_len,
-        memory_mapping,
-    )?;
+    #[test]
+    fn test_syscall_x25519_dh() {
+        // RFC 7748 section 6.1
+        let scalar: [u8; x25519::X25519_BYTES] = [
+            0x77, 0x07, 0x6d, 0x0a, 0x73, 0x18, 0xa5, 0x7d, 0x3c, 0x16, 0xc1, 0x72, 0x51, 0xb2,
+            0x66, 0x45, 0xdf, 0x4c, 0x2f, 0x87, 0xeb, 0xc0, 0x99, 0x2a, 0xb1, 0x77, 0xfb, 0xa5,
+            0x1d, 0xb9, 0x2c, 0x2a,
+        ];
+        let mut base_point = [0u8; x25519::X25519_BYTES];
+        base_point[0] = 9;
+        let small_order_point = [0u8; x25519::X25519_BYTES];
+        let output = [0u8; x25519::X25519_BYTES];
+        let memory_mapping = MemoryMapping::new(
+            vec![
+                MemoryRegion {
+                    host_addr: scalar.as_ptr() as *const _ as u64,
+                    vm_addr: 96,
+                    len: 32,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: base_point.as_ptr() as *const _ as u64,
+                    vm_addr: 128,
+                    len: 32,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: small_order_point.as_ptr() as *const _ as u64,
+                    vm_addr: 160,
+                    len: 32,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: output.as_ptr() as *const _ as u64,
+                    vm_addr: 192,
+                    len: 32,
+                    vm_gap_shift: 63,
+                    is_writable: true,
+                },
+            ],
+            &DEFAULT_CONFIG,
+        );
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 100 }));
+        let mut syscall = SyscallX25519Dh {
+            cost: 10,
+            compute_meter: compute_meter.clone(),
+            loader_id: &bpf_loader::id(),
+            reject_overlapping_buffers: true,
+        };
 
-    // Process instruction
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(96, 128, 192, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(
+            output,
+            [
+                0x85, 0x20, 0xf0, 0x09, 0x89, 0x30, 0xa7, 0x54, 0x74, 0x8b, 0x7d, 0xdc, 0xb4, 0x3e,
+                0xf7, 0x5a, 0x0d, 0xbf, 0x3a, 0x0d, 0x26, 0x38, 0x1a, 0xf4, 0xeb, 0xa4, 0xa9, 0x8e,
+                0xaa, 0x9b, 0x4e, 0x6a,
+            ]
+        );
+        assert_eq!(compute_meter.borrow().get_remaining(), 90);
 
-    invoke_context.record_instruction(&instruction);
-    let program_account =
-        (**accounts
-            .get(callee_program_id_index)
-            .ok_or(SyscallError::InstructionError(
-                InstructionError::MissingAccount,
-            ))?)
-        .clone();
-    if !program_account.borrow().executable {
-        return Err(SyscallError::InstructionError(InstructionError::AccountNotExecutable).into());
-    }
-    let executable_accounts = vec![(callee_program_id, program_account)];
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(96, 160, 192, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 1);
 
-    #[allow(clippy::deref_addrof)]
-    match MessageProcessor::process_cross_program_instruction(
-        &message,
-        &executable_accounts,
-        &accounts,
-        *(&mut *invoke_context),
-    ) {
-        Ok(()) => (),
-        Err(err) => match ProgramError::try_from(err) {
-            Ok(err) => return Ok(err.into()),
-            Err(err) => return Err(SyscallError::InstructionError(err).into()),
-        },
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(96, 128, 96, 0, 0, &memory_mapping, &mut result);
+        assert_access_violation!(result, 96, 32);
     }
 
-    // Copy results back to caller
+    #[test]
+    fn test_syscall_address_encoding() {
+        let data = [0u8, 0, 1];
+        let base58_encoded = b"112";
+        let hrp = b"cosmos";
+        let bech32_data = (0..20).collect::<Vec<u8>>();
+        let bech32_encoded = b"cosmos1qqqsyqcyq5rqwzqfpg9scrgwpugpzysnrk363e";
+        let invalid_base58 = b"1I";
 
-    for (i, (account, account_ref)) in accounts.iter().zip(account_refs).enumerate() {
-        let account = account.borrow();
-        if message.is_writable(i) && !account.executable {
-            *account_ref.lamports = account.lamports;
-            *account_ref.owner = account.owner;
-            if account_ref.data.len() != account.data.len() {
-                *account_ref.ref_to_len_in_vm = account.data.len() as u64;
-                *account_ref.serialized_len_ptr = account.data.len() as u64;
-                if !account_ref.data.is_empty() {
-                    // Only support for `CreateAccount` at this time.
-                    // Need a way to limit total realloc size across multiple CPI calls
-                    return Err(
-                        SyscallError::InstructionError(InstructionError::InvalidRealloc).into(),
-                    );
-                }
-                if account.data.len() > account_ref.data.len() + MAX_PERMITTED_DATA_INCREASE {
-                    return Err(
-                        SyscallError::InstructionError(InstructionError::InvalidRealloc).into(),
-                    );
-                }
-            }
-            account_ref
-                .data
-                .clone_from_slice(&account.data[0..account_ref.data.len()]);
+        struct MockSlice {
+            pub addr: u64,
+            pub len: usize,
         }
-    }
+        let bech32_inputs = [
+            MockSlice {
+                addr: 12288,
+                len: hrp.len(),
+            },
+            MockSlice {
+                addr: 16384,
+                len: bech32_data.len(),
+            },
+        ];
+        let output = [0u8; 64];
+        let written = [0u64; 1];
+        let mut regions = vec![
+            MemoryRegion {
+                host_addr: bech32_inputs.as_ptr() as *const _ as u64,
+                vm_addr: 96,
+                len: (bech32_inputs.len() * size_of::<MockSlice>()) as u64,
+                vm_gap_shift: 63,
+                is_writable: false,
+            },
+            MemoryRegion {
+                host_addr: output.as_ptr() as *const _ as u64,
+                vm_addr: 28672,
+                len: output.len() as u64,
+                vm_gap_shift: 63,
+                is_writable: true,
+            },
+            MemoryRegion {
+                host_addr: written.as_ptr() as *const _ as u64,
+                vm_addr: 32768,
+                len: size_of::<u64>() as u64,
+                vm_gap_shift: 63,
+                is_writable: true,
+            },
+        ];
+        for (bytes, vm_addr) in &[
+            (&data[..], 4096),
+            (&base58_encoded[..], 8192),
+            (&hrp[..], 12288),
+            (&bech32_data[..], 16384),
+            (&bech32_encoded[..], 20480),
+            (&invalid_base58[..], 24576),
+        ] {
+            regions.push(MemoryRegion {
+                host_addr: bytes.as_ptr() as *const _ as u64,
+                vm_addr: *vm_addr,
+                len: bytes.len() as u64,
+                vm_gap_shift: 63,
+                is_writable: false,
+            });
+        }
+        let memory_mapping = MemoryMapping::new(regions, &DEFAULT_CONFIG);
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 1_000 }));
+        let loader_id = bpf_loader::id();
+        let output_of = |written: &[u64; 1]| output[..written[0] as usize].to_vec();
 
-    Ok(SUCCESS)
-}
+        let mut syscall = SyscallBase58Encode {
+            base_cost: 10,
+            byte_cost: 1,
+            compute_meter: compute_meter.clone(),
+            loader_id: &loader_id,
+        };
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(4096, 3, 28672, 64, 32768, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(output_of(&written), base58_encoded.to_vec());
+        assert_eq!(compute_meter.borrow().get_remaining(), 1_000 - 10 - 3);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use solana_rbpf::{memory_region::MemoryRegion, vm::Config};
-    use solana_sdk::{
-        bpf_loader,
-        hash::hashv,
-        process_instruction::{MockComputeMeter, MockLogger},
-    };
-    use std::str::FromStr;
+        // Output buffer too small
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(4096, 3, 28672, 2, 32768, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 1);
 
-    const DEFAULT_CONFIG: Config = Config {
-        max_call_depth: 20,
-        stack_frame_size: 4_096,
-        enable_instruction_meter: true,
-        enable_instruction_tracing: false,
-    };
+        // Input over the length cap is rejected before the per-byte cost is charged
+        let remaining = compute_meter.borrow().get_remaining();
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(
+            4096,
+            base58::MAX_BASE58_DECODED_LEN as u64 + 1,
+            28672,
+            64,
+            32768,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(result.unwrap(), 1);
+        assert_eq!(compute_meter.borrow().get_remaining(), remaining - 10);
 
-    macro_rules! assert_access_violation {
-        ($result:expr, $va:expr, $len:expr) => {
-            match $result {
-                Err(EbpfError::AccessViolation(_, _, va, len, _)) if $va == va && len == len => (),
-                _ => panic!(),
-            }
+        let mut syscall = SyscallBase58Decode {
+            base_cost: 10,
+            byte_cost: 1,
+            compute_meter: compute_meter.clone(),
+            loader_id: &loader_id,
         };
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(8192, 3, 28672, 64, 32768, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(output_of(&written), data.to_vec());
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(24576, 2, 28672, 64, 32768, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 1);
+
+        let mut syscall = SyscallBech32Encode {
+            base_cost: 10,
+            byte_cost: 1,
+            compute_meter: compute_meter.clone(),
+            loader_id: &loader_id,
+        };
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(96, 2, 28672, 64, 32768, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(output_of(&written), bech32_encoded.to_vec());
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(96, 1, 28672, 64, 32768, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 1);
+
+        let mut syscall = SyscallBech32Decode {
+            base_cost: 10,
+            byte_cost: 1,
+            compute_meter: compute_meter.clone(),
+            loader_id: &loader_id,
+        };
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(
+            20480,
+            bech32_encoded.len() as u64,
+            28672,
+            64,
+            32768,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(output_of(&written), bech32_data);
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(
+            20480,
+            bech32_encoded.len() as u64 - 1,
+            28672,
+            64,
+            32768,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(result.unwrap(), 1);
+
+        // Unwritable output
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(
+            20480,
+            bech32_encoded.len() as u64,
+            4096,
+            64,
+            32768,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_access_violation!(result, 4096, 20);
     }
 
     #[test]
-    fn test_translate() {
-        const START: u64 = 100;
-        const LENGTH: u64 = 1000;
-        let data = vec![0u8; LENGTH as usize];
-        let addr = data.as_ptr() as u64;
+    fn test_syscall_decode_length_prefix() {
+        let input = [0xffu8, 0xff, 0x03, 0x00];
+        let value = [0u64; 1];
+        let consumed = [0u64; 1];
         let memory_mapping = MemoryMapping::new(
-            vec![MemoryRegion::new_from_slice(&data, START, 0, false)],
+            vec![
+                MemoryRegion {
+                    host_addr: input.as_ptr() as *const _ as u64,
+                    vm_addr: 96,
+                    len: input.len() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: value.as_ptr() as *const _ as u64,
+                    vm_addr: 128,
+                    len: size_of::<u64>() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: true,
+                },
+                MemoryRegion {
+                    host_addr: consumed.as_ptr() as *const _ as u64,
+                    vm_addr: 160,
+                    len: size_of::<u64>() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: true,
+                },
+            ],
             &DEFAULT_CONFIG,
         );
-
-        let cases = vec![
-            (true, START, 0, addr),
-            (true, START, 1, addr),
-            (true, START, LENGTH, addr),
-            (true, START + 1, LENGTH - 1, addr + 1),
-            (false, START + 1, LENGTH, 0),
-            (true, START + LENGTH - 1, 1, addr + LENGTH - 1),
-            (true, START + LENGTH, 0, addr + LENGTH),
-            (false, START + LENGTH, 1, 0),
-            (false, START, LENGTH + 1, 0),
-            (false, 0, 0, 0),
-            (false, 0, 1, 0),
-            (false, START - 1, 0, 0),
-            (false, START - 1, 1, 0),
-            (true, START + LENGTH / 2, LENGTH / 2, addr + LENGTH / 2),
-        ];
-        for (ok, start, length, value) in cases {
-            if ok {
-                assert_eq!(
-                    translate(&memory_mapping, AccessType::Load, start, length,).unwrap(),
-                    value
-                )
-            } else {
-                assert!(translate(&memory_mapping, AccessType::Load, start, length,).is_err())
-            }
-        }
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 100 }));
+        let mut syscall = SyscallDecodeLengthPrefix {
+            cost: 10,
+            compute_meter: compute_meter.clone(),
+            loader_id: &bpf_loader::id(),
+        };
+
+        // Input longer than the prefix is not charged or read beyond the prefix
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(
+            96,
+            u64::MAX,
+            LengthPrefix::CompactU16 as u64,
+            128,
+            160,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!((value[0], consumed[0]), (0xffff, 3));
+        assert_eq!(compute_meter.borrow().get_remaining(), 90);
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(
+            96,
+            4,
+            LengthPrefix::BorshU32 as u64,
+            128,
+            160,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!((value[0], consumed[0]), (0x03ffff, 4));
+
+        // Truncated prefix
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(
+            96,
+            2,
+            LengthPrefix::CompactU16 as u64,
+            128,
+            160,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(result.unwrap(), 1);
+
+        // Unknown encoding
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(96, 4, 2, 128, 160, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 1);
+
+        // Aliased outputs
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(
+            96,
+            4,
+            LengthPrefix::CompactU16 as u64,
+            128,
+            128,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(result, Err(SyscallError::OverlappingMutTranslations.into()));
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(
+            96,
+            4,
+            LengthPrefix::CompactU16 as u64,
+            96,
+            160,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_access_violation!(result, 96, 8);
     }
 
     #[test]
-    fn test_translate_type() {
-        // Pubkey
-        let pubkey = solana_sdk::pubkey::new_rand();
-        let addr = &pubkey as *const _ as u64;
-        let memory_mapping = MemoryMapping::new(
-            vec![MemoryRegion {
-                host_addr: addr,
-                vm_addr: 100,
-                len: std::mem::size_of::<Pubkey>() as u64,
-                vm_gap_shift: 63,
-                is_writable: false,
-            }],
-            &DEFAULT_CONFIG,
-        );
-        let translated_pubkey =
-            translate_type::<Pubkey>(&memory_mapping, 100, &bpf_loader::id()).unwrap();
-        assert_eq!(pubkey, *translated_pubkey);
+    fn test_syscall_get_max_invoke_depth() {
+        let memory_mapping = MemoryMapping::new(vec![], &DEFAULT_CONFIG);
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 1 }));
+        let mut syscall = SyscallGetMaxInvokeDepth {
+            cost: 1,
+            compute_meter,
+            max_invoke_depth: 4,
+        };
 
-        // Instruction
-        let instruction = Instruction::new(
-            solana_sdk::pubkey::new_rand(),
-            &"foobar",
-            vec![AccountMeta::new(solana_sdk::pubkey::new_rand(), false)],
-        );
-        let addr = &instruction as *const _ as u64;
-        let mut memory_mapping = MemoryMapping::new(
-            vec![MemoryRegion {
-                host_addr: addr,
-                vm_addr: 96,
-                len: std::mem::size_of::<Instruction>() as u64,
-                vm_gap_shift: 63,
-                is_writable: false,
-            }],
-            &DEFAULT_CONFIG,
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(0, 0, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 4);
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(0, 0, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(
+            Err(EbpfError::UserError(BPFError::SyscallError(
+                SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
+            ))),
+            result
         );
-        let translated_instruction =
-            translate_type::<Instruction>(&memory_mapping, 96, &bpf_loader::id()).unwrap();
-        assert_eq!(instruction, *translated_instruction);
-        memory_mapping.resize_region::<BPFError>(0, 1).unwrap();
-        assert!(translate_type::<Instruction>(&memory_mapping, 100, &bpf_loader::id()).is_err());
     }
 
     #[test]
-    fn test_translate_slice() {
-        // zero len
-        let good_data = vec![1u8, 2, 3, 4, 5];
-        let data: Vec<u8> = vec![];
-        assert_eq!(0x1 as *const u8, data.as_ptr());
-        let addr = good_data.as_ptr() as *const _ as u64;
-        let memory_mapping = MemoryMapping::new(
-            vec![MemoryRegion {
-                host_addr: addr,
-                vm_addr: 100,
-                len: good_data.len() as u64,
-                vm_gap_shift: 63,
-                is_writable: false,
-            }],
-            &DEFAULT_CONFIG,
+    fn test_syscall_get_runtime_version() {
+        let memory_mapping = MemoryMapping::new(vec![], &DEFAULT_CONFIG);
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 1 }));
+        let runtime_version = RuntimeVersion::new(2, 1, 0);
+        let mut syscall = SyscallGetRuntimeVersion {
+            cost: 1,
+            compute_meter,
+            runtime_version: runtime_version.to_u64(),
+        };
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(0, 0, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(RuntimeVersion::from_u64(result.unwrap()), runtime_version);
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(0, 0, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(
+            Err(EbpfError::UserError(BPFError::SyscallError(
+                SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
+            ))),
+            result
         );
-        let translated_data =
-            translate_slice::<u8>(&memory_mapping, data.as_ptr() as u64, 0, &bpf_loader::id())
-                .unwrap();
-        assert_eq!(data, translated_data);
-        assert_eq!(0, translated_data.len());
+    }
 
-        // u8
-        let mut data = vec![1u8, 2, 3, 4, 5];
-        let addr = data.as_ptr() as *const _ as u64;
+    #[test]
+    fn test_syscall_get_transaction_signature() {
+        let signatures = vec![Signature::new(&[1; 64]), Signature::new(&[2; 64])];
+        let signature = [0u8; SIGNATURE_BYTES];
         let memory_mapping = MemoryMapping::new(
             vec![MemoryRegion {
-                host_addr: addr,
-                vm_addr: 100,
-                len: data.len() as u64,
+                host_addr: signature.as_ptr() as *const _ as u64,
+                vm_addr: 96,
+                len: SIGNATURE_BYTES as u64,
                 vm_gap_shift: 63,
-                is_writable: false,
+                is_writable: true,
             }],
             &DEFAULT_CONFIG,
         );
-        let translated_data =
-            translate_slice::<u8>(&memory_mapping, 100, data.len() as u64, &bpf_loader::id())
-                .unwrap();
-        assert_eq!(data, translated_data);
-        data[0] = 10;
-        assert_eq!(data, translated_data);
-        assert!(translate_slice::<u8>(
-            &memory_mapping,
-            data.as_ptr() as u64,
-            u64::MAX,
-            &bpf_loader::id()
-        )
-        .is_err());
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 300 }));
+        let mut syscall = SyscallGetTransactionSignature {
+            cost: 100,
+            compute_meter: compute_meter.clone(),
+            signatures: signatures.clone(),
+            loader_id: &bpf_loader::id(),
+        };
 
-        assert!(translate_slice::<u8>(
-            &memory_mapping,
-            100 - 1,
-            data.len() as u64,
-            &bpf_loader::id()
-        )
-        .is_err());
+        for (index, expected) in signatures.iter().enumerate() {
+            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+            syscall.call(index as u64, 96, 0, 0, 0, &memory_mapping, &mut result);
+            assert_eq!(result.unwrap(), 0);
+            assert_eq!(&signature[..], expected.as_ref());
+        }
 
-        // u64
-        let mut data = vec![1u64, 2, 3, 4, 5];
-        let addr = data.as_ptr() as *const _ as u64;
+        // Out of range indexes are charged but leave the output untouched
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(2, 96, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 1);
+        assert_eq!(&signature[..], signatures[1].as_ref());
+        assert_eq!(compute_meter.borrow().get_remaining(), 0);
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(0, 96, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(
+            Err(EbpfError::UserError(BPFError::SyscallError(
+                SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
+            ))),
+            result
+        );
+    }
+
+    #[test]
+    fn test_syscall_get_recent_blockhash() {
+        let recent_blockhash = hashv(&[b"recent blockhash"]);
+        let blockhash = Hash::default();
         let memory_mapping = MemoryMapping::new(
             vec![MemoryRegion {
-                host_addr: addr,
+                host_addr: &blockhash as *const _ as u64,
                 vm_addr: 96,
-                len: (data.len() * size_of::<u64>()) as u64,
+                len: HASH_BYTES as u64,
                 vm_gap_shift: 63,
-                is_writable: false,
+                is_writable: true,
             }],
             &DEFAULT_CONFIG,
         );
-        let translated_data =
-            translate_slice::<u64>(&memory_mapping, 96, data.len() as u64, &bpf_loader::id())
-                .unwrap();
-        assert_eq!(data, translated_data);
-        data[0] = 10;
-        assert_eq!(data, translated_data);
-        assert!(translate_slice::<u64>(&memory_mapping, 96, u64::MAX, &bpf_loader::id(),).is_err());
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 100 }));
+        let mut syscall = SyscallGetRecentBlockhash {
+            cost: 100,
+            compute_meter,
+            recent_blockhash,
+            loader_id: &bpf_loader::id(),
+        };
 
-        // Pubkeys
-        let mut data = vec![solana_sdk::pubkey::new_rand(); 5];
-        let addr = data.as_ptr() as *const _ as u64;
-        let memory_mapping = MemoryMapping::new(
-            vec![MemoryRegion {
-                host_addr: addr,
-                vm_addr: 100,
-                len: (data.len() * std::mem::size_of::<Pubkey>()) as u64,
-                vm_gap_shift: 63,
-                is_writable: false,
-            }],
-            &DEFAULT_CONFIG,
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(96, 0, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(blockhash, recent_blockhash);
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(96, 0, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(
+            Err(EbpfError::UserError(BPFError::SyscallError(
+                SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
+            ))),
+            result
         );
-        let translated_data =
-            translate_slice::<Pubkey>(&memory_mapping, 100, data.len() as u64, &bpf_loader::id())
-                .unwrap();
-        assert_eq!(data, translated_data);
-        data[0] = solana_sdk::pubkey::new_rand(); // Both should point to same place
-        assert_eq!(data, translated_data);
     }
 
     #[test]
-    fn test_translate_string_and_do() {
-        let string = "Gaggablaghblagh!";
-        let addr = string.as_ptr() as *const _ as u64;
+    fn test_syscall_get_fee_payer() {
+        let fee_payer = solana_sdk::pubkey::new_rand();
+        let address = Pubkey::default();
         let memory_mapping = MemoryMapping::new(
             vec![MemoryRegion {
-                host_addr: addr,
-                vm_addr: 100,
-                len: string.len() as u64,
+                host_addr: &address as *const _ as u64,
+                vm_addr: 96,
+                len: size_of::<Pubkey>() as u64,
                 vm_gap_shift: 63,
-                is_writable: false,
+                is_writable: true,
             }],
             &DEFAULT_CONFIG,
         );
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 200 }));
+        let mut syscall = SyscallGetFeePayer {
+            cost: 100,
+            compute_meter,
+            fee_payer,
+            loader_id: &bpf_loader::id(),
+        };
+
+        // Unmapped output
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(97, 0, 0, 0, 0, &memory_mapping, &mut result);
+        assert_access_violation!(result, 97, size_of::<Pubkey>() as u64);
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(96, 0, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(address, fee_payer);
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(96, 0, 0, 0, 0, &memory_mapping, &mut result);
         assert_eq!(
-            42,
-            translate_string_and_do(
-                &memory_mapping,
-                100,
-                string.len() as u64,
-                &bpf_loader::id(),
-                &mut |string: &str| {
-                    assert_eq!(string, "Gaggablaghblagh!");
-                    Ok(42)
-                }
-            )
-            .unwrap()
+            Err(EbpfError::UserError(BPFError::SyscallError(
+                SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
+            ))),
+            result
         );
     }
 
     #[test]
-    #[should_panic(expected = "UserError(SyscallError(Abort))")]
-    fn test_syscall_abort() {
-        let memory_mapping = MemoryMapping::new(vec![MemoryRegion::default()], &DEFAULT_CONFIG);
-        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
-        SyscallAbort::call(
-            &mut SyscallAbort {},
-            0,
-            0,
-            0,
-            0,
-            0,
-            &memory_mapping,
-            &mut result,
+    fn test_diff_syscall_environments() {
+        let all_enabled = FeatureSet::all_enabled();
+        assert!(diff_syscall_environments(&all_enabled, &all_enabled)
+            .unwrap()
+            .is_empty());
+
+        let mut feature_set = all_enabled.clone();
+        feature_set.active.remove(&sha256_syscall_enabled::id());
+        feature_set.active.remove(&max_program_call_depth_64::id());
+        let diff = diff_syscall_environments(&feature_set, &all_enabled).unwrap();
+        assert_eq!(
+            diff,
+            SyscallEnvironmentDiff {
+                removed_syscalls: vec![],
+                added_syscalls: vec!["sol_sha256"],
+                config: vec![("max_call_depth", 20, 64)],
+                program_size_cap: None,
+            }
         );
-        result.unwrap();
+
+        // Executables verified under the program size cap are reverified without it
+        let mut feature_set = all_enabled.clone();
+        feature_set
+            .active
+            .remove(&bpf_compute_budget_balancing::id());
+        let diff = diff_syscall_environments(&feature_set, &all_enabled).unwrap();
+        assert_eq!(diff.program_size_cap, Some((true, false)));
+        assert!(!diff.is_empty());
+
+        // Syscalls registered regardless of features are never reported
+        let diff = diff_syscall_environments(&all_enabled, &FeatureSet::default()).unwrap();
+        assert!(diff.removed_syscalls.contains(&"sol_sha256"));
+        assert!(!diff.removed_syscalls.contains(&"sol_log_"));
+        assert!(diff.added_syscalls.is_empty());
     }
 
     #[test]
-    #[should_panic(expected = "UserError(SyscallError(Panic(\"Gaggablaghblagh!\", 42, 84)))")]
-    fn test_syscall_sol_panic() {
-        let string = "Gaggablaghblagh!";
-        let addr = string.as_ptr() as *const _ as u64;
+    fn test_syscall_get_runtime_limits() {
+        let limits = RuntimeLimits {
+            max_invoke_depth: 4,
+            max_call_depth: 64,
+            stack_frame_size: 4_096,
+            heap_size: 32 * 1024,
+            max_signers: MAX_SIGNERS as u64,
+            max_permitted_data_increase: MAX_PERMITTED_DATA_INCREASE as u64,
+        };
+        let output = RuntimeLimits::default();
         let memory_mapping = MemoryMapping::new(
             vec![MemoryRegion {
-                host_addr: addr,
-                vm_addr: 100,
-                len: string.len() as u64,
+                host_addr: &output as *const _ as u64,
+                vm_addr: 96,
+                len: size_of::<RuntimeLimits>() as u64,
                 vm_gap_shift: 63,
-                is_writable: false,
+                is_writable: true,
             }],
             &DEFAULT_CONFIG,
         );
-        let mut syscall_panic = SyscallPanic {
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 200 }));
+        let mut syscall = SyscallGetRuntimeLimits {
+            cost: 100,
+            compute_meter,
+            limits,
             loader_id: &bpf_loader::id(),
         };
+
+        // Unmapped output
         let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
-        syscall_panic.call(
-            100,
-            string.len() as u64,
-            42,
-            84,
-            0,
-            &memory_mapping,
-            &mut result,
+        syscall.call(97, 0, 0, 0, 0, &memory_mapping, &mut result);
+        assert_access_violation!(result, 97, size_of::<RuntimeLimits>() as u64);
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(96, 0, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(output, limits);
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(96, 0, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(
+            Err(EbpfError::UserError(BPFError::SyscallError(
+                SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
+            ))),
+            result
         );
-        result.unwrap();
     }
 
     #[test]
-    fn test_syscall_sol_log() {
-        let string = "Gaggablaghblagh!";
-        let addr = string.as_ptr() as *const _ as u64;
-
-        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
-            Rc::new(RefCell::new(MockComputeMeter { remaining: 3 }));
-        let log = Rc::new(RefCell::new(vec![]));
-        let logger: Rc<RefCell<dyn Logger>> =
-            Rc::new(RefCell::new(MockLogger { log: log.clone() }));
-        let mut syscall_sol_log = SyscallLog {
-            cost: 1,
-            compute_meter,
-            logger,
-            loader_id: &bpf_loader::id(),
-        };
+    fn test_syscall_get_pseudorandom() {
+        let recent_blockhash = hashv(&[b"blockhash"]);
+        let program_id = solana_sdk::pubkey::new_rand();
+        let bytes = [0u8; 40];
         let memory_mapping = MemoryMapping::new(
             vec![MemoryRegion {
-                host_addr: addr,
-                vm_addr: 100,
-                len: string.len() as u64,
+                host_addr: bytes.as_ptr() as u64,
+                vm_addr: 96,
+                len: bytes.len() as u64,
                 vm_gap_shift: 63,
-                is_writable: false,
+                is_writable: true,
             }],
             &DEFAULT_CONFIG,
         );
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter {
+                remaining: 4 * (100 + 40) + 100,
+            }));
+        let counter = Rc::new(RefCell::new(0));
+        let mut syscall = SyscallGetPseudorandom {
+            base_cost: 100,
+            byte_cost: 1,
+            compute_meter: compute_meter.clone(),
+            recent_blockhash,
+            program_id,
+            counter: counter.clone(),
+            loader_id: &bpf_loader::id(),
+        };
 
         let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
-        syscall_sol_log.call(
-            100,
-            string.len() as u64,
-            0,
-            0,
-            0,
-            &memory_mapping,
-            &mut result,
-        );
-        result.unwrap();
-        assert_eq!(log.borrow().len(), 1);
-        assert_eq!(log.borrow()[0], "Program log: Gaggablaghblagh!");
+        syscall.call(96, 40, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 0);
+        let mut expected = [0u8; 40];
+        derive_pseudorandom(&recent_blockhash, &program_id, 0, &mut expected);
+        assert_eq!(bytes, expected);
 
+        // Each call advances the counter
         let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
-        syscall_sol_log.call(
-            101, // AccessViolation
-            string.len() as u64,
-            0,
-            0,
-            0,
-            &memory_mapping,
-            &mut result,
-        );
-        assert_access_violation!(result, 101, string.len() as u64);
+        syscall.call(96, 40, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 0);
+        derive_pseudorandom(&recent_blockhash, &program_id, 1, &mut expected);
+        assert_eq!(bytes, expected);
+
+        // A later invocation of the program in the same transaction continues the sequence
+        let mut syscall = SyscallGetPseudorandom {
+            base_cost: 100,
+            byte_cost: 1,
+            compute_meter,
+            recent_blockhash,
+            program_id,
+            counter: counter.clone(),
+            loader_id: &bpf_loader::id(),
+        };
         let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
-        syscall_sol_log.call(
-            100,
-            string.len() as u64 * 2, // AccessViolation
-            0,
-            0,
-            0,
-            &memory_mapping,
-            &mut result,
-        );
-        assert_access_violation!(result, 100, string.len() as u64 * 2);
+        syscall.call(96, 40, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 0);
+        derive_pseudorandom(&recent_blockhash, &program_id, 2, &mut expected);
+        assert_eq!(bytes, expected);
+        assert_eq!(*counter.borrow(), 3);
+
+        // Unmapped output
         let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
-        syscall_sol_log.call(
-            100,
-            string.len() as u64,
-            0,
-            0,
-            0,
-            &memory_mapping,
-            &mut result,
+        syscall.call(97, 40, 0, 0, 0, &memory_mapping, &mut result);
+        assert_access_violation!(result, 97, 40);
+        assert_eq!(*counter.borrow(), 3);
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(96, 40, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(
+            Err(EbpfError::UserError(BPFError::SyscallError(
+                SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
+            ))),
+            result
+        );
+    }
+
+    #[test]
+    fn test_syscall_get_instruction_ancestry() {
+        let ancestry = vec![
+            solana_sdk::pubkey::new_rand(),
+            solana_sdk::pubkey::new_rand(),
+            solana_sdk::pubkey::new_rand(),
+        ];
+        let program_ids = [Pubkey::default(); 4];
+        let stack_height = [0u64; 1];
+        let memory_mapping = MemoryMapping::new(
+            vec![
+                MemoryRegion {
+                    host_addr: program_ids.as_ptr() as *const _ as u64,
+                    vm_addr: 96,
+                    len: (program_ids.len() * size_of::<Pubkey>()) as u64,
+                    vm_gap_shift: 63,
+                    is_writable: true,
+                },
+                MemoryRegion {
+                    host_addr: stack_height.as_ptr() as *const _ as u64,
+                    vm_addr: 256,
+                    len: size_of::<u64>() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: true,
+                },
+            ],
+            &DEFAULT_CONFIG,
         );
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 300 }));
+        let mut syscall = SyscallGetInstructionAncestry {
+            cost: 100,
+            compute_meter: compute_meter.clone(),
+            program_ids: ancestry.clone(),
+            loader_id: &bpf_loader::id(),
+        };
+
+        // Only the stack height is written when there is no room for program IDs
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(96, 0, 256, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(stack_height[0], 3);
+        assert_eq!(program_ids, [Pubkey::default(); 4]);
+
+        // Truncated to the outermost program IDs
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(96, 2, 256, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(&program_ids[..2], &ancestry[..2]);
+        assert_eq!(program_ids[2], Pubkey::default());
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(96, 4, 256, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(&program_ids[..3], &ancestry[..]);
+        assert_eq!(program_ids[3], Pubkey::default());
+        assert_eq!(compute_meter.borrow().get_remaining(), 0);
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(96, 4, 256, 0, 0, &memory_mapping, &mut result);
         assert_eq!(
             Err(EbpfError::UserError(BPFError::SyscallError(
                 SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
@@ -1717,288 +6516,1207 @@ mod tests {
     }
 
     #[test]
-    fn test_syscall_sol_log_u64() {
-        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
-            Rc::new(RefCell::new(MockComputeMeter {
-                remaining: std::u64::MAX,
-            }));
-        let log = Rc::new(RefCell::new(vec![]));
-        let logger: Rc<RefCell<dyn Logger>> =
-            Rc::new(RefCell::new(MockLogger { log: log.clone() }));
-        let mut syscall_sol_log_u64 = SyscallLogU64 {
-            cost: 0,
-            compute_meter,
-            logger,
-        };
-        let memory_mapping = MemoryMapping::new(vec![], &DEFAULT_CONFIG);
+    fn test_syscall_tracer() {
+        let memory_mapping = MemoryMapping::new(vec![], &DEFAULT_CONFIG);
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 7 }));
+        let recorder = Arc::new(SyscallTraceRecorder::default());
+        let new_syscall = |trace_collector, compute_profiler| SyscallTracer {
+            name: "sol_get_max_invoke_depth",
+            syscall: SyscallGetMaxInvokeDepth {
+                cost: 3,
+                compute_meter: compute_meter.clone(),
+                max_invoke_depth: 4,
+            },
+            enabled: true,
+            faults: None,
+            calls: 0,
+            compute_meter: compute_meter.clone(),
+            trace_collector,
+            compute_profiler,
+        };
+
+        // Untraced calls are not recorded
+        let mut syscall = new_syscall(None, None);
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(1, 2, 3, 4, 5, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 4);
+        assert!(recorder.take_events().is_empty());
+
+        let mut syscall = new_syscall(Some(recorder.clone()), None);
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(1, 2, 3, 4, 5, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 4);
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(6, 7, 8, 9, 10, &memory_mapping, &mut result);
+        assert!(result.is_err());
+
+        assert_eq!(
+            recorder.take_events(),
+            vec![
+                SyscallTraceEvent {
+                    name: "sol_get_max_invoke_depth".to_string(),
+                    args: [1, 2, 3, 4, 5],
+                    compute_units_consumed: 3,
+                    result: Ok(4),
+                },
+                SyscallTraceEvent {
+                    name: "sol_get_max_invoke_depth".to_string(),
+                    args: [6, 7, 8, 9, 10],
+                    compute_units_consumed: 1,
+                    result: Err(EbpfError::<BPFError>::UserError(BPFError::SyscallError(
+                        SyscallError::InstructionError(
+                            InstructionError::ComputationalBudgetExceeded
+                        )
+                    ))
+                    .to_string()),
+                },
+            ]
+        );
+
+        // Profiled calls are accumulated by syscall name
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 7 }));
+        let profiler = Arc::new(ComputeProfiler::default());
+        let invocation_profiler = Rc::new(RefCell::new(InvocationProfiler::new(profiler.clone())));
+        let mut syscall = SyscallTracer {
+            name: "sol_get_max_invoke_depth",
+            syscall: SyscallGetMaxInvokeDepth {
+                cost: 3,
+                compute_meter: compute_meter.clone(),
+                max_invoke_depth: 4,
+            },
+            enabled: true,
+            faults: None,
+            calls: 0,
+            compute_meter,
+            trace_collector: None,
+            compute_profiler: Some(invocation_profiler),
+        };
+        for _ in 0..2 {
+            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+            syscall.call(1, 2, 3, 4, 5, &memory_mapping, &mut result);
+            assert_eq!(result.unwrap(), 4);
+        }
+        assert_eq!(
+            profiler.report().syscalls["sol_get_max_invoke_depth"],
+            SyscallProfile {
+                invocations: 2,
+                compute_units_consumed: 6,
+            }
+        );
+    }
+
+    /// Stands in for `sol_invoke_signed_*`, charging `cost` and then invoking a
+    /// program that consumes `callee_cost`
+    struct MockInvoke {
+        cost: u64,
+        callee_cost: u64,
+        compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+        invocation_profiler: Rc<RefCell<InvocationProfiler>>,
+    }
+    impl SyscallObject<BPFError> for MockInvoke {
+        fn call(
+            &mut self,
+            _arg1: u64,
+            _arg2: u64,
+            _arg3: u64,
+            _arg4: u64,
+            _arg5: u64,
+            _memory_mapping: &MemoryMapping,
+            result: &mut Result<u64, EbpfError<BPFError>>,
+        ) {
+            question_mark!(self.compute_meter.consume(self.cost), result);
+            let remaining = self.compute_meter.borrow().get_remaining();
+            self.invocation_profiler
+                .borrow_mut()
+                .enter_frame(&Pubkey::default(), remaining);
+            question_mark!(self.compute_meter.consume(self.callee_cost), result);
+            let remaining = self.compute_meter.borrow().get_remaining();
+            self.invocation_profiler.borrow_mut().exit_frame(remaining);
+            *result = Ok(0);
+        }
+    }
+
+    #[test]
+    fn test_syscall_tracer_profiles_invocations_exclusively() {
+        let memory_mapping = MemoryMapping::new(vec![], &DEFAULT_CONFIG);
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 1_000 }));
+        let profiler = Arc::new(ComputeProfiler::default());
+        let invocation_profiler = Rc::new(RefCell::new(InvocationProfiler::new(profiler.clone())));
+        let caller_program_id = Pubkey::new_unique();
+        invocation_profiler
+            .borrow_mut()
+            .enter_frame(&caller_program_id, 1_000);
+
+        let mut syscall = SyscallTracer {
+            name: "sol_invoke_signed_rust",
+            syscall: MockInvoke {
+                cost: 100,
+                callee_cost: 250,
+                compute_meter: compute_meter.clone(),
+                invocation_profiler: invocation_profiler.clone(),
+            },
+            enabled: true,
+            faults: None,
+            calls: 0,
+            compute_meter: compute_meter.clone(),
+            trace_collector: None,
+            compute_profiler: Some(invocation_profiler.clone()),
+        };
+        for _ in 0..2 {
+            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+            syscall.call(1, 2, 3, 4, 5, &memory_mapping, &mut result);
+            assert_eq!(result.unwrap(), 0);
+        }
+        let remaining = compute_meter.borrow().get_remaining();
+        invocation_profiler.borrow_mut().exit_frame(remaining);
+
+        // The callees' consumption is attributed to their frames, not to the syscall
+        let report = profiler.report();
+        assert_eq!(
+            report.syscalls["sol_invoke_signed_rust"],
+            SyscallProfile {
+                invocations: 2,
+                compute_units_consumed: 200,
+            }
+        );
+        assert_eq!(report.frames.len(), 3);
+        assert_eq!(report.frames[0].compute_units_consumed, 700);
+        assert_eq!(report.frames[0].exclusive_compute_units_consumed, 200);
+        assert_eq!(report.frames[1].compute_units_consumed, 250);
+        assert_eq!(report.frames[2].compute_units_consumed, 250);
+    }
+
+    #[test]
+    fn test_syscall_tracer_disabled() {
+        let memory_mapping = MemoryMapping::new(vec![], &DEFAULT_CONFIG);
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 7 }));
+        let recorder = Arc::new(SyscallTraceRecorder::default());
+        let new_tracing = |syscall_filter| SyscallTracing {
+            compute_meter: compute_meter.clone(),
+            trace_collector: Some(recorder.clone()),
+            compute_profiler: None,
+            syscall_filter,
+            syscall_faults: None,
+        };
+        let new_syscall = || SyscallGetMaxInvokeDepth {
+            cost: 3,
+            compute_meter: compute_meter.clone(),
+            max_invoke_depth: 4,
+        };
+
+        // Without a filter every syscall is enabled
+        let tracing = new_tracing(None);
+        let mut syscall = tracing.trace("sol_get_max_invoke_depth", new_syscall());
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(1, 2, 3, 4, 5, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 4);
+        assert_eq!(compute_meter.borrow().get_remaining(), 4);
+
+        // Disabled syscalls fail without consuming compute but are still traced
+        let mut names = HashSet::new();
+        names.insert("sol_get_max_invoke_depth".to_string());
+        for syscall_filter in vec![
+            SyscallFilter::Allow(HashSet::new()),
+            SyscallFilter::Deny(names.clone()),
+        ] {
+            recorder.take_events();
+            let tracing = new_tracing(Some(Arc::new(syscall_filter)));
+            let mut syscall = tracing.trace("sol_get_max_invoke_depth", new_syscall());
+            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+            syscall.call(1, 2, 3, 4, 5, &memory_mapping, &mut result);
+            assert_eq!(
+                result.unwrap_err().to_string(),
+                EbpfError::<BPFError>::UserError(BPFError::SyscallError(
+                    SyscallError::SyscallDisabled("sol_get_max_invoke_depth")
+                ))
+                .to_string()
+            );
+            assert_eq!(compute_meter.borrow().get_remaining(), 4);
+            let events = recorder.take_events();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].compute_units_consumed, 0);
+        }
+
+        let tracing = new_tracing(Some(Arc::new(SyscallFilter::Allow(names))));
+        let mut syscall = tracing.trace("sol_get_max_invoke_depth", new_syscall());
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(1, 2, 3, 4, 5, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 4);
+        assert_eq!(compute_meter.borrow().get_remaining(), 1);
+    }
+
+    #[test]
+    fn test_syscall_tracer_faults() {
+        let memory_mapping = MemoryMapping::new(vec![], &DEFAULT_CONFIG);
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 100 }));
+        let mut faults = HashMap::new();
+        faults.insert(2, SyscallFault::Return(7));
+        faults.insert(3, SyscallFault::Fail);
+        faults.insert(4, SyscallFault::ConsumeComputeUnits(50));
+        let mut syscall_faults = SyscallFaults::new();
+        syscall_faults.insert("sol_get_max_invoke_depth".to_string(), faults);
+        let tracing = SyscallTracing {
+            compute_meter: compute_meter.clone(),
+            trace_collector: None,
+            compute_profiler: None,
+            syscall_filter: None,
+            syscall_faults: Some(Arc::new(syscall_faults)),
+        };
+        let mut syscall = tracing.trace(
+            "sol_get_max_invoke_depth",
+            SyscallGetMaxInvokeDepth {
+                cost: 3,
+                compute_meter: compute_meter.clone(),
+                max_invoke_depth: 4,
+            },
+        );
+        let mut call = || {
+            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+            syscall.call(1, 2, 3, 4, 5, &memory_mapping, &mut result);
+            result
+        };
+
+        // Calls without a fault run the syscall
+        assert_eq!(call().unwrap(), 4);
+        assert_eq!(compute_meter.borrow().get_remaining(), 97);
+
+        // Injected return values and failures skip the syscall
+        assert_eq!(call().unwrap(), 7);
+        assert_eq!(compute_meter.borrow().get_remaining(), 97);
+        assert_eq!(
+            call().unwrap_err().to_string(),
+            EbpfError::<BPFError>::UserError(BPFError::SyscallError(SyscallError::InjectedFault(
+                "sol_get_max_invoke_depth"
+            )))
+            .to_string()
+        );
+        assert_eq!(compute_meter.borrow().get_remaining(), 97);
+
+        // Injected compute is consumed on top of the syscall's own cost
+        assert_eq!(call().unwrap(), 4);
+        assert_eq!(compute_meter.borrow().get_remaining(), 44);
+        assert_eq!(call().unwrap(), 4);
+        assert_eq!(compute_meter.borrow().get_remaining(), 41);
+
+        // Other syscalls are unaffected
+        let mut syscall = tracing.trace(
+            "sol_get_max_call_depth",
+            SyscallGetMaxInvokeDepth {
+                cost: 3,
+                compute_meter: compute_meter.clone(),
+                max_invoke_depth: 4,
+            },
+        );
+        for _ in 0..4 {
+            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+            syscall.call(1, 2, 3, 4, 5, &memory_mapping, &mut result);
+            assert_eq!(result.unwrap(), 4);
+        }
+        assert_eq!(compute_meter.borrow().get_remaining(), 29);
+    }
+
+    #[test]
+    fn test_syscalls_charge_before_work() {
+        // Every metered syscall must consume compute before translating memory, logging or
+        // hashing. With no budget left and nothing mapped, charging first yields
+        // `ComputationalBudgetExceeded` where working first would yield an access violation
+        // or a log entry.
+        let memory_mapping = MemoryMapping::new(vec![], &DEFAULT_CONFIG);
+        let log = Rc::new(RefCell::new(vec![]));
+        let logger: Rc<RefCell<dyn Logger>> =
+            Rc::new(RefCell::new(MockLogger { log: log.clone() }));
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 0 }));
+        let loader_id = bpf_loader::id();
+        let mut syscalls: Vec<(&str, Box<dyn SyscallObject<BPFError>>)> = vec![
+            (
+                "sol_log_",
+                Box::new(SyscallLog {
+                    cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    logger: logger.clone(),
+                    program_id: Pubkey::default(),
+                    stack_height: 1,
+                    loader_id: &loader_id,
+                }),
+            ),
+            (
+                "sol_log_64_",
+                Box::new(SyscallLogU64 {
+                    cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    logger: logger.clone(),
+                    program_id: Pubkey::default(),
+                    stack_height: 1,
+                }),
+            ),
+            (
+                "sol_log_compute_units_",
+                Box::new(SyscallLogBpfComputeUnits {
+                    cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    logger: logger.clone(),
+                    program_id: Pubkey::default(),
+                    stack_height: 1,
+                }),
+            ),
+            (
+                "sol_log_pubkey",
+                Box::new(SyscallLogPubkey {
+                    cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    logger: logger.clone(),
+                    program_id: Pubkey::default(),
+                    stack_height: 1,
+                    loader_id: &loader_id,
+                }),
+            ),
+            (
+                "sol_log_data",
+                Box::new(SyscallLogData {
+                    cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    logger: logger.clone(),
+                    program_id: Pubkey::default(),
+                    stack_height: 1,
+                    loader_id: &loader_id,
+                }),
+            ),
+            (
+                "sol_sha256",
+                Box::new(SyscallSha256 {
+                    sha256_base_cost: 1,
+                    sha256_byte_cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    loader_id: &loader_id,
+                    reject_overlapping_buffers: true,
+                }),
+            ),
+            (
+                "sol_ristretto_mul",
+                Box::new(SyscallRistrettoMul {
+                    cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    loader_id: &loader_id,
+                    reject_overlapping_buffers: true,
+                }),
+            ),
+            (
+                "sol_get_max_invoke_depth",
+                Box::new(SyscallGetMaxInvokeDepth {
+                    cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    max_invoke_depth: 4,
+                }),
+            ),
+            (
+                "sol_get_runtime_version",
+                Box::new(SyscallGetRuntimeVersion {
+                    cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    runtime_version: RUNTIME_VERSION.to_u64(),
+                }),
+            ),
+            (
+                "sol_get_instruction_ancestry",
+                Box::new(SyscallGetInstructionAncestry {
+                    cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    program_ids: vec![Pubkey::default()],
+                    loader_id: &loader_id,
+                }),
+            ),
+            (
+                "sol_get_transaction_signature",
+                Box::new(SyscallGetTransactionSignature {
+                    cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    signatures: vec![Signature::default()],
+                    loader_id: &loader_id,
+                }),
+            ),
+            (
+                "sol_get_recent_blockhash_of_tx",
+                Box::new(SyscallGetRecentBlockhash {
+                    cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    recent_blockhash: Hash::default(),
+                    loader_id: &loader_id,
+                }),
+            ),
+            (
+                "sol_get_fee_payer",
+                Box::new(SyscallGetFeePayer {
+                    cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    fee_payer: Pubkey::default(),
+                    loader_id: &loader_id,
+                }),
+            ),
+            (
+                "sol_get_runtime_limits",
+                Box::new(SyscallGetRuntimeLimits {
+                    cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    limits: RuntimeLimits::default(),
+                    loader_id: &loader_id,
+                }),
+            ),
+            (
+                "sol_get_pseudorandom",
+                Box::new(SyscallGetPseudorandom {
+                    base_cost: 1,
+                    byte_cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    recent_blockhash: Hash::default(),
+                    program_id: Pubkey::default(),
+                    counter: Rc::new(RefCell::new(0)),
+                    loader_id: &loader_id,
+                }),
+            ),
+            (
+                "sol_create_program_addresses_batch",
+                Box::new(SyscallCreateProgramAddressesBatch {
+                    base_cost: 1,
+                    address_cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    loader_id: &loader_id,
+                    reject_overlapping_buffers: true,
+                }),
+            ),
+            (
+                "sol_create_program_address",
+                Box::new(SyscallCreateProgramAddress {
+                    cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    loader_id: &loader_id,
+                    reject_overlapping_buffers: true,
+                }),
+            ),
+            (
+                "sol_hmac_sha256",
+                Box::new(SyscallHmacSha256 {
+                    base_cost: 1,
+                    byte_cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    loader_id: &loader_id,
+                    reject_overlapping_buffers: true,
+                }),
+            ),
+            (
+                "sol_sha256_init",
+                Box::new(SyscallSha256Init {
+                    cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    contexts: Rc::new(RefCell::new(Sha256Contexts::default())),
+                }),
+            ),
+            (
+                "sol_sha256_update",
+                Box::new(SyscallSha256Update {
+                    base_cost: 1,
+                    byte_cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    contexts: Rc::new(RefCell::new(Sha256Contexts::default())),
+                    loader_id: &loader_id,
+                }),
+            ),
+            (
+                "sol_sha256_final",
+                Box::new(SyscallSha256Final {
+                    cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    contexts: Rc::new(RefCell::new(Sha256Contexts::default())),
+                    loader_id: &loader_id,
+                }),
+            ),
+            (
+                "sol_keccak256_init",
+                Box::new(SyscallKeccak256Init {
+                    cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    contexts: Rc::new(RefCell::new(Keccak256Contexts::default())),
+                }),
+            ),
+            (
+                "sol_keccak256_update",
+                Box::new(SyscallKeccak256Update {
+                    base_cost: 1,
+                    byte_cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    contexts: Rc::new(RefCell::new(Keccak256Contexts::default())),
+                    loader_id: &loader_id,
+                }),
+            ),
+            (
+                "sol_keccak256_final",
+                Box::new(SyscallKeccak256Final {
+                    cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    contexts: Rc::new(RefCell::new(Keccak256Contexts::default())),
+                    loader_id: &loader_id,
+                }),
+            ),
+            (
+                "sol_chacha20_poly1305_open",
+                Box::new(SyscallChaCha20Poly1305Open {
+                    base_cost: 1,
+                    byte_cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    loader_id: &loader_id,
+                    reject_overlapping_buffers: true,
+                }),
+            ),
+            (
+                "sol_secp256r1_verify",
+                Box::new(SyscallSecp256r1Verify {
+                    cost: 1,
+                    sha256_byte_cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    loader_id: &loader_id,
+                }),
+            ),
+            (
+                "sol_x25519_dh",
+                Box::new(SyscallX25519Dh {
+                    cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    loader_id: &loader_id,
+                    reject_overlapping_buffers: true,
+                }),
+            ),
+            (
+                "sol_base58_encode",
+                Box::new(SyscallBase58Encode {
+                    base_cost: 1,
+                    byte_cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    loader_id: &loader_id,
+                }),
+            ),
+            (
+                "sol_base58_decode",
+                Box::new(SyscallBase58Decode {
+                    base_cost: 1,
+                    byte_cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    loader_id: &loader_id,
+                }),
+            ),
+            (
+                "sol_bech32_encode",
+                Box::new(SyscallBech32Encode {
+                    base_cost: 1,
+                    byte_cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    loader_id: &loader_id,
+                }),
+            ),
+            (
+                "sol_bech32_decode",
+                Box::new(SyscallBech32Decode {
+                    base_cost: 1,
+                    byte_cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    loader_id: &loader_id,
+                }),
+            ),
+            (
+                "sol_decode_length_prefix",
+                Box::new(SyscallDecodeLengthPrefix {
+                    cost: 1,
+                    compute_meter: compute_meter.clone(),
+                    loader_id: &loader_id,
+                }),
+            ),
+        ];
+
+        for (name, syscall) in syscalls.iter_mut() {
+            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+            syscall.call(
+                MM_INPUT_START,
+                1,
+                MM_INPUT_START,
+                MM_INPUT_START,
+                1,
+                &memory_mapping,
+                &mut result,
+            );
+            assert_eq!(
+                result,
+                Err(EbpfError::UserError(BPFError::SyscallError(
+                    SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
+                ))),
+                "{}",
+                name
+            );
+            assert!(log.borrow().is_empty(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_syscall_zero_length_inputs() {
+        const DESCRIPTOR_VA: u64 = 0x1000;
+        const PROGRAM_ID_VA: u64 = 0x2000;
+        const OUTPUT_VA: u64 = 0x3000;
+        let program_id = Pubkey::new(&[3; 32]);
+        let empty_hash = hashv(&[]).to_bytes();
+        let empty_address = Pubkey::create_program_address(&[], &program_id);
 
-        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
-        syscall_sol_log_u64.call(1, 2, 3, 4, 5, &memory_mapping, &mut result);
-        result.unwrap();
+        for loader_id in &[bpf_loader::id(), bpf_loader_deprecated::id()] {
+            for (name, addr) in &[
+                ("null", 0),
+                ("dangling", 1),
+                ("region end", OUTPUT_VA + HASH_BYTES as u64),
+                ("out of range", u64::MAX),
+            ] {
+                let descriptor = [*addr, 0];
+                let mut output = [0xffu8; HASH_BYTES];
+                let memory_mapping = MemoryMapping::new(
+                    vec![
+                        MemoryRegion {
+                            host_addr: descriptor.as_ptr() as *const _ as u64,
+                            vm_addr: DESCRIPTOR_VA,
+                            len: size_of::<[u64; 2]>() as u64,
+                            vm_gap_shift: 63,
+                            is_writable: false,
+                        },
+                        MemoryRegion {
+                            host_addr: program_id.as_ref().as_ptr() as *const _ as u64,
+                            vm_addr: PROGRAM_ID_VA,
+                            len: size_of::<Pubkey>() as u64,
+                            vm_gap_shift: 63,
+                            is_writable: false,
+                        },
+                        MemoryRegion {
+                            host_addr: output.as_mut_ptr() as *const _ as u64,
+                            vm_addr: OUTPUT_VA,
+                            len: HASH_BYTES as u64,
+                            vm_gap_shift: 63,
+                            is_writable: true,
+                        },
+                    ],
+                    &DEFAULT_CONFIG,
+                );
+                let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+                    Rc::new(RefCell::new(MockComputeMeter { remaining: 0 }));
 
-        assert_eq!(log.borrow().len(), 1);
-        assert_eq!(log.borrow()[0], "Program log: 0x1, 0x2, 0x3, 0x4, 0x5");
+                // Empty log message
+                let log = Rc::new(RefCell::new(vec![]));
+                let mut syscall = SyscallLog {
+                    cost: 0,
+                    compute_meter: compute_meter.clone(),
+                    logger: Rc::new(RefCell::new(MockLogger { log: log.clone() })),
+                    program_id: Pubkey::default(),
+                    stack_height: 1,
+                    loader_id,
+                };
+                let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+                syscall.call(*addr, 0, 0, 0, 0, &memory_mapping, &mut result);
+                assert_eq!(result.unwrap(), 0, "sol_log_ {}", name);
+                assert_eq!(log.borrow().len(), 1);
+
+                // No slices to hash
+                let mut syscall = SyscallSha256 {
+                    sha256_base_cost: 0,
+                    sha256_byte_cost: 0,
+                    compute_meter: compute_meter.clone(),
+                    loader_id,
+                    reject_overlapping_buffers: true,
+                };
+                let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+                syscall.call(*addr, 0, OUTPUT_VA, 0, 0, &memory_mapping, &mut result);
+                assert_eq!(result.unwrap(), 0, "sol_sha256 vals {}", name);
+                assert_eq!(output, empty_hash);
+
+                // One empty slice to hash
+                output = [0xff; HASH_BYTES];
+                let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+                syscall.call(
+                    DESCRIPTOR_VA,
+                    1,
+                    OUTPUT_VA,
+                    0,
+                    0,
+                    &memory_mapping,
+                    &mut result,
+                );
+                assert_eq!(result.unwrap(), 0, "sol_sha256 val {}", name);
+                assert_eq!(output, empty_hash);
+
+                // No seeds
+                let mut syscall = SyscallCreateProgramAddress {
+                    cost: 0,
+                    compute_meter: compute_meter.clone(),
+                    loader_id,
+                    reject_overlapping_buffers: true,
+                };
+                let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+                syscall.call(
+                    *addr,
+                    0,
+                    PROGRAM_ID_VA,
+                    OUTPUT_VA,
+                    0,
+                    &memory_mapping,
+                    &mut result,
+                );
+                match &empty_address {
+                    Ok(address) => {
+                        assert_eq!(result.unwrap(), 0, "sol_create_program_address {}", name);
+                        assert_eq!(&output[..32], address.as_ref());
+                    }
+                    Err(_) => assert_eq!(result.unwrap(), 1),
+                }
+            }
+        }
     }
 
     #[test]
-    fn test_syscall_sol_pubkey() {
-        let pubkey = Pubkey::from_str("MoqiU1vryuCGQSxFKA1SZ316JdLEFFhoAu6cKUNk7dN").unwrap();
-        let addr = &pubkey.as_ref()[0] as *const _ as u64;
+    fn test_syscall_sha256_overlapping_output() {
+        let bytes: Vec<u8> = (0..32).collect();
+        let descriptor = [
+            (MM_INPUT_START + 16).to_le_bytes(),
+            (bytes.len() as u64).to_le_bytes(),
+        ]
+        .concat();
+        let expected = hashv(&[&bytes]).to_bytes();
 
-        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
-            Rc::new(RefCell::new(MockComputeMeter { remaining: 2 }));
-        let log = Rc::new(RefCell::new(vec![]));
-        let logger: Rc<RefCell<dyn Logger>> =
-            Rc::new(RefCell::new(MockLogger { log: log.clone() }));
-        let mut syscall_sol_pubkey = SyscallLogPubkey {
-            cost: 1,
-            compute_meter,
-            logger,
-            loader_id: &bpf_loader::id(),
-        };
-        let memory_mapping = MemoryMapping::new(
-            vec![MemoryRegion {
-                host_addr: addr,
-                vm_addr: 100,
-                len: 32,
-                vm_gap_shift: 63,
-                is_writable: false,
-            }],
-            &DEFAULT_CONFIG,
-        );
+        for reject_overlapping_buffers in &[false, true] {
+            let mut syscall = SyscallSha256 {
+                sha256_base_cost: 0,
+                sha256_byte_cost: 0,
+                compute_meter: Rc::new(RefCell::new(MockComputeMeter { remaining: 0 })),
+                loader_id: &bpf_loader_deprecated::id(),
+                reject_overlapping_buffers: *reject_overlapping_buffers,
+            };
 
-        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
-        syscall_sol_pubkey.call(100, 0, 0, 0, 0, &memory_mapping, &mut result);
-        result.unwrap();
-        assert_eq!(log.borrow().len(), 1);
-        assert_eq!(
-            log.borrow()[0],
-            "Program log: MoqiU1vryuCGQSxFKA1SZ316JdLEFFhoAu6cKUNk7dN"
-        );
-        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
-        syscall_sol_pubkey.call(
-            101, // AccessViolation
-            32,
-            0,
-            0,
-            0,
-            &memory_mapping,
-            &mut result,
-        );
-        assert_access_violation!(result, 101, 32);
-        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
-        syscall_sol_pubkey.call(100, 32, 0, 0, 0, &memory_mapping, &mut result);
-        assert_eq!(
-            Err(EbpfError::UserError(BPFError::SyscallError(
-                SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
-            ))),
-            result
-        );
+            for_each_overlapping_output(
+                &[&descriptor, &bytes],
+                HASH_BYTES,
+                |memory_mapping, output_addr| {
+                    let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+                    syscall.call(
+                        MM_INPUT_START,
+                        1,
+                        output_addr,
+                        0,
+                        0,
+                        memory_mapping,
+                        &mut result,
+                    );
+                    result
+                },
+                // Overlapping outputs are rejected, unless the feature is inactive in which
+                // case all inputs are read before writing
+                |result, output, overlapping| {
+                    if overlapping && *reject_overlapping_buffers {
+                        assert_eq!(result, Err(SyscallError::OverlappingMutTranslations.into()));
+                    } else {
+                        assert_eq!(result.unwrap(), 0);
+                        assert_eq!(output, expected);
+                    }
+                },
+            );
+        }
     }
 
     #[test]
-    fn test_syscall_sol_alloc_free() {
-        // large alloc
-        {
-            let heap = vec![0_u8; 100];
-            let memory_mapping = MemoryMapping::new(
-                vec![MemoryRegion::new_from_slice(&heap, MM_HEAP_START, 0, true)],
-                &DEFAULT_CONFIG,
-            );
-            let mut syscall = SyscallAllocFree {
-                aligned: true,
-                allocator: BPFAllocator::new(heap, MM_HEAP_START),
+    fn test_syscall_hmac_sha256_overlapping_output() {
+        let key = b"Jefe";
+        let bytes = b"what do ya want for nothing?";
+        let descriptor = [
+            (MM_INPUT_START + 16 + key.len() as u64).to_le_bytes(),
+            (bytes.len() as u64).to_le_bytes(),
+        ]
+        .concat();
+        let expected = hmac(key, bytes).to_bytes();
+
+        for reject_overlapping_buffers in &[false, true] {
+            let mut syscall = SyscallHmacSha256 {
+                base_cost: 0,
+                byte_cost: 0,
+                compute_meter: Rc::new(RefCell::new(MockComputeMeter { remaining: 0 })),
+                loader_id: &bpf_loader_deprecated::id(),
+                reject_overlapping_buffers: *reject_overlapping_buffers,
             };
-            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
-            syscall.call(100, 0, 0, 0, 0, &memory_mapping, &mut result);
-            assert_ne!(result.unwrap(), 0);
-            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
-            syscall.call(100, 0, 0, 0, 0, &memory_mapping, &mut result);
-            assert_eq!(result.unwrap(), 0);
-            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
-            syscall.call(u64::MAX, 0, 0, 0, 0, &memory_mapping, &mut result);
-            assert_eq!(result.unwrap(), 0);
-        }
-        // many small unaligned allocs
-        {
-            let heap = vec![0_u8; 100];
-            let memory_mapping = MemoryMapping::new(
-                vec![MemoryRegion::new_from_slice(&heap, MM_HEAP_START, 0, true)],
-                &DEFAULT_CONFIG,
+
+            for_each_overlapping_output(
+                &[&descriptor, key, bytes],
+                HASH_BYTES,
+                |memory_mapping, output_addr| {
+                    let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+                    syscall.call(
+                        MM_INPUT_START + 16,
+                        key.len() as u64,
+                        MM_INPUT_START,
+                        1,
+                        output_addr,
+                        memory_mapping,
+                        &mut result,
+                    );
+                    result
+                },
+                // Overlapping outputs are rejected, unless the feature is inactive in which
+                // case all inputs are read before writing
+                |result, output, overlapping| {
+                    if overlapping && *reject_overlapping_buffers {
+                        assert_eq!(result, Err(SyscallError::OverlappingMutTranslations.into()));
+                    } else {
+                        assert_eq!(result.unwrap(), 0);
+                        assert_eq!(output, expected);
+                    }
+                },
             );
-            let mut syscall = SyscallAllocFree {
-                aligned: false,
-                allocator: BPFAllocator::new(heap, MM_HEAP_START),
-            };
-            for _ in 0..100 {
-                let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
-                syscall.call(1, 0, 0, 0, 0, &memory_mapping, &mut result);
-                assert_ne!(result.unwrap(), 0);
-            }
-            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
-            syscall.call(100, 0, 0, 0, 0, &memory_mapping, &mut result);
-            assert_eq!(result.unwrap(), 0);
         }
-        // many small aligned allocs
-        {
-            let heap = vec![0_u8; 100];
-            let memory_mapping = MemoryMapping::new(
-                vec![MemoryRegion::new_from_slice(&heap, MM_HEAP_START, 0, true)],
-                &DEFAULT_CONFIG,
-            );
-            let mut syscall = SyscallAllocFree {
-                aligned: true,
-                allocator: BPFAllocator::new(heap, MM_HEAP_START),
-            };
-            for _ in 0..12 {
-                let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
-                syscall.call(1, 0, 0, 0, 0, &memory_mapping, &mut result);
-                assert_ne!(result.unwrap(), 0);
-            }
-            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
-            syscall.call(100, 0, 0, 0, 0, &memory_mapping, &mut result);
-            assert_eq!(result.unwrap(), 0);
+    }
+
+    #[test]
+    fn test_syscall_chacha20_poly1305_open_overlapping_output() {
+        let key = [0x42; chacha20_poly1305::KEY_BYTES];
+        let nonce = [0x24; chacha20_poly1305::NONCE_BYTES];
+        let aad = b"header";
+        let ciphertext = [
+            0x85, 0x73, 0xf1, 0x6f, 0x88, 0xb5, 0x78, 0xc9, 0xf1, 0x4b, 0xec, 0xea, 0x93, 0x79,
+        ];
+        let tag = [
+            0x9b, 0x13, 0xd7, 0x82, 0xe3, 0x48, 0xb7, 0x18, 0xdd, 0xe0, 0x5b, 0xd0, 0xd0, 0x6c,
+            0x33, 0x01,
+        ];
+        let inputs: [&[u8]; 5] = [&key, &nonce, aad, &ciphertext, &tag];
+        // The input descriptors followed by the inputs they refer to
+        let mut descriptors = vec![];
+        let mut input_addr = MM_INPUT_START + 16 * inputs.len() as u64;
+        for input in inputs.iter() {
+            descriptors.extend_from_slice(&input_addr.to_le_bytes());
+            descriptors.extend_from_slice(&(input.len() as u64).to_le_bytes());
+            input_addr += input.len() as u64;
         }
-        // aligned allocs
 
-        fn check_alignment<T>() {
-            let heap = vec![0_u8; 100];
-            let memory_mapping = MemoryMapping::new(
-                vec![MemoryRegion::new_from_slice(&heap, MM_HEAP_START, 0, true)],
-                &DEFAULT_CONFIG,
-            );
-            let mut syscall = SyscallAllocFree {
-                aligned: true,
-                allocator: BPFAllocator::new(heap, MM_HEAP_START),
+        for reject_overlapping_buffers in &[false, true] {
+            let mut syscall = SyscallChaCha20Poly1305Open {
+                base_cost: 0,
+                byte_cost: 0,
+                compute_meter: Rc::new(RefCell::new(MockComputeMeter { remaining: 0 })),
+                loader_id: &bpf_loader_deprecated::id(),
+                reject_overlapping_buffers: *reject_overlapping_buffers,
             };
-            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
-            syscall.call(
-                size_of::<u8>() as u64,
-                0,
-                0,
-                0,
-                0,
-                &memory_mapping,
-                &mut result,
+
+            for_each_overlapping_output(
+                &[&descriptors, &key, &nonce, aad, &ciphertext, &tag],
+                ciphertext.len(),
+                |memory_mapping, plaintext_addr| {
+                    let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+                    syscall.call(
+                        MM_INPUT_START,
+                        inputs.len() as u64,
+                        plaintext_addr,
+                        ciphertext.len() as u64,
+                        0,
+                        memory_mapping,
+                        &mut result,
+                    );
+                    result
+                },
+                // Overlapping outputs are rejected, unless the feature is inactive in which
+                // case all inputs are read before writing
+                |result, plaintext, overlapping| {
+                    if overlapping && *reject_overlapping_buffers {
+                        assert_eq!(result, Err(SyscallError::OverlappingMutTranslations.into()));
+                    } else {
+                        assert_eq!(result.unwrap(), 0);
+                        assert_eq!(plaintext, b"attack at dawn");
+                    }
+                },
             );
-            let address = result.unwrap();
-            assert_ne!(address, 0);
-            assert_eq!((address as *const u8).align_offset(align_of::<u8>()), 0);
         }
-        check_alignment::<u8>();
-        check_alignment::<u16>();
-        check_alignment::<u32>();
-        check_alignment::<u64>();
-        check_alignment::<u128>();
     }
 
     #[test]
-    fn test_syscall_sha256() {
-        let bytes1 = "Gaggablaghblagh!";
-        let bytes2 = "flurbos";
+    fn test_syscall_x25519_dh_overlapping_output() {
+        let scalar = [0x77; x25519::X25519_BYTES];
+        let mut point = [0u8; x25519::X25519_BYTES];
+        point[0] = 9;
+        let expected = x25519::diffie_hellman(&scalar, &point).unwrap();
 
-        struct MockSlice {
-            pub addr: u64,
-            pub len: usize,
+        for reject_overlapping_buffers in &[false, true] {
+            let mut syscall = SyscallX25519Dh {
+                cost: 0,
+                compute_meter: Rc::new(RefCell::new(MockComputeMeter { remaining: 0 })),
+                loader_id: &bpf_loader::id(),
+                reject_overlapping_buffers: *reject_overlapping_buffers,
+            };
+
+            for_each_overlapping_output(
+                &[&scalar, &point],
+                x25519::X25519_BYTES,
+                |memory_mapping, output_addr| {
+                    let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+                    syscall.call(
+                        MM_INPUT_START,
+                        MM_INPUT_START + x25519::X25519_BYTES as u64,
+                        output_addr,
+                        0,
+                        0,
+                        memory_mapping,
+                        &mut result,
+                    );
+                    result
+                },
+                // Overlapping outputs are rejected, unless the feature is inactive in which
+                // case all inputs are read before writing
+                |result, output, overlapping| {
+                    if overlapping && *reject_overlapping_buffers {
+                        assert_eq!(result, Err(SyscallError::OverlappingMutTranslations.into()));
+                    } else {
+                        assert_eq!(result.unwrap(), 0);
+                        assert_eq!(output, expected);
+                    }
+                },
+            );
         }
-        let mock_slice1 = MockSlice {
-            addr: 4096,
-            len: bytes1.len(),
-        };
-        let mock_slice2 = MockSlice {
-            addr: 8192,
-            len: bytes2.len(),
+    }
+
+    #[test]
+    fn test_syscall_ristretto_mul_overlapping_output() {
+        let point = curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+        let scalar = Scalar::from(7u64);
+        let expected = point * scalar;
+        let as_bytes = |point: &RistrettoPoint| unsafe {
+            std::slice::from_raw_parts(point as *const _ as *const u8, size_of::<RistrettoPoint>())
+                .to_vec()
         };
-        let bytes_to_hash = [mock_slice1, mock_slice2]; // TODO
-        let hash_result = [0; HASH_BYTES];
-        let ro_len = bytes_to_hash.len() as u64;
-        let ro_va = 96;
-        let rw_va = 192;
-        let memory_mapping = MemoryMapping::new(
-            vec![
-                MemoryRegion {
-                    host_addr: bytes1.as_ptr() as *const _ as u64,
-                    vm_addr: 4096,
-                    len: bytes1.len() as u64,
-                    vm_gap_shift: 63,
-                    is_writable: false,
+
+        for reject_overlapping_buffers in &[false, true] {
+            let mut syscall = SyscallRistrettoMul {
+                cost: 0,
+                compute_meter: Rc::new(RefCell::new(MockComputeMeter { remaining: 0 })),
+                loader_id: &bpf_loader::id(),
+                reject_overlapping_buffers: *reject_overlapping_buffers,
+            };
+
+            for_each_overlapping_aligned_output(
+                &[&as_bytes(&point), scalar.as_bytes()],
+                size_of::<RistrettoPoint>(),
+                align_of::<RistrettoPoint>(),
+                |memory_mapping, output_addr| {
+                    let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+                    syscall.call(
+                        MM_INPUT_START,
+                        MM_INPUT_START + size_of::<RistrettoPoint>() as u64,
+                        output_addr,
+                        0,
+                        0,
+                        memory_mapping,
+                        &mut result,
+                    );
+                    result
                 },
-                MemoryRegion {
-                    host_addr: bytes2.as_ptr() as *const _ as u64,
-                    vm_addr: 8192,
-                    len: bytes2.len() as u64,
-                    vm_gap_shift: 63,
-                    is_writable: false,
+                // Overlapping outputs are rejected, unless the feature is inactive in which
+                // case all inputs are read before writing
+                |result, output, overlapping| {
+                    if overlapping && *reject_overlapping_buffers {
+                        assert_eq!(result, Err(SyscallError::OverlappingMutTranslations.into()));
+                    } else {
+                        assert_eq!(result.unwrap(), 0);
+                        assert_eq!(output, &as_bytes(&expected)[..]);
+                    }
                 },
-                MemoryRegion {
-                    host_addr: bytes_to_hash.as_ptr() as *const _ as u64,
-                    vm_addr: 96,
-                    len: 32,
-                    vm_gap_shift: 63,
-                    is_writable: false,
+            );
+        }
+    }
+
+    #[test]
+    fn test_syscall_create_program_addresses_batch_overlapping_output() {
+        let program_id = Pubkey::new(&[7; 32]);
+        // Two seed set descriptors, then their seed descriptors, the seeds and the program id
+        let seed_sets = [
+            (MM_INPUT_START + 32).to_le_bytes(),
+            1u64.to_le_bytes(),
+            (MM_INPUT_START + 48).to_le_bytes(),
+            2u64.to_le_bytes(),
+        ]
+        .concat();
+        let seed_descriptors = [
+            (MM_INPUT_START + 80).to_le_bytes(),
+            5u64.to_le_bytes(),
+            (MM_INPUT_START + 85).to_le_bytes(),
+            4u64.to_le_bytes(),
+            (MM_INPUT_START + 89).to_le_bytes(),
+            5u64.to_le_bytes(),
+        ]
+        .concat();
+        let seeds = b"alphabetagamma";
+        let expected =
+            Pubkey::create_program_addresses(&[&[b"alpha"], &[b"beta", b"gamma"]], &program_id);
+
+        for reject_overlapping_buffers in &[false, true] {
+            let mut syscall = SyscallCreateProgramAddressesBatch {
+                base_cost: 0,
+                address_cost: 0,
+                compute_meter: Rc::new(RefCell::new(MockComputeMeter { remaining: 0 })),
+                loader_id: &bpf_loader::id(),
+                reject_overlapping_buffers: *reject_overlapping_buffers,
+            };
+
+            for_each_overlapping_output(
+                &[&seed_sets, &seed_descriptors, seeds, program_id.as_ref()],
+                2 * size_of::<Pubkey>(),
+                |memory_mapping, output_addr| {
+                    let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+                    syscall.call(
+                        MM_INPUT_START,
+                        2,
+                        MM_INPUT_START + 94,
+                        output_addr,
+                        0,
+                        memory_mapping,
+                        &mut result,
+                    );
+                    result
                 },
-                MemoryRegion {
-                    host_addr: hash_result.as_ptr() as *const _ as u64,
-                    vm_addr: rw_va,
-                    len: HASH_BYTES as u64,
-                    vm_gap_shift: 63,
-                    is_writable: true,
+                // Overlapping outputs are rejected, unless the feature is inactive in which
+                // case all inputs are read before writing
+                |result, output, overlapping| match &expected {
+                    Ok(_) if overlapping && *reject_overlapping_buffers => {
+                        assert_eq!(result, Err(SyscallError::OverlappingMutTranslations.into()));
+                    }
+                    Ok(addresses) => {
+                        assert_eq!(result.unwrap(), 0);
+                        assert_eq!(&output[..32], addresses[0].as_ref());
+                        assert_eq!(&output[32..], addresses[1].as_ref());
+                    }
+                    Err(_) => assert_eq!(result.unwrap(), 1),
                 },
-            ],
+            );
+        }
+    }
+
+    #[test]
+    fn test_syscall_create_program_addresses_batch() {
+        let program_id = Pubkey::new(&[7; 32]);
+        let seed = b"seed";
+        // Seed descriptors first so every descriptor is aligned, all of them refer to `seed`
+        let seed_sets_addr = MM_INPUT_START + 16 * (MAX_SEEDS as u64 + 1);
+        let seed_addr = seed_sets_addr + 32;
+        let program_id_addr = seed_addr + seed.len() as u64;
+        let addresses_addr = program_id_addr + 32;
+        let seed_descriptors = [seed_addr.to_le_bytes(), (seed.len() as u64).to_le_bytes()]
+            .concat()
+            .repeat(MAX_SEEDS + 1);
+        let mut region = [
+            seed_descriptors.as_slice(),
+            // One set with a single seed and one with too many seeds
+            &MM_INPUT_START.to_le_bytes(),
+            &1u64.to_le_bytes(),
+            &MM_INPUT_START.to_le_bytes(),
+            &(MAX_SEEDS as u64 + 1).to_le_bytes(),
+            seed,
+            program_id.as_ref(),
+        ]
+        .concat();
+        region.resize(region.len() + 2 * size_of::<Pubkey>(), 0);
+        let memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion {
+                host_addr: region.as_mut_ptr() as *const _ as u64,
+                vm_addr: MM_INPUT_START,
+                len: region.len() as u64,
+                vm_gap_shift: 63,
+                is_writable: true,
+            }],
             &DEFAULT_CONFIG,
         );
         let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
-            Rc::new(RefCell::new(MockComputeMeter {
-                remaining: (bytes1.len() + bytes2.len()) as u64,
-            }));
-        let mut syscall = SyscallSha256 {
-            sha256_base_cost: 0,
-            sha256_byte_cost: 2,
-            compute_meter,
-            loader_id: &bpf_loader_deprecated::id(),
+            Rc::new(RefCell::new(MockComputeMeter { remaining: 12 }));
+        let mut syscall = SyscallCreateProgramAddressesBatch {
+            base_cost: 1,
+            address_cost: 2,
+            compute_meter: compute_meter.clone(),
+            loader_id: &bpf_loader::id(),
+            reject_overlapping_buffers: true,
         };
 
+        // The first set alone is valid, charged the base cost and once per address
         let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
-        syscall.call(ro_va, ro_len, rw_va, 0, 0, &memory_mapping, &mut result);
-        result.unwrap();
+        syscall.call(
+            seed_sets_addr,
+            1,
+            program_id_addr,
+            addresses_addr,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(compute_meter.borrow().get_remaining(), 9);
+        let address_offset = (addresses_addr - MM_INPUT_START) as usize;
+        assert_eq!(
+            &region[address_offset..address_offset + 32],
+            Pubkey::create_program_address(&[seed], &program_id)
+                .unwrap()
+                .as_ref()
+        );
 
-        let hash_local = hashv(&[bytes1.as_ref(), bytes2.as_ref()]).to_bytes();
-        assert_eq!(hash_result, hash_local);
+        // Too many seeds in the second set, both sets are charged
         let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
         syscall.call(
-            ro_va - 1, // AccessViolation
-            ro_len,
-            rw_va,
-            0,
+            seed_sets_addr,
+            2,
+            program_id_addr,
+            addresses_addr,
             0,
             &memory_mapping,
             &mut result,
         );
-        assert_access_violation!(result, ro_va - 1, ro_len);
+        assert_eq!(result.unwrap(), 1);
+        assert_eq!(compute_meter.borrow().get_remaining(), 4);
+
+        // Empty batches still pay the base cost
         let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
         syscall.call(
-            ro_va,
-            ro_len + 1, // AccessViolation
-            rw_va,
+            seed_sets_addr,
             0,
+            program_id_addr,
+            addresses_addr,
             0,
             &memory_mapping,
             &mut result,
         );
-        assert_access_violation!(result, ro_va, ro_len + 1);
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(compute_meter.borrow().get_remaining(), 3);
+
+        // Batches that are too large fail after paying only the base cost
         let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
         syscall.call(
-            ro_va,
-            ro_len,
-            rw_va - 1, // AccessViolation
-            0,
+            seed_sets_addr,
+            MAX_PROGRAM_ADDRESSES as u64 + 1,
+            program_id_addr,
+            addresses_addr,
             0,
             &memory_mapping,
             &mut result,
         );
-        assert_access_violation!(result, rw_va - 1, HASH_BYTES as u64);
+        assert_eq!(
+            PubkeyError::from(result.unwrap()),
+            PubkeyError::TooManyProgramAddresses
+        );
+        assert_eq!(compute_meter.borrow().get_remaining(), 2);
 
-        syscall.call(ro_va, ro_len, rw_va, 0, 0, &memory_mapping, &mut result);
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(
+            seed_sets_addr,
+            1,
+            program_id_addr,
+            addresses_addr,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
         assert_eq!(
             Err(EbpfError::UserError(BPFError::SyscallError(
                 SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
@@ -2006,4 +7724,655 @@ mod tests {
             result
         );
     }
+
+    #[test]
+    fn test_syscall_create_program_address_overlapping_output() {
+        let seed = b"overlap!";
+        let program_id = Pubkey::new(&[7; 32]);
+        let descriptor = [
+            (MM_INPUT_START + 16).to_le_bytes(),
+            (seed.len() as u64).to_le_bytes(),
+        ]
+        .concat();
+        let expected = Pubkey::create_program_address(&[seed], &program_id);
+
+        for reject_overlapping_buffers in &[false, true] {
+            let mut syscall = SyscallCreateProgramAddress {
+                cost: 0,
+                compute_meter: Rc::new(RefCell::new(MockComputeMeter { remaining: 0 })),
+                loader_id: &bpf_loader_deprecated::id(),
+                reject_overlapping_buffers: *reject_overlapping_buffers,
+            };
+
+            for_each_overlapping_output(
+                &[&descriptor, seed, program_id.as_ref()],
+                32,
+                |memory_mapping, output_addr| {
+                    let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+                    syscall.call(
+                        MM_INPUT_START,
+                        1,
+                        MM_INPUT_START + 24,
+                        output_addr,
+                        0,
+                        memory_mapping,
+                        &mut result,
+                    );
+                    result
+                },
+                // Overlapping outputs are rejected, unless the feature is inactive in which
+                // case all inputs are read before writing
+                |result, output, overlapping| match &expected {
+                    Ok(_) if overlapping && *reject_overlapping_buffers => {
+                        assert_eq!(result, Err(SyscallError::OverlappingMutTranslations.into()));
+                    }
+                    Ok(address) => {
+                        assert_eq!(result.unwrap(), 0);
+                        assert_eq!(output, address.as_ref());
+                    }
+                    Err(_) => assert_eq!(result.unwrap(), 1),
+                },
+            );
+        }
+    }
+
+    #[test]
+    fn test_syscall_get_instruction_ancestry_overlapping_outputs() {
+        let ancestry = vec![Pubkey::new(&[1; 32]), Pubkey::new(&[2; 32])];
+        let mut syscall = SyscallGetInstructionAncestry {
+            cost: 0,
+            compute_meter: Rc::new(RefCell::new(MockComputeMeter { remaining: 0 })),
+            program_ids: ancestry.clone(),
+            loader_id: &bpf_loader_deprecated::id(),
+        };
+
+        for_each_overlapping_output_pair(
+            &[],
+            (2 * size_of::<Pubkey>(), size_of::<u64>()),
+            |memory_mapping, program_ids_addr, stack_height_addr| {
+                let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+                syscall.call(
+                    program_ids_addr,
+                    2,
+                    stack_height_addr,
+                    0,
+                    0,
+                    memory_mapping,
+                    &mut result,
+                );
+                result
+            },
+            |result, program_ids, stack_height, overlapping| {
+                if overlapping {
+                    assert_eq!(result, Err(SyscallError::OverlappingMutTranslations.into()));
+                } else {
+                    assert_eq!(result.unwrap(), 0);
+                    assert_eq!(
+                        program_ids,
+                        [ancestry[0].as_ref(), ancestry[1].as_ref()].concat()
+                    );
+                    assert_eq!(stack_height, 2u64.to_le_bytes());
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn test_syscall_base58_encode_overlapping_outputs() {
+        let input = b"overlap";
+        let encoded = base58::encode(input).unwrap();
+        let mut syscall = SyscallBase58Encode {
+            base_cost: 0,
+            byte_cost: 0,
+            compute_meter: Rc::new(RefCell::new(MockComputeMeter { remaining: 0 })),
+            loader_id: &bpf_loader_deprecated::id(),
+        };
+
+        for_each_overlapping_output_pair(
+            &[input],
+            (encoded.len(), size_of::<u64>()),
+            |memory_mapping, output_addr, written_addr| {
+                let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+                syscall.call(
+                    MM_INPUT_START,
+                    input.len() as u64,
+                    output_addr,
+                    encoded.len() as u64,
+                    written_addr,
+                    memory_mapping,
+                    &mut result,
+                );
+                result
+            },
+            |result, output, written, overlapping| {
+                if overlapping {
+                    assert_eq!(result, Err(SyscallError::OverlappingMutTranslations.into()));
+                } else {
+                    assert_eq!(result.unwrap(), 0);
+                    assert_eq!(output, encoded.as_bytes());
+                    assert_eq!(written, (encoded.len() as u64).to_le_bytes());
+                }
+            },
+        );
+    }
+
+    /// Memory of a C program passing two accounts to a cross-program invocation, the first
+    /// account's serialized data surrounded by room to place the second account's fields
+    #[repr(C)]
+    struct OverlappingAccountsMemory {
+        account_infos: [SolAccountInfo; 2],
+        keys: [Pubkey; 2],
+        owners: [Pubkey; 2],
+        lamports: [u64; 2],
+        // Serialized data length followed by the second account's data
+        serialized_other: [u64; 2],
+        before: [u64; 4],
+        // Serialized data length followed by the first account's data
+        serialized: [u64; 3],
+        after: [u64; 4],
+    }
+
+    #[test]
+    fn test_translate_accounts_overlapping_account_regions() {
+        let keys = [Pubkey::new(&[1; 32]), Pubkey::new(&[2; 32])];
+        // All fields are plain integers, so zeroed memory is valid
+        let mut memory = Box::new(unsafe { std::mem::zeroed::<OverlappingAccountsMemory>() });
+        let base = &*memory as *const _ as u64;
+        let vm_addr = |host_addr: *const u8| MM_INPUT_START + host_addr as u64 - base;
+        memory.keys = keys;
+        let data_addrs = [
+            vm_addr(&memory.serialized[1] as *const _ as *const u8),
+            vm_addr(&memory.serialized_other[1] as *const _ as *const u8),
+        ];
+        let data_lens = [16, 8];
+        let reset_account_infos = |memory: &mut OverlappingAccountsMemory| {
+            for i in 0..2 {
+                memory.account_infos[i] = SolAccountInfo {
+                    key_addr: vm_addr(&memory.keys[i] as *const _ as *const u8),
+                    lamports_addr: vm_addr(&memory.lamports[i] as *const _ as *const u8),
+                    data_len: data_lens[i],
+                    data_addr: data_addrs[i],
+                    owner_addr: vm_addr(&memory.owners[i] as *const _ as *const u8),
+                    rent_epoch: 0,
+                    is_signer: false,
+                    is_writable: true,
+                    executable: false,
+                };
+            }
+        };
+        let memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion {
+                host_addr: base,
+                vm_addr: MM_INPUT_START,
+                len: size_of::<OverlappingAccountsMemory>() as u64,
+                vm_gap_shift: 63,
+                is_writable: true,
+            }],
+            &DEFAULT_CONFIG,
+        );
+        let account_infos_addr = vm_addr(&memory.account_infos[0] as *const _ as *const u8);
+        let message = Message {
+            account_keys: keys.to_vec(),
+            ..Message::default()
+        };
+        let mut this_invoke_context = ThisInvokeContext::new(
+            &keys[0],
+            Rent::default(),
+            vec![],
+            &[],
+            None,
+            BpfComputeBudget::default(),
+            ExecutionHooks::default(),
+            TransactionInfo::default(),
+            Rc::new(RefCell::new(Executors::default())),
+            None,
+            Arc::new(FeatureSet::all_enabled()),
+        );
+        let invoke_context: Rc<RefCell<&mut dyn InvokeContext>> =
+            Rc::new(RefCell::new(&mut this_invoke_context));
+        let syscall = SyscallInvokeSignedC {
+            callers_keyed_accounts: &[],
+            invoke_context,
+            loader_id: &bpf_loader::id(),
+        };
+
+        // The second account's lamports and owner at every aligned offset around the first
+        // account's serialized data length and data
+        let serialized_addr = data_addrs[0] - size_of::<u64>() as u64;
+        let serialized_len = size_of::<u64>() as u64 + data_lens[0];
+        for (size, is_lamports) in &[(size_of::<u64>(), true), (size_of::<Pubkey>(), false)] {
+            for (addr, overlapping) in
+                placements_around(serialized_addr, serialized_len, *size as u64, 8)
+            {
+                for reject_overlapping_buffers in &[false, true] {
+                    reset_account_infos(&mut memory);
+                    if *is_lamports {
+                        memory.account_infos[1].lamports_addr = addr;
+                    } else {
+                        memory.account_infos[1].owner_addr = addr;
+                    }
+                    let result = syscall
+                        .translate_accounts(
+                            &message,
+                            account_infos_addr,
+                            2,
+                            &memory_mapping,
+                            *reject_overlapping_buffers,
+                        )
+                        .map(|_| ());
+                    if overlapping && *reject_overlapping_buffers {
+                        assert_eq!(result, Err(SyscallError::OverlappingMutTranslations.into()));
+                    } else {
+                        assert_eq!(result, Ok(()));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Memory of a C program calling `sol_system_transfer_c` with its `from`, `to` and system
+    /// program accounts
+    #[repr(C)]
+    struct SystemTransferMemory {
+        transfer: SystemTransfer,
+        keys: [Pubkey; 3],
+        owners: [Pubkey; 3],
+        lamports: [u64; 3],
+        // Serialized data lengths, each followed by the account's empty data
+        serialized_lens: [u64; 4],
+        account_infos: [SolAccountInfo; 3],
+        seed: [u8; 8],
+        bump_seed: [u8; 1],
+        seeds: [SolSignerSeedC; 2],
+        signers_seeds: [SolSignerSeedC; 1],
+    }
+
+    const SYSTEM_TRANSFER_SEED: &[u8; 8] = b"transfer";
+
+    /// Calls `sol_system_transfer_c` from `caller` with the `from`, `to` and system program
+    /// accounts, signing for the program address derived with `bump_seed` if given.  Returns the
+    /// result and the lamports of the accounts as seen by the caller afterwards
+    fn call_system_transfer_c(
+        caller: &Pubkey,
+        accounts: &[(Pubkey, Account, bool); 3],
+        lamports: u64,
+        bump_seed: Option<u8>,
+    ) -> (Result<u64, EbpfError<BPFError>>, [u64; 3]) {
+        // All fields are plain integers, so zeroed memory is valid
+        let mut memory = Box::new(unsafe { std::mem::zeroed::<SystemTransferMemory>() });
+        let base = &*memory as *const _ as u64;
+        let vm_addr = |host_addr: *const u8| MM_INPUT_START + host_addr as u64 - base;
+        for (i, (key, account, is_signer)) in accounts.iter().enumerate() {
+            memory.keys[i] = *key;
+            memory.owners[i] = account.owner;
+            memory.lamports[i] = account.lamports;
+            memory.account_infos[i] = SolAccountInfo {
+                key_addr: vm_addr(&memory.keys[i] as *const _ as *const u8),
+                lamports_addr: vm_addr(&memory.lamports[i] as *const _ as *const u8),
+                data_len: 0,
+                data_addr: vm_addr(&memory.serialized_lens[i + 1] as *const _ as *const u8),
+                owner_addr: vm_addr(&memory.owners[i] as *const _ as *const u8),
+                rent_epoch: account.rent_epoch,
+                is_signer: *is_signer,
+                is_writable: !account.executable,
+                executable: account.executable,
+            };
+        }
+        memory.transfer = SystemTransfer {
+            from_addr: vm_addr(&memory.keys[0] as *const _ as *const u8),
+            to_addr: vm_addr(&memory.keys[1] as *const _ as *const u8),
+            lamports,
+        };
+        memory.seed = *SYSTEM_TRANSFER_SEED;
+        memory.bump_seed = [bump_seed.unwrap_or_default()];
+        memory.seeds = [
+            SolSignerSeedC {
+                addr: vm_addr(memory.seed.as_ptr()),
+                len: memory.seed.len() as u64,
+            },
+            SolSignerSeedC {
+                addr: vm_addr(memory.bump_seed.as_ptr()),
+                len: memory.bump_seed.len() as u64,
+            },
+        ];
+        memory.signers_seeds = [SolSignerSeedC {
+            addr: vm_addr(&memory.seeds[0] as *const _ as *const u8),
+            len: memory.seeds.len() as u64,
+        }];
+        let memory_mapping = MemoryMapping::new(
+            vec![MemoryRegion {
+                host_addr: base,
+                vm_addr: MM_INPUT_START,
+                len: size_of::<SystemTransferMemory>() as u64,
+                vm_gap_shift: 63,
+                is_writable: true,
+            }],
+            &DEFAULT_CONFIG,
+        );
+
+        let account_cells = accounts
+            .iter()
+            .map(|(_, account, _)| RefCell::new(account.clone()))
+            .collect::<Vec<_>>();
+        let keyed_accounts = accounts
+            .iter()
+            .zip(account_cells.iter())
+            .map(|((key, account, is_signer), account_cell)| {
+                if account.executable {
+                    KeyedAccount::new_readonly(key, *is_signer, account_cell)
+                } else {
+                    KeyedAccount::new(key, *is_signer, account_cell)
+                }
+            })
+            .collect::<Vec<_>>();
+        let pre_accounts = accounts
+            .iter()
+            .map(|(key, account, is_signer)| {
+                PreAccount::new(key, account, *is_signer, !account.executable)
+            })
+            .collect();
+        let mut this_invoke_context = ThisInvokeContext::new(
+            caller,
+            Rent::default(),
+            pre_accounts,
+            &[],
+            None,
+            BpfComputeBudget::default(),
+            ExecutionHooks::default(),
+            TransactionInfo::default(),
+            Rc::new(RefCell::new(Executors::default())),
+            None,
+            Arc::new(FeatureSet::all_enabled()),
+        );
+        let invoke_context: Rc<RefCell<&mut dyn InvokeContext>> =
+            Rc::new(RefCell::new(&mut this_invoke_context));
+        let mut syscall = SyscallSystemTransferC {
+            syscall: SyscallInvokeSignedC {
+                callers_keyed_accounts: &keyed_accounts,
+                invoke_context,
+                loader_id: &bpf_loader::id(),
+            },
+        };
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(
+            vm_addr(&memory.transfer as *const _ as *const u8),
+            vm_addr(&memory.account_infos[0] as *const _ as *const u8),
+            memory.account_infos.len() as u64,
+            vm_addr(&memory.signers_seeds[0] as *const _ as *const u8),
+            if bump_seed.is_some() { 1 } else { 0 },
+            &memory_mapping,
+            &mut result,
+        );
+        (result, memory.lamports)
+    }
+
+    #[test]
+    fn test_syscall_system_transfer() {
+        let caller = solana_sdk::pubkey::new_rand();
+        let from = solana_sdk::pubkey::new_rand();
+        let to = solana_sdk::pubkey::new_rand();
+        let system_account = Account::new(100, 0, &system_program::id());
+        let system_program_account = Account {
+            lamports: 1,
+            owner: native_loader::id(),
+            executable: true,
+            ..Account::default()
+        };
+        let accounts = |from: Pubkey, from_account: &Account, is_signer: bool| {
+            [
+                (from, from_account.clone(), is_signer),
+                (to, system_account.clone(), false),
+                (system_program::id(), system_program_account.clone(), false),
+            ]
+        };
+
+        // Success
+        let (result, lamports) =
+            call_system_transfer_c(&caller, &accounts(from, &system_account, true), 40, None);
+        assert_eq!(result.unwrap(), SUCCESS);
+        assert_eq!(lamports, [60, 140, 1]);
+
+        // Missing signer
+        let (result, lamports) =
+            call_system_transfer_c(&caller, &accounts(from, &system_account, false), 40, None);
+        assert_eq!(
+            result,
+            Err(SyscallError::InstructionError(InstructionError::PrivilegeEscalation).into())
+        );
+        assert_eq!(lamports, [100, 100, 1]);
+
+        // Signed for by the caller with the program address's seeds
+        let (address, bump_seed) = Pubkey::find_program_address(&[SYSTEM_TRANSFER_SEED], &caller);
+        let (result, lamports) = call_system_transfer_c(
+            &caller,
+            &accounts(address, &system_account, false),
+            40,
+            Some(bump_seed),
+        );
+        assert_eq!(result.unwrap(), SUCCESS);
+        assert_eq!(lamports, [60, 140, 1]);
+
+        // The caller's seeds only sign for its own program address
+        let (result, lamports) = call_system_transfer_c(
+            &caller,
+            &accounts(from, &system_account, false),
+            40,
+            Some(bump_seed),
+        );
+        assert_eq!(
+            result,
+            Err(SyscallError::InstructionError(InstructionError::PrivilegeEscalation).into())
+        );
+        assert_eq!(lamports, [100, 100, 1]);
+
+        // Source not owned by the system program
+        let program_account = Account::new(100, 0, &caller);
+        let (result, lamports) =
+            call_system_transfer_c(&caller, &accounts(from, &program_account, true), 40, None);
+        assert_eq!(
+            result,
+            Err(
+                SyscallError::InstructionError(InstructionError::ExternalAccountLamportSpend)
+                    .into()
+            )
+        );
+        assert_eq!(lamports, [100, 100, 1]);
+
+        // Insufficient lamports are reported to the caller as a program error
+        let (result, lamports) =
+            call_system_transfer_c(&caller, &accounts(from, &system_account, true), 101, None);
+        assert_eq!(
+            result.unwrap(),
+            u64::from(ProgramError::Custom(
+                SystemError::ResultWithNegativeLamports as u32
+            ))
+        );
+        assert_eq!(lamports, [100, 100, 1]);
+    }
+
+    #[cfg(feature = "experimental-token-transfer")]
+    fn token_account(
+        mint: &Pubkey,
+        owner: &Pubkey,
+        amount: u64,
+        delegate: Option<(&Pubkey, u64)>,
+        state: u8,
+    ) -> RefCell<Account> {
+        let mut account = Account::new(1, 165, &spl_token::id());
+        account.data[0..32].copy_from_slice(mint.as_ref());
+        account.data[32..64].copy_from_slice(owner.as_ref());
+        account.data[64..72].copy_from_slice(&amount.to_le_bytes());
+        if let Some((delegate, delegated_amount)) = delegate {
+            account.data[72..76].copy_from_slice(&[1, 0, 0, 0]);
+            account.data[76..108].copy_from_slice(delegate.as_ref());
+            account.data[121..129].copy_from_slice(&delegated_amount.to_le_bytes());
+        }
+        account.data[108] = state;
+        RefCell::new(account)
+    }
+
+    #[cfg(feature = "experimental-token-transfer")]
+    fn token_amount(account: &RefCell<Account>) -> u64 {
+        let mut amount = [0; 8];
+        amount.copy_from_slice(&account.borrow().data[64..72]);
+        u64::from_le_bytes(amount)
+    }
+
+    #[cfg(feature = "experimental-token-transfer")]
+    fn token_transfer_data(amount: u64, decimals: Option<u8>) -> Vec<u8> {
+        let mut data = vec![if decimals.is_some() { 12 } else { 3 }];
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.extend(decimals);
+        data
+    }
+
+    #[cfg(feature = "experimental-token-transfer")]
+    #[test]
+    fn test_process_token_transfer() {
+        const INITIALIZED: u8 = 1;
+        const FROZEN: u8 = 2;
+        let mint_key = solana_sdk::pubkey::new_rand();
+        let owner_key = solana_sdk::pubkey::new_rand();
+        let source_key = solana_sdk::pubkey::new_rand();
+        let destination_key = solana_sdk::pubkey::new_rand();
+        let mut mint = Account::new(1, 82, &spl_token::id());
+        mint.data[44] = 2;
+        mint.data[45] = 1;
+        let mint = RefCell::new(mint);
+        let owner = RefCell::new(Account::default());
+
+        // Transfer
+        let source = token_account(&mint_key, &owner_key, 100, None, INITIALIZED);
+        let destination = token_account(&mint_key, &owner_key, 0, None, INITIALIZED);
+        let keyed_accounts = [
+            KeyedAccount::new(&source_key, false, &source),
+            KeyedAccount::new(&destination_key, false, &destination),
+            KeyedAccount::new_readonly(&owner_key, true, &owner),
+        ];
+        process_token_transfer(&keyed_accounts, &token_transfer_data(40, None)).unwrap();
+        assert_eq!(token_amount(&source), 60);
+        assert_eq!(token_amount(&destination), 40);
+
+        // The owner must sign
+        let keyed_accounts = [
+            KeyedAccount::new(&source_key, false, &source),
+            KeyedAccount::new(&destination_key, false, &destination),
+            KeyedAccount::new_readonly(&owner_key, false, &owner),
+        ];
+        assert_eq!(
+            process_token_transfer(&keyed_accounts, &token_transfer_data(40, None)),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+
+        // TransferChecked
+        let keyed_accounts = [
+            KeyedAccount::new(&source_key, false, &source),
+            KeyedAccount::new_readonly(&mint_key, false, &mint),
+            KeyedAccount::new(&destination_key, false, &destination),
+            KeyedAccount::new_readonly(&owner_key, true, &owner),
+        ];
+        process_token_transfer(&keyed_accounts, &token_transfer_data(60, Some(2))).unwrap();
+        assert_eq!(token_amount(&source), 0);
+        assert_eq!(token_amount(&destination), 100);
+
+        // TransferChecked with the wrong decimals
+        assert_eq!(
+            process_token_transfer(&keyed_accounts, &token_transfer_data(0, Some(9))),
+            Err(InstructionError::Custom(18)) // MintDecimalsMismatch
+        );
+
+        // TransferChecked with a mint other than the accounts'
+        let other_mint_key = solana_sdk::pubkey::new_rand();
+        let keyed_accounts = [
+            KeyedAccount::new(&source_key, false, &source),
+            KeyedAccount::new_readonly(&other_mint_key, false, &mint),
+            KeyedAccount::new(&destination_key, false, &destination),
+            KeyedAccount::new_readonly(&owner_key, true, &owner),
+        ];
+        assert_eq!(
+            process_token_transfer(&keyed_accounts, &token_transfer_data(0, Some(2))),
+            Err(InstructionError::Custom(3)) // MintMismatch
+        );
+
+        // Accounts of different mints
+        let source = token_account(&mint_key, &owner_key, 100, None, INITIALIZED);
+        let destination = token_account(&other_mint_key, &owner_key, 0, None, INITIALIZED);
+        let keyed_accounts = [
+            KeyedAccount::new(&source_key, false, &source),
+            KeyedAccount::new(&destination_key, false, &destination),
+            KeyedAccount::new_readonly(&owner_key, true, &owner),
+        ];
+        assert_eq!(
+            process_token_transfer(&keyed_accounts, &token_transfer_data(40, None)),
+            Err(InstructionError::Custom(3)) // MintMismatch
+        );
+        assert_eq!(token_amount(&source), 100);
+
+        // Frozen accounts
+        for (source_state, destination_state) in &[(FROZEN, INITIALIZED), (INITIALIZED, FROZEN)] {
+            let source = token_account(&mint_key, &owner_key, 100, None, *source_state);
+            let destination = token_account(&mint_key, &owner_key, 0, None, *destination_state);
+            let keyed_accounts = [
+                KeyedAccount::new(&source_key, false, &source),
+                KeyedAccount::new(&destination_key, false, &destination),
+                KeyedAccount::new_readonly(&owner_key, true, &owner),
+            ];
+            assert_eq!(
+                process_token_transfer(&keyed_accounts, &token_transfer_data(40, None)),
+                Err(InstructionError::Custom(17)) // AccountFrozen
+            );
+            assert_eq!(token_amount(&source), 100);
+        }
+    }
+
+    #[cfg(feature = "experimental-token-transfer")]
+    #[test]
+    fn test_process_token_transfer_delegate() {
+        const INITIALIZED: u8 = 1;
+        let mint_key = solana_sdk::pubkey::new_rand();
+        let owner_key = solana_sdk::pubkey::new_rand();
+        let delegate_key = solana_sdk::pubkey::new_rand();
+        let source_key = solana_sdk::pubkey::new_rand();
+        let destination_key = solana_sdk::pubkey::new_rand();
+        let source = token_account(
+            &mint_key,
+            &owner_key,
+            100,
+            Some((&delegate_key, 30)),
+            INITIALIZED,
+        );
+        let destination = token_account(&mint_key, &owner_key, 0, None, INITIALIZED);
+        let delegate = RefCell::new(Account::default());
+        let keyed_accounts = [
+            KeyedAccount::new(&source_key, false, &source),
+            KeyedAccount::new(&destination_key, false, &destination),
+            KeyedAccount::new_readonly(&delegate_key, true, &delegate),
+        ];
+
+        // The delegate cannot exceed its allowance
+        assert_eq!(
+            process_token_transfer(&keyed_accounts, &token_transfer_data(40, None)),
+            Err(InstructionError::Custom(1)) // InsufficientFunds
+        );
+        assert_eq!(token_amount(&source), 100);
+
+        // Spending part of the allowance leaves the rest delegated
+        process_token_transfer(&keyed_accounts, &token_transfer_data(20, None)).unwrap();
+        assert_eq!(token_amount(&source), 80);
+        assert_eq!(token_amount(&destination), 20);
+        assert_eq!(source.borrow().data[72..76], [1, 0, 0, 0]);
+        assert_eq!(source.borrow().data[121..129], 10u64.to_le_bytes());
+
+        // Spending all of the allowance revokes the delegate
+        process_token_transfer(&keyed_accounts, &token_transfer_data(10, None)).unwrap();
+        assert_eq!(token_amount(&source), 70);
+        assert_eq!(token_amount(&destination), 30);
+        assert!(source.borrow().data[72..108].iter().all(|byte| *byte == 0));
+        assert_eq!(source.borrow().data[121..129], 0u64.to_le_bytes());
+        assert_eq!(
+            process_token_transfer(&keyed_accounts, &token_transfer_data(1, None)),
+            Err(InstructionError::Custom(4)) // OwnerMismatch
+        );
+    }
 }
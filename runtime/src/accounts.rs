@@ -34,6 +34,14 @@ use std::{
     sync::{Arc, Mutex, RwLock},
 };
 
+/// Maximum number of accounts a transaction may lock once
+/// `feature_set::limit_tx_account_locks` is active
+pub const MAX_TX_ACCOUNT_LOCKS: usize = 64;
+
+/// Maximum number of accounts a transaction may lock once
+/// `feature_set::increase_tx_account_lock_limit` is active
+pub const MAX_TX_ACCOUNT_LOCKS_INCREASED: usize = 128;
+
 #[derive(Default, Debug, AbiExample)]
 pub(crate) struct ReadonlyLock {
     lock_count: Mutex<u64>,
@@ -662,6 +670,7 @@ impl Accounts {
             Err(TransactionError::AccountInUse) => (),
             Err(TransactionError::SanitizeFailure) => (),
             Err(TransactionError::AccountLoadedTwice) => (),
+            Err(TransactionError::TooManyAccountLocks) => (),
             _ => {
                 let (writable_keys, readonly_keys) = &tx.message().get_account_keys_by_lock_type();
                 for k in writable_keys {
@@ -696,6 +705,7 @@ impl Accounts {
         &self,
         txs: &[Transaction],
         txs_iteration_order: Option<&[usize]>,
+        tx_account_lock_limit: Option<usize>,
     ) -> Vec<Result<()>> {
         use solana_sdk::sanitize::Sanitize;
         let keys: Vec<Result<_>> = OrderedIterator::new(txs, txs_iteration_order)
@@ -706,6 +716,8 @@ impl Accounts {
                     return Err(TransactionError::AccountLoadedTwice);
                 }
 
+                Self::check_account_lock_limit(tx, tx_account_lock_limit)?;
+
                 Ok(tx.message().get_account_keys_by_lock_type())
             })
             .collect();
@@ -720,6 +732,19 @@ impl Accounts {
             .collect()
     }
 
+    /// Fail transactions that would lock more accounts than `tx_account_lock_limit`, if any
+    pub fn check_account_lock_limit(
+        tx: &Transaction,
+        tx_account_lock_limit: Option<usize>,
+    ) -> Result<()> {
+        match tx_account_lock_limit {
+            Some(limit) if tx.message.account_keys.len() > limit => {
+                Err(TransactionError::TooManyAccountLocks)
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Once accounts are unlocked, new transactions that modify that state can enter the pipeline
     pub fn unlock_accounts(
         &self,
@@ -1436,6 +1461,133 @@ mod tests {
         );
     }
 
+    /// Load-stage failures that can be injected into an otherwise loadable transaction
+    #[derive(Clone, Copy, Debug)]
+    enum LoadFault {
+        None,
+        MissingFeePayer,
+        FeePayerOwnerMismatch,
+        FeePayerNotRentExempt,
+        MissingProgram,
+        ProgramNotExecutable,
+    }
+
+    impl LoadFault {
+        fn inject(
+            self,
+            fee_payer: &Pubkey,
+            program_id: &Pubkey,
+            min_balance: u64,
+            fee: u64,
+            accounts: &mut Vec<(Pubkey, Account)>,
+        ) {
+            fn account<'a>(accounts: &'a mut [(Pubkey, Account)], key: &Pubkey) -> &'a mut Account {
+                &mut accounts
+                    .iter_mut()
+                    .find(|(pubkey, _)| pubkey == key)
+                    .unwrap()
+                    .1
+            }
+            match self {
+                LoadFault::None => {}
+                LoadFault::MissingFeePayer => accounts.retain(|(key, _)| key != fee_payer),
+                LoadFault::FeePayerOwnerMismatch => {
+                    account(accounts, fee_payer).owner = solana_sdk::pubkey::new_rand()
+                }
+                LoadFault::FeePayerNotRentExempt => {
+                    *account(accounts, fee_payer) = Account::new_data(
+                        min_balance + fee - 1,
+                        &nonce::state::Versions::new_current(nonce::State::Initialized(
+                            nonce::state::Data::default(),
+                        )),
+                        &system_program::id(),
+                    )
+                    .unwrap()
+                }
+                LoadFault::MissingProgram => accounts.retain(|(key, _)| key != program_id),
+                LoadFault::ProgramNotExecutable => account(accounts, program_id).executable = false,
+            }
+        }
+
+        fn expected(self) -> Result<()> {
+            match self {
+                LoadFault::None => Ok(()),
+                LoadFault::MissingFeePayer => Err(TransactionError::AccountNotFound),
+                LoadFault::FeePayerOwnerMismatch => Err(TransactionError::InvalidAccountForFee),
+                LoadFault::FeePayerNotRentExempt => Err(TransactionError::InsufficientFundsForFee),
+                LoadFault::MissingProgram => Err(TransactionError::ProgramAccountNotFound),
+                LoadFault::ProgramNotExecutable => {
+                    Err(TransactionError::InvalidProgramForExecution)
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_load_accounts_injected_faults() {
+        let rent_collector = RentCollector::new(
+            0,
+            &EpochSchedule::default(),
+            500_000.0,
+            &Rent {
+                lamports_per_byte_year: 42,
+                ..Rent::default()
+            },
+        );
+        let min_balance = rent_collector.rent.minimum_balance(nonce::State::size());
+        let fee = 10;
+        let fee_calculator = FeeCalculator::new(fee);
+        let keypair = Keypair::new();
+        let fee_payer = keypair.pubkey();
+        let program_id = Pubkey::new(&[5u8; 32]);
+
+        for fault in &[
+            LoadFault::None,
+            LoadFault::MissingFeePayer,
+            LoadFault::FeePayerOwnerMismatch,
+            LoadFault::FeePayerNotRentExempt,
+            LoadFault::MissingProgram,
+            LoadFault::ProgramNotExecutable,
+        ] {
+            let mut program = Account::new(40, 1, &native_loader::id());
+            program.executable = true;
+            let mut accounts = vec![
+                (
+                    fee_payer,
+                    Account::new(min_balance + fee, 0, &system_program::id()),
+                ),
+                (program_id, program),
+            ];
+            fault.inject(&fee_payer, &program_id, min_balance, fee, &mut accounts);
+
+            let instructions = vec![CompiledInstruction::new(1, &(), vec![0])];
+            let tx = Transaction::new_with_compiled_instructions(
+                &[&keypair],
+                &[],
+                Hash::default(),
+                vec![program_id],
+                instructions,
+            );
+            let mut error_counters = ErrorCounters::default();
+            let loaded_accounts = load_accounts_with_fee_and_rent(
+                tx,
+                &accounts,
+                &fee_calculator,
+                &rent_collector,
+                &mut error_counters,
+            );
+
+            assert_eq!(loaded_accounts.len(), 1);
+            let (load_res, _nonce_rollback) = &loaded_accounts[0];
+            assert_eq!(
+                load_res.as_ref().map(|_| ()).map_err(|err| err.clone()),
+                fault.expected(),
+                "{:?}",
+                fault
+            );
+        }
+    }
+
     #[test]
     fn test_load_accounts_multiple_loaders() {
         let mut accounts: Vec<(Pubkey, Account)> = Vec::new();
@@ -1546,6 +1698,47 @@ mod tests {
         accounts.bank_hash_at(1);
     }
 
+    #[test]
+    fn test_lock_accounts_with_too_many_accounts() {
+        let keypair = Keypair::new();
+        let accounts = Accounts::new(Vec::new(), &ClusterType::Development);
+        let new_tx = |num_account_keys: usize| {
+            let mut account_keys = vec![keypair.pubkey()];
+            account_keys.extend((2..num_account_keys).map(|_| solana_sdk::pubkey::new_rand()));
+            account_keys.push(native_loader::id());
+            let instructions = vec![CompiledInstruction::new(
+                (num_account_keys - 1) as u8,
+                &(),
+                vec![0],
+            )];
+            let message = Message::new_with_compiled_instructions(
+                1,
+                0,
+                1,
+                account_keys,
+                Hash::default(),
+                instructions,
+            );
+            Transaction::new(&[&keypair], message, Hash::default())
+        };
+
+        let txs = vec![
+            new_tx(MAX_TX_ACCOUNT_LOCKS),
+            new_tx(MAX_TX_ACCOUNT_LOCKS + 1),
+        ];
+        let results = accounts.lock_accounts(&txs, None, Some(MAX_TX_ACCOUNT_LOCKS));
+        assert_eq!(results[0], Ok(()));
+        assert_eq!(results[1], Err(TransactionError::TooManyAccountLocks));
+        accounts.unlock_accounts(&txs, None, &results);
+        assert!(accounts.account_locks.lock().unwrap().is_empty());
+
+        let results = accounts.lock_accounts(&txs, None, Some(MAX_TX_ACCOUNT_LOCKS_INCREASED));
+        assert_eq!(results[0], Ok(()));
+        assert_eq!(results[1], Err(TransactionError::AccountInUse));
+        accounts.unlock_accounts(&txs, None, &results);
+        assert!(accounts.account_locks.lock().unwrap().is_empty());
+    }
+
     #[test]
     fn test_accounts_locks() {
         let keypair0 = Keypair::new();
@@ -1574,7 +1767,7 @@ mod tests {
             instructions,
         );
         let tx = Transaction::new(&[&keypair0], message, Hash::default());
-        let results0 = accounts.lock_accounts(&[tx.clone()], None);
+        let results0 = accounts.lock_accounts(&[tx.clone()], None, Some(MAX_TX_ACCOUNT_LOCKS));
 
         assert!(results0[0].is_ok());
         assert_eq!(
@@ -1613,7 +1806,7 @@ mod tests {
         );
         let tx1 = Transaction::new(&[&keypair1], message, Hash::default());
         let txs = vec![tx0, tx1];
-        let results1 = accounts.lock_accounts(&txs, None);
+        let results1 = accounts.lock_accounts(&txs, None, Some(MAX_TX_ACCOUNT_LOCKS));
 
         assert!(results1[0].is_ok()); // Read-only account (keypair1) can be referenced multiple times
         assert!(results1[1].is_err()); // Read-only account (keypair1) cannot also be locked as writable
@@ -1645,7 +1838,7 @@ mod tests {
             instructions,
         );
         let tx = Transaction::new(&[&keypair1], message, Hash::default());
-        let results2 = accounts.lock_accounts(&[tx], None);
+        let results2 = accounts.lock_accounts(&[tx], None, Some(MAX_TX_ACCOUNT_LOCKS));
 
         assert!(results2[0].is_ok()); // Now keypair1 account can be locked as writable
 
@@ -1707,7 +1900,10 @@ mod tests {
             let exit_clone = exit_clone.clone();
             loop {
                 let txs = vec![writable_tx.clone()];
-                let results = accounts_clone.clone().lock_accounts(&txs, None);
+                let results =
+                    accounts_clone
+                        .clone()
+                        .lock_accounts(&txs, None, Some(MAX_TX_ACCOUNT_LOCKS));
                 for result in results.iter() {
                     if result.is_ok() {
                         counter_clone.clone().fetch_add(1, Ordering::SeqCst);
@@ -1722,7 +1918,10 @@ mod tests {
         let counter_clone = counter;
         for _ in 0..5 {
             let txs = vec![readonly_tx.clone()];
-            let results = accounts_arc.clone().lock_accounts(&txs, None);
+            let results =
+                accounts_arc
+                    .clone()
+                    .lock_accounts(&txs, None, Some(MAX_TX_ACCOUNT_LOCKS));
             if results[0].is_ok() {
                 let counter_value = counter_clone.clone().load(Ordering::SeqCst);
                 thread::sleep(time::Duration::from_millis(50));
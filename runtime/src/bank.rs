@@ -5,7 +5,7 @@
 use crate::{
     accounts::{
         AccountAddressFilter, Accounts, TransactionAccounts, TransactionLoadResult,
-        TransactionLoaders,
+        TransactionLoaders, MAX_TX_ACCOUNT_LOCKS, MAX_TX_ACCOUNT_LOCKS_INCREASED,
     },
     accounts_db::{ErrorCounters, SnapshotStorages},
     accounts_index::Ancestors,
@@ -35,6 +35,7 @@ use solana_sdk::{
         MAX_PROCESSING_AGE, MAX_RECENT_BLOCKHASHES, MAX_TRANSACTION_FORWARDING_DELAY,
         SECONDS_PER_DAY,
     },
+    compute_profiler::ComputeProfiler,
     epoch_info::EpochInfo,
     epoch_schedule::EpochSchedule,
     feature,
@@ -50,10 +51,14 @@ use solana_sdk::{
     native_loader,
     native_token::sol_to_lamports,
     nonce, nonce_account,
-    process_instruction::{BpfComputeBudget, Executor, ProcessInstructionWithContext},
+    process_instruction::{
+        BpfComputeBudget, Executor, ProcessInstructionWithContext, ProgramLogRecord,
+        SyscallCostOverrides, SyscallFilter, SyscallTraceCollector,
+    },
     program_utils::limited_deserialize,
     pubkey::Pubkey,
     recent_blockhashes_account,
+    runtime_version::RuntimeVersion,
     sanitize::Sanitize,
     signature::{Keypair, Signature},
     slot_hashes::SlotHashes,
@@ -404,6 +409,9 @@ pub type InnerInstructionsList = Vec<InnerInstructions>;
 /// A list of log messages emitted during a transaction
 pub type TransactionLogMessages = Vec<String>;
 
+/// Structured form of the program messages among a transaction's log messages
+pub type TransactionLogRecords = Vec<ProgramLogRecord>;
+
 #[derive(Serialize, Deserialize, AbiExample, AbiEnumVisitor, Debug, PartialEq)]
 pub enum TransactionLogCollectorFilter {
     All,
@@ -430,6 +438,7 @@ pub struct TransactionLogInfo {
     pub result: Result<()>,
     pub is_vote: bool,
     pub log_messages: TransactionLogMessages,
+    pub log_records: TransactionLogRecords,
 }
 
 #[derive(AbiExample, Default, Debug)]
@@ -711,6 +720,37 @@ pub struct RewardInfo {
     pub post_balance: u64, // Account balance in lamports after `lamports` was applied
 }
 
+/// Lamport flows accounted for by a bank, see `Bank::accounting_summary()`
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct BankAccountingSummary {
+    /// Transaction fees collected from fee payers
+    pub collected_fees: u64,
+    /// Portion of `collected_fees` that is burned
+    pub burned_fees: u64,
+    /// Rent collected from rent-paying accounts
+    pub collected_rent: u64,
+    /// Portion of `collected_rent` that is burned
+    pub burned_rent: u64,
+    /// Fees distributed to the collector
+    pub fee_rewards: i64,
+    /// Rent distributed to validators
+    pub rent_rewards: i64,
+    /// Inflation rewards distributed to stake accounts
+    pub staking_rewards: i64,
+    /// Inflation rewards distributed to vote accounts
+    pub voting_rewards: i64,
+    /// Capitalization as tracked by the bank
+    pub capitalization: u64,
+    /// Capitalization as calculated from the balances of all accounts
+    pub calculated_capitalization: u64,
+}
+impl BankAccountingSummary {
+    /// Whether the tracked capitalization matches the sum of all account balances
+    pub fn is_conserved(&self) -> bool {
+        self.capitalization == self.calculated_capitalization
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct OptionalDropCallback(Option<Box<dyn DropCallback + Send + Sync>>);
 
@@ -831,6 +871,12 @@ pub struct Bank {
 
     bpf_compute_budget: Option<BpfComputeBudget>,
 
+    /// Maximum number of cross-program invoked instructions recorded per transaction
+    max_instruction_trace_length: Option<usize>,
+
+    /// Maximum number of accounts a transaction may lock, overrides the feature gated limit
+    transaction_account_lock_limit: Option<usize>,
+
     /// Builtin programs activated dynamically by feature
     feature_builtins: Arc<Vec<(Builtin, Pubkey, ActivationType)>>,
 
@@ -998,6 +1044,8 @@ impl Bank {
             signature_count: AtomicU64::new(0),
             message_processor: parent.message_processor.clone(),
             bpf_compute_budget: parent.bpf_compute_budget,
+            max_instruction_trace_length: parent.max_instruction_trace_length,
+            transaction_account_lock_limit: parent.transaction_account_lock_limit,
             feature_builtins: parent.feature_builtins.clone(),
             hard_forks: parent.hard_forks.clone(),
             last_vote_sync: AtomicU64::new(parent.last_vote_sync.load(Relaxed)),
@@ -1128,6 +1176,8 @@ impl Bank {
             is_delta: AtomicBool::new(fields.is_delta),
             message_processor: new(),
             bpf_compute_budget: None,
+            max_instruction_trace_length: None,
+            transaction_account_lock_limit: None,
             feature_builtins: new(),
             last_vote_sync: new(),
             rewards: new(),
@@ -2300,10 +2350,11 @@ impl Bank {
         txs: &'b [Transaction],
         iteration_order: Option<Vec<usize>>,
     ) -> TransactionBatch<'a, 'b> {
-        let results = self
-            .rc
-            .accounts
-            .lock_accounts(txs, iteration_order.as_deref());
+        let results = self.rc.accounts.lock_accounts(
+            txs,
+            iteration_order.as_deref(),
+            self.get_transaction_account_lock_limit(),
+        );
         TransactionBatch::new(results, &self, txs, iteration_order)
     }
 
@@ -2311,9 +2362,13 @@ impl Bank {
         &'a self,
         txs: &'b [Transaction],
     ) -> TransactionBatch<'a, 'b> {
+        let tx_account_lock_limit = self.get_transaction_account_lock_limit();
         let lock_results: Vec<_> = txs
             .iter()
-            .map(|tx| tx.sanitize().map_err(|e| e.into()))
+            .map(|tx| {
+                tx.sanitize()?;
+                Accounts::check_account_lock_limit(tx, tx_account_lock_limit)
+            })
             .collect();
         let mut batch = TransactionBatch::new(lock_results, &self, txs, None);
         batch.needs_unlock = false;
@@ -2791,6 +2846,7 @@ impl Bank {
         let mut inner_instructions: Vec<Option<InnerInstructionsList>> =
             Vec::with_capacity(txs.len());
         let mut transaction_log_messages = Vec::with_capacity(txs.len());
+        let mut transaction_log_records: Vec<TransactionLogRecords> = Vec::with_capacity(txs.len());
         let bpf_compute_budget = self
             .bpf_compute_budget
             .unwrap_or_else(|| BpfComputeBudget::new(&self.feature_set));
@@ -2809,10 +2865,10 @@ impl Bank {
                         Self::accounts_to_refcells(accounts, loaders);
 
                     let instruction_recorders = if enable_cpi_recording {
-                        let ix_count = tx.message.instructions.len();
-                        let mut recorders = Vec::with_capacity(ix_count);
-                        recorders.resize_with(ix_count, InstructionRecorder::default);
-                        Some(recorders)
+                        Some(InstructionRecorder::new_for_transaction(
+                            tx.message.instructions.len(),
+                            self.max_instruction_trace_length,
+                        ))
                     } else {
                         None
                     };
@@ -2825,6 +2881,7 @@ impl Bank {
 
                     let process_result = self.message_processor.process_message(
                         tx.message(),
+                        &tx.signatures,
                         &loader_refcells,
                         &account_refcells,
                         &self.rent_collector,
@@ -2835,13 +2892,22 @@ impl Bank {
                         bpf_compute_budget,
                     );
 
+                    if let (Some(log_collector), Some(recorders)) =
+                        (&log_collector, &instruction_recorders)
+                    {
+                        if recorders.iter().any(|recorder| recorder.is_truncated()) {
+                            log_collector.log("Instruction trace truncated");
+                        }
+                    }
+
                     if enable_log_recording {
-                        let log_messages: TransactionLogMessages =
+                        let (log_messages, log_records) =
                             Rc::try_unwrap(log_collector.unwrap_or_default())
                                 .unwrap_or_default()
-                                .into();
+                                .into_messages_and_records();
 
                         transaction_log_messages.push(log_messages);
+                        transaction_log_records.push(log_records);
                     }
 
                     Self::compile_recorded_instructions(
@@ -2938,6 +3004,7 @@ impl Bank {
                         result: r.clone(),
                         is_vote,
                         log_messages: transaction_log_messages.get(i).cloned().unwrap_or_default(),
+                        log_records: transaction_log_records.get(i).cloned().unwrap_or_default(),
                     });
                 }
             }
@@ -3875,6 +3942,78 @@ impl Bank {
         self.bpf_compute_budget = bpf_compute_budget;
     }
 
+    /// Limit the number of cross-program invoked instructions recorded per transaction,
+    /// further instructions are dropped and the transaction logs note the truncation
+    pub fn set_max_instruction_trace_length(
+        &mut self,
+        max_instruction_trace_length: Option<usize>,
+    ) {
+        self.max_instruction_trace_length = max_instruction_trace_length;
+    }
+
+    /// Maximum number of accounts a transaction may lock in this bank, if limited
+    pub fn get_transaction_account_lock_limit(&self) -> Option<usize> {
+        if self.transaction_account_lock_limit.is_some() {
+            self.transaction_account_lock_limit
+        } else if self
+            .feature_set
+            .is_active(&feature_set::increase_tx_account_lock_limit::id())
+        {
+            Some(MAX_TX_ACCOUNT_LOCKS_INCREASED)
+        } else if self
+            .feature_set
+            .is_active(&feature_set::limit_tx_account_locks::id())
+        {
+            Some(MAX_TX_ACCOUNT_LOCKS)
+        } else {
+            None
+        }
+    }
+
+    pub fn set_transaction_account_lock_limit(
+        &mut self,
+        transaction_account_lock_limit: Option<usize>,
+    ) {
+        self.transaction_account_lock_limit = transaction_account_lock_limit;
+    }
+
+    pub fn set_syscall_cost_overrides(
+        &mut self,
+        syscall_cost_overrides: Option<SyscallCostOverrides>,
+    ) {
+        self.message_processor
+            .set_syscall_cost_overrides(syscall_cost_overrides);
+    }
+
+    pub fn set_syscall_filter(&mut self, syscall_filter: Option<SyscallFilter>) {
+        self.message_processor.set_syscall_filter(syscall_filter);
+    }
+
+    #[cfg(feature = "syscall-fault-injection")]
+    pub fn set_syscall_faults(
+        &mut self,
+        syscall_faults: Option<solana_sdk::process_instruction::SyscallFaults>,
+    ) {
+        self.message_processor.set_syscall_faults(syscall_faults);
+    }
+
+    pub fn set_syscall_trace_collector(
+        &mut self,
+        syscall_trace_collector: Option<Arc<dyn SyscallTraceCollector>>,
+    ) {
+        self.message_processor
+            .set_syscall_trace_collector(syscall_trace_collector);
+    }
+
+    pub fn set_compute_profiler(&mut self, compute_profiler: Option<Arc<ComputeProfiler>>) {
+        self.message_processor
+            .set_compute_profiler(compute_profiler);
+    }
+
+    pub fn set_runtime_version(&mut self, runtime_version: Option<RuntimeVersion>) {
+        self.message_processor.set_runtime_version(runtime_version);
+    }
+
     pub fn hard_forks(&self) -> Arc<RwLock<HardForks>> {
         self.hard_forks.clone()
     }
@@ -4104,6 +4243,36 @@ impl Bank {
         self.rc.accounts.calculate_capitalization(&self.ancestors)
     }
 
+    /// Summarize the fees, rent and rewards accounted for by this bank.  The fee and rent
+    /// distributions only happen when the bank is frozen, so call this on a frozen bank
+    /// to see the complete picture
+    pub fn accounting_summary(&self) -> BankAccountingSummary {
+        let collected_fees = self.collector_fees.load(Relaxed);
+        let (_unburned, burned_fees) = self.fee_rate_governor.burn(collected_fees);
+        let collected_rent = self.collected_rent.load(Relaxed);
+        let (burned_rent, _distributed) = self.rent_collector.rent.calculate_burn(collected_rent);
+
+        let mut summary = BankAccountingSummary {
+            collected_fees,
+            burned_fees,
+            collected_rent,
+            burned_rent,
+            capitalization: self.capitalization(),
+            calculated_capitalization: self.calculate_capitalization(),
+            ..BankAccountingSummary::default()
+        };
+        for (_pubkey, reward_info) in self.rewards.read().unwrap().iter() {
+            let rewards = match reward_info.reward_type {
+                RewardType::Fee => &mut summary.fee_rewards,
+                RewardType::Rent => &mut summary.rent_rewards,
+                RewardType::Staking => &mut summary.staking_rewards,
+                RewardType::Voting => &mut summary.voting_rewards,
+            };
+            *rewards += reward_info.lamports;
+        }
+        summary
+    }
+
     pub fn calculate_and_verify_capitalization(&self) -> bool {
         let calculated = self.calculate_capitalization();
         let expected = self.capitalization();
@@ -4712,7 +4881,7 @@ pub(crate) mod tests {
         message::{Message, MessageHeader},
         nonce,
         poh_config::PohConfig,
-        process_instruction::InvokeContext,
+        process_instruction::{InvokeContext, ProgramLogKind},
         rent::Rent,
         signature::{keypair_from_seed, Keypair, Signer},
         system_instruction::{self, SystemError},
@@ -7100,6 +7269,47 @@ pub(crate) mod tests {
         );
     }
 
+    #[test]
+    fn test_bank_accounting_summary() {
+        let leader = solana_sdk::pubkey::new_rand();
+        let GenesisConfigInfo {
+            mut genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config_with_leader(10_000, &leader, 3);
+        genesis_config.fee_rate_governor = FeeRateGovernor::new(4, 0);
+        let expected_fee_paid = genesis_config
+            .fee_rate_governor
+            .create_fee_calculator()
+            .lamports_per_signature;
+        let (expected_fee_collected, expected_fee_burned) =
+            genesis_config.fee_rate_governor.burn(expected_fee_paid);
+
+        let mut bank = Bank::new(&genesis_config);
+        let tx = system_transaction::transfer(
+            &mint_keypair,
+            &solana_sdk::pubkey::new_rand(),
+            42,
+            bank.last_blockhash(),
+        );
+        assert_eq!(bank.process_transaction(&tx), Ok(()));
+        goto_end_of_slot(&mut bank);
+
+        let summary = bank.accounting_summary();
+        assert_eq!(summary.collected_fees, expected_fee_paid);
+        assert_eq!(summary.burned_fees, expected_fee_burned);
+        assert_eq!(summary.fee_rewards, expected_fee_collected as i64);
+        assert_eq!(summary.rent_rewards, 0);
+        assert_eq!(summary.staking_rewards, 0);
+        assert_eq!(summary.voting_rewards, 0);
+        assert_eq!(summary.capitalization, bank.capitalization());
+        assert!(summary.is_conserved());
+
+        // Lamports appearing out of thin air break conservation
+        bank.capitalization.fetch_add(1, Relaxed);
+        assert!(!bank.accounting_summary().is_conserved());
+    }
+
     #[test]
     fn test_bank_tx_fee() {
         solana_logger::setup();
@@ -10586,6 +10796,146 @@ pub(crate) mod tests {
         assert!(bank.feature_set.is_active(&test_feature));
     }
 
+    #[test]
+    fn test_transaction_account_lock_limit() {
+        let (genesis_config, mint_keypair) = create_genesis_config(100_000);
+        let mut bank = Bank::new(&genesis_config);
+        let blockhash = bank.last_blockhash();
+        let new_tx = |num_account_keys: usize| {
+            let mut account_keys = vec![mint_keypair.pubkey()];
+            account_keys.extend((1..num_account_keys).map(|_| solana_sdk::pubkey::new_rand()));
+            let message =
+                Message::new_with_compiled_instructions(1, 0, 0, account_keys, blockhash, vec![]);
+            Transaction::new(&[&mint_keypair], message, blockhash)
+        };
+        let with_features = |features: &[Pubkey]| {
+            let mut feature_set = FeatureSet::default();
+            for feature in features {
+                feature_set.inactive.remove(feature);
+                feature_set.active.insert(*feature, 0);
+            }
+            Arc::new(feature_set)
+        };
+        // Results of locking `txs` for execution and for simulation
+        let lock_results = |bank: &Bank, txs: &[Transaction]| {
+            let batch = bank.prepare_batch(txs, None);
+            let simulation_batch = bank.prepare_simulation_batch(txs);
+            (
+                batch.lock_results().clone(),
+                simulation_batch.lock_results().clone(),
+            )
+        };
+        let too_many = Err(TransactionError::TooManyAccountLocks);
+
+        // Without the feature transactions may lock any number of accounts
+        bank.feature_set = with_features(&[]);
+        assert_eq!(bank.get_transaction_account_lock_limit(), None);
+        let txs = [new_tx(MAX_TX_ACCOUNT_LOCKS_INCREASED + 1)];
+        assert_eq!(lock_results(&bank, &txs), (vec![Ok(())], vec![Ok(())]));
+
+        // With the feature the limit applies to execution and simulation alike
+        bank.feature_set = with_features(&[feature_set::limit_tx_account_locks::id()]);
+        assert_eq!(
+            bank.get_transaction_account_lock_limit(),
+            Some(MAX_TX_ACCOUNT_LOCKS)
+        );
+        let txs = [new_tx(MAX_TX_ACCOUNT_LOCKS)];
+        assert_eq!(lock_results(&bank, &txs), (vec![Ok(())], vec![Ok(())]));
+        let txs = [new_tx(MAX_TX_ACCOUNT_LOCKS + 1)];
+        assert_eq!(
+            lock_results(&bank, &txs),
+            (vec![too_many.clone()], vec![too_many.clone()])
+        );
+
+        // The increased limit takes precedence
+        bank.feature_set = with_features(&[
+            feature_set::limit_tx_account_locks::id(),
+            feature_set::increase_tx_account_lock_limit::id(),
+        ]);
+        assert_eq!(lock_results(&bank, &txs), (vec![Ok(())], vec![Ok(())]));
+        let txs = [new_tx(MAX_TX_ACCOUNT_LOCKS_INCREASED + 1)];
+        assert_eq!(
+            lock_results(&bank, &txs),
+            (vec![too_many.clone()], vec![too_many.clone()])
+        );
+
+        // And the override takes precedence over both, with or without the features
+        bank.set_transaction_account_lock_limit(Some(2));
+        let txs = [new_tx(3)];
+        assert_eq!(
+            lock_results(&bank, &txs),
+            (vec![too_many.clone()], vec![too_many.clone()])
+        );
+        bank.feature_set = with_features(&[]);
+        assert_eq!(
+            lock_results(&bank, &txs),
+            (vec![too_many.clone()], vec![too_many])
+        );
+        let txs = [new_tx(2)];
+        assert_eq!(lock_results(&bank, &txs), (vec![Ok(())], vec![Ok(())]));
+    }
+
+    #[test]
+    fn test_transaction_log_records() {
+        let (genesis_config, mint_keypair) = create_genesis_config(100_000);
+        let mut bank = Bank::new(&genesis_config);
+        bank.transaction_log_collector_config
+            .write()
+            .unwrap()
+            .filter = TransactionLogCollectorFilter::All;
+
+        fn mock_log_processor(
+            program_id: &Pubkey,
+            _keyed_accounts: &[KeyedAccount],
+            instruction_data: &[u8],
+            invoke_context: &mut dyn InvokeContext,
+        ) -> std::result::Result<(), InstructionError> {
+            invoke_context
+                .get_logger()
+                .borrow()
+                .log_record(&ProgramLogRecord {
+                    program_id: *program_id,
+                    stack_height: invoke_context.invoke_depth(),
+                    kind: ProgramLogKind::U64s([u64::from(instruction_data[0]), 0, 0, 0, 0]),
+                });
+            Ok(())
+        }
+        let mock_program_id = solana_sdk::pubkey::new_rand();
+        bank.add_builtin("mock_log_program", mock_program_id, mock_log_processor);
+
+        let instructions = [
+            Instruction::new(mock_program_id, &1u8, vec![]),
+            Instruction::new(mock_program_id, &2u8, vec![]),
+        ];
+        let message = Message::new(&instructions, Some(&mint_keypair.pubkey()));
+        let tx = Transaction::new(&[&mint_keypair], message, bank.last_blockhash());
+        let txs = [tx];
+        let batch = bank.prepare_batch(&txs, None);
+        let (results, _, _, transaction_logs) = bank.load_execute_and_commit_transactions(
+            &batch,
+            MAX_PROCESSING_AGE,
+            false,
+            false,
+            true,
+        );
+        assert_eq!(results.fee_collection_results, vec![Ok(())]);
+
+        let records = (1..=2)
+            .map(|arg| ProgramLogRecord {
+                program_id: mock_program_id,
+                stack_height: 1,
+                kind: ProgramLogKind::U64s([arg, 0, 0, 0, 0]),
+            })
+            .collect::<Vec<_>>();
+        let logs = bank.get_transaction_logs(None).unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].log_records, records);
+        assert_eq!(logs[0].log_messages, transaction_logs[0]);
+        for record in records.iter() {
+            assert!(logs[0].log_messages.contains(&record.to_string()));
+        }
+    }
+
     #[test]
     fn test_spl_token_v2_multisig_fix() {
         let (genesis_config, _mint_keypair) = create_genesis_config(0);
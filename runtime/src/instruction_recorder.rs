@@ -5,13 +5,44 @@ use solana_sdk::{
     message::Message,
 };
 
+/// Instruction count shared by all of a transaction's recorders
+#[derive(Debug)]
+struct InstructionTrace {
+    max_length: usize,
+    length: usize,
+    truncated: bool,
+}
+
 /// Records and compiles cross-program invoked instructions
 #[derive(Clone, Default)]
 pub struct InstructionRecorder {
     inner: Rc<RefCell<Vec<Instruction>>>,
+    trace: Option<Rc<RefCell<InstructionTrace>>>,
 }
 
 impl InstructionRecorder {
+    /// Create a recorder for each of a transaction's `instruction_count` instructions.  If
+    /// `max_trace_length` is set, the recorders together record at most that many
+    /// instructions and any further instructions truncate the trace
+    pub fn new_for_transaction(
+        instruction_count: usize,
+        max_trace_length: Option<usize>,
+    ) -> Vec<Self> {
+        let trace = max_trace_length.map(|max_length| {
+            Rc::new(RefCell::new(InstructionTrace {
+                max_length,
+                length: 0,
+                truncated: false,
+            }))
+        });
+        (0..instruction_count)
+            .map(|_| Self {
+                inner: Rc::default(),
+                trace: trace.clone(),
+            })
+            .collect()
+    }
+
     pub fn compile_instructions(&self, message: &Message) -> Vec<CompiledInstruction> {
         self.inner
             .borrow()
@@ -21,6 +52,52 @@ impl InstructionRecorder {
     }
 
     pub fn record_instruction(&self, instruction: Instruction) {
+        if let Some(trace) = &self.trace {
+            let mut trace = trace.borrow_mut();
+            if trace.length >= trace.max_length {
+                trace.truncated = true;
+                return;
+            }
+            trace.length += 1;
+        }
         self.inner.borrow_mut().push(instruction);
     }
+
+    /// Whether instructions were dropped because the trace reached its maximum length
+    pub fn is_truncated(&self) -> bool {
+        self.trace
+            .as_ref()
+            .map(|trace| trace.borrow().truncated)
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn test_instruction_trace_truncation() {
+        let instruction = Instruction::new(Pubkey::new_unique(), &0u8, vec![]);
+
+        let recorders = InstructionRecorder::new_for_transaction(2, None);
+        for _ in 0..10 {
+            recorders[0].record_instruction(instruction.clone());
+        }
+        assert_eq!(recorders[0].inner.borrow().len(), 10);
+        assert!(!recorders[0].is_truncated());
+
+        // The limit spans all of the transaction's recorders
+        let recorders = InstructionRecorder::new_for_transaction(2, Some(3));
+        recorders[0].record_instruction(instruction.clone());
+        recorders[1].record_instruction(instruction.clone());
+        recorders[1].record_instruction(instruction.clone());
+        assert!(!recorders[1].is_truncated());
+        recorders[0].record_instruction(instruction);
+        assert_eq!(recorders[0].inner.borrow().len(), 1);
+        assert_eq!(recorders[1].inner.borrow().len(), 2);
+        assert!(recorders[0].is_truncated());
+        assert!(recorders[1].is_truncated());
+    }
 }
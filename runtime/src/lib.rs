@@ -26,6 +26,7 @@ pub mod snapshot_package;
 pub mod snapshot_utils;
 pub mod stakes;
 pub mod status_cache;
+pub mod syscall_trace_recorder;
 mod system_instruction_processor;
 pub mod transaction_batch;
 pub mod transaction_utils;
@@ -1,3 +1,4 @@
+use solana_sdk::process_instruction::ProgramLogRecord;
 use std::cell::RefCell;
 
 const LOG_MESSAGES_BYTES_LIMIT: usize = 10 * 1000;
@@ -5,6 +6,7 @@ const LOG_MESSAGES_BYTES_LIMIT: usize = 10 * 1000;
 #[derive(Default)]
 struct LogCollectorInner {
     messages: Vec<String>,
+    records: Vec<ProgramLogRecord>,
     bytes_written: usize,
     limit_warning: bool,
 }
@@ -16,6 +18,28 @@ pub struct LogCollector {
 
 impl LogCollector {
     pub fn log(&self, message: &str) {
+        self.push(message);
+    }
+
+    /// Log a program's message, keeping its structured form alongside the string rendering
+    pub fn log_record(&self, record: &ProgramLogRecord) {
+        if self.push(&record.to_string()) {
+            self.inner.borrow_mut().records.push(record.clone());
+        }
+    }
+
+    /// Structured form of the program messages logged so far, in order
+    pub fn get_records(&self) -> Vec<ProgramLogRecord> {
+        self.inner.borrow().records.clone()
+    }
+
+    /// The logged messages along with the structured form of the program messages among them
+    pub fn into_messages_and_records(self) -> (Vec<String>, Vec<ProgramLogRecord>) {
+        let inner = self.inner.into_inner();
+        (inner.messages, inner.records)
+    }
+
+    fn push(&self, message: &str) -> bool {
         let mut inner = self.inner.borrow_mut();
 
         if inner.bytes_written + message.len() >= LOG_MESSAGES_BYTES_LIMIT {
@@ -23,9 +47,11 @@ impl LogCollector {
                 inner.limit_warning = true;
                 inner.messages.push(String::from("Log truncated"));
             }
+            false
         } else {
             inner.bytes_written += message.len();
             inner.messages.push(message.to_string());
+            true
         }
     }
 }
@@ -39,6 +65,7 @@ impl Into<Vec<String>> for LogCollector {
 #[cfg(test)]
 pub(crate) mod tests {
     use super::*;
+    use solana_sdk::{process_instruction::ProgramLogKind, pubkey::Pubkey};
 
     #[test]
     fn test_log_messages_bytes_limit() {
@@ -55,4 +82,81 @@ pub(crate) mod tests {
         }
         assert_eq!(logs.last(), Some(&"Log truncated".to_string()));
     }
+
+    #[test]
+    fn test_log_records() {
+        let lc = LogCollector::default();
+        let program_id = solana_sdk::pubkey::new_rand();
+        let records = vec![
+            ProgramLogRecord {
+                program_id,
+                stack_height: 1,
+                kind: ProgramLogKind::Msg("hello".to_string()),
+            },
+            ProgramLogRecord {
+                program_id,
+                stack_height: 2,
+                kind: ProgramLogKind::U64s([1, 2, 3, 4, 5]),
+            },
+            ProgramLogRecord {
+                program_id,
+                stack_height: 1,
+                kind: ProgramLogKind::Pubkey(program_id),
+            },
+            ProgramLogRecord {
+                program_id,
+                stack_height: 1,
+                kind: ProgramLogKind::ComputeUnits(42),
+            },
+            ProgramLogRecord {
+                program_id,
+                stack_height: 1,
+                kind: ProgramLogKind::Data(vec![0, 1, 2, 0xfe, 0xff]),
+            },
+            ProgramLogRecord {
+                program_id,
+                stack_height: 1,
+                kind: ProgramLogKind::AbortCode(7),
+            },
+        ];
+
+        lc.log("Program invoke [1]");
+        for record in records.iter() {
+            lc.log_record(record);
+        }
+        assert_eq!(lc.get_records(), records);
+
+        let (logs, logged_records) = lc.into_messages_and_records();
+        assert_eq!(logged_records, records);
+        assert_eq!(
+            logs,
+            vec![
+                "Program invoke [1]".to_string(),
+                "Program log: hello".to_string(),
+                "Program log: 0x1, 0x2, 0x3, 0x4, 0x5".to_string(),
+                format!("Program log: {}", program_id),
+                "Program consumption: 42 units remaining".to_string(),
+                "Program data: AAEC/v8=".to_string(),
+                "Program aborted with code 7".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_log_records_bytes_limit() {
+        let lc = LogCollector::default();
+        let record = ProgramLogRecord {
+            program_id: Pubkey::default(),
+            stack_height: 1,
+            kind: ProgramLogKind::Msg("x".repeat(LOG_MESSAGES_BYTES_LIMIT / 2)),
+        };
+
+        lc.log_record(&record);
+        lc.log_record(&record);
+        assert_eq!(lc.get_records(), vec![record]);
+
+        let logs: Vec<_> = lc.into();
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs.last(), Some(&"Log truncated".to_string()));
+    }
 }
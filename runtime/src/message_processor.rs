@@ -7,17 +7,22 @@ use serde::{Deserialize, Serialize};
 use solana_sdk::{
     account::Account,
     clock::Epoch,
+    compute_profiler::{ComputeProfiler, InvocationProfiler},
     feature_set::{instructions_sysvar_enabled, FeatureSet},
+    hash::Hash,
     instruction::{CompiledInstruction, Instruction, InstructionError},
     keyed_account::{create_keyed_readonly_accounts, KeyedAccount},
     message::Message,
     native_loader,
     process_instruction::{
         BpfComputeBudget, ComputeMeter, Executor, InvokeContext, Logger,
-        ProcessInstructionWithContext,
+        ProcessInstructionWithContext, ProgramLogRecord, SyscallCostOverrides, SyscallFaults,
+        SyscallFilter, SyscallTraceCollector, RUNTIME_VERSION,
     },
     pubkey::Pubkey,
     rent::Rent,
+    runtime_version::RuntimeVersion,
+    signature::Signature,
     system_program,
     transaction::TransactionError,
 };
@@ -201,6 +206,35 @@ impl ComputeMeter for ThisComputeMeter {
         self.remaining
     }
 }
+/// Host-configurable hooks into program execution, shared by the invoke
+/// contexts of every message a `MessageProcessor` processes
+#[derive(Clone, Debug, Default)]
+pub struct ExecutionHooks {
+    /// Syscall costs that take precedence over the `BpfComputeBudget`
+    pub syscall_cost_overrides: Option<Arc<SyscallCostOverrides>>,
+    /// Restricts which syscalls are enabled
+    pub syscall_filter: Option<Arc<SyscallFilter>>,
+    /// Faults injected into syscalls
+    #[cfg(feature = "syscall-fault-injection")]
+    pub syscall_faults: Option<Arc<SyscallFaults>>,
+    /// Collects a trace of every syscall made
+    pub syscall_trace_collector: Option<Arc<dyn SyscallTraceCollector>>,
+    /// Records the compute consumed by syscalls and invocations
+    pub compute_profiler: Option<Arc<ComputeProfiler>>,
+    /// Version presented to programs instead of `RUNTIME_VERSION`
+    pub runtime_version: Option<RuntimeVersion>,
+}
+
+/// The transaction whose message is being processed
+#[derive(Clone, Debug, Default)]
+pub struct TransactionInfo<'a> {
+    pub signatures: &'a [Signature],
+    pub recent_blockhash: Hash,
+    pub fee_payer: Pubkey,
+    /// Number of pseudorandom draws made so far by any of the transaction's instructions
+    pub pseudorandom_counter: Rc<RefCell<u64>>,
+}
+
 pub struct ThisInvokeContext<'a> {
     program_ids: Vec<Pubkey>,
     rent: Rent,
@@ -208,12 +242,17 @@ pub struct ThisInvokeContext<'a> {
     programs: &'a [(Pubkey, ProcessInstructionWithContext)],
     logger: Rc<RefCell<dyn Logger>>,
     bpf_compute_budget: BpfComputeBudget,
+    hooks: ExecutionHooks,
+    transaction: TransactionInfo<'a>,
+    compute_profiler: Option<Rc<RefCell<InvocationProfiler>>>,
     compute_meter: Rc<RefCell<dyn ComputeMeter>>,
     executors: Rc<RefCell<Executors>>,
     instruction_recorder: Option<InstructionRecorder>,
     feature_set: Arc<FeatureSet>,
+    abort_code: Option<u64>,
 }
 impl<'a> ThisInvokeContext<'a> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         program_id: &Pubkey,
         rent: Rent,
@@ -221,6 +260,8 @@ impl<'a> ThisInvokeContext<'a> {
         programs: &'a [(Pubkey, ProcessInstructionWithContext)],
         log_collector: Option<Rc<LogCollector>>,
         bpf_compute_budget: BpfComputeBudget,
+        hooks: ExecutionHooks,
+        transaction: TransactionInfo<'a>,
         executors: Rc<RefCell<Executors>>,
         instruction_recorder: Option<InstructionRecorder>,
         feature_set: Arc<FeatureSet>,
@@ -234,12 +275,18 @@ impl<'a> ThisInvokeContext<'a> {
             programs,
             logger: Rc::new(RefCell::new(ThisLogger { log_collector })),
             bpf_compute_budget,
+            compute_profiler: hooks.compute_profiler.clone().map(|compute_profiler| {
+                Rc::new(RefCell::new(InvocationProfiler::new(compute_profiler)))
+            }),
+            hooks,
+            transaction,
             compute_meter: Rc::new(RefCell::new(ThisComputeMeter {
                 remaining: bpf_compute_budget.max_units,
             })),
             executors,
             instruction_recorder,
             feature_set,
+            abort_code: None,
         }
     }
 }
@@ -253,10 +300,20 @@ impl<'a> InvokeContext for ThisInvokeContext<'a> {
             return Err(InstructionError::ReentrancyNotAllowed);
         }
         self.program_ids.push(*key);
+        if let Some(compute_profiler) = &self.compute_profiler {
+            compute_profiler
+                .borrow_mut()
+                .enter_frame(key, self.compute_meter.borrow().get_remaining());
+        }
         Ok(())
     }
     fn pop(&mut self) {
         self.program_ids.pop();
+        if let Some(compute_profiler) = &self.compute_profiler {
+            compute_profiler
+                .borrow_mut()
+                .exit_frame(self.compute_meter.borrow().get_remaining());
+        }
     }
     fn invoke_depth(&self) -> usize {
         self.program_ids.len()
@@ -284,6 +341,9 @@ impl<'a> InvokeContext for ThisInvokeContext<'a> {
             .last()
             .ok_or(InstructionError::GenericError)
     }
+    fn get_program_ids(&self) -> &[Pubkey] {
+        &self.program_ids
+    }
     fn get_programs(&self) -> &[(Pubkey, ProcessInstructionWithContext)] {
         self.programs
     }
@@ -296,6 +356,44 @@ impl<'a> InvokeContext for ThisInvokeContext<'a> {
     fn get_compute_meter(&self) -> Rc<RefCell<dyn ComputeMeter>> {
         self.compute_meter.clone()
     }
+    fn get_syscall_cost_override(&self, syscall_name: &str) -> Option<u64> {
+        self.hooks
+            .syscall_cost_overrides
+            .as_ref()
+            .and_then(|overrides| overrides.get(syscall_name).copied())
+    }
+    fn get_syscall_filter(&self) -> Option<Arc<SyscallFilter>> {
+        self.hooks.syscall_filter.clone()
+    }
+    #[cfg(feature = "syscall-fault-injection")]
+    fn get_syscall_faults(&self) -> Option<Arc<SyscallFaults>> {
+        self.hooks.syscall_faults.clone()
+    }
+    #[cfg(not(feature = "syscall-fault-injection"))]
+    fn get_syscall_faults(&self) -> Option<Arc<SyscallFaults>> {
+        None
+    }
+    fn get_syscall_trace_collector(&self) -> Option<Arc<dyn SyscallTraceCollector>> {
+        self.hooks.syscall_trace_collector.clone()
+    }
+    fn get_compute_profiler(&self) -> Option<Rc<RefCell<InvocationProfiler>>> {
+        self.compute_profiler.clone()
+    }
+    fn get_runtime_version(&self) -> RuntimeVersion {
+        self.hooks.runtime_version.unwrap_or(RUNTIME_VERSION)
+    }
+    fn get_transaction_signatures(&self) -> &[Signature] {
+        self.transaction.signatures
+    }
+    fn get_recent_blockhash(&self) -> &Hash {
+        &self.transaction.recent_blockhash
+    }
+    fn get_fee_payer(&self) -> &Pubkey {
+        &self.transaction.fee_payer
+    }
+    fn get_pseudorandom_counter(&self) -> Rc<RefCell<u64>> {
+        self.transaction.pseudorandom_counter.clone()
+    }
     fn add_executor(&self, pubkey: &Pubkey, executor: Arc<dyn Executor>) {
         self.executors.borrow_mut().insert(*pubkey, executor);
     }
@@ -307,6 +405,12 @@ impl<'a> InvokeContext for ThisInvokeContext<'a> {
             recorder.record_instruction(instruction.clone());
         }
     }
+    fn record_abort_code(&mut self, code: u64) {
+        self.abort_code = Some(code);
+    }
+    fn get_abort_code(&self) -> Option<u64> {
+        self.abort_code
+    }
     fn is_feature_active(&self, feature_id: &Pubkey) -> bool {
         self.feature_set.is_active(feature_id)
     }
@@ -324,6 +428,12 @@ impl Logger for ThisLogger {
             log_collector.log(message);
         }
     }
+    fn log_record(&self, record: &ProgramLogRecord) {
+        debug!("{}", record);
+        if let Some(log_collector) = &self.log_collector {
+            log_collector.log_record(record);
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize)]
@@ -332,6 +442,8 @@ pub struct MessageProcessor {
     programs: Vec<(Pubkey, ProcessInstructionWithContext)>,
     #[serde(skip)]
     native_loader: NativeLoader,
+    #[serde(skip)]
+    hooks: ExecutionHooks,
 }
 
 impl std::fmt::Debug for MessageProcessor {
@@ -340,6 +452,7 @@ impl std::fmt::Debug for MessageProcessor {
         struct MessageProcessor<'a> {
             programs: Vec<String>,
             native_loader: &'a NativeLoader,
+            hooks: &'a ExecutionHooks,
         }
 
         // These are just type aliases for work around of Debug-ing above pointers
@@ -363,6 +476,7 @@ impl std::fmt::Debug for MessageProcessor {
                 })
                 .collect::<Vec<_>>(),
             native_loader: &self.native_loader,
+            hooks: &self.hooks,
         };
 
         write!(f, "{:?}", processor)
@@ -374,6 +488,7 @@ impl Default for MessageProcessor {
         Self {
             programs: vec![],
             native_loader: NativeLoader::default(),
+            hooks: ExecutionHooks::default(),
         }
     }
 }
@@ -382,6 +497,7 @@ impl Clone for MessageProcessor {
         MessageProcessor {
             programs: self.programs.clone(),
             native_loader: NativeLoader::default(),
+            hooks: self.hooks.clone(),
         }
     }
 }
@@ -416,6 +532,47 @@ impl MessageProcessor {
         self.add_program(program_id, process_instruction);
     }
 
+    /// Reprice individual syscalls for all subsequently processed messages,
+    /// `None` restores the costs of the `BpfComputeBudget`
+    pub fn set_syscall_cost_overrides(
+        &mut self,
+        syscall_cost_overrides: Option<SyscallCostOverrides>,
+    ) {
+        self.hooks.syscall_cost_overrides = syscall_cost_overrides.map(Arc::new);
+    }
+
+    /// Restrict the syscalls available to programs of all subsequently
+    /// processed messages, `None` enables every syscall
+    pub fn set_syscall_filter(&mut self, syscall_filter: Option<SyscallFilter>) {
+        self.hooks.syscall_filter = syscall_filter.map(Arc::new);
+    }
+
+    /// Inject faults into the syscalls made by programs of all subsequently
+    /// processed messages, `None` runs every syscall normally
+    #[cfg(feature = "syscall-fault-injection")]
+    pub fn set_syscall_faults(&mut self, syscall_faults: Option<SyscallFaults>) {
+        self.hooks.syscall_faults = syscall_faults.map(Arc::new);
+    }
+
+    /// Trace the syscalls made by programs of all subsequently processed messages
+    pub fn set_syscall_trace_collector(
+        &mut self,
+        syscall_trace_collector: Option<Arc<dyn SyscallTraceCollector>>,
+    ) {
+        self.hooks.syscall_trace_collector = syscall_trace_collector;
+    }
+
+    /// Profile the compute consumption of all subsequently processed messages
+    pub fn set_compute_profiler(&mut self, compute_profiler: Option<Arc<ComputeProfiler>>) {
+        self.hooks.compute_profiler = compute_profiler;
+    }
+
+    /// Present `runtime_version` to the programs of all subsequently processed
+    /// messages, `None` restores `RUNTIME_VERSION`
+    pub fn set_runtime_version(&mut self, runtime_version: Option<RuntimeVersion>) {
+        self.hooks.runtime_version = runtime_version;
+    }
+
     /// Create the KeyedAccounts that will be passed to the program
     fn create_keyed_accounts<'a>(
         message: &'a Message,
@@ -755,6 +912,7 @@ impl MessageProcessor {
     fn execute_instruction(
         &self,
         message: &Message,
+        transaction: TransactionInfo,
         instruction: &CompiledInstruction,
         executable_accounts: &[(Pubkey, RefCell<Account>)],
         accounts: &[Rc<RefCell<Account>>],
@@ -789,13 +947,29 @@ impl MessageProcessor {
             &self.programs,
             log_collector,
             bpf_compute_budget,
+            self.hooks.clone(),
+            transaction,
             executors,
             instruction_recorder,
             feature_set,
         );
         let keyed_accounts =
             Self::create_keyed_accounts(message, instruction, executable_accounts, accounts)?;
-        self.process_instruction(&keyed_accounts, &instruction.data, &mut invoke_context)?;
+        let compute_profiler = invoke_context.get_compute_profiler();
+        if let Some(compute_profiler) = &compute_profiler {
+            compute_profiler.borrow_mut().enter_frame(
+                instruction.program_id(&message.account_keys),
+                invoke_context.get_compute_meter().borrow().get_remaining(),
+            );
+        }
+        let result =
+            self.process_instruction(&keyed_accounts, &instruction.data, &mut invoke_context);
+        if let Some(compute_profiler) = &compute_profiler {
+            compute_profiler
+                .borrow_mut()
+                .exit_frame(invoke_context.get_compute_meter().borrow().get_remaining());
+        }
+        result?;
         Self::verify(
             message,
             instruction,
@@ -814,6 +988,7 @@ impl MessageProcessor {
     pub fn process_message(
         &self,
         message: &Message,
+        signatures: &[Signature],
         loaders: &[Vec<(Pubkey, RefCell<Account>)>],
         accounts: &[Rc<RefCell<Account>>],
         rent_collector: &RentCollector,
@@ -823,12 +998,19 @@ impl MessageProcessor {
         feature_set: Arc<FeatureSet>,
         bpf_compute_budget: BpfComputeBudget,
     ) -> Result<(), TransactionError> {
+        let transaction = TransactionInfo {
+            signatures,
+            recent_blockhash: message.recent_blockhash,
+            fee_payer: message.account_keys[0],
+            pseudorandom_counter: Rc::new(RefCell::new(0)),
+        };
         for (instruction_index, instruction) in message.instructions.iter().enumerate() {
             let instruction_recorder = instruction_recorders
                 .as_ref()
                 .map(|recorders| recorders[instruction_index].clone());
             self.execute_instruction(
                 message,
+                transaction.clone(),
                 instruction,
                 &loaders[instruction_index],
                 accounts,
@@ -890,6 +1072,8 @@ mod tests {
             &[],
             None,
             BpfComputeBudget::default(),
+            ExecutionHooks::default(),
+            TransactionInfo::default(),
             Rc::new(RefCell::new(Executors::default())),
             None,
             Arc::new(FeatureSet::all_enabled()),
@@ -905,6 +1089,10 @@ mod tests {
         }
         assert_ne!(depth_reached, 0);
         assert!(depth_reached < MAX_DEPTH);
+        assert_eq!(
+            invoke_context.get_program_ids(),
+            &program_ids[..depth_reached]
+        );
 
         // Mock each invocation
         for owned_index in (1..depth_reached).rev() {
@@ -953,6 +1141,106 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_invoke_context_syscall_cost_overrides() {
+        let program_id = solana_sdk::pubkey::new_rand();
+        let new_invoke_context = |syscall_cost_overrides| {
+            ThisInvokeContext::new(
+                &program_id,
+                Rent::default(),
+                vec![],
+                &[],
+                None,
+                BpfComputeBudget::default(),
+                ExecutionHooks {
+                    syscall_cost_overrides,
+                    ..ExecutionHooks::default()
+                },
+                TransactionInfo::default(),
+                Rc::new(RefCell::new(Executors::default())),
+                None,
+                Arc::new(FeatureSet::all_enabled()),
+            )
+        };
+
+        let invoke_context = new_invoke_context(None);
+        assert_eq!(invoke_context.get_syscall_cost_override("sol_log_"), None);
+
+        let mut syscall_cost_overrides = SyscallCostOverrides::default();
+        syscall_cost_overrides.insert("sol_log_".to_string(), 42);
+        let invoke_context = new_invoke_context(Some(Arc::new(syscall_cost_overrides)));
+        assert_eq!(
+            invoke_context.get_syscall_cost_override("sol_log_"),
+            Some(42)
+        );
+        assert_eq!(invoke_context.get_syscall_cost_override("sol_sha256"), None);
+
+        // Overrides set on the processor carry over to clones
+        let mut message_processor = MessageProcessor::default();
+        let mut syscall_cost_overrides = SyscallCostOverrides::default();
+        syscall_cost_overrides.insert("sol_sha256".to_string(), 7);
+        message_processor.set_syscall_cost_overrides(Some(syscall_cost_overrides));
+        let message_processor = message_processor.clone();
+        let invoke_context = new_invoke_context(message_processor.hooks.syscall_cost_overrides);
+        assert_eq!(
+            invoke_context.get_syscall_cost_override("sol_sha256"),
+            Some(7)
+        );
+    }
+
+    #[test]
+    fn test_invoke_context_compute_profiler() {
+        let caller_program_id = solana_sdk::pubkey::new_rand();
+        let callee_program_id = solana_sdk::pubkey::new_rand();
+        let compute_profiler = Arc::new(ComputeProfiler::default());
+        let mut invoke_context = ThisInvokeContext::new(
+            &caller_program_id,
+            Rent::default(),
+            vec![],
+            &[],
+            None,
+            BpfComputeBudget::default(),
+            ExecutionHooks {
+                compute_profiler: Some(compute_profiler.clone()),
+                ..ExecutionHooks::default()
+            },
+            TransactionInfo::default(),
+            Rc::new(RefCell::new(Executors::default())),
+            None,
+            Arc::new(FeatureSet::all_enabled()),
+        );
+        let compute_meter = invoke_context.get_compute_meter();
+        let invocation_profiler = invoke_context.get_compute_profiler().unwrap();
+        let remaining = compute_meter.borrow().get_remaining();
+        invocation_profiler
+            .borrow_mut()
+            .enter_frame(&caller_program_id, remaining);
+
+        compute_meter.borrow_mut().consume(10).unwrap();
+        invoke_context.push(&callee_program_id).unwrap();
+        compute_meter.borrow_mut().consume(20).unwrap();
+        invoke_context.pop();
+        // Rejected invocations are not recorded
+        assert_eq!(
+            invoke_context.push(&caller_program_id),
+            Err(InstructionError::ReentrancyNotAllowed)
+        );
+
+        // Frames are recorded once the instruction's own frame completes
+        assert!(compute_profiler.report().frames.is_empty());
+        let remaining = compute_meter.borrow().get_remaining();
+        invocation_profiler.borrow_mut().exit_frame(remaining);
+        let frames = compute_profiler.report().frames;
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].program_id, caller_program_id);
+        assert_eq!(frames[0].invoke_depth, 1);
+        assert_eq!(frames[0].compute_units_consumed, 30);
+        assert_eq!(frames[0].exclusive_compute_units_consumed, 10);
+        assert_eq!(frames[1].program_id, callee_program_id);
+        assert_eq!(frames[1].invoke_depth, 2);
+        assert_eq!(frames[1].compute_units_consumed, 20);
+    }
+
     #[test]
     fn test_is_zeroed() {
         const ZEROS_LEN: usize = 1024;
@@ -1115,6 +1403,82 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_verify_account_changes_owner_invariants() {
+        let owner = solana_sdk::pubkey::new_rand();
+        let new_owner = solana_sdk::pubkey::new_rand();
+        let mallory_program_id = solana_sdk::pubkey::new_rand();
+
+        // Exhaustively check that an owner change is accepted only if every runtime
+        // guarantee holds, so that relaxing any single check shows up as a failure
+        for &is_writable in &[true, false] {
+            for &is_executable in &[true, false] {
+                for &is_owner in &[true, false] {
+                    for &is_zeroed in &[true, false] {
+                        let program_id = if is_owner { owner } else { mallory_program_id };
+                        let post_data = if is_zeroed { vec![0] } else { vec![42] };
+                        let mut change = Change::new(&owner, &program_id)
+                            .owner(&new_owner)
+                            .executable(is_executable, is_executable)
+                            .data(post_data.clone(), post_data);
+                        if !is_writable {
+                            change = change.read_only();
+                        }
+                        let expected = if is_writable && !is_executable && is_owner && is_zeroed {
+                            Ok(())
+                        } else {
+                            Err(InstructionError::ModifiedProgramId)
+                        };
+                        assert_eq!(
+                            change.verify(),
+                            expected,
+                            "writable: {} executable: {} owner: {} zeroed: {}",
+                            is_writable,
+                            is_executable,
+                            is_owner,
+                            is_zeroed
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify_account_changes_executable_invariants() {
+        let owner = solana_sdk::pubkey::new_rand();
+
+        // Once executable, an account's lamports and data are frozen regardless of who
+        // is executing or whether the account is writable
+        for &is_writable in &[true, false] {
+            for program_id in &[owner, solana_sdk::pubkey::new_rand()] {
+                let change = |change: Change| {
+                    if is_writable {
+                        change
+                    } else {
+                        change.read_only()
+                    }
+                };
+                assert!(change(Change::new(&owner, program_id))
+                    .executable(true, true)
+                    .lamports(1, 2)
+                    .verify()
+                    .is_err());
+                assert!(change(Change::new(&owner, program_id))
+                    .executable(true, true)
+                    .data(vec![1], vec![2])
+                    .verify()
+                    .is_err());
+                assert_eq!(
+                    change(Change::new(&owner, program_id))
+                        .executable(true, false)
+                        .verify(),
+                    Err(InstructionError::ExecutableModified)
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_verify_account_changes_executable() {
         let owner = solana_sdk::pubkey::new_rand();
@@ -1439,6 +1803,7 @@ mod tests {
 
         let result = message_processor.process_message(
             &message,
+            &[],
             &loaders,
             &accounts,
             &rent_collector,
@@ -1463,6 +1828,7 @@ mod tests {
 
         let result = message_processor.process_message(
             &message,
+            &[],
             &loaders,
             &accounts,
             &rent_collector,
@@ -1491,6 +1857,7 @@ mod tests {
 
         let result = message_processor.process_message(
             &message,
+            &[],
             &loaders,
             &accounts,
             &rent_collector,
@@ -1603,6 +1970,7 @@ mod tests {
         );
         let result = message_processor.process_message(
             &message,
+            &[],
             &loaders,
             &accounts,
             &rent_collector,
@@ -1631,6 +1999,7 @@ mod tests {
         );
         let result = message_processor.process_message(
             &message,
+            &[],
             &loaders,
             &accounts,
             &rent_collector,
@@ -1656,6 +2025,7 @@ mod tests {
         );
         let result = message_processor.process_message(
             &message,
+            &[],
             &loaders,
             &accounts,
             &rent_collector,
@@ -1671,6 +2041,144 @@ mod tests {
         assert_eq!(accounts[0].borrow().data, vec![42]);
     }
 
+    #[test]
+    fn test_process_message_pseudorandom_counter() {
+        // Each instruction draws once, expecting the counter value in its data
+        fn mock_process_instruction(
+            _program_id: &Pubkey,
+            _keyed_accounts: &[KeyedAccount],
+            data: &[u8],
+            invoke_context: &mut dyn InvokeContext,
+        ) -> Result<(), InstructionError> {
+            let expected: u64 =
+                bincode::deserialize(data).map_err(|_| InstructionError::InvalidInstructionData)?;
+            let counter = invoke_context.get_pseudorandom_counter();
+            let mut counter = counter.borrow_mut();
+            if *counter != expected {
+                return Err(InstructionError::InvalidArgument);
+            }
+            *counter += 1;
+            Ok(())
+        }
+
+        let mock_program_id = Pubkey::new(&[2u8; 32]);
+        let rent_collector = RentCollector::default();
+        let mut message_processor = MessageProcessor::default();
+        message_processor.add_program(mock_program_id, mock_process_instruction);
+
+        let loaders: Vec<Vec<(Pubkey, RefCell<Account>)>> = (0..2)
+            .map(|_| {
+                vec![(
+                    mock_program_id,
+                    RefCell::new(create_loadable_account("mock_program", 1)),
+                )]
+            })
+            .collect();
+        let executors = Rc::new(RefCell::new(Executors::default()));
+        let payer = solana_sdk::pubkey::new_rand();
+        let process_message = |counters: &[u64]| {
+            let instructions: Vec<_> = counters
+                .iter()
+                .map(|counter| Instruction::new(mock_program_id, counter, vec![]))
+                .collect();
+            message_processor.process_message(
+                &Message::new(&instructions, Some(&payer)),
+                &[],
+                &loaders,
+                &[],
+                &rent_collector,
+                None,
+                executors.clone(),
+                None,
+                Arc::new(FeatureSet::all_enabled()),
+                BpfComputeBudget::new(&FeatureSet::all_enabled()),
+            )
+        };
+
+        // The counter carries over between the instructions of a transaction
+        assert_eq!(process_message(&[0, 1]), Ok(()));
+        assert_eq!(
+            process_message(&[0, 0]),
+            Err(TransactionError::InstructionError(
+                1,
+                InstructionError::InvalidArgument
+            ))
+        );
+
+        // and restarts with every transaction
+        assert_eq!(process_message(&[0]), Ok(()));
+    }
+
+    #[test]
+    fn test_process_message_duplicate_account_privileges() {
+        fn mock_process_instruction(
+            _program_id: &Pubkey,
+            keyed_accounts: &[KeyedAccount],
+            _data: &[u8],
+            _invoke_context: &mut dyn InvokeContext,
+        ) -> Result<(), InstructionError> {
+            // Every occurrence of the duplicated account sees the union of its privileges
+            for keyed_account in &keyed_accounts[..3] {
+                if keyed_account.signer_key().is_none() || !keyed_account.is_writable() {
+                    return Err(InstructionError::InvalidArgument);
+                }
+            }
+            if keyed_accounts[3].signer_key().is_some() || keyed_accounts[3].is_writable() {
+                return Err(InstructionError::InvalidArgument);
+            }
+            Ok(())
+        }
+
+        let mock_program_id = Pubkey::new(&[2u8; 32]);
+        let rent_collector = RentCollector::default();
+        let mut message_processor = MessageProcessor::default();
+        message_processor.add_program(mock_program_id, mock_process_instruction);
+
+        let accounts = vec![
+            Account::new_ref(100, 1, &mock_program_id),
+            Account::new_ref(0, 1, &mock_program_id),
+        ];
+        let loaders = vec![vec![(
+            mock_program_id,
+            RefCell::new(create_loadable_account("mock_program", 1)),
+        )]];
+
+        let dup_pubkey = solana_sdk::pubkey::new_rand();
+        let readonly_pubkey = solana_sdk::pubkey::new_rand();
+        let account_metas = vec![
+            AccountMeta::new_readonly(dup_pubkey, false),
+            AccountMeta::new(dup_pubkey, false),
+            AccountMeta::new_readonly(dup_pubkey, true),
+            AccountMeta::new_readonly(readonly_pubkey, false),
+        ];
+        let message = Message::new(
+            &[Instruction::new(mock_program_id, &(), account_metas)],
+            None,
+        );
+        assert_eq!(
+            message.instruction_account_metas(0).unwrap()[..3],
+            [
+                AccountMeta::new(dup_pubkey, true),
+                AccountMeta::new(dup_pubkey, true),
+                AccountMeta::new(dup_pubkey, true),
+            ]
+        );
+
+        let result = message_processor.process_message(
+            &message,
+            &[],
+            &loaders,
+            &accounts,
+            &rent_collector,
+            None,
+            Rc::new(RefCell::new(Executors::default())),
+            None,
+            Arc::new(FeatureSet::all_enabled()),
+            BpfComputeBudget::new(&FeatureSet::all_enabled()),
+        );
+        assert_eq!(result, Ok(()));
+    }
+
     #[test]
     fn test_process_cross_program() {
         #[derive(Serialize, Deserialize)]
@@ -1746,6 +2254,8 @@ mod tests {
             programs.as_slice(),
             None,
             BpfComputeBudget::default(),
+            ExecutionHooks::default(),
+            TransactionInfo::default(),
             Rc::new(RefCell::new(Executors::default())),
             None,
             Arc::new(FeatureSet::all_enabled()),
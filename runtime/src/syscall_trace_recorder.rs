@@ -0,0 +1,50 @@
+use solana_sdk::process_instruction::{SyscallTraceCollector, SyscallTraceEvent};
+use std::sync::Mutex;
+
+/// Records the syscalls made by all programs, in invocation order
+#[derive(Debug, Default)]
+pub struct SyscallTraceRecorder {
+    events: Mutex<Vec<SyscallTraceEvent>>,
+}
+
+impl SyscallTraceRecorder {
+    /// Remove and return all events recorded so far
+    pub fn take_events(&self) -> Vec<SyscallTraceEvent> {
+        std::mem::take(&mut *self.events.lock().unwrap())
+    }
+}
+
+impl SyscallTraceCollector for SyscallTraceRecorder {
+    fn collect(&self, event: SyscallTraceEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_syscall_trace_recorder() {
+        let recorder = SyscallTraceRecorder::default();
+        assert!(recorder.take_events().is_empty());
+
+        let event = SyscallTraceEvent {
+            name: "sol_log_".to_string(),
+            args: [0x4_0000_0000, 5, 0, 0, 0],
+            compute_units_consumed: 100,
+            result: Ok(0),
+        };
+        recorder.collect(event.clone());
+        recorder.collect(SyscallTraceEvent {
+            result: Err("Compute budget exceeded".to_string()),
+            ..event.clone()
+        });
+
+        let events = recorder.take_events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0], event);
+        assert!(events[1].result.is_err());
+        assert!(recorder.take_events().is_empty());
+    }
+}
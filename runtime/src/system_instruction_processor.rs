@@ -171,7 +171,12 @@ fn transfer_verified(
     Ok(())
 }
 
-fn transfer(from: &KeyedAccount, to: &KeyedAccount, lamports: u64) -> Result<(), InstructionError> {
+/// Transfer `lamports` from a system account, `from` must sign
+pub fn transfer(
+    from: &KeyedAccount,
+    to: &KeyedAccount,
+    lamports: u64,
+) -> Result<(), InstructionError> {
     if lamports == 0 {
         return Ok(());
     }
@@ -276,6 +276,14 @@ static void sol_free(void *ptr) {
 void sol_panic_(const char *, uint64_t, uint64_t, uint64_t);
 #define sol_panic() sol_panic_(__FILE__, sizeof(__FILE__), __LINE__, 0)
 
+/**
+ * Aborts with a code
+ *
+ * Causes the BPF VM to immediately halt execution, reporting `code` as the
+ * reason. No accounts' data are updated
+ */
+void sol_abort_with_code(uint64_t code);
+
 /**
  * Asserts
  */
@@ -437,6 +445,263 @@ static uint64_t sol_sha256(
     const uint8_t *result
 );
 
+/**
+ * HMAC-Sha256
+ *
+ * @param key Key bytes
+ * @param key_len Number of key bytes
+ * @param bytes Array of byte arrays
+ * @param bytes_len Number of byte arrays
+ * @param result 32 byte array to hold the result
+ */
+uint64_t sol_hmac_sha256(
+    const uint8_t *key,
+    uint64_t key_len,
+    const SolBytes *bytes,
+    int bytes_len,
+    const uint8_t *result
+);
+
+/**
+ * Open a Sha256 context for hashing data incrementally
+ *
+ * @return Handle of the context
+ */
+uint64_t sol_sha256_init(void);
+
+/**
+ * Hash more data into an open Sha256 context
+ *
+ * @param handle Handle returned by sol_sha256_init
+ * @param bytes Array of byte arrays
+ * @param bytes_len Number of byte arrays
+ */
+uint64_t sol_sha256_update(uint64_t handle, const SolBytes *bytes, int bytes_len);
+
+/**
+ * Close a Sha256 context
+ *
+ * @param handle Handle returned by sol_sha256_init
+ * @param result 32 byte array to hold the hash of all the data hashed into the context
+ */
+uint64_t sol_sha256_final(uint64_t handle, const uint8_t *result);
+
+/**
+ * Open a Keccak-256 context for hashing data incrementally
+ *
+ * @return Handle of the context
+ */
+uint64_t sol_keccak256_init(void);
+
+/**
+ * Hash more data into an open Keccak-256 context
+ *
+ * @param handle Handle returned by sol_keccak256_init
+ * @param bytes Array of byte arrays
+ * @param bytes_len Number of byte arrays
+ */
+uint64_t sol_keccak256_update(uint64_t handle, const SolBytes *bytes, int bytes_len);
+
+/**
+ * Close a Keccak-256 context
+ *
+ * @param handle Handle returned by sol_keccak256_init
+ * @param result 32 byte array to hold the hash of all the data hashed into the context
+ */
+uint64_t sol_keccak256_final(uint64_t handle, const uint8_t *result);
+
+/**
+ * ChaCha20-Poly1305 authenticated decryption
+ *
+ * @param inputs Key (32 bytes), nonce (12 bytes), additional data, ciphertext and tag (16 bytes)
+ * @param inputs_len Number of inputs, must be 5
+ * @param plaintext Array to hold the decrypted ciphertext
+ * @param plaintext_len Length of plaintext, must equal the length of the ciphertext
+ * @return 0 if the ciphertext was authenticated and decrypted, 1 if it failed authentication
+ */
+uint64_t sol_chacha20_poly1305_open(
+  const SolBytes *inputs,
+  int inputs_len,
+  uint8_t *plaintext,
+  uint64_t plaintext_len
+);
+
+/**
+ * Verify a secp256r1 (P-256) ECDSA signature over the Sha256 of a message
+ *
+ * @param message Message bytes
+ * @param message_len Number of message bytes
+ * @param signature 64 byte signature, r followed by s, both big-endian
+ * @param pubkey 65 byte SEC1 uncompressed public key
+ * @return 0 if the signature is valid, 1 otherwise
+ */
+uint64_t sol_secp256r1_verify(
+  const uint8_t *message,
+  uint64_t message_len,
+  const uint8_t *signature,
+  const uint8_t *pubkey
+);
+
+/**
+ * X25519 Diffie-Hellman
+ *
+ * @param scalar 32 byte secret scalar, clamped before use
+ * @param point 32 byte Montgomery u-coordinate
+ * @param result 32 byte array to hold the shared secret
+ * @return 0 on success, 1 if point is of small order
+ */
+uint64_t sol_x25519_dh(const uint8_t *scalar, const uint8_t *point, uint8_t *result);
+
+/**
+ * Base58 encode
+ *
+ * @param input Bytes to encode, at most 64
+ * @param input_len Number of bytes to encode
+ * @param output Array to hold the encoded characters, 88 bytes is always large enough
+ * @param output_len Length of output
+ * @param written Set to the number of characters written to output
+ * @return 0 on success, 1 if the input is too long or output too small
+ */
+uint64_t sol_base58_encode(
+  const uint8_t *input,
+  uint64_t input_len,
+  uint8_t *output,
+  uint64_t output_len,
+  uint64_t *written
+);
+
+/**
+ * Base58 decode
+ *
+ * @param input Characters to decode, at most 88
+ * @param input_len Number of characters to decode
+ * @param output Array to hold the decoded bytes, 64 bytes is always large enough
+ * @param output_len Length of output
+ * @param written Set to the number of bytes written to output
+ * @return 0 on success, 1 if the input is too long, not valid base58 or output too small
+ */
+uint64_t sol_base58_decode(
+  const uint8_t *input,
+  uint64_t input_len,
+  uint8_t *output,
+  uint64_t output_len,
+  uint64_t *written
+);
+
+/**
+ * Bech32 encode
+ *
+ * @param inputs Human-readable part and data to encode
+ * @param inputs_len Number of inputs, must be 2
+ * @param output Array to hold the encoded characters, 90 bytes is always large enough
+ * @param output_len Length of output
+ * @param written Set to the number of characters written to output
+ * @return 0 on success, 1 if the inputs are invalid, the encoding would exceed 90 characters
+ * or output is too small
+ */
+uint64_t sol_bech32_encode(
+  const SolBytes *inputs,
+  int inputs_len,
+  uint8_t *output,
+  uint64_t output_len,
+  uint64_t *written
+);
+
+/**
+ * Bech32 decode, only the data is output
+ *
+ * @param input Characters to decode, at most 90
+ * @param input_len Number of characters to decode
+ * @param output Array to hold the decoded data
+ * @param output_len Length of output
+ * @param written Set to the number of bytes written to output
+ * @return 0 on success, 1 if the input is too long, not valid bech32 or output too small
+ */
+uint64_t sol_bech32_decode(
+  const uint8_t *input,
+  uint64_t input_len,
+  uint8_t *output,
+  uint64_t output_len,
+  uint64_t *written
+);
+
+/**
+ * Length prefix encodings
+ */
+#define SOL_LENGTH_PREFIX_COMPACT_U16 0
+#define SOL_LENGTH_PREFIX_BORSH_U32 1
+
+/**
+ * Decode the length prefix of a serialized sequence
+ *
+ * @param input Serialized bytes starting with the prefix
+ * @param input_len Number of serialized bytes, only the prefix is read
+ * @param encoding SOL_LENGTH_PREFIX_COMPACT_U16 or SOL_LENGTH_PREFIX_BORSH_U32
+ * @param value Set to the decoded length
+ * @param consumed Set to the number of bytes the prefix occupies
+ * @return 0 on success, 1 if the encoding is unknown or the prefix is invalid
+ */
+uint64_t sol_decode_length_prefix(
+  const uint8_t *input,
+  uint64_t input_len,
+  uint64_t encoding,
+  uint64_t *value,
+  uint64_t *consumed
+);
+
+/**
+ * Version of the interface presented by the runtime, the major, minor and
+ * patch versions are packed into bits 32-47, 16-31 and 0-15 respectively
+ */
+uint64_t sol_get_runtime_version(void);
+#define SOL_RUNTIME_VERSION_MAJOR(version) (((version) >> 32) & 0xffff)
+#define SOL_RUNTIME_VERSION_MINOR(version) (((version) >> 16) & 0xffff)
+#define SOL_RUNTIME_VERSION_PATCH(version) ((version) & 0xffff)
+
+/**
+ * Copy the transaction signature at `index` into the 64 byte `signature`
+ *
+ * @return 0 on success, 1 if the transaction has no signature at `index`
+ */
+uint64_t sol_get_transaction_signature(uint64_t index, uint8_t *signature);
+
+/**
+ * Copy the 32 byte recent blockhash the transaction was signed against into
+ * `blockhash`
+ */
+uint64_t sol_get_recent_blockhash_of_tx(uint8_t *blockhash);
+
+/**
+ * Copy the transaction's fee payer into `fee_payer`
+ */
+uint64_t sol_get_fee_payer(SolPubkey *fee_payer);
+
+/**
+ * Limits the runtime enforces on the executing program
+ */
+typedef struct {
+  uint64_t max_invoke_depth; /** Maximum cross-program invocation depth, including the top-level instruction */
+  uint64_t max_call_depth; /** Maximum BPF to BPF call depth */
+  uint64_t stack_frame_size; /** Size of each stack frame in bytes */
+  uint64_t heap_size; /** Size of the program heap in bytes */
+  uint64_t max_signers; /** Maximum number of program signers in a cross-program invocation */
+  uint64_t max_permitted_data_increase; /** Number of bytes an account's data may grow by within one instruction */
+} SolRuntimeLimits;
+
+/**
+ * Copy the limits the runtime enforces on the executing program into `limits`
+ */
+uint64_t sol_get_runtime_limits(SolRuntimeLimits *limits);
+
+/**
+ * Fill `bytes` with `len` pseudorandom bytes derived from the transaction's
+ * recent blockhash, the program id and a per-invocation counter
+ *
+ * The bytes are deterministic and can be computed before the transaction
+ * executes, they are not a source of unpredictable randomness
+ */
+uint64_t sol_get_pseudorandom(uint8_t *bytes, uint64_t len);
+
 /**
  * Account Meta
  */
@@ -489,6 +754,28 @@ static uint64_t sol_create_program_address(
     const SolPubkey *address
 );
 
+/**
+ * Maximum number of addresses created by one sol_create_program_addresses_batch call
+ */
+#define MAX_PROGRAM_ADDRESSES 16
+
+/**
+ * Create a program address for each set of seeds
+ *
+ * @param seed_sets Sets of seed bytes, one per address
+ * @param seed_sets_len Length of the seed_sets array, at most MAX_PROGRAM_ADDRESSES
+ * @param Progam id of the signer
+ * @param Program addresses created, seed_sets_len of them, filled on return
+ * @return 0 on success, 1 if any set of seeds does not produce a valid address, 2 if
+ * there are more than MAX_PROGRAM_ADDRESSES sets
+ */
+static uint64_t sol_create_program_addresses_batch(
+    const SolSignerSeeds *seed_sets,
+    int seed_sets_len,
+    const SolPubkey *program_id,
+    SolPubkey *addresses
+);
+
 /**
  * Cross-program invocation
  *  * @{
@@ -548,6 +835,103 @@ static uint64_t sol_invoke(
   );
 }
 
+/**
+ * Lamport transfer out of a system account
+ */
+typedef struct {
+  const SolPubkey *from;  /** Account to debit */
+  const SolPubkey *to;    /** Account to credit */
+  uint64_t lamports;      /** Number of lamports to transfer */
+} SolSystemTransfer;
+
+/**
+ * Transfer lamports out of a system account without invoking the system
+ * program, with program signatures
+ *
+ * The system program must be included in `account_infos`
+ *
+ * @param from Account to debit, must be a signer or signed for by signers_seeds
+ * @param to Account to credit
+ * @param lamports Number of lamports to transfer
+ * @param account_infos Accounts used by the transfer
+ * @param account_infos_len Length of account_infos array
+ * @param signers_seeds Seed bytes used to sign program accounts
+ * @param signers_seeds_len Length of the seeds array
+ */
+static uint64_t sol_system_transfer_signed(
+    const SolPubkey *from,
+    const SolPubkey *to,
+    uint64_t lamports,
+    const SolAccountInfo *account_infos,
+    int account_infos_len,
+    const SolSignerSeeds *signers_seeds,
+    int signers_seeds_len
+) {
+  uint64_t sol_system_transfer_c(
+    const SolSystemTransfer *transfer,
+    const SolAccountInfo *account_infos,
+    int account_infos_len,
+    const SolSignerSeeds *signers_seeds,
+    int signers_seeds_len
+  );
+
+  const SolSystemTransfer transfer = {from, to, lamports};
+  return sol_system_transfer_c(
+    &transfer,
+    account_infos,
+    account_infos_len,
+    signers_seeds,
+    signers_seeds_len
+  );
+}
+/**
+ * Transfer lamports out of a system account without invoking the system program
+ *
+ * The system program must be included in `account_infos`
+ *
+ * @param from Account to debit, must be a signer
+ * @param to Account to credit
+ * @param lamports Number of lamports to transfer
+ * @param account_infos Accounts used by the transfer
+ * @param account_infos_len Length of account_infos array
+ */
+static uint64_t sol_system_transfer(
+    const SolPubkey *from,
+    const SolPubkey *to,
+    uint64_t lamports,
+    const SolAccountInfo *account_infos,
+    int account_infos_len
+) {
+  const SolSignerSeeds signers_seeds[] = {{}};
+  return sol_system_transfer_signed(
+    from,
+    to,
+    lamports,
+    account_infos,
+    account_infos_len,
+    signers_seeds,
+    0
+  );
+}
+
+/**
+ * Maximum depth of cross-program invocations, counting the top-level instruction
+ */
+uint64_t sol_get_max_invoke_depth(void);
+
+/**
+ * Program ids of the invocation stack, from the top-level instruction's
+ * program to the currently executing program.  A program id's index plus one
+ * is its stack height.
+ *
+ * Writes at most `program_ids_len` program ids and the full stack height
+ */
+uint64_t sol_get_instruction_ancestry(
+  SolPubkey *program_ids,
+  uint64_t program_ids_len,
+  uint64_t *stack_height
+);
+
 /**@}*/
 
 /**
@@ -564,6 +948,17 @@ void sol_log_pubkey(
     const SolPubkey *pubkey
 );
 
+/**
+ * Prints raw bytes, rendered as base64
+ *
+ * @param data The bytes to print
+ * @param data_len Number of bytes to print
+ */
+void sol_log_data(
+    const uint8_t *data,
+    uint64_t data_len
+);
+
 /**
  * Prints the hexadecimal representation of an array
  *
@@ -636,6 +1031,13 @@ void sol_log_pubkey(const SolPubkey *pubkey) {
   }
   printf("\n");
 }
+void sol_log_data(const uint8_t *data, uint64_t data_len) {
+  printf("Program data: ");
+  for (uint64_t i = 0; i < data_len; i++) {
+    printf("%02x", data[i]);
+  }
+  printf("\n");
+}
 void sol_log_compute_units_() {
   printf("Program consumption: __ units remaining\n");
 }
@@ -643,6 +1045,10 @@ void sol_panic_(const char *file, uint64_t len, uint64_t line, uint64_t column)
   printf("Panic in %s at %d:%d\n", file, line, column);
   abort();
 }
+void sol_abort_with_code(uint64_t code) {
+  printf("Aborted with code %llu\n", code);
+  abort();
+}
 #endif
 
 #ifdef __cplusplus
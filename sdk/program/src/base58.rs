@@ -0,0 +1,148 @@
+//! Base58 encoding of byte data, using the Bitcoin alphabet as for `Pubkey` and `Hash`
+
+use thiserror::Error;
+
+/// Maximum length of data that may be encoded, large enough for a signature
+pub const MAX_BASE58_DECODED_LEN: usize = 64;
+/// Maximum length of a string that may be decoded
+pub const MAX_BASE58_ENCODED_LEN: usize = 88;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum Base58Error {
+    #[error("input exceeds the maximum length")]
+    TooLong,
+    #[error("invalid base58 encoding")]
+    InvalidEncoding,
+}
+
+/// Encode `data` as a base58 string
+pub fn encode(data: &[u8]) -> Result<String, Base58Error> {
+    if data.len() > MAX_BASE58_DECODED_LEN {
+        return Err(Base58Error::TooLong);
+    }
+
+    // Perform the calculation inline, calling this from within a program is
+    // not supported
+    #[cfg(not(target_arch = "bpf"))]
+    {
+        Ok(bs58::encode(data).into_string())
+    }
+    // Call via a system call to perform the calculation
+    #[cfg(target_arch = "bpf")]
+    {
+        extern "C" {
+            fn sol_base58_encode(
+                input_addr: *const u8,
+                input_len: u64,
+                output_addr: *mut u8,
+                output_len: u64,
+                written_addr: *mut u64,
+            ) -> u64;
+        };
+        let mut output = vec![0; MAX_BASE58_ENCODED_LEN];
+        let mut written = 0;
+        let result = unsafe {
+            sol_base58_encode(
+                data.as_ptr(),
+                data.len() as u64,
+                output.as_mut_ptr(),
+                output.len() as u64,
+                &mut written,
+            )
+        };
+        match result {
+            0 => {
+                output.truncate(written as usize);
+                String::from_utf8(output).map_err(|_| Base58Error::InvalidEncoding)
+            }
+            _ => Err(Base58Error::InvalidEncoding),
+        }
+    }
+}
+
+/// Decode the base58 string `encoded`
+pub fn decode(encoded: &str) -> Result<Vec<u8>, Base58Error> {
+    if encoded.len() > MAX_BASE58_ENCODED_LEN {
+        return Err(Base58Error::TooLong);
+    }
+
+    // Perform the calculation inline, calling this from within a program is
+    // not supported
+    #[cfg(not(target_arch = "bpf"))]
+    {
+        let decoded = bs58::decode(encoded)
+            .into_vec()
+            .map_err(|_| Base58Error::InvalidEncoding)?;
+        if decoded.len() > MAX_BASE58_DECODED_LEN {
+            return Err(Base58Error::TooLong);
+        }
+        Ok(decoded)
+    }
+    // Call via a system call to perform the calculation
+    #[cfg(target_arch = "bpf")]
+    {
+        extern "C" {
+            fn sol_base58_decode(
+                input_addr: *const u8,
+                input_len: u64,
+                output_addr: *mut u8,
+                output_len: u64,
+                written_addr: *mut u64,
+            ) -> u64;
+        };
+        let mut output = vec![0; MAX_BASE58_DECODED_LEN];
+        let mut written = 0;
+        let result = unsafe {
+            sol_base58_decode(
+                encoded.as_ptr(),
+                encoded.len() as u64,
+                output.as_mut_ptr(),
+                output.len() as u64,
+                &mut written,
+            )
+        };
+        match result {
+            0 => {
+                output.truncate(written as usize);
+                Ok(output)
+            }
+            _ => Err(Base58Error::InvalidEncoding),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pubkey::Pubkey;
+
+    #[test]
+    fn test_encode_decode() {
+        let pubkey = Pubkey::new_unique();
+        let encoded = encode(pubkey.as_ref()).unwrap();
+        assert_eq!(encoded, pubkey.to_string());
+        assert_eq!(decode(&encoded), Ok(pubkey.to_bytes().to_vec()));
+
+        assert_eq!(encode(&[0, 0, 1]), Ok("112".to_string()));
+        assert_eq!(decode("112"), Ok(vec![0, 0, 1]));
+        assert_eq!(decode("I"), Err(Base58Error::InvalidEncoding));
+    }
+
+    #[test]
+    fn test_length_cap() {
+        assert_eq!(encode(&[0xff; MAX_BASE58_DECODED_LEN]).unwrap().len(), 88);
+        assert_eq!(
+            encode(&[0; MAX_BASE58_DECODED_LEN + 1]),
+            Err(Base58Error::TooLong)
+        );
+        assert_eq!(
+            decode(&"2".repeat(MAX_BASE58_ENCODED_LEN + 1)),
+            Err(Base58Error::TooLong)
+        );
+        // Within the string length cap but decodes to too many bytes
+        assert_eq!(
+            decode(&"1".repeat(MAX_BASE58_DECODED_LEN + 1)),
+            Err(Base58Error::TooLong)
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! Bech32 encoding of byte data, as used by Cosmos addresses, specified by BIP-173
+
+use thiserror::Error;
+
+/// Maximum length of an encoded string
+pub const MAX_BECH32_ENCODED_LEN: usize = 90;
+/// Number of characters in the checksum
+const CHECKSUM_LEN: usize = 6;
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const SEPARATOR: u8 = b'1';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum Bech32Error {
+    #[error("encoding exceeds the maximum length")]
+    TooLong,
+    #[error("invalid human-readable part")]
+    InvalidHrp,
+    #[error("invalid bech32 encoding")]
+    InvalidEncoding,
+}
+
+/// Encode `data` under the human-readable part `hrp`
+pub fn encode(hrp: &str, data: &[u8]) -> Result<String, Bech32Error> {
+    if hrp.is_empty() || hrp.bytes().any(|c| !(33..=126).contains(&c)) {
+        return Err(Bech32Error::InvalidHrp);
+    }
+    if hrp.len() + 1 + (data.len() * 8 + 4) / 5 + CHECKSUM_LEN > MAX_BECH32_ENCODED_LEN {
+        return Err(Bech32Error::TooLong);
+    }
+
+    // Perform the calculation inline, calling this from within a program is
+    // not supported
+    #[cfg(not(target_arch = "bpf"))]
+    {
+        let hrp = hrp.to_ascii_lowercase();
+        let mut values = convert_bits(data, 8, 5, true).ok_or(Bech32Error::InvalidEncoding)?;
+        let checksum = polymod(&hrp, &values, &[0; CHECKSUM_LEN]) ^ 1;
+        for i in 0..CHECKSUM_LEN {
+            values.push(((checksum >> (5 * (5 - i))) & 31) as u8);
+        }
+
+        let mut encoded = hrp;
+        encoded.push(SEPARATOR as char);
+        encoded.extend(values.iter().map(|value| CHARSET[*value as usize] as char));
+        Ok(encoded)
+    }
+    // Call via a system call to perform the calculation
+    #[cfg(target_arch = "bpf")]
+    {
+        extern "C" {
+            fn sol_bech32_encode(
+                inputs_addr: *const u8,
+                inputs_len: u64,
+                output_addr: *mut u8,
+                output_len: u64,
+                written_addr: *mut u64,
+            ) -> u64;
+        };
+        let inputs: [&[u8]; 2] = [hrp.as_bytes(), data];
+        let mut output = vec![0; MAX_BECH32_ENCODED_LEN];
+        let mut written = 0;
+        let result = unsafe {
+            sol_bech32_encode(
+                &inputs as *const _ as *const u8,
+                inputs.len() as u64,
+                output.as_mut_ptr(),
+                output.len() as u64,
+                &mut written,
+            )
+        };
+        match result {
+            0 => {
+                output.truncate(written as usize);
+                String::from_utf8(output).map_err(|_| Bech32Error::InvalidEncoding)
+            }
+            _ => Err(Bech32Error::InvalidEncoding),
+        }
+    }
+}
+
+/// Decode `encoded` into its lowercase human-readable part and data
+pub fn decode(encoded: &str) -> Result<(String, Vec<u8>), Bech32Error> {
+    if encoded.len() > MAX_BECH32_ENCODED_LEN {
+        return Err(Bech32Error::TooLong);
+    }
+    let separator = encoded
+        .bytes()
+        .rposition(|c| c == SEPARATOR)
+        .ok_or(Bech32Error::InvalidEncoding)?;
+    if separator == 0 {
+        return Err(Bech32Error::InvalidHrp);
+    }
+    let hrp = encoded[..separator].to_ascii_lowercase();
+
+    // Perform the calculation inline, calling this from within a program is
+    // not supported
+    #[cfg(not(target_arch = "bpf"))]
+    {
+        if encoded.bytes().any(|c| !(33..=126).contains(&c))
+            || (encoded.bytes().any(|c| c.is_ascii_lowercase())
+                && encoded.bytes().any(|c| c.is_ascii_uppercase()))
+        {
+            return Err(Bech32Error::InvalidEncoding);
+        }
+        let values = encoded[separator + 1..]
+            .bytes()
+            .map(|c| {
+                CHARSET
+                    .iter()
+                    .position(|value| *value == c.to_ascii_lowercase())
+                    .map(|value| value as u8)
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or(Bech32Error::InvalidEncoding)?;
+        if values.len() < CHECKSUM_LEN || polymod(&hrp, &values, &[]) != 1 {
+            return Err(Bech32Error::InvalidEncoding);
+        }
+        let data = convert_bits(&values[..values.len() - CHECKSUM_LEN], 5, 8, false)
+            .ok_or(Bech32Error::InvalidEncoding)?;
+        Ok((hrp, data))
+    }
+    // Call via a system call to perform the calculation
+    #[cfg(target_arch = "bpf")]
+    {
+        extern "C" {
+            fn sol_bech32_decode(
+                input_addr: *const u8,
+                input_len: u64,
+                output_addr: *mut u8,
+                output_len: u64,
+                written_addr: *mut u64,
+            ) -> u64;
+        };
+        let mut output = vec![0; MAX_BECH32_ENCODED_LEN];
+        let mut written = 0;
+        let result = unsafe {
+            sol_bech32_decode(
+                encoded.as_ptr(),
+                encoded.len() as u64,
+                output.as_mut_ptr(),
+                output.len() as u64,
+                &mut written,
+            )
+        };
+        match result {
+            0 => {
+                output.truncate(written as usize);
+                Ok((hrp, output))
+            }
+            _ => Err(Bech32Error::InvalidEncoding),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "bpf"))]
+fn polymod(hrp: &str, values: &[u8], suffix: &[u8]) -> u32 {
+    const GENERATORS: [u32; 5] = [
+        0x3b6a_57b2,
+        0x2650_8e6d,
+        0x1ea1_19fa,
+        0x3d42_33dd,
+        0x2a14_62b3,
+    ];
+
+    let hrp_values = hrp
+        .bytes()
+        .map(|c| c >> 5)
+        .chain(std::iter::once(0))
+        .chain(hrp.bytes().map(|c| c & 31));
+    let mut checksum = 1u32;
+    for value in hrp_values
+        .chain(values.iter().copied())
+        .chain(suffix.iter().copied())
+    {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x1ff_ffff) << 5) ^ u32::from(value);
+        for (i, generator) in GENERATORS.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+/// Regroup `data` from `from`-bit to `to`-bit values, `None` if the leftover
+/// bits are not valid padding
+#[cfg(not(target_arch = "bpf"))]
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let mut accumulator = 0u32;
+    let mut bits = 0;
+    let max_value = (1 << to) - 1;
+    let mut converted = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+    for value in data {
+        accumulator = (accumulator << from) | u32::from(*value);
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            converted.push(((accumulator >> bits) & max_value) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            converted.push(((accumulator << (to - bits)) & max_value) as u8);
+        }
+    } else if bits >= from || (accumulator << (to - bits)) & max_value != 0 {
+        return None;
+    }
+    Some(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        let data = (0..20).collect::<Vec<u8>>();
+        let encoded = "cosmos1qqqsyqcyq5rqwzqfpg9scrgwpugpzysnrk363e";
+        assert_eq!(encode("cosmos", &data), Ok(encoded.to_string()));
+        assert_eq!(decode(encoded), Ok(("cosmos".to_string(), data.clone())));
+        assert_eq!(
+            decode(&encoded.to_ascii_uppercase()),
+            Ok(("cosmos".to_string(), data))
+        );
+
+        // BIP-173 test vector with no data
+        assert_eq!(encode("a", &[]), Ok("a12uel5l".to_string()));
+        assert_eq!(decode("A12UEL5L"), Ok(("a".to_string(), vec![])));
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        // Mixed case
+        assert_eq!(
+            decode("Cosmos1qqqsyqcyq5rqwzqfpg9scrgwpugpzysnrk363e"),
+            Err(Bech32Error::InvalidEncoding)
+        );
+        // Bad checksum
+        assert_eq!(
+            decode("cosmos1qqqsyqcyq5rqwzqfpg9scrgwpugpzysnrk363f"),
+            Err(Bech32Error::InvalidEncoding)
+        );
+        // Character outside the charset
+        assert_eq!(decode("a1b2uel5l"), Err(Bech32Error::InvalidEncoding));
+        // No separator or empty human-readable part
+        assert_eq!(decode("a2uel5l"), Err(Bech32Error::InvalidEncoding));
+        assert_eq!(decode("1qqqqqq"), Err(Bech32Error::InvalidHrp));
+    }
+
+    #[test]
+    fn test_length_cap() {
+        // 51 bytes of data is 82 characters, leaving room for a one character
+        // human-readable part
+        assert!(encode("a", &[0; 51]).is_ok());
+        assert_eq!(encode("ab", &[0; 51]), Err(Bech32Error::TooLong));
+        assert_eq!(
+            decode(&"q".repeat(MAX_BECH32_ENCODED_LEN + 1)),
+            Err(Bech32Error::TooLong)
+        );
+        assert_eq!(encode("", &[]), Err(Bech32Error::InvalidHrp));
+    }
+}
@@ -0,0 +1,181 @@
+//! ChaCha20-Poly1305 authenticated decryption, as specified by RFC 8439
+
+use thiserror::Error;
+
+pub const KEY_BYTES: usize = 32;
+pub const NONCE_BYTES: usize = 12;
+pub const TAG_BYTES: usize = 16;
+
+/// Reasons a ciphertext cannot be opened
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum OpenError {
+    #[error("plaintext buffer length does not match the ciphertext")]
+    InvalidLength,
+    #[error("ciphertext failed authentication")]
+    AuthenticationFailed,
+}
+
+/// Verify `ciphertext` and its additional data `aad` against `tag`, then decrypt
+/// `ciphertext` into `plaintext`, which must be the same length.  `plaintext` is left
+/// untouched if authentication fails
+pub fn open(
+    key: &[u8; KEY_BYTES],
+    nonce: &[u8; NONCE_BYTES],
+    aad: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8; TAG_BYTES],
+    plaintext: &mut [u8],
+) -> Result<(), OpenError> {
+    if plaintext.len() != ciphertext.len() {
+        return Err(OpenError::InvalidLength);
+    }
+
+    // Perform the calculation inline, calling this from within a program is
+    // not supported
+    #[cfg(not(target_arch = "bpf"))]
+    {
+        use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305};
+
+        // Only fails for keys of the wrong length
+        let key = LessSafeKey::new(UnboundKey::new(&CHACHA20_POLY1305, key).unwrap());
+        let mut in_out = Vec::with_capacity(ciphertext.len() + TAG_BYTES);
+        in_out.extend_from_slice(ciphertext);
+        in_out.extend_from_slice(tag);
+        let opened = key
+            .open_in_place(
+                Nonce::assume_unique_for_key(*nonce),
+                Aad::from(aad),
+                &mut in_out,
+            )
+            .map_err(|_| OpenError::AuthenticationFailed)?;
+        plaintext.copy_from_slice(opened);
+        Ok(())
+    }
+    // Call via a system call to perform the calculation
+    #[cfg(target_arch = "bpf")]
+    {
+        extern "C" {
+            fn sol_chacha20_poly1305_open(
+                inputs: *const u8,
+                inputs_len: u64,
+                plaintext: *mut u8,
+                plaintext_len: u64,
+            ) -> u64;
+        };
+        let inputs: [&[u8]; 5] = [key, nonce, aad, ciphertext, tag];
+        let result = unsafe {
+            sol_chacha20_poly1305_open(
+                &inputs as *const _ as *const u8,
+                inputs.len() as u64,
+                plaintext.as_mut_ptr(),
+                plaintext.len() as u64,
+            )
+        };
+        match result {
+            0 => Ok(()),
+            _ => Err(OpenError::AuthenticationFailed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open() {
+        // RFC 8439 section 2.8.2
+        let mut key = [0; KEY_BYTES];
+        for (i, byte) in key.iter_mut().enumerate() {
+            *byte = 0x80 + i as u8;
+        }
+        let nonce = [7, 0, 0, 0, 0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47];
+        let aad = hex::decode("50515253c0c1c2c3c4c5c6c7").unwrap();
+        let ciphertext = hex::decode(
+            "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d63dbea45e8ca967128\
+             2fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b3692ddbd7f2d778b8c9803aee328091b58fa\
+             b324e4fad675945585808b4831d7bc3ff4def08e4b7a9de576d26586cec64b6116",
+        )
+        .unwrap();
+        let mut tag = [0; TAG_BYTES];
+        tag.copy_from_slice(&hex::decode("1ae10b594f09e26a7e902ecbd0600691").unwrap());
+
+        let mut plaintext = vec![0; ciphertext.len()];
+        assert_eq!(
+            open(&key, &nonce, &aad, &ciphertext, &tag, &mut plaintext),
+            Ok(())
+        );
+        assert_eq!(
+            plaintext,
+            b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for \
+              the future, sunscreen would be it."
+                .to_vec()
+        );
+
+        let mut plaintext = vec![0; ciphertext.len()];
+        assert_eq!(
+            open(&key, &nonce, &aad[1..], &ciphertext, &tag, &mut plaintext),
+            Err(OpenError::AuthenticationFailed)
+        );
+        assert!(plaintext.iter().all(|byte| *byte == 0));
+        let mut bad_tag = tag;
+        bad_tag[TAG_BYTES - 1] ^= 1;
+        assert_eq!(
+            open(&key, &nonce, &aad, &ciphertext, &bad_tag, &mut plaintext),
+            Err(OpenError::AuthenticationFailed)
+        );
+        assert_eq!(
+            open(&key, &nonce, &aad, &ciphertext, &tag, &mut plaintext[1..]),
+            Err(OpenError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn test_open_rfc8439_appendix_a5() {
+        let mut key = [0; KEY_BYTES];
+        key.copy_from_slice(
+            &hex::decode("1c9240a5eb55d38af333888604f6b5f0473917c1402b80099dca5cbc207075c0")
+                .unwrap(),
+        );
+        let nonce = [0, 0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 8];
+        let aad = hex::decode("f33388860000000000004e91").unwrap();
+        let ciphertext = hex::decode(
+            "64a0861575861af460f062c79be643bd5e805cfd345cf389f108670ac76c8cb24c6cfc18755d43eea\
+             09ee94e382d26b0bdb7b73c321b0100d4f03b7f355894cf332f830e710b97ce98c8a84abd0b948114\
+             ad176e008d33bd60f982b1ff37c8559797a06ef4f0ef61c186324e2b3506383606907b6a7c02b0f9f\
+             6157b53c867e4b9166c767b804d46a59b5216cde7a4e99040c5a40433225ee282a1b0a06c523eaf45\
+             34d7f83fa1155b0047718cbc546a0d072b04b3564eea1b422273f548271a0bb2316053fa76991955e\
+             bd63159434ecebb4e466dae5a1073a6727627097a1049e617d91d361094fa68f0ff77987130305bea\
+             ba2eda04df997b714d6c6f2c29a6ad5cb4022b02709b",
+        )
+        .unwrap();
+        let mut tag = [0; TAG_BYTES];
+        tag.copy_from_slice(&hex::decode("eead9d67890cbb22392336fea1851f38").unwrap());
+
+        let mut plaintext = vec![0; ciphertext.len()];
+        assert_eq!(
+            open(&key, &nonce, &aad, &ciphertext, &tag, &mut plaintext),
+            Ok(())
+        );
+        assert_eq!(
+            String::from_utf8(plaintext).unwrap(),
+            "Internet-Drafts are draft documents valid for a maximum of six months and may be \
+             updated, replaced, or obsoleted by other documents at any time. It is inappropriate \
+             to use Internet-Drafts as reference material or to cite them other than as \
+             /\u{201c}work in progress./\u{201d}"
+        );
+    }
+
+    #[test]
+    fn test_open_empty() {
+        let key = [0xff; KEY_BYTES];
+        let nonce = [0xff; NONCE_BYTES];
+        let mut tag = [0; TAG_BYTES];
+        tag.copy_from_slice(&hex::decode("0b5528ab3ff425fbf1fce9fcfb979029").unwrap());
+        assert_eq!(open(&key, &nonce, &[], &[], &tag, &mut []), Ok(()));
+        assert_eq!(
+            open(&key, &nonce, &[0], &[], &tag, &mut []),
+            Err(OpenError::AuthenticationFailed)
+        );
+    }
+}
@@ -91,3 +91,73 @@ pub struct Clock {
     /// timestamp_correction and timestamp_bounding features
     pub unix_timestamp: UnixTimestamp,
 }
+
+impl Clock {
+    /// Clock at `slot` consistent with `epoch_schedule`, with timestamps advancing by
+    /// `DEFAULT_MS_PER_SLOT` from `genesis_timestamp` instead of following wallclock time
+    pub fn new_for_slot(
+        slot: Slot,
+        epoch_schedule: &crate::epoch_schedule::EpochSchedule,
+        genesis_timestamp: UnixTimestamp,
+    ) -> Self {
+        let timestamp_at = |slot: Slot| {
+            genesis_timestamp
+                .saturating_add((slot.saturating_mul(DEFAULT_MS_PER_SLOT) / 1_000) as UnixTimestamp)
+        };
+        let epoch = epoch_schedule.get_epoch(slot);
+        Self {
+            slot,
+            epoch_start_timestamp: timestamp_at(epoch_schedule.get_first_slot_in_epoch(epoch)),
+            epoch,
+            leader_schedule_epoch: epoch_schedule.get_leader_schedule_epoch(slot),
+            unix_timestamp: timestamp_at(slot),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::epoch_schedule::EpochSchedule;
+
+    #[test]
+    fn test_clock_new_for_slot() {
+        for epoch_schedule in &[
+            EpochSchedule::custom(64, 64, true),
+            EpochSchedule::custom(64, 64, false),
+        ] {
+            for slot in 0..epoch_schedule.first_normal_slot + 3 * epoch_schedule.slots_per_epoch {
+                let clock = Clock::new_for_slot(slot, epoch_schedule, 1_000);
+                let epoch = epoch_schedule.get_epoch(slot);
+                let first_slot = epoch_schedule.get_first_slot_in_epoch(epoch);
+                assert_eq!(clock.slot, slot);
+                assert_eq!(clock.epoch, epoch);
+                assert!(clock.leader_schedule_epoch >= clock.epoch);
+                assert!(clock.epoch_start_timestamp <= clock.unix_timestamp);
+                assert_eq!(
+                    clock.epoch_start_timestamp,
+                    Clock::new_for_slot(first_slot, epoch_schedule, 1_000).unix_timestamp
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_clock_new_for_slot_near_max() {
+        let epoch_schedule = EpochSchedule::custom(64, 64, true);
+        let mut last_timestamp = 0;
+        for slot in std::u64::MAX - 2 * epoch_schedule.slots_per_epoch..=std::u64::MAX {
+            let clock = Clock::new_for_slot(slot, &epoch_schedule, 1_000);
+            assert_eq!(clock.slot, slot);
+            assert!(clock.leader_schedule_epoch >= clock.epoch);
+            assert!(clock.epoch_start_timestamp <= clock.unix_timestamp);
+            assert!(clock.unix_timestamp >= last_timestamp);
+            last_timestamp = clock.unix_timestamp;
+        }
+
+        // Timestamps saturate rather than wrap
+        let clock = Clock::new_for_slot(std::u64::MAX, &epoch_schedule, UnixTimestamp::MAX);
+        assert_eq!(clock.unix_timestamp, UnixTimestamp::MAX);
+        assert_eq!(clock.epoch_start_timestamp, UnixTimestamp::MAX);
+    }
+}
@@ -92,7 +92,7 @@ impl EpochSchedule {
             self.get_epoch_and_slot_index(slot).0 + 1
         } else {
             self.first_normal_epoch
-                + (slot - self.first_normal_slot + self.leader_schedule_slot_offset)
+                + (slot - self.first_normal_slot).saturating_add(self.leader_schedule_slot_offset)
                     / self.slots_per_epoch
         }
     }
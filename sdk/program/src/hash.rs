@@ -129,11 +129,144 @@ pub fn hashv(vals: &[&[u8]]) -> Hash {
     }
 }
 
+/// Sha256 hash computed incrementally, for data that is not available all at once.
+///
+/// Within a program the runtime holds the intermediate state, so the data does not have to be
+/// gathered into a single buffer first
+pub struct Sha256Context {
+    #[cfg(not(target_arch = "bpf"))]
+    hasher: Hasher,
+    #[cfg(target_arch = "bpf")]
+    handle: u64,
+}
+
+#[cfg(target_arch = "bpf")]
+extern "C" {
+    fn sol_sha256_init() -> u64;
+    fn sol_sha256_update(handle: u64, vals: *const u8, val_len: u64) -> u64;
+    fn sol_sha256_final(handle: u64, hash_result: *mut u8) -> u64;
+}
+
+impl Default for Sha256Context {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sha256Context {
+    pub fn new() -> Self {
+        #[cfg(not(target_arch = "bpf"))]
+        {
+            Self {
+                hasher: Hasher::default(),
+            }
+        }
+        #[cfg(target_arch = "bpf")]
+        {
+            Self {
+                handle: unsafe { sol_sha256_init() },
+            }
+        }
+    }
+
+    /// Hash the given data into the context
+    pub fn update(&mut self, vals: &[&[u8]]) {
+        #[cfg(not(target_arch = "bpf"))]
+        self.hasher.hashv(vals);
+        #[cfg(target_arch = "bpf")]
+        unsafe {
+            sol_sha256_update(
+                self.handle,
+                vals as *const _ as *const u8,
+                vals.len() as u64,
+            );
+        }
+    }
+
+    /// Return the Sha256 hash of all the data hashed into the context
+    pub fn finalize(self) -> Hash {
+        #[cfg(not(target_arch = "bpf"))]
+        {
+            self.hasher.result()
+        }
+        #[cfg(target_arch = "bpf")]
+        {
+            let mut hash_result = [0; HASH_BYTES];
+            unsafe {
+                sol_sha256_final(self.handle, &mut hash_result as *mut _ as *mut u8);
+            }
+            Hash::new_from_array(hash_result)
+        }
+    }
+}
+
 /// Return a Sha256 hash for the given data.
 pub fn hash(val: &[u8]) -> Hash {
     hashv(&[val])
 }
 
+/// Return a HMAC-SHA256 of the given data under the given key.
+pub fn hmacv(key: &[u8], vals: &[&[u8]]) -> Hash {
+    // Perform the calculation inline, calling this from within a program is
+    // not supported
+    #[cfg(not(target_arch = "bpf"))]
+    {
+        const SHA256_BLOCK_SIZE: usize = 64;
+
+        let mut padded_key = [0u8; SHA256_BLOCK_SIZE];
+        if key.len() > SHA256_BLOCK_SIZE {
+            padded_key[..HASH_BYTES].copy_from_slice(hash(key).as_ref());
+        } else {
+            padded_key[..key.len()].copy_from_slice(key);
+        }
+        let mut inner_pad = [0x36u8; SHA256_BLOCK_SIZE];
+        let mut outer_pad = [0x5cu8; SHA256_BLOCK_SIZE];
+        for ((inner, outer), key) in inner_pad
+            .iter_mut()
+            .zip(outer_pad.iter_mut())
+            .zip(padded_key.iter())
+        {
+            *inner ^= key;
+            *outer ^= key;
+        }
+
+        let mut hasher = Hasher::default();
+        hasher.hash(&inner_pad);
+        hasher.hashv(vals);
+        let inner_hash = hasher.result();
+        hashv(&[&outer_pad, inner_hash.as_ref()])
+    }
+    // Call via a system call to perform the calculation
+    #[cfg(target_arch = "bpf")]
+    {
+        extern "C" {
+            fn sol_hmac_sha256(
+                key: *const u8,
+                key_len: u64,
+                vals: *const u8,
+                val_len: u64,
+                hash_result: *mut u8,
+            ) -> u64;
+        };
+        let mut hash_result = [0; HASH_BYTES];
+        unsafe {
+            sol_hmac_sha256(
+                key.as_ptr(),
+                key.len() as u64,
+                vals as *const _ as *const u8,
+                vals.len() as u64,
+                &mut hash_result as *mut _ as *mut u8,
+            );
+        }
+        Hash::new_from_array(hash_result)
+    }
+}
+
+/// Return a HMAC-SHA256 of the given data under the given key.
+pub fn hmac(key: &[u8], val: &[u8]) -> Hash {
+    hmacv(key, &[val])
+}
+
 /// Return the hash of the given hash extended with the given value.
 pub fn extend_and_hash(id: &Hash, val: &[u8]) -> Hash {
     let mut hash_data = id.as_ref().to_vec();
@@ -145,6 +278,47 @@ pub fn extend_and_hash(id: &Hash, val: &[u8]) -> Hash {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_sha256_context() {
+        let mut context = Sha256Context::new();
+        context.update(&[b"Gaggablaghblagh!"]);
+        context.update(&[]);
+        context.update(&[b"flur", b"bos"]);
+        assert_eq!(
+            context.finalize(),
+            hashv(&[b"Gaggablaghblagh!", b"flurbos"])
+        );
+        assert_eq!(Sha256Context::default().finalize(), hashv(&[]));
+    }
+
+    #[test]
+    fn test_hmac() {
+        // RFC 4231 test cases 1, 2 and 6
+        let cases: Vec<(Vec<u8>, &[u8], &str)> = vec![
+            (
+                vec![0x0b; 20],
+                b"Hi There",
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+            ),
+            (
+                b"Jefe".to_vec(),
+                b"what do ya want for nothing?",
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            ),
+            (
+                vec![0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First",
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+            ),
+        ];
+        for (key, data, expected) in cases {
+            let expected = Hash::new(&hex::decode(expected).unwrap());
+            assert_eq!(hmac(&key, data), expected);
+            let (head, tail) = data.split_at(data.len() / 2);
+            assert_eq!(hmacv(&key, &[head, &[], tail]), expected);
+        }
+    }
+
     #[test]
     fn test_new_unique() {
         assert!(Hash::new_unique() != Hash::new_unique());
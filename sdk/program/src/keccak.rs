@@ -0,0 +1,114 @@
+//! The `keccak` module provides the Keccak-256 hash used by Ethereum
+
+#[cfg(not(target_arch = "bpf"))]
+use sha3::Digest;
+
+pub const HASH_BYTES: usize = 32;
+
+/// Keccak-256 hash computed incrementally, for data that is not available all at once.
+///
+/// Within a program the runtime holds the intermediate state, so the data does not have to be
+/// gathered into a single buffer first
+pub struct Keccak256Context {
+    #[cfg(not(target_arch = "bpf"))]
+    hasher: sha3::Keccak256,
+    #[cfg(target_arch = "bpf")]
+    handle: u64,
+}
+
+#[cfg(target_arch = "bpf")]
+extern "C" {
+    fn sol_keccak256_init() -> u64;
+    fn sol_keccak256_update(handle: u64, vals: *const u8, val_len: u64) -> u64;
+    fn sol_keccak256_final(handle: u64, hash_result: *mut u8) -> u64;
+}
+
+impl Default for Keccak256Context {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Keccak256Context {
+    pub fn new() -> Self {
+        #[cfg(not(target_arch = "bpf"))]
+        {
+            Self {
+                hasher: sha3::Keccak256::new(),
+            }
+        }
+        #[cfg(target_arch = "bpf")]
+        {
+            Self {
+                handle: unsafe { sol_keccak256_init() },
+            }
+        }
+    }
+
+    /// Hash the given data into the context
+    pub fn update(&mut self, vals: &[&[u8]]) {
+        #[cfg(not(target_arch = "bpf"))]
+        for val in vals {
+            self.hasher.update(val);
+        }
+        #[cfg(target_arch = "bpf")]
+        unsafe {
+            sol_keccak256_update(
+                self.handle,
+                vals as *const _ as *const u8,
+                vals.len() as u64,
+            );
+        }
+    }
+
+    /// Return the Keccak-256 hash of all the data hashed into the context
+    pub fn finalize(self) -> [u8; HASH_BYTES] {
+        let mut hash_result = [0; HASH_BYTES];
+        #[cfg(not(target_arch = "bpf"))]
+        hash_result.copy_from_slice(&self.hasher.finalize());
+        #[cfg(target_arch = "bpf")]
+        unsafe {
+            sol_keccak256_final(self.handle, &mut hash_result as *mut _ as *mut u8);
+        }
+        hash_result
+    }
+}
+
+/// Return a Keccak-256 hash for the given data.
+pub fn hashv(vals: &[&[u8]]) -> [u8; HASH_BYTES] {
+    let mut context = Keccak256Context::new();
+    context.update(vals);
+    context.finalize()
+}
+
+/// Return a Keccak-256 hash for the given data.
+pub fn hash(val: &[u8]) -> [u8; HASH_BYTES] {
+    hashv(&[val])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keccak256() {
+        assert_eq!(
+            hex::encode(hash(&[])),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        assert_eq!(
+            hex::encode(hash(b"abc")),
+            "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+        );
+    }
+
+    #[test]
+    fn test_keccak256_context() {
+        let mut context = Keccak256Context::new();
+        context.update(&[b"Gaggablaghblagh!"]);
+        context.update(&[]);
+        context.update(&[b"flur", b"bos"]);
+        assert_eq!(context.finalize(), hash(b"Gaggablaghblagh!flurbos"));
+        assert_eq!(Keccak256Context::default().finalize(), hash(&[]));
+    }
+}
@@ -0,0 +1,113 @@
+//! Decoding of the length prefixes that precede serialized sequences
+
+#[cfg(not(target_arch = "bpf"))]
+use crate::short_vec;
+use std::convert::TryFrom;
+
+/// Encodings of a length prefix
+#[repr(u64)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LengthPrefix {
+    /// Compact-u16, as used by `short_vec`, 1 to 3 bytes
+    CompactU16 = 0,
+    /// Little-endian u32, as used by Borsh, 4 bytes
+    BorshU32 = 1,
+}
+
+impl LengthPrefix {
+    /// Maximum number of bytes the prefix occupies
+    pub fn max_size(self) -> usize {
+        match self {
+            LengthPrefix::CompactU16 => 3,
+            LengthPrefix::BorshU32 => 4,
+        }
+    }
+}
+
+impl TryFrom<u64> for LengthPrefix {
+    type Error = ();
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(LengthPrefix::CompactU16),
+            1 => Ok(LengthPrefix::BorshU32),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Return the decoded length and how many bytes the prefix consumed, `None`
+/// if `bytes` does not start with a valid prefix
+pub fn decode(prefix: LengthPrefix, bytes: &[u8]) -> Option<(usize, usize)> {
+    // Perform the calculation inline, calling this from within a program is
+    // not supported
+    #[cfg(not(target_arch = "bpf"))]
+    {
+        let bytes = &bytes[..bytes.len().min(prefix.max_size())];
+        match prefix {
+            LengthPrefix::CompactU16 => short_vec::decode_len(bytes).ok(),
+            LengthPrefix::BorshU32 => {
+                let mut value = [0; 4];
+                value.copy_from_slice(bytes.get(..4)?);
+                Some((u32::from_le_bytes(value) as usize, 4))
+            }
+        }
+    }
+    // Call via a system call to perform the calculation
+    #[cfg(target_arch = "bpf")]
+    {
+        extern "C" {
+            fn sol_decode_length_prefix(
+                input_addr: *const u8,
+                input_len: u64,
+                encoding: u64,
+                value_addr: *mut u64,
+                consumed_addr: *mut u64,
+            ) -> u64;
+        };
+        let mut value = 0u64;
+        let mut consumed = 0u64;
+        let result = unsafe {
+            sol_decode_length_prefix(
+                bytes.as_ptr(),
+                bytes.len() as u64,
+                prefix as u64,
+                &mut value,
+                &mut consumed,
+            )
+        };
+        match result {
+            0 => Some((value as usize, consumed as usize)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(
+            decode(LengthPrefix::CompactU16, &[0x7f, 0xff]),
+            Some((0x7f, 1))
+        );
+        assert_eq!(
+            decode(LengthPrefix::CompactU16, &[0xff, 0xff, 0x03, 0xff]),
+            Some((0xffff, 3))
+        );
+        assert_eq!(decode(LengthPrefix::CompactU16, &[0x80]), None);
+        assert_eq!(
+            decode(LengthPrefix::CompactU16, &[0x80, 0x80, 0x80, 0x00]),
+            None
+        );
+        assert_eq!(decode(LengthPrefix::CompactU16, &[]), None);
+
+        assert_eq!(
+            decode(LengthPrefix::BorshU32, &[0x01, 0x02, 0x00, 0x00, 0xff]),
+            Some((0x201, 4))
+        );
+        assert_eq!(decode(LengthPrefix::BorshU32, &[0x01, 0x02, 0x00]), None);
+    }
+}
@@ -5,8 +5,11 @@
 extern crate self as solana_program;
 
 pub mod account_info;
+pub mod base58;
+pub mod bech32;
 pub mod bpf_loader;
 pub mod bpf_loader_deprecated;
+pub mod chacha20_poly1305;
 pub mod clock;
 pub mod decode_error;
 pub mod entrypoint;
@@ -17,6 +20,8 @@ pub mod fee_calculator;
 pub mod hash;
 pub mod incinerator;
 pub mod instruction;
+pub mod keccak;
+pub mod length_prefix;
 pub mod loader_instruction;
 pub mod log;
 pub mod message;
@@ -27,10 +32,14 @@ pub mod program_error;
 pub mod program_option;
 pub mod program_pack;
 pub mod program_stubs;
+pub mod pseudorandom;
 pub mod pubkey;
 pub mod rent;
+pub mod runtime_limits;
+pub mod runtime_version;
 pub mod sanitize;
 pub mod secp256k1_program;
+pub mod secp256r1;
 pub mod serialize_utils;
 pub mod short_vec;
 pub mod slot_hashes;
@@ -39,6 +48,8 @@ pub mod stake_history;
 pub mod system_instruction;
 pub mod system_program;
 pub mod sysvar;
+pub mod transaction_info;
+pub mod x25519;
 
 /// Convenience macro to declare a static public key and functions to interact with it
 ///
@@ -85,6 +85,23 @@ extern "C" {
     fn sol_log_64_(arg1: u64, arg2: u64, arg3: u64, arg4: u64, arg5: u64);
 }
 
+/// Print raw bytes to the log, rendered as base64
+///
+/// @param data - Bytes to print
+#[inline]
+pub fn sol_log_data(data: &[u8]) {
+    #[cfg(target_arch = "bpf")]
+    {
+        extern "C" {
+            fn sol_log_data(data: *const u8, len: u64);
+        };
+        unsafe { sol_log_data(data.as_ptr(), data.len() as u64) };
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    crate::program_stubs::sol_log_data(data);
+}
+
 /// Print the hexadecimal representation of a slice
 ///
 /// @param slice - The array to print
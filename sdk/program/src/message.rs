@@ -331,6 +331,25 @@ impl Message {
         i < self.header.num_required_signatures as usize
     }
 
+    /// Return the account metas of an instruction as the runtime will see them.  Duplicate
+    /// pubkeys are collapsed into a single message key when the message is created, so every
+    /// occurrence of a duplicated account carries the union of the privileges requested for it.
+    pub fn instruction_account_metas(&self, instruction_index: usize) -> Option<Vec<AccountMeta>> {
+        let instruction = self.instructions.get(instruction_index)?;
+        instruction
+            .accounts
+            .iter()
+            .map(|account_index| {
+                let account_index = *account_index as usize;
+                Some(AccountMeta {
+                    pubkey: *self.account_keys.get(account_index)?,
+                    is_signer: self.is_signer(account_index),
+                    is_writable: self.is_writable(account_index),
+                })
+            })
+            .collect()
+    }
+
     pub fn get_account_keys_by_lock_type(&self) -> (Vec<&Pubkey>, Vec<&Pubkey>) {
         let mut writable_keys = vec![];
         let mut readonly_keys = vec![];
@@ -783,6 +802,58 @@ mod tests {
         );
     }
 
+    fn duplicate_account_metas(pubkey: Pubkey) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(pubkey, false),
+            AccountMeta::new(pubkey, false),
+            AccountMeta::new_readonly(pubkey, true),
+        ]
+    }
+
+    #[test]
+    fn test_message_duplicate_account_metas() {
+        let program_id = Pubkey::new_unique();
+        let id0 = Pubkey::new_unique();
+        let id1 = Pubkey::new_unique();
+        let mut account_metas = duplicate_account_metas(id0);
+        account_metas.push(AccountMeta::new_readonly(id1, false));
+        let instructions = vec![Instruction::new(program_id, &0, account_metas)];
+
+        // Duplicates collapse into a single signed, writable key
+        let keys = get_keys(&instructions, None);
+        assert_eq!(keys, InstructionKeys::new(vec![id0], vec![id1, program_id], 0, 2));
+
+        let message = Message::new(&instructions, None);
+        let metas = message.instruction_account_metas(0).unwrap();
+        assert_eq!(metas.len(), 4);
+        for meta in &metas[..3] {
+            assert_eq!(*meta, AccountMeta::new(id0, true));
+        }
+        assert_eq!(metas[3], AccountMeta::new_readonly(id1, false));
+        assert_eq!(message.instruction_account_metas(1), None);
+    }
+
+    #[test]
+    fn test_message_duplicate_account_metas_across_instructions() {
+        let program_id = Pubkey::new_unique();
+        let id0 = Pubkey::new_unique();
+        let instructions = vec![
+            Instruction::new(program_id, &0, vec![AccountMeta::new_readonly(id0, false)]),
+            Instruction::new(program_id, &0, vec![AccountMeta::new(id0, false)]),
+        ];
+
+        // Privileges requested by a later instruction apply to earlier ones as well
+        let message = Message::new(&instructions, None);
+        assert_eq!(
+            message.instruction_account_metas(0),
+            Some(vec![AccountMeta::new(id0, false)])
+        );
+        assert_eq!(
+            message.instruction_account_metas(1),
+            Some(vec![AccountMeta::new(id0, false)])
+        );
+    }
+
     #[test]
     fn test_decompile_instructions() {
         solana_logger::setup();
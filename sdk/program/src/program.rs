@@ -1,4 +1,6 @@
-use crate::{account_info::AccountInfo, entrypoint::ProgramResult, instruction::Instruction};
+use crate::{
+    account_info::AccountInfo, entrypoint::ProgramResult, instruction::Instruction, pubkey::Pubkey,
+};
 
 /// Invoke a cross-program instruction
 ///
@@ -54,6 +56,127 @@ pub fn invoke_signed(
     crate::program_stubs::sol_invoke_signed(instruction, account_infos, signers_seeds)
 }
 
+/// Transfer lamports out of a system account
+///
+/// Behaves like invoking `system_instruction::transfer`, but without the overhead of a
+/// cross-program invocation.  `from` must be a signer and, as with `invoke`, the system program
+/// must also be included in `account_infos`.
+pub fn system_transfer(
+    from: &Pubkey,
+    to: &Pubkey,
+    lamports: u64,
+    account_infos: &[AccountInfo],
+) -> ProgramResult {
+    system_transfer_signed(from, to, lamports, account_infos, &[])
+}
+
+/// Transfer lamports out of a system account with program signatures
+///
+/// Like `system_transfer`, but `from` may instead be a program derived address signed for by
+/// `signers_seeds`, as with `invoke_signed`.
+pub fn system_transfer_signed(
+    from: &Pubkey,
+    to: &Pubkey,
+    lamports: u64,
+    account_infos: &[AccountInfo],
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    #[cfg(target_arch = "bpf")]
+    {
+        /// Must match the layout of `SolSystemTransfer` in the C SDK
+        #[repr(C)]
+        struct SystemTransfer<'a> {
+            from: &'a Pubkey,
+            to: &'a Pubkey,
+            lamports: u64,
+        }
+
+        // Check that the account RefCells are consistent with the request
+        for account_info in account_infos.iter() {
+            if account_info.key == from || account_info.key == to {
+                let _ = account_info.try_borrow_mut_lamports()?;
+            }
+        }
+
+        let result = unsafe {
+            sol_system_transfer_rust(
+                &SystemTransfer { from, to, lamports } as *const _ as *const u8,
+                account_infos as *const _ as *const u8,
+                account_infos.len() as u64,
+                signers_seeds as *const _ as *const u8,
+                signers_seeds.len() as u64,
+            )
+        };
+        match result {
+            crate::entrypoint::SUCCESS => Ok(()),
+            _ => Err(result.into()),
+        }
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    invoke_signed(
+        &crate::system_instruction::transfer(from, to, lamports),
+        account_infos,
+        signers_seeds,
+    )
+}
+
+/// Maximum depth of cross-program invocations, counting the top-level instruction
+///
+/// Programs can compare this against their own depth to decide whether another cross-program
+/// invocation is possible.
+pub fn get_max_invoke_depth() -> u64 {
+    #[cfg(target_arch = "bpf")]
+    unsafe {
+        sol_get_max_invoke_depth()
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    crate::program_stubs::sol_get_max_invoke_depth()
+}
+
+/// Program IDs of the invocation stack, from the top-level instruction's program to the
+/// currently executing program
+///
+/// A program ID's index plus one is its stack height, so programs can check which programs,
+/// if any, invoked them.
+pub fn get_instruction_ancestry() -> Vec<Pubkey> {
+    #[cfg(target_arch = "bpf")]
+    {
+        // Query the stack height first to size the buffer
+        let mut stack_height = 0;
+        unsafe {
+            sol_get_instruction_ancestry(std::ptr::null_mut(), 0, &mut stack_height);
+        }
+        let mut program_ids = vec![Pubkey::default(); stack_height as usize];
+        unsafe {
+            sol_get_instruction_ancestry(
+                program_ids.as_mut_ptr(),
+                program_ids.len() as u64,
+                &mut stack_height,
+            );
+        }
+        program_ids
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    crate::program_stubs::sol_get_instruction_ancestry()
+}
+
+/// Halt the program immediately, reporting `code` as the reason
+///
+/// Unlike returning a custom `ProgramError`, aborting stops execution at the call site, so
+/// assertion helpers can report a machine readable reason instead of a panic message.
+pub fn abort_with_code(code: u64) -> ! {
+    #[cfg(target_arch = "bpf")]
+    unsafe {
+        sol_abort_with_code(code)
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    crate::program_stubs::sol_abort_with_code(code)
+}
+
 #[cfg(target_arch = "bpf")]
 extern "C" {
     fn sol_invoke_signed_rust(
@@ -63,4 +186,22 @@ extern "C" {
         signers_seeds_addr: *const u8,
         signers_seeds_len: u64,
     ) -> u64;
+
+    fn sol_system_transfer_rust(
+        transfer_addr: *const u8,
+        account_infos_addr: *const u8,
+        account_infos_len: u64,
+        signers_seeds_addr: *const u8,
+        signers_seeds_len: u64,
+    ) -> u64;
+
+    fn sol_get_max_invoke_depth() -> u64;
+
+    fn sol_abort_with_code(code: u64) -> !;
+
+    fn sol_get_instruction_ancestry(
+        program_ids_addr: *mut Pubkey,
+        program_ids_len: u64,
+        stack_height_addr: *mut u64,
+    ) -> u64;
 }
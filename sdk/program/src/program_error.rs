@@ -209,6 +209,7 @@ impl From<PubkeyError> for ProgramError {
         match error {
             PubkeyError::MaxSeedLengthExceeded => ProgramError::MaxSeedLengthExceeded,
             PubkeyError::InvalidSeeds => ProgramError::InvalidSeeds,
+            PubkeyError::TooManyProgramAddresses => ProgramError::InvalidArgument,
         }
     }
 }
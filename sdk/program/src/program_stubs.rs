@@ -2,7 +2,10 @@
 
 #![cfg(not(target_arch = "bpf"))]
 
-use crate::{account_info::AccountInfo, entrypoint::ProgramResult, instruction::Instruction};
+use crate::{
+    account_info::AccountInfo, entrypoint::ProgramResult, hash::Hash, instruction::Instruction,
+    pubkey::Pubkey, runtime_limits::RuntimeLimits, transaction_info::SIGNATURE_BYTES,
+};
 use std::sync::{Arc, RwLock};
 
 lazy_static::lazy_static! {
@@ -19,6 +22,9 @@ pub trait SyscallStubs: Sync + Send {
     fn sol_log(&self, message: &str) {
         println!("{}", message);
     }
+    fn sol_log_data(&self, data: &[u8]) {
+        println!("Program data: {}", base64::encode(data));
+    }
     fn sol_log_compute_units(&self) {
         sol_log("SyscallStubs: sol_log_compute_units() not available");
     }
@@ -31,6 +37,40 @@ pub trait SyscallStubs: Sync + Send {
         sol_log("SyscallStubs: sol_invoke_signed() not available");
         Ok(())
     }
+    fn sol_abort_with_code(&self, code: u64) -> ! {
+        panic!("SyscallStubs: program aborted with code {}", code);
+    }
+    fn sol_get_max_invoke_depth(&self) -> u64 {
+        sol_log("SyscallStubs: sol_get_max_invoke_depth() not available");
+        0
+    }
+    fn sol_get_runtime_version(&self) -> u64 {
+        sol_log("SyscallStubs: sol_get_runtime_version() not available");
+        0
+    }
+    fn sol_get_runtime_limits(&self) -> RuntimeLimits {
+        sol_log("SyscallStubs: sol_get_runtime_limits() not available");
+        RuntimeLimits::default()
+    }
+    fn sol_get_pseudorandom(&self, _bytes: &mut [u8]) {
+        sol_log("SyscallStubs: sol_get_pseudorandom() not available");
+    }
+    fn sol_get_instruction_ancestry(&self) -> Vec<Pubkey> {
+        sol_log("SyscallStubs: sol_get_instruction_ancestry() not available");
+        vec![]
+    }
+    fn sol_get_transaction_signature(&self, _index: u64) -> Option<[u8; SIGNATURE_BYTES]> {
+        sol_log("SyscallStubs: sol_get_transaction_signature() not available");
+        None
+    }
+    fn sol_get_recent_blockhash_of_tx(&self) -> Hash {
+        sol_log("SyscallStubs: sol_get_recent_blockhash_of_tx() not available");
+        Hash::default()
+    }
+    fn sol_get_fee_payer(&self) -> Pubkey {
+        sol_log("SyscallStubs: sol_get_fee_payer() not available");
+        Pubkey::default()
+    }
 }
 
 struct DefaultSyscallStubs {}
@@ -47,10 +87,56 @@ pub(crate) fn sol_log_64(arg1: u64, arg2: u64, arg3: u64, arg4: u64, arg5: u64)
     ));
 }
 
+pub(crate) fn sol_log_data(data: &[u8]) {
+    SYSCALL_STUBS.read().unwrap().sol_log_data(data);
+}
+
 pub(crate) fn sol_log_compute_units() {
     SYSCALL_STUBS.read().unwrap().sol_log_compute_units();
 }
 
+pub(crate) fn sol_abort_with_code(code: u64) -> ! {
+    SYSCALL_STUBS.read().unwrap().sol_abort_with_code(code)
+}
+
+pub(crate) fn sol_get_max_invoke_depth() -> u64 {
+    SYSCALL_STUBS.read().unwrap().sol_get_max_invoke_depth()
+}
+
+pub(crate) fn sol_get_runtime_version() -> u64 {
+    SYSCALL_STUBS.read().unwrap().sol_get_runtime_version()
+}
+
+pub(crate) fn sol_get_runtime_limits() -> RuntimeLimits {
+    SYSCALL_STUBS.read().unwrap().sol_get_runtime_limits()
+}
+
+pub(crate) fn sol_get_pseudorandom(bytes: &mut [u8]) {
+    SYSCALL_STUBS.read().unwrap().sol_get_pseudorandom(bytes)
+}
+
+pub(crate) fn sol_get_instruction_ancestry() -> Vec<Pubkey> {
+    SYSCALL_STUBS.read().unwrap().sol_get_instruction_ancestry()
+}
+
+pub(crate) fn sol_get_transaction_signature(index: u64) -> Option<[u8; SIGNATURE_BYTES]> {
+    SYSCALL_STUBS
+        .read()
+        .unwrap()
+        .sol_get_transaction_signature(index)
+}
+
+pub(crate) fn sol_get_recent_blockhash_of_tx() -> Hash {
+    SYSCALL_STUBS
+        .read()
+        .unwrap()
+        .sol_get_recent_blockhash_of_tx()
+}
+
+pub(crate) fn sol_get_fee_payer() -> Pubkey {
+    SYSCALL_STUBS.read().unwrap().sol_get_fee_payer()
+}
+
 pub(crate) fn sol_invoke_signed(
     instruction: &Instruction,
     account_infos: &[AccountInfo],
@@ -0,0 +1,95 @@
+//! Deterministic pseudorandom bytes
+//!
+//! The bytes are derived from the transaction's recent blockhash, the executing
+//! program's id and a counter of the draws made so far in the transaction, all of
+//! which are known before the transaction is executed.  They are *not* unpredictable: the transaction's signer and the
+//! leader can both compute them in advance, so they must not decide outcomes an
+//! adversary could profit from knowing.
+
+use crate::{
+    hash::{hashv, Hash, HASH_BYTES},
+    pubkey::Pubkey,
+};
+
+/// Fill `bytes` with the pseudorandom bytes for `counter`
+///
+/// Block `i` of `HASH_BYTES` bytes is the SHA256 of the recent blockhash, the
+/// program id, `counter` and `i`, with both integers as little-endian `u64`s.
+pub fn derive_pseudorandom(
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    counter: u64,
+    bytes: &mut [u8],
+) {
+    for (i, chunk) in bytes.chunks_mut(HASH_BYTES).enumerate() {
+        let block = hashv(&[
+            recent_blockhash.as_ref(),
+            program_id.as_ref(),
+            &counter.to_le_bytes(),
+            &(i as u64).to_le_bytes(),
+        ]);
+        chunk.copy_from_slice(&block.as_ref()[..chunk.len()]);
+    }
+}
+
+/// Fill `bytes` with deterministic pseudorandom bytes
+///
+/// Each call within the transaction yields new bytes: the counter is shared by
+/// every instruction and cross-program invocation, so a program invoked twice in
+/// a transaction does not see the same sequence twice.
+pub fn get_pseudorandom(bytes: &mut [u8]) {
+    #[cfg(target_arch = "bpf")]
+    {
+        extern "C" {
+            fn sol_get_pseudorandom(bytes_addr: *mut u8, bytes_len: u64) -> u64;
+        }
+        unsafe {
+            sol_get_pseudorandom(bytes.as_mut_ptr(), bytes.len() as u64);
+        }
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    crate::program_stubs::sol_get_pseudorandom(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive_pseudorandom() {
+        let recent_blockhash = hashv(&[b"blockhash"]);
+        let program_id = Pubkey::new_unique();
+
+        let mut bytes = [0; HASH_BYTES + 8];
+        derive_pseudorandom(&recent_blockhash, &program_id, 0, &mut bytes);
+        let first = hashv(&[
+            recent_blockhash.as_ref(),
+            program_id.as_ref(),
+            &0u64.to_le_bytes(),
+            &0u64.to_le_bytes(),
+        ]);
+        let second = hashv(&[
+            recent_blockhash.as_ref(),
+            program_id.as_ref(),
+            &0u64.to_le_bytes(),
+            &1u64.to_le_bytes(),
+        ]);
+        assert_eq!(&bytes[..HASH_BYTES], first.as_ref());
+        assert_eq!(&bytes[HASH_BYTES..], &second.as_ref()[..8]);
+
+        // Shorter outputs are a prefix of longer ones
+        let mut prefix = [0; 4];
+        derive_pseudorandom(&recent_blockhash, &program_id, 0, &mut prefix);
+        assert_eq!(prefix, bytes[..4]);
+
+        // Every input changes the output
+        let mut other = [0; HASH_BYTES + 8];
+        derive_pseudorandom(&recent_blockhash, &program_id, 1, &mut other);
+        assert_ne!(other, bytes);
+        derive_pseudorandom(&recent_blockhash, &Pubkey::new_unique(), 0, &mut other);
+        assert_ne!(other, bytes);
+        derive_pseudorandom(&Hash::default(), &program_id, 0, &mut other);
+        assert_ne!(other, bytes);
+    }
+}
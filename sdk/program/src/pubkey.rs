@@ -7,6 +7,8 @@ use thiserror::Error;
 pub const MAX_SEED_LEN: usize = 32;
 /// Maximum number of seeds
 pub const MAX_SEEDS: usize = 16;
+/// Maximum number of addresses created by one `create_program_addresses` call
+pub const MAX_PROGRAM_ADDRESSES: usize = 16;
 
 #[derive(Error, Debug, Serialize, Clone, PartialEq, FromPrimitive, ToPrimitive)]
 pub enum PubkeyError {
@@ -15,6 +17,9 @@ pub enum PubkeyError {
     MaxSeedLengthExceeded,
     #[error("Provided seeds do not result in a valid address")]
     InvalidSeeds,
+    /// More sets of seeds than `MAX_PROGRAM_ADDRESSES` for a single call
+    #[error("Too many sets of seeds for address generation")]
+    TooManyProgramAddresses,
 }
 impl<T> DecodeError<T> for PubkeyError {
     fn type_of() -> &'static str {
@@ -26,6 +31,7 @@ impl From<u64> for PubkeyError {
         match error {
             0 => PubkeyError::MaxSeedLengthExceeded,
             1 => PubkeyError::InvalidSeeds,
+            2 => PubkeyError::TooManyProgramAddresses,
             _ => panic!("Unsupported PubkeyError"),
         }
     }
@@ -194,6 +200,54 @@ impl Pubkey {
         }
     }
 
+    /// Create a valid program address for each set of seeds
+    ///
+    /// Equivalent to calling `create_program_address` for each set, but made in a single
+    /// system call from a program.  Fails if any set of seeds does not produce a valid
+    /// program address or if there are more than `MAX_PROGRAM_ADDRESSES` sets.
+    pub fn create_program_addresses(
+        seed_sets: &[&[&[u8]]],
+        program_id: &Pubkey,
+    ) -> Result<Vec<Pubkey>, PubkeyError> {
+        if seed_sets.len() > MAX_PROGRAM_ADDRESSES {
+            return Err(PubkeyError::TooManyProgramAddresses);
+        }
+        // Perform the calculation inline, calling this from within a program is
+        // not supported
+        #[cfg(not(target_arch = "bpf"))]
+        {
+            seed_sets
+                .iter()
+                .map(|seeds| Self::create_program_address(seeds, program_id))
+                .collect()
+        }
+        // Call via a system call to perform the calculation
+        #[cfg(target_arch = "bpf")]
+        {
+            extern "C" {
+                fn sol_create_program_addresses_batch(
+                    seed_sets_addr: *const u8,
+                    seed_sets_len: u64,
+                    program_id_addr: *const u8,
+                    addresses_addr: *mut u8,
+                ) -> u64;
+            };
+            let mut addresses = vec![Pubkey::default(); seed_sets.len()];
+            let result = unsafe {
+                sol_create_program_addresses_batch(
+                    seed_sets as *const _ as *const u8,
+                    seed_sets.len() as u64,
+                    program_id as *const _ as *const u8,
+                    addresses.as_mut_ptr() as *mut u8,
+                )
+            };
+            match result {
+                crate::entrypoint::SUCCESS => Ok(addresses),
+                _ => Err(result.into()),
+            }
+        }
+    }
+
     /// Find a valid program address and its corresponding bump seed which must be passed
     /// as an additional seed when calling `invoke_signed`.
     ///
@@ -205,10 +259,23 @@ impl Pubkey {
 
     /// Find a valid program address and its corresponding bump seed which must be passed
     /// as an additional seed when calling `invoke_signed`
-    #[allow(clippy::same_item_push)]
     pub fn try_find_program_address(seeds: &[&[u8]], program_id: &Pubkey) -> Option<(Pubkey, u8)> {
-        let mut bump_seed = [std::u8::MAX];
-        for _ in 0..std::u8::MAX {
+        Self::try_find_program_address_from(seeds, program_id, std::u8::MAX)
+    }
+
+    /// Find a valid program address and its corresponding bump seed, trying bump seeds
+    /// downwards from `starting_bump_seed` rather than from `u8::MAX`
+    ///
+    /// Programs that know an upper bound on the bump seed can use this to cap the number
+    /// of addresses created, and so the compute consumed, in the worst case.
+    #[allow(clippy::same_item_push)]
+    pub fn try_find_program_address_from(
+        seeds: &[&[u8]],
+        program_id: &Pubkey,
+        starting_bump_seed: u8,
+    ) -> Option<(Pubkey, u8)> {
+        let mut bump_seed = [starting_bump_seed];
+        for _ in 0..starting_bump_seed {
             {
                 let mut seeds_with_bump = seeds.to_vec();
                 seeds_with_bump.push(&bump_seed);
@@ -403,6 +470,87 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_find_program_address_from() {
+        for _ in 0..100 {
+            let program_id = Pubkey::new_unique();
+            let canonical = Pubkey::find_program_address(&[b"Lil'", b"Bits"], &program_id);
+            let (_, bump_seed) = canonical;
+            assert_eq!(
+                Pubkey::try_find_program_address_from(&[b"Lil'", b"Bits"], &program_id, bump_seed),
+                Some(canonical)
+            );
+            if bump_seed < std::u8::MAX {
+                assert_eq!(
+                    Pubkey::try_find_program_address_from(
+                        &[b"Lil'", b"Bits"],
+                        &program_id,
+                        bump_seed + 1
+                    ),
+                    Some(canonical)
+                );
+            }
+            // Starting below the canonical bump seed never yields it
+            if let Some((address, lower_bump_seed)) = Pubkey::try_find_program_address_from(
+                &[b"Lil'", b"Bits"],
+                &program_id,
+                bump_seed - 1,
+            ) {
+                assert!(lower_bump_seed < bump_seed);
+                assert_eq!(
+                    address,
+                    Pubkey::create_program_address(
+                        &[b"Lil'", b"Bits", &[lower_bump_seed]],
+                        &program_id
+                    )
+                    .unwrap()
+                );
+            }
+        }
+        // Bump seed zero is never tried
+        assert_eq!(
+            Pubkey::try_find_program_address_from(&[b"Lil'"], &Pubkey::new_unique(), 0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_create_program_addresses() {
+        let program_id = Pubkey::new_unique();
+        let (address1, bump_seed1) = Pubkey::find_program_address(&[b"Talking"], &program_id);
+        let (address2, bump_seed2) = Pubkey::find_program_address(&[b"Lil'", b"Bits"], &program_id);
+        assert_eq!(
+            Pubkey::create_program_addresses(
+                &[
+                    &[b"Talking", &[bump_seed1]],
+                    &[b"Lil'", b"Bits", &[bump_seed2]]
+                ],
+                &program_id
+            ),
+            Ok(vec![address1, address2])
+        );
+        assert_eq!(
+            Pubkey::create_program_addresses(&[], &program_id),
+            Ok(vec![])
+        );
+        assert_eq!(
+            Pubkey::create_program_addresses(
+                &[&[b"Talking", &[bump_seed1]], &[&[127; MAX_SEED_LEN + 1]]],
+                &program_id
+            ),
+            Err(PubkeyError::MaxSeedLengthExceeded)
+        );
+        let seeds: &[&[u8]] = &[b"Talking", &[bump_seed1]];
+        assert_eq!(
+            Pubkey::create_program_addresses(&[seeds; MAX_PROGRAM_ADDRESSES], &program_id),
+            Ok(vec![address1; MAX_PROGRAM_ADDRESSES])
+        );
+        assert_eq!(
+            Pubkey::create_program_addresses(&[seeds; MAX_PROGRAM_ADDRESSES + 1], &program_id),
+            Err(PubkeyError::TooManyProgramAddresses)
+        );
+    }
+
     #[test]
     fn test_pubkey_off_curve() {
         // try a bunch of random input, all successful generated program
@@ -0,0 +1,40 @@
+//! Limits the runtime enforces on programs
+//!
+//! Several of these depend on which features are active, so programs that size
+//! their work against them should query them rather than hard-code constants.
+
+/// Limits in effect for the executing program
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RuntimeLimits {
+    /// Maximum cross-program invocation depth, including the top-level instruction
+    pub max_invoke_depth: u64,
+    /// Maximum BPF to BPF call depth
+    pub max_call_depth: u64,
+    /// Size of each stack frame in bytes
+    pub stack_frame_size: u64,
+    /// Size of the program heap in bytes
+    pub heap_size: u64,
+    /// Maximum number of program signers in a cross-program invocation
+    pub max_signers: u64,
+    /// Number of bytes an account's data may grow by within one instruction
+    pub max_permitted_data_increase: u64,
+}
+
+/// Limits the runtime enforces on the executing program
+pub fn get_runtime_limits() -> RuntimeLimits {
+    #[cfg(target_arch = "bpf")]
+    {
+        extern "C" {
+            fn sol_get_runtime_limits(limits_addr: *mut u8) -> u64;
+        }
+        let mut limits = RuntimeLimits::default();
+        unsafe {
+            sol_get_runtime_limits(&mut limits as *mut _ as *mut u8);
+        }
+        limits
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    crate::program_stubs::sol_get_runtime_limits()
+}
@@ -0,0 +1,79 @@
+//! Version of the interface the runtime presents to programs
+//!
+//! Unlike feature gates, which toggle individual behaviors, the runtime version
+//! identifies a revision of the program ABI as a whole so programs and SDKs can
+//! branch on it.
+
+/// Semantic version of the runtime's program interface
+#[repr(C)]
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub struct RuntimeVersion {
+    /// Incremented for changes that existing programs may observe
+    pub major: u16,
+    /// Incremented for backwards compatible additions such as new syscalls
+    pub minor: u16,
+    /// Incremented for backwards compatible fixes
+    pub patch: u16,
+}
+
+impl RuntimeVersion {
+    pub const fn new(major: u16, minor: u16, patch: u16) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Pack into the `u64` returned by `sol_get_runtime_version`, ordered the
+    /// same way as the versions themselves
+    pub fn to_u64(self) -> u64 {
+        (u64::from(self.major) << 32) | (u64::from(self.minor) << 16) | u64::from(self.patch)
+    }
+
+    /// Unpack the `u64` returned by `sol_get_runtime_version`
+    pub fn from_u64(value: u64) -> Self {
+        Self {
+            major: (value >> 32) as u16,
+            minor: (value >> 16) as u16,
+            patch: value as u16,
+        }
+    }
+}
+
+impl std::fmt::Display for RuntimeVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Version of the interface presented by the runtime executing the program
+pub fn get_runtime_version() -> RuntimeVersion {
+    #[cfg(target_arch = "bpf")]
+    {
+        extern "C" {
+            fn sol_get_runtime_version() -> u64;
+        }
+        RuntimeVersion::from_u64(unsafe { sol_get_runtime_version() })
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    RuntimeVersion::from_u64(crate::program_stubs::sol_get_runtime_version())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runtime_version_packing() {
+        let version = RuntimeVersion::new(1, 2, 3);
+        assert_eq!(version.to_u64(), 0x0001_0002_0003);
+        assert_eq!(RuntimeVersion::from_u64(version.to_u64()), version);
+        assert_eq!(version.to_string(), "1.2.3");
+        assert!(RuntimeVersion::new(1, 3, 0).to_u64() > RuntimeVersion::new(1, 2, 65_535).to_u64());
+        assert!(RuntimeVersion::new(1, 3, 0) > RuntimeVersion::new(1, 2, 65_535));
+    }
+}
@@ -0,0 +1,75 @@
+//! Verification of secp256r1 (NIST P-256) ECDSA signatures, as produced by WebAuthn
+//! authenticators
+
+/// Size of a SEC1 uncompressed public key
+pub const SECP256R1_PUBKEY_BYTES: usize = 65;
+/// Size of a signature encoded as the big-endian `r` followed by the big-endian `s`
+pub const SECP256R1_SIGNATURE_BYTES: usize = 64;
+
+/// Verify `signature` by `pubkey` over the SHA256 of `message`
+pub fn verify(
+    message: &[u8],
+    signature: &[u8; SECP256R1_SIGNATURE_BYTES],
+    pubkey: &[u8; SECP256R1_PUBKEY_BYTES],
+) -> bool {
+    #[cfg(target_arch = "bpf")]
+    {
+        extern "C" {
+            fn sol_secp256r1_verify(
+                message_addr: *const u8,
+                message_len: u64,
+                signature_addr: *const u8,
+                pubkey_addr: *const u8,
+            ) -> u64;
+        };
+        let result = unsafe {
+            sol_secp256r1_verify(
+                message.as_ptr(),
+                message.len() as u64,
+                signature.as_ptr(),
+                pubkey.as_ptr(),
+            )
+        };
+        result == 0
+    }
+
+    // Perform the verification inline, calling this from within a program is
+    // not supported
+    #[cfg(not(target_arch = "bpf"))]
+    {
+        use ring::signature::{UnparsedPublicKey, ECDSA_P256_SHA256_FIXED};
+
+        UnparsedPublicKey::new(&ECDSA_P256_SHA256_FIXED, &pubkey[..])
+            .verify(message, signature)
+            .is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify() {
+        let pubkey: [u8; SECP256R1_PUBKEY_BYTES] = [
+            0x04, 0x9f, 0xad, 0x84, 0xae, 0xae, 0x08, 0xbb, 0xef, 0x7f, 0x01, 0x00, 0x14, 0xd8,
+            0x2c, 0xef, 0x6a, 0x09, 0xde, 0x2b, 0x0c, 0xf8, 0x71, 0xb5, 0xce, 0x0c, 0x4f, 0x1d,
+            0x13, 0xa5, 0x9a, 0x59, 0x34, 0x07, 0xcb, 0x45, 0x76, 0x9f, 0x10, 0x70, 0xe2, 0xc2,
+            0x47, 0x0f, 0xe5, 0xb1, 0xbf, 0xe6, 0x31, 0x33, 0xc0, 0xb0, 0xcd, 0xc6, 0x4e, 0xa4,
+            0xbf, 0x37, 0x91, 0xa8, 0xec, 0x2a, 0x07, 0xfd, 0x4f,
+        ];
+        let signature: [u8; SECP256R1_SIGNATURE_BYTES] = [
+            0x17, 0x3d, 0x5e, 0x75, 0x8f, 0xc5, 0x20, 0xe6, 0xfa, 0x97, 0x59, 0x55, 0xc5, 0xef,
+            0x60, 0xdb, 0xc7, 0x11, 0x31, 0x1f, 0x25, 0x37, 0xd2, 0xb1, 0x01, 0x91, 0x81, 0x73,
+            0x3e, 0x2f, 0xd8, 0x81, 0xeb, 0x46, 0x46, 0xad, 0xb8, 0xb1, 0x60, 0x83, 0x32, 0x38,
+            0x75, 0xe4, 0x62, 0x22, 0xe8, 0x53, 0x7d, 0xe0, 0x2b, 0xf5, 0x0e, 0xed, 0xd8, 0x1f,
+            0xc0, 0x41, 0x44, 0x3e, 0xa5, 0x6c, 0x61, 0x09,
+        ];
+        assert!(verify(b"hello passkey", &signature, &pubkey));
+        assert!(!verify(b"hello passkez", &signature, &pubkey));
+
+        let mut malformed_pubkey = pubkey;
+        malformed_pubkey[0] = 0x02;
+        assert!(!verify(b"hello passkey", &signature, &malformed_pubkey));
+    }
+}
@@ -34,6 +34,14 @@ impl SlotHashes {
         slot_hashes.sort_by(|(a, _), (b, _)| b.cmp(a));
         Self(slot_hashes)
     }
+    /// Slot hashes as seen by a bank at `slot` whose ancestors are every preceding slot,
+    /// each with a hash derived from the slot number
+    pub fn new_for_slot(slot: Slot) -> Self {
+        (slot.saturating_sub(MAX_ENTRIES as Slot)..slot)
+            .rev()
+            .map(|slot| (slot, crate::hash::hashv(&[&slot.to_le_bytes()])))
+            .collect()
+    }
 }
 
 impl FromIterator<(Slot, Hash)> for SlotHashes {
@@ -54,6 +62,27 @@ mod tests {
     use super::*;
     use crate::hash::hash;
 
+    #[test]
+    fn test_new_for_slot() {
+        assert!(SlotHashes::new_for_slot(0).is_empty());
+        let slot_hashes = SlotHashes::new_for_slot(3);
+        assert_eq!(
+            slot_hashes
+                .iter()
+                .map(|(slot, _)| *slot)
+                .collect::<Vec<_>>(),
+            vec![2, 1, 0]
+        );
+        let slot_hashes = SlotHashes::new_for_slot(MAX_ENTRIES as Slot + 10);
+        assert_eq!(slot_hashes.len(), MAX_ENTRIES);
+        assert_eq!(slot_hashes[0].0, MAX_ENTRIES as Slot + 9);
+        assert_eq!(slot_hashes[MAX_ENTRIES - 1].0, 10);
+        assert_eq!(
+            slot_hashes,
+            SlotHashes::new_for_slot(MAX_ENTRIES as Slot + 10)
+        );
+    }
+
     #[test]
     fn test() {
         let mut slot_hashes = SlotHashes::new(&[(1, Hash::default()), (3, Hash::default())]);
@@ -1,6 +1,7 @@
 //! named accounts for synthesized data accounts for bank state, etc.
 //!
 use crate::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
+use std::{convert::TryInto, mem::size_of, ops::Range};
 
 pub mod clock;
 pub mod epoch_schedule;
@@ -72,6 +73,29 @@ pub trait Sysvar:
     }
 }
 
+/// Size of the entry count that precedes the entries of list sysvars
+const ENTRY_COUNT_SIZE: usize = size_of::<u64>();
+
+/// Byte range of the `index`th entry of a list sysvar's account data
+pub(crate) fn entry_range(index: usize, entry_size: usize) -> Range<usize> {
+    let start = ENTRY_COUNT_SIZE.saturating_add(index.saturating_mul(entry_size));
+    start..start.saturating_add(entry_size)
+}
+
+/// Deserialize only the `index`th entry of a list sysvar's account data, `None` if the
+/// sysvar holds fewer entries
+pub(crate) fn get_entry<T: serde::de::DeserializeOwned>(
+    data: &[u8],
+    index: usize,
+    entry_size: usize,
+) -> Option<T> {
+    let entry_count = u64::from_le_bytes(data.get(..ENTRY_COUNT_SIZE)?.try_into().ok()?);
+    if index as u64 >= entry_count {
+        return None;
+    }
+    bincode::deserialize(data.get(entry_range(index, entry_size))?).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
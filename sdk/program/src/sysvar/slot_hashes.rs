@@ -4,7 +4,8 @@
 //!
 pub use crate::slot_hashes::SlotHashes;
 
-use crate::sysvar::Sysvar;
+use crate::{clock::Slot, hash::HASH_BYTES, slot_hashes::SlotHash, sysvar::Sysvar};
+use std::{mem::size_of, ops::Range};
 
 crate::declare_sysvar_id!("SysvarS1otHashes111111111111111111111111111", SlotHashes);
 
@@ -16,10 +17,24 @@ impl Sysvar for SlotHashes {
     }
 }
 
+/// Size of a serialized `SlotHash`
+pub const ENTRY_SIZE: usize = size_of::<Slot>() + HASH_BYTES;
+
+/// Byte range of the `index`th entry, newest first, within the account data
+pub fn entry_range(index: usize) -> Range<usize> {
+    super::entry_range(index, ENTRY_SIZE)
+}
+
+/// Read the `index`th entry, newest first, from the account data without deserializing
+/// the whole sysvar
+pub fn get_entry(data: &[u8], index: usize) -> Option<SlotHash> {
+    super::get_entry(data, index, ENTRY_SIZE)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{clock::Slot, hash::Hash, slot_hashes::MAX_ENTRIES};
+    use crate::{hash::Hash, slot_hashes::MAX_ENTRIES};
 
     #[test]
     fn test_size_of() {
@@ -33,4 +48,27 @@ mod tests {
             .unwrap() as usize
         );
     }
+
+    #[test]
+    fn test_get_entry() {
+        let slot_hashes = (0..10)
+            .map(|slot| (slot as Slot, Hash::new_unique()))
+            .collect::<SlotHashes>();
+        let mut data = vec![0; SlotHashes::size_of()];
+        bincode::serialize_into(&mut data[..], &slot_hashes).unwrap();
+
+        for (index, slot_hash) in slot_hashes.iter().enumerate() {
+            assert_eq!(get_entry(&data, index), Some(*slot_hash));
+            assert_eq!(
+                bincode::deserialize::<SlotHash>(&data[entry_range(index)]).unwrap(),
+                *slot_hash
+            );
+        }
+        assert_eq!(get_entry(&data, slot_hashes.len()), None);
+        assert_eq!(get_entry(&data, MAX_ENTRIES), None);
+        assert_eq!(get_entry(&data, usize::MAX), None);
+        assert_eq!(get_entry(&data[..entry_range(3).end - 1], 3), None);
+        assert_eq!(get_entry(&[], 0), None);
+        assert_eq!(entry_range(MAX_ENTRIES - 1).end, SlotHashes::size_of());
+    }
 }
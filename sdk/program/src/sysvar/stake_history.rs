@@ -4,7 +4,8 @@
 //!
 pub use crate::stake_history::StakeHistory;
 
-use crate::sysvar::Sysvar;
+use crate::{clock::Epoch, stake_history::StakeHistoryEntry, sysvar::Sysvar};
+use std::{mem::size_of, ops::Range};
 
 crate::declare_sysvar_id!("SysvarStakeHistory1111111111111111111111111", StakeHistory);
 
@@ -16,6 +17,20 @@ impl Sysvar for StakeHistory {
     }
 }
 
+/// Size of a serialized `(Epoch, StakeHistoryEntry)`
+pub const ENTRY_SIZE: usize = size_of::<Epoch>() + 3 * size_of::<u64>();
+
+/// Byte range of the `index`th entry, newest first, within the account data
+pub fn entry_range(index: usize) -> Range<usize> {
+    super::entry_range(index, ENTRY_SIZE)
+}
+
+/// Read the `index`th entry, newest first, from the account data without deserializing
+/// the whole sysvar
+pub fn get_entry(data: &[u8], index: usize) -> Option<(Epoch, StakeHistoryEntry)> {
+    super::get_entry(data, index, ENTRY_SIZE)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -40,6 +55,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_get_entry() {
+        let mut stake_history = StakeHistory::default();
+        for i in 0..10 {
+            stake_history.add(
+                i,
+                StakeHistoryEntry {
+                    effective: i * 3,
+                    activating: i * 2,
+                    deactivating: i,
+                },
+            );
+        }
+        let mut data = vec![0; StakeHistory::size_of()];
+        bincode::serialize_into(&mut data[..], &stake_history).unwrap();
+
+        for (index, entry) in stake_history.iter().enumerate() {
+            assert_eq!(get_entry(&data, index).as_ref(), Some(entry));
+        }
+        assert_eq!(get_entry(&data, 0).unwrap().0, 9);
+        assert_eq!(get_entry(&data, stake_history.len()), None);
+        assert_eq!(get_entry(&data, usize::MAX), None);
+        assert_eq!(entry_range(MAX_ENTRIES - 1).end, StakeHistory::size_of());
+    }
+
     #[test]
     fn test_create_account() {
         let mut stake_history = StakeHistory::default();
@@ -0,0 +1,69 @@
+//! Information about the transaction being processed
+
+use crate::{hash::Hash, pubkey::Pubkey};
+
+/// Number of bytes in a transaction signature
+pub const SIGNATURE_BYTES: usize = 64;
+
+/// Signature of the transaction being processed at `index`, `None` if the transaction has
+/// fewer signatures
+///
+/// The first signature uniquely identifies the transaction, so it can serve as a source of
+/// per-transaction entropy or a deduplication key.
+pub fn get_transaction_signature(index: usize) -> Option<[u8; SIGNATURE_BYTES]> {
+    #[cfg(target_arch = "bpf")]
+    {
+        extern "C" {
+            fn sol_get_transaction_signature(index: u64, signature_addr: *mut u8) -> u64;
+        }
+        let mut signature = [0; SIGNATURE_BYTES];
+        match unsafe { sol_get_transaction_signature(index as u64, signature.as_mut_ptr()) } {
+            0 => Some(signature),
+            _ => None,
+        }
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    crate::program_stubs::sol_get_transaction_signature(index as u64)
+}
+
+/// Recent blockhash the transaction being processed was signed against
+///
+/// Programs implementing expiry or commit-reveal schemes can use this rather than the
+/// `RecentBlockhashes` sysvar.
+pub fn get_recent_blockhash() -> Hash {
+    #[cfg(target_arch = "bpf")]
+    {
+        extern "C" {
+            fn sol_get_recent_blockhash_of_tx(blockhash_addr: *mut u8) -> u64;
+        }
+        let mut blockhash = Hash::default();
+        unsafe {
+            sol_get_recent_blockhash_of_tx(&mut blockhash as *mut _ as *mut u8);
+        }
+        blockhash
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    crate::program_stubs::sol_get_recent_blockhash_of_tx()
+}
+
+/// Fee payer of the transaction being processed
+///
+/// Programs no longer need the payer passed as an instruction account purely to identify it.
+pub fn get_fee_payer() -> Pubkey {
+    #[cfg(target_arch = "bpf")]
+    {
+        extern "C" {
+            fn sol_get_fee_payer(fee_payer_addr: *mut u8) -> u64;
+        }
+        let mut fee_payer = Pubkey::default();
+        unsafe {
+            sol_get_fee_payer(&mut fee_payer as *mut _ as *mut u8);
+        }
+        fee_payer
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    crate::program_stubs::sol_get_fee_payer()
+}
@@ -0,0 +1,98 @@
+//! X25519 Diffie-Hellman key agreement, as specified by RFC 7748
+
+pub const X25519_BYTES: usize = 32;
+
+/// Multiply the Montgomery u-coordinate `point` by the clamped `scalar`, `None` if `point` is of
+/// small order and the shared secret would be all zeroes
+pub fn diffie_hellman(
+    scalar: &[u8; X25519_BYTES],
+    point: &[u8; X25519_BYTES],
+) -> Option<[u8; X25519_BYTES]> {
+    // Perform the calculation inline, calling this from within a program is
+    // not supported
+    #[cfg(not(target_arch = "bpf"))]
+    {
+        use curve25519_dalek::{montgomery::MontgomeryPoint, scalar::Scalar};
+
+        let mut clamped = *scalar;
+        clamped[0] &= 248;
+        clamped[31] &= 127;
+        clamped[31] |= 64;
+        let shared_secret = (MontgomeryPoint(*point) * Scalar::from_bits(clamped)).to_bytes();
+        if shared_secret == [0; X25519_BYTES] {
+            None
+        } else {
+            Some(shared_secret)
+        }
+    }
+    // Call via a system call to perform the calculation
+    #[cfg(target_arch = "bpf")]
+    {
+        extern "C" {
+            fn sol_x25519_dh(
+                scalar_addr: *const u8,
+                point_addr: *const u8,
+                out_addr: *mut u8,
+            ) -> u64;
+        };
+        let mut shared_secret = [0; X25519_BYTES];
+        let result =
+            unsafe { sol_x25519_dh(scalar.as_ptr(), point.as_ptr(), shared_secret.as_mut_ptr()) };
+        match result {
+            0 => Some(shared_secret),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_hex(s: &str) -> [u8; X25519_BYTES] {
+        let mut bytes = [0; X25519_BYTES];
+        bytes.copy_from_slice(&hex::decode(s).unwrap());
+        bytes
+    }
+
+    #[test]
+    fn test_diffie_hellman() {
+        // RFC 7748 section 5.2
+        assert_eq!(
+            diffie_hellman(
+                &from_hex("a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4"),
+                &from_hex("e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c"),
+            ),
+            Some(from_hex(
+                "c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552"
+            ))
+        );
+
+        // RFC 7748 section 6.1
+        let alice_secret =
+            from_hex("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a");
+        let bob_secret =
+            from_hex("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb");
+        let mut base_point = [0; X25519_BYTES];
+        base_point[0] = 9;
+        let alice_public = diffie_hellman(&alice_secret, &base_point).unwrap();
+        let bob_public = diffie_hellman(&bob_secret, &base_point).unwrap();
+        assert_eq!(
+            alice_public,
+            from_hex("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a")
+        );
+        let shared_secret =
+            from_hex("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742");
+        assert_eq!(
+            diffie_hellman(&alice_secret, &bob_public),
+            Some(shared_secret)
+        );
+        assert_eq!(
+            diffie_hellman(&bob_secret, &alice_public),
+            Some(shared_secret)
+        );
+
+        // Small order points are rejected
+        assert_eq!(diffie_hellman(&alice_secret, &[0; X25519_BYTES]), None);
+    }
+}
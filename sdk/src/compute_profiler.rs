@@ -0,0 +1,232 @@
+//! Breakdown of the compute units consumed by programs, by syscall and by invocation
+
+use crate::pubkey::Pubkey;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+/// Compute units consumed by all invocations of a single syscall
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SyscallProfile {
+    /// Number of times the syscall was invoked
+    pub invocations: u64,
+    /// Compute units consumed by the syscall, excluding any programs it
+    /// invoked, which are profiled as frames of their own
+    pub compute_units_consumed: u64,
+}
+
+/// Compute units consumed by a single program invocation
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FrameProfile {
+    /// Program invoked
+    pub program_id: Pubkey,
+    /// Depth of the invocation stack, 1 for instructions of the message itself
+    pub invoke_depth: usize,
+    /// Compute units consumed, including any programs it invoked
+    pub compute_units_consumed: u64,
+    /// Compute units consumed by the program itself
+    pub exclusive_compute_units_consumed: u64,
+}
+
+/// Report produced by a `ComputeProfiler`
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ComputeProfile {
+    /// Syscall profiles keyed by syscall name
+    pub syscalls: BTreeMap<String, SyscallProfile>,
+    /// Frame profiles, the frames of each instruction are contiguous and in
+    /// the order the programs were invoked
+    pub frames: Vec<FrameProfile>,
+}
+
+/// Records the compute units consumed by every syscall and every program
+/// invocation, shared by all the instructions being processed
+#[derive(Debug, Default)]
+pub struct ComputeProfiler {
+    profile: Mutex<ComputeProfile>,
+}
+
+impl ComputeProfiler {
+    /// Record an invocation of the syscall named `name`
+    pub fn record_syscall(&self, name: &str, compute_units_consumed: u64) {
+        let mut profile = self.profile.lock().unwrap();
+        let syscall = profile.syscalls.entry(name.to_string()).or_default();
+        syscall.invocations += 1;
+        syscall.compute_units_consumed += compute_units_consumed;
+    }
+
+    fn record_frames(&self, frames: &mut Vec<FrameProfile>) {
+        self.profile.lock().unwrap().frames.append(frames);
+    }
+
+    /// Get the profile of everything recorded so far
+    pub fn report(&self) -> ComputeProfile {
+        self.profile.lock().unwrap().clone()
+    }
+}
+
+#[derive(Debug)]
+struct OpenFrame {
+    index: usize,
+    remaining_at_entry: u64,
+    consumed_by_callees: u64,
+}
+
+/// Tracks the invocation stack of a single instruction on behalf of a
+/// `ComputeProfiler`, its frames are recorded once the outermost invocation
+/// completes so that instructions processed in parallel do not interleave
+#[derive(Debug)]
+pub struct InvocationProfiler {
+    profiler: Arc<ComputeProfiler>,
+    open_frames: Vec<OpenFrame>,
+    frames: Vec<FrameProfile>,
+}
+
+impl InvocationProfiler {
+    pub fn new(profiler: Arc<ComputeProfiler>) -> Self {
+        Self {
+            profiler,
+            open_frames: vec![],
+            frames: vec![],
+        }
+    }
+
+    /// Record an invocation of `program_id`, `remaining` is the number of
+    /// compute units left before it starts
+    pub fn enter_frame(&mut self, program_id: &Pubkey, remaining: u64) {
+        self.open_frames.push(OpenFrame {
+            index: self.frames.len(),
+            remaining_at_entry: remaining,
+            consumed_by_callees: 0,
+        });
+        self.frames.push(FrameProfile {
+            program_id: *program_id,
+            invoke_depth: self.open_frames.len(),
+            compute_units_consumed: 0,
+            exclusive_compute_units_consumed: 0,
+        });
+    }
+
+    /// Complete the most recently entered invocation, `remaining` is the
+    /// number of compute units left after it returned
+    pub fn exit_frame(&mut self, remaining: u64) {
+        if let Some(frame) = self.open_frames.pop() {
+            let consumed = frame.remaining_at_entry.saturating_sub(remaining);
+            let profile = &mut self.frames[frame.index];
+            profile.compute_units_consumed = consumed;
+            profile.exclusive_compute_units_consumed =
+                consumed.saturating_sub(frame.consumed_by_callees);
+            match self.open_frames.last_mut() {
+                Some(caller) => caller.consumed_by_callees += consumed,
+                None => self.profiler.record_frames(&mut self.frames),
+            }
+        }
+    }
+
+    /// Compute units consumed so far by the completed callees of the current
+    /// invocation
+    pub fn consumed_by_callees(&self) -> u64 {
+        self.open_frames
+            .last()
+            .map_or(0, |frame| frame.consumed_by_callees)
+    }
+
+    /// Record an invocation of the syscall named `name`, excluding the compute
+    /// units consumed by the programs it invoked
+    pub fn record_syscall(&self, name: &str, compute_units_consumed: u64) {
+        self.profiler.record_syscall(name, compute_units_consumed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_profiler() {
+        let profiler = Arc::new(ComputeProfiler::default());
+        assert_eq!(profiler.report(), ComputeProfile::default());
+
+        let caller = Pubkey::new_unique();
+        let callee = Pubkey::new_unique();
+        let mut invocation_profiler = InvocationProfiler::new(profiler.clone());
+        invocation_profiler.enter_frame(&caller, 1_000);
+        invocation_profiler.record_syscall("sol_log_", 100);
+        invocation_profiler.enter_frame(&callee, 800);
+        invocation_profiler.record_syscall("sol_log_", 100);
+        invocation_profiler.record_syscall("sol_sha256", 85);
+        invocation_profiler.exit_frame(500);
+        assert_eq!(invocation_profiler.consumed_by_callees(), 300);
+        invocation_profiler.enter_frame(&callee, 450);
+        invocation_profiler.exit_frame(400);
+        assert_eq!(invocation_profiler.consumed_by_callees(), 350);
+
+        // Frames are only recorded once the outermost invocation completes
+        assert!(profiler.report().frames.is_empty());
+        invocation_profiler.exit_frame(300);
+        assert_eq!(invocation_profiler.consumed_by_callees(), 0);
+        let mut invocation_profiler = InvocationProfiler::new(profiler.clone());
+        invocation_profiler.enter_frame(&caller, 1_000);
+        invocation_profiler.exit_frame(900);
+
+        let report = profiler.report();
+        assert_eq!(
+            report.syscalls.get("sol_log_"),
+            Some(&SyscallProfile {
+                invocations: 2,
+                compute_units_consumed: 200,
+            })
+        );
+        assert_eq!(report.syscalls["sol_sha256"].invocations, 1);
+        assert_eq!(
+            frame_summaries(&report),
+            vec![
+                (caller, 1, 700, 350),
+                (callee, 2, 300, 300),
+                (callee, 2, 50, 50),
+                (caller, 1, 100, 100),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compute_profiler_interleaved_instructions() {
+        // Instructions processed in parallel share the profiler but not their stacks
+        let profiler = Arc::new(ComputeProfiler::default());
+        let first = Pubkey::new_unique();
+        let second = Pubkey::new_unique();
+        let callee = Pubkey::new_unique();
+        let mut first_profiler = InvocationProfiler::new(profiler.clone());
+        let mut second_profiler = InvocationProfiler::new(profiler.clone());
+        first_profiler.enter_frame(&first, 1_000);
+        second_profiler.enter_frame(&second, 1_000);
+        first_profiler.enter_frame(&callee, 900);
+        second_profiler.exit_frame(980);
+        first_profiler.exit_frame(800);
+        first_profiler.exit_frame(700);
+
+        assert_eq!(
+            frame_summaries(&profiler.report()),
+            vec![
+                (second, 1, 20, 20),
+                (first, 1, 300, 200),
+                (callee, 2, 100, 100),
+            ]
+        );
+    }
+
+    fn frame_summaries(report: &ComputeProfile) -> Vec<(Pubkey, usize, u64, u64)> {
+        report
+            .frames
+            .iter()
+            .map(|frame| {
+                (
+                    frame.program_id,
+                    frame.invoke_depth,
+                    frame.compute_units_consumed,
+                    frame.exclusive_compute_units_consumed,
+                )
+            })
+            .collect()
+    }
+}
@@ -98,6 +98,102 @@ pub mod filter_stake_delegation_accounts {
     solana_sdk::declare_id!("GE7fRxmW46K6EmCD9AMZSbnaJ2e3LfqCZzdHi9hmYAgi");
 }
 
+pub mod system_transfer_syscall_enabled {
+    solana_sdk::declare_id!("8wNnNWQQqJsd9s6Qf9vPTtsi7ucrUU6hcjDGTPM6mNmA");
+}
+
+pub mod get_max_invoke_depth_syscall_enabled {
+    solana_sdk::declare_id!("AwGWsgSzhtYozsuHfDfZoMNckexTFh7DLjyopembHSX4");
+}
+
+pub mod increase_tx_account_lock_limit {
+    solana_sdk::declare_id!("96WatF6HKFRh17ZgbtGaHCXMWWUe2cqKd88fJh73fFPF");
+}
+
+pub mod sha256_context_syscalls_enabled {
+    solana_sdk::declare_id!("EMJQGSNM2AxDzfyDZTnHhHjAmD4cWurpEoqzevEeTmbz");
+}
+
+pub mod hmac_sha256_syscall_enabled {
+    solana_sdk::declare_id!("BSsQPuRc4nLQb49UE5fg7123ndM3WJtpgoxi5XBuTJ4U");
+}
+
+pub mod chacha20_poly1305_syscall_enabled {
+    solana_sdk::declare_id!("7pesqGpdsf1RHVH93Ki7xNLFcfo1hKCpE6SHx7GxZjAi");
+}
+
+pub mod secp256r1_verify_syscall_enabled {
+    solana_sdk::declare_id!("6au5VokUWoA1HejvP25FNuWCqt4xFhKoaf94cKRfq6QD");
+}
+
+pub mod x25519_dh_syscall_enabled {
+    solana_sdk::declare_id!("AxYbN4ndVGaYPCVcopEaFpFi6MMDkcQyndmYB3b2AH5Q");
+}
+
+pub mod address_encoding_syscalls_enabled {
+    solana_sdk::declare_id!("2GfXyUihdnzaYF6ar37bHoeonZtJjimSisoi7BqhBMa6");
+}
+
+pub mod length_prefix_decode_syscall_enabled {
+    solana_sdk::declare_id!("4pwp9x2LuokTbDb92ctNwgyy4V7AXgFPqxsnWF52eZMZ");
+}
+
+pub mod runtime_version_syscall_enabled {
+    solana_sdk::declare_id!("yWoj9fgBmrFo9y65NRHWXcR28xmb5e97v9dPX3N6aZf");
+}
+
+pub mod instruction_ancestry_syscall_enabled {
+    solana_sdk::declare_id!("6ddNXyHkpy7LcwG6G7g8jQ4ETYUGBjGfnoLKiqNb3Jqt");
+}
+
+pub mod transaction_signature_syscall_enabled {
+    solana_sdk::declare_id!("43PUBy4X3psAURqREVN9LN4JLAJAUbVZHxk382mrruqe");
+}
+
+pub mod recent_blockhash_syscall_enabled {
+    solana_sdk::declare_id!("A8bz73MSEYCFUpoVuFRksmA8CWGaCWBAx4pvkXKyMaRc");
+}
+
+pub mod heap_free_enabled {
+    solana_sdk::declare_id!("2hQEeru3gQCmAN12xL1RbQ7ArGdpUJgKNuzNG5Uvx8rB");
+}
+
+pub mod fee_payer_syscall_enabled {
+    solana_sdk::declare_id!("7qKyCa5J4c69jyo3vgtGXKchSrkmUNCaamvoK6jMvECf");
+}
+
+pub mod abort_with_code_syscall_enabled {
+    solana_sdk::declare_id!("74LoHKz7eztUThtrDe3HTcbQcQPE6oXS1xHoT5CubMtL");
+}
+
+pub mod runtime_limits_syscall_enabled {
+    solana_sdk::declare_id!("BjK5ykW5Ft5xvobxxWANbULFM349rhq3ezbQyFQU27v7");
+}
+
+pub mod pseudorandom_syscall_enabled {
+    solana_sdk::declare_id!("EkP8gsgDcseQojK2sy2C6dDp7mbRQxbXUz4gkAAc3gdr");
+}
+
+pub mod create_program_addresses_batch_syscall_enabled {
+    solana_sdk::declare_id!("4qcHDdfRocJL9Gyae9AaxmG4vXgwEHEkSx2E55QKNabS");
+}
+
+pub mod reject_overlapping_syscall_buffers {
+    solana_sdk::declare_id!("9hVQFoWfHsDEYH2f2xfnZGArKgstNx2u1aoxkkhv9LX7");
+}
+
+pub mod limit_tx_account_locks {
+    solana_sdk::declare_id!("8ZL9sCa2xJhvFG1pzE1ZNqboq186QrkFrQ21nUSvBM1K");
+}
+
+pub mod keccak256_context_syscalls_enabled {
+    solana_sdk::declare_id!("gMDfKfy3oyJoSz7hEnpeCbXgsjLNGLon7q5iGZzQ6PN");
+}
+
+pub mod log_data_syscall_enabled {
+    solana_sdk::declare_id!("GvBmvMNw9DtYPMSgeXc3yG3mFEU8ReiVNfKBLs16oaSL");
+}
+
 lazy_static! {
     /// Map of feature identifiers to user-visible description
     pub static ref FEATURE_NAMES: HashMap<Pubkey, &'static str> = [
@@ -124,6 +220,30 @@ lazy_static! {
         (stake_program_v2::id(), "solana_stake_program v2"),
         (rewrite_stake::id(), "rewrite stake"),
         (filter_stake_delegation_accounts::id(), "filter stake_delegation_accounts #14062"),
+        (system_transfer_syscall_enabled::id(), "system transfer syscall"),
+        (get_max_invoke_depth_syscall_enabled::id(), "sol_get_max_invoke_depth syscall"),
+        (increase_tx_account_lock_limit::id(), "increase tx account lock limit to 128"),
+        (sha256_context_syscalls_enabled::id(), "sha256 init, update and final syscalls"),
+        (hmac_sha256_syscall_enabled::id(), "hmac-sha256 syscall"),
+        (chacha20_poly1305_syscall_enabled::id(), "chacha20-poly1305 open syscall"),
+        (secp256r1_verify_syscall_enabled::id(), "secp256r1 verify syscall"),
+        (x25519_dh_syscall_enabled::id(), "x25519 diffie-hellman syscall"),
+        (address_encoding_syscalls_enabled::id(), "base58 and bech32 encode and decode syscalls"),
+        (length_prefix_decode_syscall_enabled::id(), "compact-u16 and borsh length prefix decode syscall"),
+        (runtime_version_syscall_enabled::id(), "sol_get_runtime_version syscall"),
+        (instruction_ancestry_syscall_enabled::id(), "sol_get_instruction_ancestry syscall"),
+        (transaction_signature_syscall_enabled::id(), "sol_get_transaction_signature syscall"),
+        (recent_blockhash_syscall_enabled::id(), "sol_get_recent_blockhash_of_tx syscall"),
+        (heap_free_enabled::id(), "sol_alloc_free_ reuses freed heap memory"),
+        (fee_payer_syscall_enabled::id(), "sol_get_fee_payer syscall"),
+        (abort_with_code_syscall_enabled::id(), "sol_abort_with_code syscall"),
+        (runtime_limits_syscall_enabled::id(), "sol_get_runtime_limits syscall"),
+        (pseudorandom_syscall_enabled::id(), "sol_get_pseudorandom syscall"),
+        (create_program_addresses_batch_syscall_enabled::id(), "sol_create_program_addresses_batch syscall"),
+        (reject_overlapping_syscall_buffers::id(), "reject overlapping syscall and cross-program invocation buffers"),
+        (limit_tx_account_locks::id(), "limit tx account locks to 64"),
+        (keccak256_context_syscalls_enabled::id(), "keccak256 init, update and final syscalls"),
+        (log_data_syscall_enabled::id(), "sol_log_data syscall"),
         /*************** ADD NEW FEATURES HERE ***************/
     ]
     .iter()
@@ -11,6 +11,7 @@ pub mod account_utils;
 pub mod builtins;
 pub mod client;
 pub mod commitment_config;
+pub mod compute_profiler;
 pub mod deserialize_utils;
 pub mod entrypoint;
 pub mod entrypoint_deprecated;
@@ -1,15 +1,32 @@
 use solana_sdk::{
     account::Account,
+    compute_profiler::InvocationProfiler,
     feature_set::{
-        bpf_compute_budget_balancing, max_invoke_depth_4, max_program_call_depth_64,
-        pubkey_log_syscall_enabled, FeatureSet,
+        address_encoding_syscalls_enabled, bpf_compute_budget_balancing,
+        chacha20_poly1305_syscall_enabled, create_program_addresses_batch_syscall_enabled,
+        fee_payer_syscall_enabled, hmac_sha256_syscall_enabled,
+        instruction_ancestry_syscall_enabled, keccak256_context_syscalls_enabled,
+        length_prefix_decode_syscall_enabled, max_invoke_depth_4, max_program_call_depth_64,
+        pseudorandom_syscall_enabled, pubkey_log_syscall_enabled, recent_blockhash_syscall_enabled,
+        runtime_limits_syscall_enabled, secp256r1_verify_syscall_enabled,
+        system_transfer_syscall_enabled, transaction_signature_syscall_enabled,
+        x25519_dh_syscall_enabled, FeatureSet,
     },
+    hash::Hash,
     instruction::{CompiledInstruction, Instruction, InstructionError},
     keyed_account::KeyedAccount,
     message::Message,
     pubkey::Pubkey,
+    runtime_version::RuntimeVersion,
+    signature::Signature,
+};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    fmt::{self, Debug},
+    rc::Rc,
+    sync::Arc,
 };
-use std::{cell::RefCell, fmt::Debug, rc::Rc, sync::Arc};
 
 // Prototype of a native loader entry point
 ///
@@ -27,6 +44,66 @@ pub type LoaderEntrypoint = unsafe extern "C" fn(
 pub type ProcessInstructionWithContext =
     fn(&Pubkey, &[KeyedAccount], &[u8], &mut dyn InvokeContext) -> Result<(), InstructionError>;
 
+/// Compute costs keyed by syscall name that take precedence over the
+/// corresponding `BpfComputeBudget` costs, per-byte costs are keyed by
+/// `syscall_byte_cost_key` and per-address costs by `syscall_address_cost_key`
+pub type SyscallCostOverrides = HashMap<String, u64>;
+
+/// Key of the per-byte cost of the named syscall in `SyscallCostOverrides`
+pub fn syscall_byte_cost_key(syscall_name: &str) -> String {
+    format!("{}:byte", syscall_name)
+}
+
+/// Key of the per-address cost of the named syscall in `SyscallCostOverrides`
+pub fn syscall_address_cost_key(syscall_name: &str) -> String {
+    format!("{}:address", syscall_name)
+}
+
+/// Restricts which syscalls programs may invoke, calls to a disabled syscall
+/// fail without consuming compute
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SyscallFilter {
+    /// Only the named syscalls are enabled
+    Allow(HashSet<String>),
+    /// All but the named syscalls are enabled
+    Deny(HashSet<String>),
+}
+impl SyscallFilter {
+    pub fn is_enabled(&self, syscall_name: &str) -> bool {
+        match self {
+            SyscallFilter::Allow(names) => names.contains(syscall_name),
+            SyscallFilter::Deny(names) => !names.contains(syscall_name),
+        }
+    }
+}
+
+/// Fault injected into a call of a syscall, to exercise error handling that
+/// programs cannot otherwise reach
+///
+/// Calls are counted separately in every program invocation: a program
+/// invoked through a CPI counts its own calls from 1, and its caller's count
+/// resumes where it left off once the CPI returns. Only hosts built with the
+/// runtime's `syscall-fault-injection` feature, such as `solana-program-test`,
+/// can inject faults.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyscallFault {
+    /// The syscall is not run and returns the given value to the program
+    Return(u64),
+    /// The syscall is not run and fails, aborting the program
+    Fail,
+    /// The given compute units are consumed before the syscall is run
+    ConsumeComputeUnits(u64),
+}
+
+/// Faults keyed by syscall name and then by the 1-based index of the faulted
+/// call within each program invocation, including each CPI
+pub type SyscallFaults = HashMap<String, HashMap<u64, SyscallFault>>;
+
+/// Version of the interface this runtime presents to programs, increment the
+/// minor version when adding syscalls and the major version for changes
+/// existing programs may observe
+pub const RUNTIME_VERSION: RuntimeVersion = RuntimeVersion::new(1, 0, 0);
+
 /// Invocation context passed to loaders
 pub trait InvokeContext {
     /// Push a program ID on to the invocation stack
@@ -44,6 +121,9 @@ pub trait InvokeContext {
     ) -> Result<(), InstructionError>;
     /// Get the program ID of the currently executing program
     fn get_caller(&self) -> Result<&Pubkey, InstructionError>;
+    /// Get the program IDs of the invocation stack, from the top-level instruction's
+    /// program to the currently executing program
+    fn get_program_ids(&self) -> &[Pubkey];
     /// Get a list of built-in programs
     fn get_programs(&self) -> &[(Pubkey, ProcessInstructionWithContext)];
     /// Get this invocation's logger
@@ -52,6 +132,27 @@ pub trait InvokeContext {
     fn get_bpf_compute_budget(&self) -> &BpfComputeBudget;
     /// Get this invocation's compute meter
     fn get_compute_meter(&self) -> Rc<RefCell<dyn ComputeMeter>>;
+    /// Get the overridden cost of a syscall, if any
+    fn get_syscall_cost_override(&self, syscall_name: &str) -> Option<u64>;
+    /// Get the filter restricting which syscalls are enabled, if any
+    fn get_syscall_filter(&self) -> Option<Arc<SyscallFilter>>;
+    /// Get the faults injected into syscalls, if any
+    fn get_syscall_faults(&self) -> Option<Arc<SyscallFaults>>;
+    /// Get the collector syscall invocations are traced to, if any
+    fn get_syscall_trace_collector(&self) -> Option<Arc<dyn SyscallTraceCollector>>;
+    /// Get the profiler compute consumption is recorded to, if any
+    fn get_compute_profiler(&self) -> Option<Rc<RefCell<InvocationProfiler>>>;
+    /// Get the version of the interface presented to programs
+    fn get_runtime_version(&self) -> RuntimeVersion;
+    /// Get the signatures of the transaction being processed
+    fn get_transaction_signatures(&self) -> &[Signature];
+    /// Get the recent blockhash the transaction being processed was signed against
+    fn get_recent_blockhash(&self) -> &Hash;
+    /// Get the fee payer of the transaction being processed
+    fn get_fee_payer(&self) -> &Pubkey;
+    /// Get the number of pseudorandom draws made so far, shared by every program the
+    /// transaction being processed invokes
+    fn get_pseudorandom_counter(&self) -> Rc<RefCell<u64>>;
     /// Loaders may need to do work in order to execute a program.  Cache
     /// the work that can be re-used across executions
     fn add_executor(&self, pubkey: &Pubkey, executor: Arc<dyn Executor>);
@@ -59,6 +160,10 @@ pub trait InvokeContext {
     fn get_executor(&self, pubkey: &Pubkey) -> Option<Arc<dyn Executor>>;
     /// Record invoked instruction
     fn record_instruction(&self, instruction: &Instruction);
+    /// Record the code a program aborted with, whether or not logging is enabled
+    fn record_abort_code(&mut self, code: u64);
+    /// Get the code the most recently aborted program aborted with, if any
+    fn get_abort_code(&self) -> Option<u64>;
     /// Get the bank's active feature set
     fn is_feature_active(&self, feature_id: &Pubkey) -> bool;
 }
@@ -74,6 +179,9 @@ pub struct BpfComputeBudget {
     pub log_64_units: u64,
     /// Number of compute units consumed by a create_program_address call
     pub create_program_address_units: u64,
+    /// Base number of compute units consumed by a create_program_addresses_batch call, on top
+    /// of `create_program_address_units` per address
+    pub create_program_addresses_batch_base_units: u64,
     /// Number of compute units consumed by an invoke call (not including the cost incurred by
     /// the called program)
     pub invoke_units: u64,
@@ -89,6 +197,48 @@ pub struct BpfComputeBudget {
     pub stack_frame_size: usize,
     /// Number of compute units consumed by logging a `Pubkey`
     pub log_pubkey_units: u64,
+    /// Number of compute units consumed by a system transfer call
+    pub system_transfer_units: u64,
+    /// Number of compute units consumed to verify a secp256r1 signature, not including
+    /// hashing the message
+    pub secp256r1_verify_units: u64,
+    /// Number of compute units consumed by an X25519 Diffie-Hellman call
+    pub x25519_dh_units: u64,
+    /// Base number of compute units consumed by a base58 or bech32 encode or decode call
+    pub address_encoding_base_units: u64,
+    /// Incremental number of compute units consumed by a base58 or bech32 encode or decode
+    /// call (based on input bytes)
+    pub address_encoding_byte_units: u64,
+    /// Number of compute units consumed to decode a length prefix
+    pub length_prefix_decode_units: u64,
+    /// Number of compute units consumed to get the program IDs of the invocation stack
+    pub instruction_ancestry_units: u64,
+    /// Number of compute units consumed to copy a transaction signature
+    pub transaction_signature_units: u64,
+    /// Number of compute units consumed to get the transaction's recent blockhash
+    pub recent_blockhash_units: u64,
+    /// Number of compute units consumed to get the transaction's fee payer
+    pub fee_payer_units: u64,
+    /// Number of compute units consumed to get the runtime limits
+    pub runtime_limits_units: u64,
+    /// Base number of compute units consumed to get pseudorandom bytes
+    pub pseudorandom_base_units: u64,
+    /// Incremental number of compute units consumed to get pseudorandom bytes (based on bytes)
+    pub pseudorandom_byte_units: u64,
+    /// Base number of compute units consumed by a ChaCha20-Poly1305 open call
+    pub chacha20_poly1305_base_units: u64,
+    /// Incremental number of compute units consumed by a ChaCha20-Poly1305 open call (based on
+    /// additional data and ciphertext bytes)
+    pub chacha20_poly1305_byte_units: u64,
+    /// Base number of compute units consumed by a Keccak-256 init, update or final call
+    pub keccak256_base_units: u64,
+    /// Incremental number of compute units consumed by a Keccak-256 update call (based on bytes)
+    pub keccak256_byte_units: u64,
+    /// Base number of compute units consumed by an HMAC-SHA256 call
+    pub hmac_sha256_base_units: u64,
+    /// Incremental number of compute units consumed by an HMAC-SHA256 call (based on key and
+    /// data bytes)
+    pub hmac_sha256_byte_units: u64,
 }
 impl Default for BpfComputeBudget {
     fn default() -> Self {
@@ -104,6 +254,7 @@ impl BpfComputeBudget {
             log_units: 0,
             log_64_units: 0,
             create_program_address_units: 0,
+            create_program_addresses_batch_base_units: 0,
             invoke_units: 0,
             max_invoke_depth: 1,
             sha256_base_cost: 85,
@@ -111,6 +262,25 @@ impl BpfComputeBudget {
             max_call_depth: 20,
             stack_frame_size: 4_096,
             log_pubkey_units: 0,
+            system_transfer_units: 0,
+            secp256r1_verify_units: 0,
+            x25519_dh_units: 0,
+            address_encoding_base_units: 0,
+            address_encoding_byte_units: 0,
+            length_prefix_decode_units: 0,
+            instruction_ancestry_units: 0,
+            transaction_signature_units: 0,
+            recent_blockhash_units: 0,
+            fee_payer_units: 0,
+            runtime_limits_units: 0,
+            pseudorandom_base_units: 0,
+            pseudorandom_byte_units: 0,
+            chacha20_poly1305_base_units: 0,
+            chacha20_poly1305_byte_units: 0,
+            keccak256_base_units: 0,
+            keccak256_byte_units: 0,
+            hmac_sha256_base_units: 0,
+            hmac_sha256_byte_units: 0,
         };
 
         if feature_set.is_active(&bpf_compute_budget_balancing::id()) {
@@ -142,6 +312,101 @@ impl BpfComputeBudget {
                 ..bpf_compute_budget
             };
         }
+        if feature_set.is_active(&system_transfer_syscall_enabled::id()) {
+            bpf_compute_budget = BpfComputeBudget {
+                system_transfer_units: 250,
+                ..bpf_compute_budget
+            };
+        }
+        if feature_set.is_active(&secp256r1_verify_syscall_enabled::id()) {
+            bpf_compute_budget = BpfComputeBudget {
+                secp256r1_verify_units: 25_000,
+                ..bpf_compute_budget
+            };
+        }
+        if feature_set.is_active(&x25519_dh_syscall_enabled::id()) {
+            bpf_compute_budget = BpfComputeBudget {
+                x25519_dh_units: 2_500,
+                ..bpf_compute_budget
+            };
+        }
+        if feature_set.is_active(&address_encoding_syscalls_enabled::id()) {
+            bpf_compute_budget = BpfComputeBudget {
+                address_encoding_base_units: 100,
+                address_encoding_byte_units: 1,
+                ..bpf_compute_budget
+            };
+        }
+        if feature_set.is_active(&length_prefix_decode_syscall_enabled::id()) {
+            bpf_compute_budget = BpfComputeBudget {
+                length_prefix_decode_units: 10,
+                ..bpf_compute_budget
+            };
+        }
+        if feature_set.is_active(&instruction_ancestry_syscall_enabled::id()) {
+            bpf_compute_budget = BpfComputeBudget {
+                instruction_ancestry_units: 100,
+                ..bpf_compute_budget
+            };
+        }
+        if feature_set.is_active(&transaction_signature_syscall_enabled::id()) {
+            bpf_compute_budget = BpfComputeBudget {
+                transaction_signature_units: 100,
+                ..bpf_compute_budget
+            };
+        }
+        if feature_set.is_active(&recent_blockhash_syscall_enabled::id()) {
+            bpf_compute_budget = BpfComputeBudget {
+                recent_blockhash_units: 100,
+                ..bpf_compute_budget
+            };
+        }
+        if feature_set.is_active(&fee_payer_syscall_enabled::id()) {
+            bpf_compute_budget = BpfComputeBudget {
+                fee_payer_units: 100,
+                ..bpf_compute_budget
+            };
+        }
+        if feature_set.is_active(&runtime_limits_syscall_enabled::id()) {
+            bpf_compute_budget = BpfComputeBudget {
+                runtime_limits_units: 100,
+                ..bpf_compute_budget
+            };
+        }
+        if feature_set.is_active(&pseudorandom_syscall_enabled::id()) {
+            bpf_compute_budget = BpfComputeBudget {
+                pseudorandom_base_units: 100,
+                pseudorandom_byte_units: 1,
+                ..bpf_compute_budget
+            };
+        }
+        if feature_set.is_active(&chacha20_poly1305_syscall_enabled::id()) {
+            bpf_compute_budget = BpfComputeBudget {
+                chacha20_poly1305_base_units: 100,
+                chacha20_poly1305_byte_units: 1,
+                ..bpf_compute_budget
+            };
+        }
+        if feature_set.is_active(&create_program_addresses_batch_syscall_enabled::id()) {
+            bpf_compute_budget = BpfComputeBudget {
+                create_program_addresses_batch_base_units: 100,
+                ..bpf_compute_budget
+            };
+        }
+        if feature_set.is_active(&keccak256_context_syscalls_enabled::id()) {
+            bpf_compute_budget = BpfComputeBudget {
+                keccak256_base_units: 85,
+                keccak256_byte_units: 1,
+                ..bpf_compute_budget
+            };
+        }
+        if feature_set.is_active(&hmac_sha256_syscall_enabled::id()) {
+            bpf_compute_budget = BpfComputeBudget {
+                hmac_sha256_base_units: 170,
+                hmac_sha256_byte_units: 1,
+                ..bpf_compute_budget
+            };
+        }
         bpf_compute_budget
     }
 }
@@ -163,6 +428,105 @@ pub trait Logger {
     /// Unless explicitly stated, log messages are not considered stable and may change in the
     /// future as necessary
     fn log(&self, message: &str);
+
+    /// Log a message from a program in structured form.
+    ///
+    /// By default only the record's string rendering is logged
+    fn log_record(&self, record: &ProgramLogRecord) {
+        self.log(&record.to_string());
+    }
+}
+
+/// Severity of a message logged by a program
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ProgramLogLevel {
+    /// Runtime diagnostics such as compute consumption
+    Debug,
+    /// Output the program logged
+    Info,
+    /// Reports of the program failing
+    Error,
+}
+
+/// Payload of a message logged by a program
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProgramLogKind {
+    /// Message logged by `sol_log_`
+    Msg(String),
+    /// Values logged by `sol_log_64_`
+    U64s([u64; 5]),
+    /// Public key logged by `sol_log_pubkey`
+    Pubkey(Pubkey),
+    /// Compute units remaining when `sol_log_compute_units_` was called
+    ComputeUnits(u64),
+    /// Code the program passed to `sol_abort_with_code`
+    AbortCode(u64),
+    /// Raw bytes logged by `sol_log_data`
+    Data(Vec<u8>),
+}
+
+impl ProgramLogKind {
+    /// Severity of the message, for consumers filtering program output
+    pub fn level(&self) -> ProgramLogLevel {
+        match self {
+            ProgramLogKind::Msg(_)
+            | ProgramLogKind::U64s(_)
+            | ProgramLogKind::Pubkey(_)
+            | ProgramLogKind::Data(_) => ProgramLogLevel::Info,
+            ProgramLogKind::ComputeUnits(_) => ProgramLogLevel::Debug,
+            ProgramLogKind::AbortCode(_) => ProgramLogLevel::Error,
+        }
+    }
+}
+
+/// A message logged by a program
+///
+/// Its `Display` rendering is the string form found in transaction logs
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProgramLogRecord {
+    /// Program that logged the message
+    pub program_id: Pubkey,
+    /// Invocation stack height of the program, the top-level instruction is 1
+    pub stack_height: usize,
+    pub kind: ProgramLogKind,
+}
+
+impl fmt::Display for ProgramLogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ProgramLogKind::Msg(message) => write!(f, "Program log: {}", message),
+            ProgramLogKind::U64s([arg1, arg2, arg3, arg4, arg5]) => write!(
+                f,
+                "Program log: {:#x}, {:#x}, {:#x}, {:#x}, {:#x}",
+                arg1, arg2, arg3, arg4, arg5
+            ),
+            ProgramLogKind::Pubkey(pubkey) => write!(f, "Program log: {}", pubkey),
+            ProgramLogKind::ComputeUnits(remaining) => {
+                write!(f, "Program consumption: {} units remaining", remaining)
+            }
+            ProgramLogKind::AbortCode(code) => write!(f, "Program aborted with code {}", code),
+            ProgramLogKind::Data(data) => write!(f, "Program data: {}", base64::encode(data)),
+        }
+    }
+}
+
+/// A single syscall invocation made by a program
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyscallTraceEvent {
+    /// Name the syscall is registered under
+    pub name: String,
+    /// Raw arguments as passed in registers r1 through r5
+    pub args: [u64; 5],
+    /// Compute units consumed by the syscall, including any programs it invoked
+    pub compute_units_consumed: u64,
+    /// Value returned in r0, or the error that aborted the program
+    pub result: Result<u64, String>,
+}
+
+/// Syscall trace events
+pub trait SyscallTraceCollector: Debug + Send + Sync {
+    /// Collect the event for a completed syscall
+    fn collect(&self, event: SyscallTraceEvent);
 }
 
 ///
@@ -203,6 +567,17 @@ pub mod stable_log {
         }
     }
 
+    /// Log a structured message from the program itself.
+    ///
+    /// The record is rendered in the same form as `program_log` for messages, u64s and pubkeys
+    pub fn program_log_record(logger: &Rc<RefCell<dyn Logger>>, record: ProgramLogRecord) {
+        if let Ok(logger) = logger.try_borrow_mut() {
+            if logger.log_enabled() {
+                logger.log_record(&record)
+            }
+        }
+    }
+
     /// Log successful program execution.
     ///
     /// The general form is:
@@ -278,22 +653,46 @@ impl Logger for MockLogger {
 
 pub struct MockInvokeContext {
     pub key: Pubkey,
+    pub program_ids: Vec<Pubkey>,
     pub logger: MockLogger,
     pub bpf_compute_budget: BpfComputeBudget,
     pub compute_meter: MockComputeMeter,
     pub programs: Vec<(Pubkey, ProcessInstructionWithContext)>,
+    pub syscall_cost_overrides: SyscallCostOverrides,
+    pub syscall_filter: Option<Arc<SyscallFilter>>,
+    pub syscall_faults: Option<Arc<SyscallFaults>>,
+    pub syscall_trace_collector: Option<Arc<dyn SyscallTraceCollector>>,
+    pub compute_profiler: Option<Rc<RefCell<InvocationProfiler>>>,
+    pub runtime_version: RuntimeVersion,
+    pub signatures: Vec<Signature>,
+    pub recent_blockhash: Hash,
+    pub fee_payer: Pubkey,
+    pub pseudorandom_counter: Rc<RefCell<u64>>,
+    pub abort_code: Option<u64>,
     invoke_depth: usize,
 }
 impl Default for MockInvokeContext {
     fn default() -> Self {
         MockInvokeContext {
             key: Pubkey::default(),
+            program_ids: vec![],
             logger: MockLogger::default(),
             bpf_compute_budget: BpfComputeBudget::default(),
             compute_meter: MockComputeMeter {
                 remaining: std::i64::MAX as u64,
             },
             programs: vec![],
+            syscall_cost_overrides: SyscallCostOverrides::default(),
+            syscall_filter: None,
+            syscall_faults: None,
+            syscall_trace_collector: None,
+            compute_profiler: None,
+            runtime_version: RUNTIME_VERSION,
+            signatures: vec![],
+            recent_blockhash: Hash::default(),
+            fee_payer: Pubkey::default(),
+            pseudorandom_counter: Rc::new(RefCell::new(0)),
+            abort_code: None,
             invoke_depth: 0,
         }
     }
@@ -320,6 +719,9 @@ impl InvokeContext for MockInvokeContext {
     fn get_caller(&self) -> Result<&Pubkey, InstructionError> {
         Ok(&self.key)
     }
+    fn get_program_ids(&self) -> &[Pubkey] {
+        &self.program_ids
+    }
     fn get_programs(&self) -> &[(Pubkey, ProcessInstructionWithContext)] {
         &self.programs
     }
@@ -332,11 +734,47 @@ impl InvokeContext for MockInvokeContext {
     fn get_compute_meter(&self) -> Rc<RefCell<dyn ComputeMeter>> {
         Rc::new(RefCell::new(self.compute_meter.clone()))
     }
+    fn get_syscall_cost_override(&self, syscall_name: &str) -> Option<u64> {
+        self.syscall_cost_overrides.get(syscall_name).copied()
+    }
+    fn get_syscall_filter(&self) -> Option<Arc<SyscallFilter>> {
+        self.syscall_filter.clone()
+    }
+    fn get_syscall_faults(&self) -> Option<Arc<SyscallFaults>> {
+        self.syscall_faults.clone()
+    }
+    fn get_syscall_trace_collector(&self) -> Option<Arc<dyn SyscallTraceCollector>> {
+        self.syscall_trace_collector.clone()
+    }
+    fn get_compute_profiler(&self) -> Option<Rc<RefCell<InvocationProfiler>>> {
+        self.compute_profiler.clone()
+    }
+    fn get_runtime_version(&self) -> RuntimeVersion {
+        self.runtime_version
+    }
+    fn get_transaction_signatures(&self) -> &[Signature] {
+        &self.signatures
+    }
+    fn get_recent_blockhash(&self) -> &Hash {
+        &self.recent_blockhash
+    }
+    fn get_fee_payer(&self) -> &Pubkey {
+        &self.fee_payer
+    }
+    fn get_pseudorandom_counter(&self) -> Rc<RefCell<u64>> {
+        self.pseudorandom_counter.clone()
+    }
     fn add_executor(&self, _pubkey: &Pubkey, _executor: Arc<dyn Executor>) {}
     fn get_executor(&self, _pubkey: &Pubkey) -> Option<Arc<dyn Executor>> {
         None
     }
     fn record_instruction(&self, _instruction: &Instruction) {}
+    fn record_abort_code(&mut self, code: u64) {
+        self.abort_code = Some(code);
+    }
+    fn get_abort_code(&self) -> Option<u64> {
+        self.abort_code
+    }
     fn is_feature_active(&self, _feature_id: &Pubkey) -> bool {
         true
     }
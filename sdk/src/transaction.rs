@@ -92,6 +92,10 @@ pub enum TransactionError {
 
     #[error("Transactions are currently disabled due to cluster maintenance")]
     ClusterMaintenance,
+
+    /// Transaction locks more accounts than allowed
+    #[error("Transaction locked too many accounts")]
+    TooManyAccountLocks,
 }
 
 pub type Result<T> = result::Result<T, TransactionError>;